@@ -0,0 +1,256 @@
+use std::env;
+use std::error::Error;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use futures::stream::{self, StreamExt};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::nonblocking::pubsub_client::PubsubClient;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcAccountInfoConfig;
+use solana_sdk::account::Account;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use tokio::sync::mpsc;
+use tokio::time::sleep;
+
+type StreamError = Box<dyn Error + Send + Sync>;
+
+const CHANNEL_CAPACITY: usize = 1024;
+// Ограничение RPC на количество аккаунтов в getMultipleAccounts
+const MAX_ACCOUNTS_PER_REQUEST: usize = 100;
+
+// Новое содержимое аккаунта, полученное по подписке или опросом
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountUpdate {
+    pub pubkey: Pubkey,
+    pub slot: u64,
+    pub data: Vec<u8>,
+}
+
+// Событие, которое двигает основной цикл бота. Время задает источник событий,
+// поэтому при воспроизведении записи цикл видит исходные моменты времени.
+#[derive(Debug, Clone)]
+pub enum FeedEvent {
+    Account { timestamp_ms: i64, update: AccountUpdate },
+    // Момент запуска стратегий
+    Tick { timestamp_ms: i64 },
+}
+
+pub fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as i64)
+        .unwrap_or_default()
+}
+
+#[derive(Debug, Clone)]
+pub struct AccountStreamConfig {
+    // Без WebSocket поток работает только опросом
+    pub ws_url: Option<String>,
+    pub rpc_url: String,
+    pub poll_interval: Duration,
+    pub reconnect_delay: Duration,
+    // После стольких неудачных переподключений подряд переходим на опрос
+    pub max_reconnect_attempts: u32,
+    // Сколько работать опросом, прежде чем снова пробовать WebSocket
+    pub fallback_duration: Duration,
+}
+
+impl AccountStreamConfig {
+    pub fn from_env(rpc_url: &str) -> Self {
+        let ws_url = match env::var("PRICE_FEED_MODE").as_deref() {
+            Ok("poll") => None,
+            _ => Some(env::var("SOLANA_WS_URL").unwrap_or_else(|_| default_ws_url(rpc_url))),
+        };
+
+        Self {
+            ws_url,
+            rpc_url: rpc_url.to_string(),
+            poll_interval: Duration::from_secs(1),
+            reconnect_delay: Duration::from_secs(1),
+            max_reconnect_attempts: 5,
+            fallback_duration: Duration::from_secs(60),
+        }
+    }
+}
+
+// WebSocket-эндпоинт по умолчанию получается из HTTP-адреса RPC
+fn default_ws_url(rpc_url: &str) -> String {
+    rpc_url.replacen("https://", "wss://", 1).replacen("http://", "ws://", 1)
+}
+
+// Запускает фоновую задачу, которая доставляет обновления аккаунтов в канал.
+// Задача завершается, когда получатель закрыт.
+pub fn spawn_account_stream(config: AccountStreamConfig, accounts: Vec<Pubkey>) -> mpsc::Receiver<AccountUpdate> {
+    let (sender, receiver) = mpsc::channel(CHANNEL_CAPACITY);
+    tokio::spawn(run_account_stream(config, accounts, sender));
+    receiver
+}
+
+// Живой источник событий: обновления аккаунтов из потока и тики стратегий
+// с интервалом `tick_interval`, помеченные текущим временем
+pub fn spawn_live_feed(config: AccountStreamConfig, accounts: Vec<Pubkey>, tick_interval: Duration) -> mpsc::Receiver<FeedEvent> {
+    let mut updates = spawn_account_stream(config, accounts);
+    let (sender, receiver) = mpsc::channel(CHANNEL_CAPACITY);
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(tick_interval);
+        loop {
+            let event = tokio::select! {
+                update = updates.recv() => match update {
+                    Some(update) => FeedEvent::Account { timestamp_ms: now_ms(), update },
+                    None => return,
+                },
+                _ = ticker.tick() => FeedEvent::Tick { timestamp_ms: now_ms() },
+            };
+            if sender.send(event).await.is_err() {
+                return;
+            }
+        }
+    });
+
+    receiver
+}
+
+async fn run_account_stream(config: AccountStreamConfig, accounts: Vec<Pubkey>, sender: mpsc::Sender<AccountUpdate>) {
+    let rpc_client = RpcClient::new_with_commitment(config.rpc_url.clone(), CommitmentConfig::confirmed());
+    let mut failures = 0;
+
+    while !sender.is_closed() {
+        if let Some(ws_url) = &config.ws_url {
+            if failures < config.max_reconnect_attempts {
+                let mut received = false;
+                match subscribe_accounts(ws_url, &rpc_client, &accounts, &sender, &mut received).await {
+                    Ok(()) => return,
+                    Err(e) => {
+                        // Сессия, успевшая доставить данные, не считается неудачной попыткой
+                        failures = if received { 1 } else { failures + 1 };
+                        warn!("Account subscription lost: {}. Reconnecting (attempt {})...", e, failures);
+                        sleep(config.reconnect_delay * failures).await;
+                        continue;
+                    }
+                }
+            }
+            warn!("WebSocket unavailable, falling back to polling for {:?}", config.fallback_duration);
+        }
+
+        let deadline = config.ws_url.as_ref().map(|_| tokio::time::Instant::now() + config.fallback_duration);
+        if poll_accounts(&rpc_client, &accounts, &sender, config.poll_interval, deadline).await {
+            return;
+        }
+        failures = 0;
+    }
+}
+
+// Подписывается на все аккаунты и пересылает уведомления, пока соединение живо.
+// После подписки аккаунты один раз читаются по RPC: изменения, пришедшие, пока
+// соединения не было, по подписке уже не придут. Ok(()) означает, что
+// получатель закрыт и работа закончена.
+async fn subscribe_accounts(
+    ws_url: &str,
+    rpc_client: &RpcClient,
+    accounts: &[Pubkey],
+    sender: &mpsc::Sender<AccountUpdate>,
+    received: &mut bool,
+) -> Result<(), StreamError> {
+    let client = PubsubClient::new(ws_url).await?;
+    let config = RpcAccountInfoConfig {
+        encoding: Some(UiAccountEncoding::Base64),
+        commitment: Some(CommitmentConfig::confirmed()),
+        ..RpcAccountInfoConfig::default()
+    };
+
+    let mut subscriptions = Vec::with_capacity(accounts.len());
+    for &pubkey in accounts {
+        let (notifications, _unsubscribe) = client.account_subscribe(&pubkey, Some(config.clone())).await?;
+        subscriptions.push(notifications.map(move |response| (pubkey, response)));
+    }
+    info!("Subscribed to {} accounts via {}", accounts.len(), ws_url);
+    if fetch_accounts(rpc_client, accounts, sender).await {
+        return Ok(());
+    }
+
+    let mut notifications = stream::select_all(subscriptions);
+    while let Some((pubkey, response)) = notifications.next().await {
+        let account = match response.value.decode::<Account>() {
+            Some(account) => account,
+            None => {
+                warn!("Failed to decode account notification for {}", pubkey);
+                continue;
+            }
+        };
+
+        *received = true;
+        let update = AccountUpdate {
+            pubkey,
+            slot: response.context.slot,
+            data: account.data,
+        };
+        if sender.send(update).await.is_err() {
+            return Ok(());
+        }
+    }
+
+    Err("WebSocket connection closed".into())
+}
+
+// Опрашивает аккаунты до наступления `deadline` (или бесконечно).
+// Возвращает true, если получатель закрыт.
+async fn poll_accounts(
+    rpc_client: &RpcClient,
+    accounts: &[Pubkey],
+    sender: &mpsc::Sender<AccountUpdate>,
+    interval: Duration,
+    deadline: Option<tokio::time::Instant>,
+) -> bool {
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        ticker.tick().await;
+        if let Some(deadline) = deadline {
+            if tokio::time::Instant::now() >= deadline {
+                return false;
+            }
+        }
+
+        if fetch_accounts(rpc_client, accounts, sender).await {
+            return true;
+        }
+    }
+}
+
+// Читает все аккаунты по RPC и пересылает их. Возвращает true, если получатель закрыт.
+async fn fetch_accounts(rpc_client: &RpcClient, accounts: &[Pubkey], sender: &mpsc::Sender<AccountUpdate>) -> bool {
+    for chunk in accounts.chunks(MAX_ACCOUNTS_PER_REQUEST) {
+        let response = match rpc_client
+            .get_multiple_accounts_with_commitment(chunk, CommitmentConfig::confirmed())
+            .await
+        {
+            Ok(response) => response,
+            Err(e) => {
+                warn!("Failed to fetch accounts: {}", e);
+                continue;
+            }
+        };
+
+        for (pubkey, account) in chunk.iter().zip(response.value) {
+            if let Some(account) = account {
+                let update = AccountUpdate {
+                    pubkey: *pubkey,
+                    slot: response.context.slot,
+                    data: account.data,
+                };
+                if sender.send(update).await.is_err() {
+                    return true;
+                }
+            }
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+#[path = "account_stream_test.rs"]
+mod tests;
@@ -0,0 +1,177 @@
+use super::*;
+use futures::SinkExt;
+use serde_json::{json, Value};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_tungstenite::tungstenite::Message;
+
+// base64 от [1, 2, 3, 4]
+const ACCOUNT_DATA_BASE64: &str = "AQIDBA==";
+
+fn account_json() -> Value {
+    json!({
+        "data": [ACCOUNT_DATA_BASE64, "base64"],
+        "executable": false,
+        "lamports": 1_000_000,
+        "owner": Pubkey::default().to_string(),
+        "rentEpoch": 0
+    })
+}
+
+// Читает HTTP-запрос целиком и возвращает его JSON-тело
+async fn read_request(socket: &mut TcpStream) -> Value {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 4096];
+    loop {
+        let read = socket.read(&mut chunk).await.unwrap();
+        assert!(read > 0, "connection closed before the request was read");
+        buffer.extend_from_slice(&chunk[..read]);
+
+        let text = String::from_utf8_lossy(&buffer).to_string();
+        if let Some(end) = text.find("\r\n\r\n") {
+            let length = text[..end].lines()
+                .find_map(|line| line.to_ascii_lowercase().strip_prefix("content-length:").map(|value| value.trim().parse::<usize>().unwrap()))
+                .unwrap_or(0);
+            if buffer.len() >= end + 4 + length {
+                return serde_json::from_slice(&buffer[end + 4..end + 4 + length]).unwrap();
+            }
+        }
+    }
+}
+
+// RPC-сервер: на каждый getMultipleAccounts отвечает всеми аккаунтами со слотом `slot`
+async fn serve_rpc(listener: TcpListener, slot: u64) {
+    loop {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let request = read_request(&mut socket).await;
+        assert_eq!(request["method"], "getMultipleAccounts");
+
+        let accounts: Vec<Value> = request["params"][0].as_array().unwrap().iter().map(|_| account_json()).collect();
+        let body = json!({
+            "jsonrpc": "2.0",
+            "result": {"context": {"slot": slot}, "value": accounts},
+            "id": request["id"]
+        }).to_string();
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(), body
+        );
+        socket.write_all(response.as_bytes()).await.unwrap();
+    }
+}
+
+async fn spawn_rpc(slot: u64) -> (String, tokio::task::JoinHandle<()>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let rpc_url = format!("http://{}", listener.local_addr().unwrap());
+    (rpc_url, tokio::spawn(serve_rpc(listener, slot)))
+}
+
+async fn next_update(updates: &mut mpsc::Receiver<AccountUpdate>) -> AccountUpdate {
+    tokio::time::timeout(Duration::from_secs(5), updates.recv()).await.unwrap().unwrap()
+}
+
+// Принимает одно соединение, отвечает на accountSubscribe, отправляет
+// уведомление со слотом `slot` и, если `close` установлен, разрывает соединение
+async fn serve_connection(listener: &TcpListener, slot: u64, close: bool) {
+    let (socket, _) = listener.accept().await.unwrap();
+    let mut ws = tokio_tungstenite::accept_async(socket).await.unwrap();
+
+    let request = loop {
+        match ws.next().await.unwrap().unwrap() {
+            Message::Text(text) => break serde_json::from_str::<Value>(&text).unwrap(),
+            _ => continue,
+        }
+    };
+    assert_eq!(request["method"], "accountSubscribe");
+
+    let subscription = 42;
+    let response = json!({"jsonrpc": "2.0", "result": subscription, "id": request["id"]});
+    ws.send(Message::Text(response.to_string())).await.unwrap();
+
+    let notification = json!({
+        "jsonrpc": "2.0",
+        "method": "accountNotification",
+        "params": {
+            "result": {
+                "context": {"slot": slot},
+                "value": account_json()
+            },
+            "subscription": subscription
+        }
+    });
+    ws.send(Message::Text(notification.to_string())).await.unwrap();
+
+    if close {
+        ws.close(None).await.unwrap();
+    } else {
+        // Держим соединение открытым, пока тест не завершится
+        while ws.next().await.is_some() {}
+    }
+}
+
+fn test_config(ws_url: Option<String>, rpc_url: String) -> AccountStreamConfig {
+    AccountStreamConfig {
+        ws_url,
+        rpc_url,
+        poll_interval: Duration::from_millis(10),
+        reconnect_delay: Duration::from_millis(10),
+        max_reconnect_attempts: 3,
+        fallback_duration: Duration::from_secs(1),
+    }
+}
+
+#[tokio::test]
+async fn test_updates_are_delivered_and_resubscribed_after_disconnect() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let ws_url = format!("ws://{}", listener.local_addr().unwrap());
+    let server = tokio::spawn(async move {
+        serve_connection(&listener, 5, true).await;
+        serve_connection(&listener, 6, false).await;
+    });
+    let (rpc_url, rpc) = spawn_rpc(100).await;
+
+    let account = Pubkey::new_unique();
+    let mut updates = spawn_account_stream(test_config(Some(ws_url), rpc_url), vec![account]);
+
+    // Сразу после подписки аккаунт читается по RPC, затем приходят уведомления
+    let fetched = next_update(&mut updates).await;
+    assert_eq!((fetched.pubkey, fetched.slot), (account, 100));
+    let first = next_update(&mut updates).await;
+    assert_eq!(first.pubkey, account);
+    assert_eq!(first.slot, 5);
+    assert_eq!(first.data, vec![1, 2, 3, 4]);
+
+    // После разрыва поток сам переподключается, подписывается заново и
+    // перечитывает аккаунт: изменения за время разрыва не теряются
+    let refetched = next_update(&mut updates).await;
+    assert_eq!((refetched.pubkey, refetched.slot), (account, 100));
+    let second = next_update(&mut updates).await;
+    assert_eq!(second.pubkey, account);
+    assert_eq!(second.slot, 6);
+
+    server.abort();
+    rpc.abort();
+}
+
+#[tokio::test]
+async fn test_polling_without_websocket() {
+    let (rpc_url, rpc) = spawn_rpc(7).await;
+    let accounts = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+    let mut updates = spawn_account_stream(test_config(None, rpc_url), accounts.clone());
+
+    // Каждый опрос выдает все аккаунты, а опросы повторяются с poll_interval
+    for _ in 0..2 {
+        for account in &accounts {
+            let update = next_update(&mut updates).await;
+            assert_eq!((update.pubkey, update.slot, update.data), (*account, 7, vec![1, 2, 3, 4]));
+        }
+    }
+
+    rpc.abort();
+}
+
+#[test]
+fn test_default_ws_url_from_rpc_url() {
+    assert_eq!(default_ws_url("https://api.devnet.solana.com"), "wss://api.devnet.solana.com");
+    assert_eq!(default_ws_url("http://127.0.0.1:8899"), "ws://127.0.0.1:8899");
+}
@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::Path;
+
+use crate::market_registry::MarketRegistry;
+use crate::orderbook::{L2Book, MarketFill, OrderBookMirror};
+use crate::recorder::{self, RecordReader, RecordedEvent};
+
+// Событие исторических данных; события идут в порядке времени
+#[derive(Debug, Clone, PartialEq)]
+pub enum BacktestEvent {
+    // Цена пары: строка CSV или сведенная цена записанного тика
+    Price {
+        timestamp_ms: i64,
+        pair: String,
+        price: f64,
+        // Объем сделок в котируемом токене с прошлой цены
+        volume: f64,
+    },
+    // Снимок стакана пары после обновления записанного аккаунта bids или asks
+    Book {
+        timestamp_ms: i64,
+        pair: String,
+        book: L2Book,
+    },
+    // Сделка из записанной event queue
+    Trade { timestamp_ms: i64, fill: MarketFill },
+    // Тик стратегий после всех цен с тем же временем
+    Tick { timestamp_ms: i64 },
+}
+
+impl BacktestEvent {
+    pub fn timestamp_ms(&self) -> i64 {
+        match self {
+            BacktestEvent::Price { timestamp_ms, .. }
+            | BacktestEvent::Book { timestamp_ms, .. }
+            | BacktestEvent::Trade { timestamp_ms, .. }
+            | BacktestEvent::Tick { timestamp_ms } => *timestamp_ms,
+        }
+    }
+}
+
+// Загружает CSV-файл или записи бота (файл .bin или каталог с записями)
+pub fn load(path: &Path, markets: &MarketRegistry) -> Result<Vec<BacktestEvent>, Box<dyn Error>> {
+    if path.extension() == Some(OsStr::new("csv")) {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        parse_csv(&contents)
+    } else {
+        load_recordings(path, markets)
+    }
+}
+
+// CSV с заголовком, timestamp - unix-время в секундах. Тики задаются колонками
+// `timestamp,pair,price[,volume]`, бары - `timestamp,pair,open,high,low,close[,volume]`.
+// Время бара - его начало; цены бара распределяются внутри него (open, затем
+// ближний к open экстремум, дальний и close в последнюю секунду), чтобы
+// стратегия не видела close раньше закрытия бара. Длина бара - шаг между
+// первыми двумя барами пары.
+pub fn parse_csv(contents: &str) -> Result<Vec<BacktestEvent>, Box<dyn Error>> {
+    let mut lines = contents.lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+    let header: Vec<&str> = match lines.next() {
+        Some((_, header)) => header.split(',').map(str::trim).collect(),
+        None => return Err("CSV file is empty".into()),
+    };
+    let column = |name: &str| header.iter().position(|&column| column == name);
+    let timestamp = column("timestamp").ok_or("CSV has no timestamp column")?;
+    let pair = column("pair").ok_or("CSV has no pair column")?;
+    let volume = column("volume");
+    let price_columns = match (column("open"), column("high"), column("low"), column("close"), column("price")) {
+        (Some(open), Some(high), Some(low), Some(close), _) => vec![open, high, low, close],
+        (_, _, _, _, Some(price)) => vec![price],
+        _ => return Err("CSV needs either a price column or open,high,low,close columns".into()),
+    };
+
+    let mut rows = Vec::new();
+    for (index, line) in lines {
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let field = |column: usize| -> Result<&str, Box<dyn Error>> {
+            fields.get(column).copied().ok_or_else(|| format!("line {}: missing column {}", index + 1, header[column]).into())
+        };
+        let number = |column: usize| -> Result<f64, Box<dyn Error>> {
+            field(column)?.parse::<f64>().map_err(|e| format!("line {}: invalid {}: {}", index + 1, header[column], e).into())
+        };
+        let prices = price_columns.iter().map(|&column| number(column)).collect::<Result<Vec<f64>, _>>()?;
+        if prices.iter().any(|&price| !(price.is_finite() && price > 0.0)) {
+            return Err(format!("line {}: prices must be positive", index + 1).into());
+        }
+        rows.push(CsvRow {
+            timestamp: field(timestamp)?.parse().map_err(|e| format!("line {}: invalid timestamp: {}", index + 1, e))?,
+            pair: field(pair)?.to_string(),
+            prices,
+            volume: match volume {
+                Some(volume) => number(volume)?,
+                None => 0.0,
+            },
+        });
+    }
+    rows.sort_by_key(|row| row.timestamp);
+
+    // Длина бара по паре: шаг между первыми двумя барами
+    let mut bar_secs: HashMap<&str, i64> = HashMap::new();
+    let mut first_bar: HashMap<&str, i64> = HashMap::new();
+    for row in rows.iter().filter(|row| row.prices.len() == 4) {
+        match first_bar.get(row.pair.as_str()) {
+            None => {
+                first_bar.insert(&row.pair, row.timestamp);
+            }
+            Some(&first) if row.timestamp > first && !bar_secs.contains_key(row.pair.as_str()) => {
+                bar_secs.insert(&row.pair, row.timestamp - first);
+            }
+            Some(_) => {}
+        }
+    }
+
+    let mut events = Vec::with_capacity(rows.len() * 4);
+    for row in &rows {
+        let start_ms = row.timestamp * 1000;
+        let offsets_ms = match bar_secs.get(row.pair.as_str()) {
+            Some(&secs) if row.prices.len() == 4 => {
+                let last_ms = (secs - 1) * 1000;
+                vec![0, last_ms / 3, last_ms * 2 / 3, last_ms]
+            }
+            _ => vec![0; row.prices.len()],
+        };
+        let path = match row.prices.as_slice() {
+            // Растущий бар сначала проходит минимум, падающий - максимум
+            &[open, high, low, close] if close >= open => vec![open, low, high, close],
+            &[open, high, low, close] => vec![open, high, low, close],
+            path => path.to_vec(),
+        };
+        let last = path.len() - 1;
+        for (step, (&price, &offset_ms)) in path.iter().zip(&offsets_ms).enumerate() {
+            events.push(BacktestEvent::Price {
+                timestamp_ms: start_ms + offset_ms,
+                pair: row.pair.clone(),
+                price,
+                volume: if step == last { row.volume } else { 0.0 },
+            });
+        }
+    }
+    events.sort_by_key(BacktestEvent::timestamp_ms);
+    Ok(with_ticks(events))
+}
+
+struct CsvRow {
+    timestamp: i64,
+    pair: String,
+    prices: Vec<f64>,
+    volume: f64,
+}
+
+// Тик после каждой группы цен с одинаковым временем
+fn with_ticks(prices: Vec<BacktestEvent>) -> Vec<BacktestEvent> {
+    let mut events = Vec::with_capacity(prices.len() * 2);
+    let mut prices = prices.into_iter().peekable();
+    while let Some(event) = prices.next() {
+        let timestamp_ms = event.timestamp_ms();
+        events.push(event);
+        if prices.peek().map(BacktestEvent::timestamp_ms) != Some(timestamp_ms) {
+            events.push(BacktestEvent::Tick { timestamp_ms });
+        }
+    }
+    events
+}
+
+// Записи бота: сведенные цены записанных тиков, снимки стаканов и сделки рынков
+// реестра, восстановленные из записанных аккаунтов. Остальные аккаунты и
+// записанные сигналы пропускаются - сигналы заново генерируют стратегии.
+pub fn load_recordings(path: &Path, markets: &MarketRegistry) -> Result<Vec<BacktestEvent>, Box<dyn Error>> {
+    let mut order_books = OrderBookMirror::new();
+    order_books.watch_registry(markets);
+
+    let mut events = Vec::new();
+    for file in recorder::record_files(path)? {
+        let mut reader = RecordReader::open(&file)?;
+        while let Some(record) = reader.next_record()? {
+            let timestamp_ms = record.timestamp_ms;
+            match record.event {
+                RecordedEvent::Tick { prices } => {
+                    for (pair, price) in prices {
+                        events.push(BacktestEvent::Price { timestamp_ms, pair, price, volume: 0.0 });
+                    }
+                    events.push(BacktestEvent::Tick { timestamp_ms });
+                }
+                RecordedEvent::Account(update) if order_books.is_tracked(&update.pubkey) => {
+                    let fills = order_books.on_account_update(&update.pubkey, &update.data, update.slot)?;
+                    // Отмены записанных ордеров не нужны: свои ордера отменяет симулятор
+                    order_books.take_outs();
+                    events.extend(fills.into_iter().map(|fill| BacktestEvent::Trade { timestamp_ms, fill }));
+                    if let Some((pair, book)) = order_books.book_of_account(&update.pubkey) {
+                        events.push(BacktestEvent::Book { timestamp_ms, pair: pair.to_string(), book: book.clone() });
+                    }
+                }
+                RecordedEvent::Account(_) | RecordedEvent::Signal(_) => {}
+            }
+        }
+    }
+    Ok(events)
+}
+
+#[cfg(test)]
+#[path = "data_test.rs"]
+mod tests;
@@ -0,0 +1,51 @@
+use super::*;
+
+fn prices(events: &[BacktestEvent]) -> Vec<(i64, &str, f64)> {
+    events.iter()
+        .filter_map(|event| match event {
+            BacktestEvent::Price { timestamp_ms, pair, price, .. } => Some((*timestamp_ms, pair.as_str(), *price)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_ticks_grouped_by_timestamp() {
+    let events = parse_csv("timestamp,pair,price,volume\n\
+        2,SOL/USDC,21.0,5\n\
+        1,SOL/USDC,20.0,0\n\
+        1,RAY/USDC,1.5,0\n").unwrap();
+
+    // Строки сортируются по времени, тик идет после всех цен момента
+    assert_eq!(events.len(), 5);
+    assert_eq!(prices(&events), vec![(1000, "SOL/USDC", 20.0), (1000, "RAY/USDC", 1.5), (2000, "SOL/USDC", 21.0)]);
+    assert_eq!(events[2], BacktestEvent::Tick { timestamp_ms: 1000 });
+    assert_eq!(events[4], BacktestEvent::Tick { timestamp_ms: 2000 });
+    match &events[3] {
+        BacktestEvent::Price { volume, .. } => assert_eq!(*volume, 5.0),
+        event => panic!("unexpected event {:?}", event),
+    }
+}
+
+#[test]
+fn test_bars_spread_inside_bar() {
+    let events = parse_csv("timestamp,pair,open,high,low,close,volume\n\
+        0,SOL/USDC,20,22,19,21,100\n\
+        60,SOL/USDC,21,21.5,18,19,50\n").unwrap();
+
+    // Растущий бар проходит минимум раньше максимума, падающий - наоборот;
+    // close приходится на последнюю секунду бара
+    assert_eq!(prices(&events), vec![
+        (0, "SOL/USDC", 20.0), (19_666, "SOL/USDC", 19.0), (39_333, "SOL/USDC", 22.0), (59_000, "SOL/USDC", 21.0),
+        (60_000, "SOL/USDC", 21.0), (79_666, "SOL/USDC", 21.5), (99_333, "SOL/USDC", 18.0), (119_000, "SOL/USDC", 19.0),
+    ]);
+    assert_eq!(events.iter().filter(|event| matches!(event, BacktestEvent::Tick { .. })).count(), 8);
+}
+
+#[test]
+fn test_invalid_csv_rejected() {
+    assert!(parse_csv("").is_err());
+    assert!(parse_csv("timestamp,pair,volume\n1,SOL/USDC,5\n").is_err());
+    assert!(parse_csv("timestamp,pair,price\n1,SOL/USDC,-2\n").is_err());
+    assert!(parse_csv("timestamp,pair,price\n1,SOL/USDC\n").is_err());
+}
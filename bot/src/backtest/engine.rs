@@ -0,0 +1,268 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+use log::debug;
+use solana_sdk::pubkey::Pubkey;
+
+use super::data::BacktestEvent;
+use super::exchange::{ExchangeConfig, ExchangeEvent, ExchangeFill, SimulatedExchange, SLOT_MS};
+use super::stats::{EquityPoint, Stats, Trade, TradeLedger};
+use crate::candles::CandleBuilder;
+use crate::jito_integration::TradeAction;
+use crate::market_registry::MarketRegistry;
+use crate::orderbook::MarketFill;
+use crate::price_feed::PriceData;
+use crate::price_history::{PriceHistory, PricePoint};
+use crate::strategies::runner::{self, StrategyRunner};
+use crate::strategies::{RunnerConfig, StrategyRegistry};
+
+// Сколько точек истории цен на пару видят стратегии, как и в живом фиде
+const HISTORY_CAPACITY: usize = 1000;
+
+pub struct BacktestReport {
+    pub equity: Vec<EquityPoint>,
+    pub fills: Vec<ExchangeFill>,
+    pub trades: Vec<Trade>,
+    pub stats: Stats,
+}
+
+impl BacktestReport {
+    // Кривая капитала, исполнения, сделки и статистика в каталог `dir`
+    pub fn write(&self, dir: &Path) -> Result<(), Box<dyn Error>> {
+        fs::create_dir_all(dir)?;
+
+        let mut equity = BufWriter::new(File::create(dir.join("equity.csv"))?);
+        writeln!(equity, "timestamp_ms,equity,in_market")?;
+        for point in &self.equity {
+            writeln!(equity, "{},{:.6},{}", point.timestamp_ms, point.equity, point.in_market)?;
+        }
+        equity.flush()?;
+
+        let mut fills = BufWriter::new(File::create(dir.join("fills.csv"))?);
+        writeln!(fills, "timestamp,strategy_id,pair,action,price,size,fee,maker")?;
+        for fill in &self.fills {
+            writeln!(
+                fills, "{},{},{},{:?},{},{},{:.6},{}",
+                fill.fill.timestamp, fill.strategy_id, fill.fill.pair, fill.fill.action,
+                fill.fill.price, fill.fill.size, fill.fill.fee, fill.maker
+            )?;
+        }
+        fills.flush()?;
+
+        let mut trades = BufWriter::new(File::create(dir.join("trades.csv"))?);
+        writeln!(trades, "strategy_id,pair,side,size,entry_price,exit_price,entry_time,exit_time,fees,pnl")?;
+        for trade in &self.trades {
+            writeln!(
+                trades, "{},{},{:?},{},{},{},{},{},{:.6},{:.6}",
+                trade.strategy_id, trade.pair, trade.side, trade.size, trade.entry_price,
+                trade.exit_price, trade.entry_time, trade.exit_time, trade.fees, trade.pnl
+            )?;
+        }
+        trades.flush()?;
+
+        fs::write(dir.join("stats.json"), serde_json::to_string_pretty(&self.stats)?)?;
+        Ok(())
+    }
+}
+
+// Прогоняет исторические события через стратегии так же, как основной цикл:
+// цены обновляют бары и историю, тик вызывает on_tick и таймеры, одобренные
+// сигналы уходят на симулированную биржу, а ее исполнения возвращаются в
+// капитал экземпляров и в on_fill.
+pub struct Backtest {
+    runner: StrategyRunner,
+    markets: Arc<MarketRegistry>,
+    exchange: SimulatedExchange,
+    candles: CandleBuilder,
+    history: HashMap<String, PriceHistory>,
+    // Объем, накопленный с последнего тика
+    pending_volume: HashMap<String, f64>,
+    ledger: TradeLedger,
+    equity: Vec<EquityPoint>,
+    fills: Vec<ExchangeFill>,
+    fees: f64,
+    // Номер события мейкера для исполнений стоящих ордеров
+    seq_num: u64,
+}
+
+impl Backtest {
+    pub fn new(
+        config: &RunnerConfig,
+        strategies: &StrategyRegistry,
+        markets: Arc<MarketRegistry>,
+        exchange: ExchangeConfig,
+    ) -> Result<Self, Box<dyn Error>> {
+        Ok(Self {
+            runner: StrategyRunner::new(config, strategies, markets.clone(), Pubkey::default())?,
+            exchange: SimulatedExchange::new(exchange, markets.clone()),
+            markets,
+            candles: CandleBuilder::new(),
+            history: HashMap::new(),
+            pending_volume: HashMap::new(),
+            ledger: TradeLedger::new(),
+            equity: Vec::new(),
+            fills: Vec::new(),
+            fees: 0.0,
+            seq_num: 0,
+        })
+    }
+
+    pub fn run(mut self, events: &[BacktestEvent], period_secs: i64) -> Result<BacktestReport, Box<dyn Error>> {
+        for event in events {
+            match event {
+                BacktestEvent::Price { timestamp_ms, pair, price, volume } => {
+                    // Ордера, дошедшие до биржи раньше этой цены, исполняются по предыдущей
+                    let executed = self.exchange.execute_due(timestamp_ms - 1)?;
+                    self.apply(executed, *timestamp_ms)?;
+                    let executed = self.exchange.on_price(pair, *price, *timestamp_ms);
+                    self.apply(executed, *timestamp_ms)?;
+
+                    let timestamp = timestamp_ms / 1000;
+                    self.candles.on_fill(pair, timestamp, *price, *volume);
+                    *self.pending_volume.entry(pair.clone()).or_insert(0.0) += volume;
+                }
+                BacktestEvent::Book { timestamp_ms, pair, book } => {
+                    let executed = self.exchange.execute_due(timestamp_ms - 1)?;
+                    self.apply(executed, *timestamp_ms)?;
+                    let executed = self.exchange.on_book(pair, book.clone(), *timestamp_ms);
+                    self.apply(executed, *timestamp_ms)?;
+                }
+                BacktestEvent::Trade { timestamp_ms, fill } => {
+                    let executed = self.exchange.execute_due(timestamp_ms - 1)?;
+                    self.apply(executed, *timestamp_ms)?;
+                    let executed = self.exchange.on_trade(fill, *timestamp_ms);
+                    self.apply(executed, *timestamp_ms)?;
+
+                    // Объем сделки учитывается один раз - по событию тейкера, как в живом фиде
+                    if !fill.maker {
+                        let pair = fill.pair.to_string();
+                        let volume = fill.price * fill.size;
+                        self.candles.on_fill(&pair, timestamp_ms / 1000, fill.price, volume);
+                        *self.pending_volume.entry(pair).or_insert(0.0) += volume;
+                    }
+                }
+                BacktestEvent::Tick { timestamp_ms } => self.on_tick(*timestamp_ms)?,
+            }
+        }
+
+        let stats = Stats::compute(&self.equity, self.ledger.trades(), self.fees, period_secs);
+        Ok(BacktestReport {
+            equity: self.equity,
+            fills: self.fills,
+            trades: self.ledger.into_trades(),
+            stats,
+        })
+    }
+
+    fn on_tick(&mut self, timestamp_ms: i64) -> Result<(), Box<dyn Error>> {
+        let executed = self.exchange.execute_due(timestamp_ms)?;
+        self.apply(executed, timestamp_ms)?;
+
+        let timestamp = timestamp_ms / 1000;
+        let slot = (timestamp_ms / SLOT_MS) as u64;
+        for (pair, &price) in self.exchange.prices() {
+            self.history.entry(pair.clone())
+                .or_insert_with(|| PriceHistory::new(HISTORY_CAPACITY))
+                .push(PricePoint {
+                    price,
+                    confidence: 0.0,
+                    slot,
+                    timestamp,
+                    volume: self.pending_volume.remove(pair).unwrap_or(0.0),
+                });
+        }
+        self.candles.advance(timestamp);
+
+        let price_data = PriceData::simulated(self.exchange.prices().clone(), self.history.clone(), &self.candles, self.exchange.order_books(), slot);
+
+        let mut signals = self.runner.on_tick(&price_data);
+        signals.extend(self.runner.on_timer(timestamp));
+        for bundle in runner::bundles(signals) {
+            for leg in &bundle {
+                self.runner.on_submitted(leg)?;
+            }
+            self.exchange.submit(bundle, timestamp_ms);
+        }
+
+        let prices = self.exchange.prices();
+        let equity: f64 = self.runner.allocations().map(|(_, allocation)| allocation.equity(prices)).sum();
+        let in_market = self.runner.allocations()
+            .any(|(_, allocation)| allocation.positions.values().any(|position| position.abs() > 1e-9));
+        self.equity.push(EquityPoint { timestamp_ms, equity, in_market });
+        Ok(())
+    }
+
+    // Возвращает события биржи в капитал экземпляров и в стратегии
+    fn apply(&mut self, events: Vec<ExchangeEvent>, timestamp_ms: i64) -> Result<(), Box<dyn Error>> {
+        for event in events {
+            if let Some(fill) = dispatch_event(&mut self.runner, &self.markets, event, &mut self.seq_num, timestamp_ms)? {
+                self.ledger.record(&fill.strategy_id, &fill.fill);
+                self.fees += fill.fill.fee;
+                self.fills.push(fill);
+            }
+        }
+        Ok(())
+    }
+}
+
+// Передает событие симулированной биржи экземплярам стратегий так же, как
+// основной цикл передает исполнения бандлов и события мейкера из event queue.
+// `seq_num` - счетчик номеров синтезированных событий мейкера. Возвращает
+// исполнение, если событие было сделкой.
+pub fn dispatch_event(
+    runner: &mut StrategyRunner,
+    markets: &MarketRegistry,
+    event: ExchangeEvent,
+    seq_num: &mut u64,
+    timestamp_ms: i64,
+) -> Result<Option<ExchangeFill>, Box<dyn Error>> {
+    let timestamp = timestamp_ms / 1000;
+    match event {
+        ExchangeEvent::Resting(signal) => runner.on_executed(&signal, timestamp)?,
+        ExchangeEvent::Canceled { client_order_id } => runner.on_canceled(client_order_id),
+        ExchangeEvent::Rejected { signal, reason } => {
+            debug!("{} signal rejected: {} ({})", signal.strategy_id, reason, signal.signal);
+            runner.on_reject(&signal);
+        }
+        ExchangeEvent::Filled(fill) => {
+            if fill.maker {
+                // Стоящий ордер исполняется через события мейкера, как в живом цикле
+                *seq_num += 1;
+                let market = markets.get(&fill.fill.pair)?;
+                let market_fill = MarketFill {
+                    pair: fill.fill.pair.clone(),
+                    market: market.dex_market,
+                    seq_num: *seq_num,
+                    slot: (timestamp_ms / SLOT_MS) as u64,
+                    price: fill.fill.price,
+                    size: fill.fill.size,
+                    taker_action: opposite(fill.fill.action),
+                    maker: true,
+                    owner: market.open_orders.unwrap_or_default(),
+                    client_order_id: fill.fill.client_order_id,
+                    fee: fill.fill.fee,
+                };
+                runner.on_own_fills(&[market_fill], timestamp)?;
+            } else {
+                runner.on_taker_fill(&fill.strategy_id, &fill.fill)?;
+            }
+            return Ok(Some(fill));
+        }
+    }
+    Ok(None)
+}
+
+fn opposite(action: TradeAction) -> TradeAction {
+    match action {
+        TradeAction::Buy => TradeAction::Sell,
+        TradeAction::Sell => TradeAction::Buy,
+    }
+}
+
+#[cfg(test)]
+#[path = "engine_test.rs"]
+mod tests;
@@ -0,0 +1,84 @@
+use super::*;
+use crate::backtest::data::parse_csv;
+use crate::jito_integration::TradingSignal;
+use crate::market_registry::test_registry;
+use crate::strategies::registry::parse_params;
+use crate::strategies::{Strategy, StrategyContext};
+use serde::Deserialize;
+use serde_json::Value;
+
+// Покупает 1 SOL без позиции и продает позицию, когда цена дошла до цели
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct BuyThenSell {
+    target: f64,
+}
+
+impl Strategy for BuyThenSell {
+    fn name(&self) -> &str {
+        "buy_then_sell"
+    }
+
+    fn on_tick(&mut self, ctx: &StrategyContext, price_data: &PriceData) -> Result<Vec<TradingSignal>, Box<dyn Error>> {
+        let pair = &ctx.pairs[0];
+        let price = price_data.get_price(pair)?;
+        let position = ctx.position(pair);
+        let signal = if position <= 0.0 {
+            ctx.build_signal(pair, TradeAction::Buy, 1.0, price * 1.01)?
+        } else if price >= self.target {
+            ctx.build_signal(pair, TradeAction::Sell, position, price * 0.99)?
+        } else {
+            None
+        };
+        Ok(signal.into_iter().collect())
+    }
+}
+
+fn backtest(capital: f64, exchange: ExchangeConfig) -> Backtest {
+    let mut strategies = StrategyRegistry::new();
+    strategies.register("buy_then_sell", |params: &Value, _| Ok(Box::new(parse_params::<BuyThenSell>(params)?)));
+    let config = RunnerConfig::from_json(&format!(r#"{{"strategies": [
+        {{"id": "swing", "strategy": "buy_then_sell", "pairs": ["SOL/USDC"], "capital": {}, "params": {{"target": 22.0}}}}
+    ]}}"#, capital)).unwrap();
+    Backtest::new(&config, &strategies, Arc::new(test_registry()), exchange).unwrap()
+}
+
+#[test]
+fn test_round_trip_through_simulated_exchange() {
+    let events = parse_csv("timestamp,pair,price\n\
+        0,SOL/USDC,20\n\
+        1,SOL/USDC,20.5\n\
+        2,SOL/USDC,22\n\
+        3,SOL/USDC,21\n").unwrap();
+    let exchange = ExchangeConfig { latency_ms: 500, slippage_bps: 0.0, ..ExchangeConfig::default() };
+    let report = backtest(100.0, exchange).run(&events, 1).unwrap();
+
+    // Покупка с тика 0 исполняется по цене до прихода ордера (20), продажа с тика 2 -
+    // по 22; сигнал на покупку с тика 3 не дошел до биржи до конца данных
+    assert_eq!(report.fills.len(), 2);
+    assert_eq!((report.fills[0].fill.price, report.fills[1].fill.price), (20.0, 22.0));
+    assert_eq!(report.trades.len(), 1);
+    let fees = (20.0 + 22.0) * 0.001;
+    assert!((report.trades[0].pnl - (2.0 - fees)).abs() < 1e-9);
+    assert!((report.stats.final_equity - (102.0 - fees)).abs() < 1e-9);
+    assert!((report.stats.fees - fees).abs() < 1e-9);
+    assert_eq!(report.stats.win_rate, 1.0);
+
+    // Кривая капитала по тикам: позиция открыта с тика 1 по тик 2
+    let in_market: Vec<bool> = report.equity.iter().map(|point| point.in_market).collect();
+    assert_eq!(in_market, vec![false, true, true, false]);
+}
+
+#[test]
+fn test_orders_in_flight_reserve_capital() {
+    let events = parse_csv("timestamp,pair,price\n\
+        0,SOL/USDC,20\n\
+        1,SOL/USDC,20\n\
+        2,SOL/USDC,20\n\
+        3,SOL/USDC,20\n").unwrap();
+    let exchange = ExchangeConfig { latency_ms: 1_500, slippage_bps: 0.0, ..ExchangeConfig::default() };
+    let report = backtest(30.0, exchange).run(&events, 1).unwrap();
+
+    // Покупка с тика 0 еще в пути на тике 1: ее резерв не оставляет капитала на вторую
+    assert_eq!(report.fills.len(), 1);
+}
@@ -0,0 +1,410 @@
+use std::collections::{HashMap, VecDeque};
+use std::error::Error;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::jito_integration::{OrderAction, TimeInForce, TradeAction};
+use crate::market_registry::MarketRegistry;
+use crate::matching::{MatchingEngine, SimFill, SimOrder};
+use crate::orderbook::{L2Book, MarketFill};
+use crate::price_feed::TokenPair;
+use crate::quantization::Rounding;
+use crate::strategies::runner::StrategySignal;
+use crate::strategies::Fill;
+
+// Средняя длительность слота Solana: по ней время переводится в слоты
+pub const SLOT_MS: i64 = 400;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ExchangeConfig {
+    // Задержка от сигнала до исполнения на бирже
+    pub latency_ms: i64,
+    // Проскальзывание тейкера от текущей цены
+    pub slippage_bps: f64,
+    // Дополнительное проскальзывание на каждые 1000 единиц котируемого токена в ордере
+    pub impact_bps_per_1000: f64,
+    // Комиссия тейкера; по умолчанию - комиссия рынка из реестра
+    pub taker_fee_bps: Option<f64>,
+    // Комиссия мейкера; отрицательная - ребейт
+    pub maker_fee_bps: f64,
+}
+
+impl Default for ExchangeConfig {
+    fn default() -> Self {
+        Self {
+            latency_ms: 400,
+            slippage_bps: 5.0,
+            impact_bps_per_1000: 0.0,
+            taker_fee_bps: None,
+            maker_fee_bps: 0.0,
+        }
+    }
+}
+
+// Сделка симулированной биржи
+#[derive(Debug, Clone)]
+pub struct ExchangeFill {
+    pub strategy_id: String,
+    pub fill: Fill,
+    // Исполнение стоящего ордера
+    pub maker: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum ExchangeEvent {
+    // Ордер встал в стакан
+    Resting(StrategySignal),
+    // Стоящий ордер снят
+    Canceled { client_order_id: u64 },
+    Filled(ExchangeFill),
+    // Ордер не исполнен: истек срок, цена ушла за предел, post-only пересек спред
+    Rejected { signal: StrategySignal, reason: String },
+}
+
+// Ордер в стакане симулированной биржи, в UI-единицах
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RestingOrder {
+    pub strategy_id: String,
+    pub pair: TokenPair,
+    pub client_order_id: u64,
+    pub action: TradeAction,
+    pub price: f64,
+    pub size: f64,
+    pub fee_bps: f64,
+}
+
+// Биржа для бэктеста: ордера доходят до нее с задержкой, тейкер исполняется
+// по текущей цене с проскальзыванием, стоящий ордер - по своей цене, когда
+// цена пары проходит сквозь него. Если для пары есть записанный стакан, ордера
+// исполняются против его глубины с учетом очереди (см. `MatchingEngine`).
+// Ноги бандла исполняются вместе или не исполняются вовсе.
+pub struct SimulatedExchange {
+    config: ExchangeConfig,
+    markets: Arc<MarketRegistry>,
+    // Последние цены по ключу пары
+    prices: HashMap<String, f64>,
+    // Бандлы в пути со временем прихода на биржу
+    pending: VecDeque<(i64, Vec<StrategySignal>)>,
+    // Стоящие ордера в порядке выставления
+    resting: Vec<RestingOrder>,
+    // Стаканы пар и очереди стоящих в них ордеров
+    matching: MatchingEngine,
+    // Известный слот и его время: от них время переводится в слоты
+    slot_anchor: (u64, i64),
+}
+
+impl SimulatedExchange {
+    pub fn new(config: ExchangeConfig, markets: Arc<MarketRegistry>) -> Self {
+        Self {
+            markets,
+            prices: HashMap::new(),
+            pending: VecDeque::new(),
+            resting: Vec::new(),
+            matching: MatchingEngine::new(config.taker_fee_bps, config.maker_fee_bps),
+            slot_anchor: (0, 0),
+            config,
+        }
+    }
+
+    pub fn prices(&self) -> &HashMap<String, f64> {
+        &self.prices
+    }
+
+    pub fn resting_orders(&self) -> &[RestingOrder] {
+        &self.resting
+    }
+
+    // Отправленные бандлы, которые еще не дошли до биржи
+    pub fn pending_bundles(&self) -> impl Iterator<Item = &[StrategySignal]> {
+        self.pending.iter().map(|(_, bundle)| bundle.as_slice())
+    }
+
+    // Возвращает сохраненные стоящие ордера. Место в очереди записанного стакана
+    // не восстанавливается: такой ордер исполняется, когда цена проходит сквозь него.
+    pub fn restore_resting(&mut self, orders: Vec<RestingOrder>) {
+        self.resting.extend(orders);
+    }
+
+    // Привязывает время к слотам кластера, чтобы срок действия сигналов,
+    // выставленный по живому слоту, проверялся по нему же. Без привязки
+    // слот - время, деленное на SLOT_MS.
+    pub fn sync_slot(&mut self, slot: u64, timestamp_ms: i64) {
+        self.slot_anchor = (slot, timestamp_ms);
+    }
+
+    // Стаканы с учетом объема, снятого ордерами бэктеста
+    pub fn order_books(&self) -> HashMap<String, L2Book> {
+        self.matching.books().clone()
+    }
+
+    // Новый снимок стакана пары исполняет стоящие ордера, до которых дошла очередь
+    pub fn on_book(&mut self, pair: &str, book: L2Book, timestamp_ms: i64) -> Vec<ExchangeEvent> {
+        let fills = self.matching.on_book(pair, book);
+        self.maker_events(fills, timestamp_ms)
+    }
+
+    // Сделка на рынке продвигает очередь стоящих ордеров по ее цене
+    pub fn on_trade(&mut self, trade: &MarketFill, timestamp_ms: i64) -> Vec<ExchangeEvent> {
+        let fills = self.matching.on_trade(trade);
+        self.maker_events(fills, timestamp_ms)
+    }
+
+    pub fn submit(&mut self, bundle: Vec<StrategySignal>, timestamp_ms: i64) {
+        if !bundle.is_empty() {
+            self.pending.push_back((timestamp_ms + self.config.latency_ms, bundle));
+        }
+    }
+
+    // Новая цена пары исполняет стоящие ордера, сквозь которые она прошла
+    pub fn on_price(&mut self, pair: &str, price: f64, timestamp_ms: i64) -> Vec<ExchangeEvent> {
+        self.prices.insert(pair.to_string(), price);
+
+        let mut events = Vec::new();
+        let mut index = 0;
+        while index < self.resting.len() {
+            let order = &self.resting[index];
+            // Ордера в записанном стакане исполняются по его очереди
+            let crossed = order.pair.to_string() == pair && !self.matching.has_order(order.client_order_id) && match order.action {
+                TradeAction::Buy => price < order.price,
+                TradeAction::Sell => price > order.price,
+            };
+            if !crossed {
+                index += 1;
+                continue;
+            }
+            let order = self.resting.remove(index);
+            events.push(ExchangeEvent::Filled(ExchangeFill {
+                fill: Fill {
+                    pair: order.pair,
+                    client_order_id: order.client_order_id,
+                    action: order.action,
+                    price: order.price,
+                    size: order.size,
+                    fee: order.price * order.size * order.fee_bps / 10_000.0,
+                    timestamp: timestamp_ms / 1000,
+                },
+                strategy_id: order.strategy_id,
+                maker: true,
+            }));
+        }
+        events
+    }
+
+    // Исполняет бандлы, дошедшие до биржи к `timestamp_ms` включительно
+    pub fn execute_due(&mut self, timestamp_ms: i64) -> Result<Vec<ExchangeEvent>, Box<dyn Error>> {
+        let mut events = Vec::new();
+        while let Some((arrives_at_ms, bundle)) = self.pending.pop_front() {
+            if arrives_at_ms > timestamp_ms {
+                self.pending.push_front((arrives_at_ms, bundle));
+                break;
+            }
+            events.extend(self.execute_bundle(bundle, arrives_at_ms)?);
+        }
+        Ok(events)
+    }
+
+    fn execute_bundle(&mut self, bundle: Vec<StrategySignal>, timestamp_ms: i64) -> Result<Vec<ExchangeEvent>, Box<dyn Error>> {
+        let (anchor_slot, anchor_ms) = self.slot_anchor;
+        let slot = (anchor_slot as i64 + (timestamp_ms - anchor_ms).div_euclid(SLOT_MS)).max(0) as u64;
+        if let Some(expired) = bundle.iter().find(|signal| signal.signal.is_expired(slot)) {
+            let reason = format!("expired at slot {} (expiry {:?})", slot, expired.signal.expiry_slot);
+            return Ok(reject_all(bundle, &reason));
+        }
+
+        // Ноги снимают объем с копии стаканов, которая принимается, только если бандл исполнен
+        let mut matching = self.matching.clone();
+        let mut outcomes = Vec::with_capacity(bundle.len());
+        for signal in &bundle {
+            outcomes.push(self.match_order(signal, &mut matching, timestamp_ms)?);
+        }
+        // Бандл атомарен: одна неисполненная нога отменяет все
+        if bundle.len() > 1 {
+            if let Some(reason) = outcomes.iter().find_map(|outcome| match outcome {
+                Outcome::Rejected(reason) => Some(reason.clone()),
+                _ => None,
+            }) {
+                return Ok(reject_all(bundle, &format!("bundle leg rejected: {}", reason)));
+            }
+        }
+        self.matching = matching;
+
+        let mut events = Vec::new();
+        for (signal, outcome) in bundle.into_iter().zip(outcomes) {
+            let cancel_id = match signal.signal.order_action {
+                OrderAction::Place => None,
+                OrderAction::Cancel => Some(signal.signal.client_order_id),
+                OrderAction::Replace { cancel_client_order_id } => Some(cancel_client_order_id),
+            };
+            if let Some(cancel_id) = cancel_id {
+                if let Some(index) = self.resting.iter().position(|order| order.client_order_id == cancel_id) {
+                    self.resting.remove(index);
+                    self.matching.cancel(cancel_id);
+                    events.push(ExchangeEvent::Canceled { client_order_id: cancel_id });
+                }
+            }
+
+            match outcome {
+                Outcome::None => {}
+                Outcome::Rejected(reason) => events.push(ExchangeEvent::Rejected { signal, reason }),
+                Outcome::Taker { price, size, fee, rest } => {
+                    events.push(ExchangeEvent::Filled(ExchangeFill {
+                        fill: Fill {
+                            pair: signal.pair.clone(),
+                            client_order_id: signal.signal.client_order_id,
+                            action: signal.signal.action,
+                            price,
+                            size,
+                            fee,
+                            timestamp: timestamp_ms / 1000,
+                        },
+                        strategy_id: signal.strategy_id.clone(),
+                        maker: false,
+                    }));
+                    // Неисполненный остаток лимитного ордера встает в стакан
+                    if let Some(order) = rest {
+                        let quantizer = self.markets.get(&signal.pair)?.quantizer();
+                        let mut remainder = signal;
+                        remainder.signal.amount = quantizer.size_to_native(quantizer.size_from_ui(order.size, Rounding::Nearest)?)?;
+                        self.resting.push(order);
+                        events.push(ExchangeEvent::Resting(remainder));
+                    }
+                }
+                Outcome::Rest(order) => {
+                    self.resting.push(order);
+                    events.push(ExchangeEvent::Resting(signal));
+                }
+            }
+        }
+        Ok(events)
+    }
+
+    // Что произойдет с ордером по текущей цене или стакану; меняется только
+    // переданная копия стаканов
+    fn match_order(&self, signal: &StrategySignal, matching: &mut MatchingEngine, timestamp_ms: i64) -> Result<Outcome, Box<dyn Error>> {
+        if signal.signal.order_action == OrderAction::Cancel {
+            return Ok(Outcome::None);
+        }
+        let market = self.markets.get(&signal.pair)?;
+        let quantizer = market.quantizer();
+        let size = quantizer.size_native_to_ui(signal.signal.amount);
+        let limit = quantizer.price_native_to_ui(signal.signal.limit_price());
+        let resting_order = |size: f64| RestingOrder {
+            strategy_id: signal.strategy_id.clone(),
+            pair: signal.pair.clone(),
+            client_order_id: signal.signal.client_order_id,
+            action: signal.signal.action,
+            price: limit,
+            size,
+            fee_bps: self.config.maker_fee_bps,
+        };
+
+        let pair = signal.pair.to_string();
+        if matching.book(&pair).is_some() {
+            let placement = matching.place(&pair, &SimOrder {
+                client_order_id: signal.signal.client_order_id,
+                action: signal.signal.action,
+                limit,
+                size,
+                resting: signal.signal.is_resting(),
+                post_only: signal.signal.time_in_force == TimeInForce::PostOnly,
+            })?;
+            if let Some(reason) = placement.rejected {
+                return Ok(Outcome::Rejected(reason));
+            }
+            let rest = if placement.rested > 0.0 { Some(resting_order(placement.rested)) } else { None };
+            return Ok(match (placement.taker, rest) {
+                (Some(fill), rest) => Outcome::Taker { price: fill.price, size: fill.size, fee: fill.fee, rest },
+                (None, Some(order)) => Outcome::Rest(order),
+                (None, None) => Outcome::None,
+            });
+        }
+
+        let market_price = match self.prices.get(&pair) {
+            Some(&price) => price,
+            None => return Ok(Outcome::Rejected(format!("no price for {} at {}", signal.pair, timestamp_ms))),
+        };
+        let crosses = match signal.signal.action {
+            TradeAction::Buy => market_price <= limit,
+            TradeAction::Sell => market_price >= limit,
+        };
+
+        if signal.signal.is_resting() && !crosses {
+            return Ok(Outcome::Rest(resting_order(size)));
+        }
+        if signal.signal.time_in_force == TimeInForce::PostOnly && signal.signal.is_resting() {
+            return Ok(Outcome::Rejected("post-only order would cross the spread".to_string()));
+        }
+
+        let slippage = (self.config.slippage_bps + self.config.impact_bps_per_1000 * market_price * size / 1000.0) / 10_000.0;
+        let price = match signal.signal.action {
+            TradeAction::Buy => market_price * (1.0 + slippage),
+            TradeAction::Sell => market_price * (1.0 - slippage),
+        };
+        // Лимитный ордер, пересекший спред, исполняется не хуже своей цены
+        let price = match signal.signal.action {
+            TradeAction::Buy if signal.signal.is_resting() => price.min(limit),
+            TradeAction::Sell if signal.signal.is_resting() => price.max(limit),
+            TradeAction::Buy if price > limit => return Ok(Outcome::Rejected(format!("price {:.6} above limit {:.6}", price, limit))),
+            TradeAction::Sell if price < limit => return Ok(Outcome::Rejected(format!("price {:.6} below limit {:.6}", price, limit))),
+            _ => price,
+        };
+        let fee_bps = self.config.taker_fee_bps.unwrap_or(market.taker_fee_bps as f64);
+        Ok(Outcome::Taker {
+            price,
+            size,
+            fee: price * size * fee_bps / 10_000.0,
+            rest: None,
+        })
+    }
+
+    // Исполнения стоящих ордеров из стакана; полностью исполненные снимаются
+    fn maker_events(&mut self, fills: Vec<SimFill>, timestamp_ms: i64) -> Vec<ExchangeEvent> {
+        let mut events = Vec::new();
+        for fill in fills {
+            let index = match self.resting.iter().position(|order| order.client_order_id == fill.client_order_id) {
+                Some(index) => index,
+                None => continue,
+            };
+            let order = &mut self.resting[index];
+            order.size -= fill.size;
+            events.push(ExchangeEvent::Filled(ExchangeFill {
+                fill: Fill {
+                    pair: order.pair.clone(),
+                    client_order_id: fill.client_order_id,
+                    action: fill.action,
+                    price: fill.price,
+                    size: fill.size,
+                    fee: fill.fee,
+                    timestamp: timestamp_ms / 1000,
+                },
+                strategy_id: order.strategy_id.clone(),
+                maker: true,
+            }));
+            if !self.matching.has_order(fill.client_order_id) {
+                self.resting.remove(index);
+            }
+        }
+        events
+    }
+}
+
+enum Outcome {
+    // Отмена: снимается только стоящий ордер
+    None,
+    Rejected(String),
+    // Исполнение тейкера; остаток лимитного ордера может встать в стакан
+    Taker { price: f64, size: f64, fee: f64, rest: Option<RestingOrder> },
+    Rest(RestingOrder),
+}
+
+fn reject_all(bundle: Vec<StrategySignal>, reason: &str) -> Vec<ExchangeEvent> {
+    bundle.into_iter()
+        .map(|signal| ExchangeEvent::Rejected { signal, reason: reason.to_string() })
+        .collect()
+}
+
+#[cfg(test)]
+#[path = "exchange_test.rs"]
+mod tests;
@@ -0,0 +1,140 @@
+use super::*;
+use crate::market_registry::test_registry;
+use crate::strategies::{bundle, Allocation, StrategyContext};
+use crate::jito_integration::TradingSignal;
+use solana_sdk::pubkey::Pubkey;
+
+fn exchange(config: ExchangeConfig) -> SimulatedExchange {
+    SimulatedExchange::new(config, Arc::new(test_registry()))
+}
+
+// Сигнал экземпляра "test" по SOL/USDC, собранный так же, как в стратегиях
+fn signal(build: impl Fn(&StrategyContext, &TokenPair) -> Option<TradingSignal>) -> StrategySignal {
+    let markets = test_registry();
+    let pairs = [TokenPair::new("SOL", "USDC")];
+    let allocation = Allocation::new(1000.0);
+    let ctx = StrategyContext { id: "test", pairs: &pairs, markets: &markets, allocation: &allocation, payer: Pubkey::new_unique() };
+    StrategySignal {
+        strategy_id: "test".to_string(),
+        pair: pairs[0].clone(),
+        signal: build(&ctx, &pairs[0]).unwrap(),
+    }
+}
+
+fn fills(events: &[ExchangeEvent]) -> Vec<(f64, f64, bool)> {
+    events.iter()
+        .filter_map(|event| match event {
+            ExchangeEvent::Filled(fill) => Some((fill.fill.price, fill.fill.fee, fill.maker)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_taker_fills_after_latency_with_slippage() {
+    let mut exchange = exchange(ExchangeConfig { latency_ms: 500, slippage_bps: 10.0, ..ExchangeConfig::default() });
+    exchange.on_price("SOL/USDC", 20.0, 0);
+    exchange.submit(vec![signal(|ctx, pair| ctx.build_signal(pair, TradeAction::Buy, 1.0, 20.1).unwrap())], 0);
+
+    // До прихода на биржу ордер не исполняется
+    assert!(exchange.execute_due(499).unwrap().is_empty());
+    exchange.on_price("SOL/USDC", 20.05, 450);
+    let events = exchange.execute_due(500).unwrap();
+    let (price, fee, maker) = fills(&events)[0];
+    assert!((price - 20.05 * 1.001).abs() < 1e-9);
+    // Комиссия тейкера рынка - 10 bps
+    assert!((fee - price * 0.001).abs() < 1e-9);
+    assert!(!maker);
+
+    // Цена ушла выше предела: IOC не исполняется
+    exchange.on_price("SOL/USDC", 20.2, 600);
+    exchange.submit(vec![signal(|ctx, pair| ctx.build_signal(pair, TradeAction::Buy, 1.0, 20.1).unwrap())], 600);
+    assert!(matches!(exchange.execute_due(1_100).unwrap().as_slice(), [ExchangeEvent::Rejected { .. }]));
+}
+
+#[test]
+fn test_resting_order_fills_when_price_trades_through() {
+    let mut exchange = exchange(ExchangeConfig { latency_ms: 0, maker_fee_bps: -2.0, ..ExchangeConfig::default() });
+    exchange.on_price("SOL/USDC", 20.0, 0);
+    exchange.submit(vec![signal(|ctx, pair| ctx.quote_signal(pair, TradeAction::Buy, 1.0, 19.9).unwrap())], 0);
+    assert!(matches!(exchange.execute_due(0).unwrap().as_slice(), [ExchangeEvent::Resting(_)]));
+
+    // Касание цены ордера не исполняет его, проход сквозь - исполняет по цене ордера с ребейтом
+    assert!(exchange.on_price("SOL/USDC", 19.9, 1_000).is_empty());
+    let events = exchange.on_price("SOL/USDC", 19.8, 2_000);
+    let (price, fee, maker) = fills(&events)[0];
+    assert!((price - 19.9).abs() < 1e-9 && (fee + 19.9 * 0.0002).abs() < 1e-9 && maker);
+
+    // Post-only ордер, пересекающий спред, отклоняется
+    exchange.submit(vec![signal(|ctx, pair| ctx.quote_signal(pair, TradeAction::Buy, 1.0, 20.0).unwrap())], 2_000);
+    assert!(matches!(exchange.execute_due(2_000).unwrap().as_slice(), [ExchangeEvent::Rejected { .. }]));
+}
+
+#[test]
+fn test_expired_and_partial_bundles_rejected() {
+    let mut exchange = exchange(ExchangeConfig { latency_ms: 2_000, slippage_bps: 0.0, ..ExchangeConfig::default() });
+    exchange.on_price("SOL/USDC", 20.0, 0);
+
+    // Сигнал живет до слота 2, а до биржи доходит в слоте 5
+    let mut expiring = signal(|ctx, pair| ctx.build_signal(pair, TradeAction::Buy, 1.0, 20.0).unwrap());
+    expiring.signal.expiry_slot = Some(2);
+    exchange.submit(vec![expiring], 0);
+    match exchange.execute_due(2_000).unwrap().as_slice() {
+        [ExchangeEvent::Rejected { reason, .. }] => assert!(reason.contains("expired")),
+        events => panic!("unexpected events {:?}", events),
+    }
+
+    // Продажа не проходит по пределу - покупка из того же бандла тоже отменяется
+    let legs = bundle(vec![
+        signal(|ctx, pair| ctx.build_signal(pair, TradeAction::Buy, 1.0, 20.0).unwrap()).signal,
+        signal(|ctx, pair| ctx.build_signal(pair, TradeAction::Sell, 1.0, 20.5).unwrap()).signal,
+    ]);
+    let legs = legs.into_iter()
+        .map(|signal| StrategySignal { strategy_id: "test".to_string(), pair: TokenPair::new("SOL", "USDC"), signal })
+        .collect();
+    exchange.submit(legs, 3_000);
+    let events = exchange.execute_due(5_000).unwrap();
+    assert_eq!(events.len(), 2);
+    assert!(events.iter().all(|event| matches!(event, ExchangeEvent::Rejected { .. })));
+}
+
+#[test]
+fn test_book_fills_partially_and_rests_remainder() {
+    let mut exchange = exchange(ExchangeConfig { latency_ms: 0, ..ExchangeConfig::default() });
+    let pair = TokenPair::new("SOL", "USDC");
+    let params = test_registry().get(&pair).unwrap().serum_params();
+    // asks: 20.00 x 1 SOL, 20.05 x 5 SOL; bids: 19.99 x 3 SOL
+    exchange.on_book("SOL/USDC", L2Book::with_levels(params, &[(19_990, 30)], &[(20_000, 10), (20_050, 50)]), 0);
+
+    // GTC-покупка 3 SOL по 20.01 забирает 1 SOL из стакана, остаток встает лучшим bid
+    let mut gtc = signal(|ctx, pair| ctx.build_signal(pair, TradeAction::Buy, 3.0, 20.01).unwrap());
+    gtc.signal.time_in_force = TimeInForce::Gtc;
+    exchange.submit(vec![gtc], 0);
+    match exchange.execute_due(0).unwrap().as_slice() {
+        [ExchangeEvent::Filled(fill), ExchangeEvent::Resting(remainder)] => {
+            assert!((fill.fill.price - 20.0).abs() < 1e-9 && (fill.fill.size - 1.0).abs() < 1e-9 && !fill.maker);
+            assert_eq!(remainder.signal.amount, 2_000_000_000);
+        }
+        events => panic!("unexpected events {:?}", events),
+    }
+
+    // Цена не исполняет ордер в стакане, а продажа тейкера ниже его цены - исполняет
+    assert!(exchange.on_price("SOL/USDC", 19.5, 1_000).is_empty());
+    let trade = MarketFill {
+        pair: pair.clone(),
+        market: Pubkey::default(),
+        seq_num: 1,
+        slot: 5,
+        price: 19.99,
+        size: 3.0,
+        taker_action: TradeAction::Sell,
+        maker: true,
+        owner: Pubkey::default(),
+        client_order_id: 0,
+        fee: 0.0,
+    };
+    let events = exchange.on_trade(&trade, 2_000);
+    let (price, _, maker) = fills(&events)[0];
+    assert!((price - 20.01).abs() < 1e-9 && maker);
+    assert!(exchange.on_trade(&trade, 3_000).is_empty());
+}
@@ -0,0 +1,57 @@
+use std::error::Error;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::market_registry::MarketRegistry;
+use crate::strategies::{RunnerConfig, StrategyRegistry};
+
+pub mod data;
+pub mod engine;
+pub mod exchange;
+pub mod monte_carlo;
+pub mod optimize;
+pub mod rng;
+pub mod stats;
+
+pub use engine::{Backtest, BacktestReport};
+pub use exchange::ExchangeConfig;
+pub use monte_carlo::MonteCarloConfig;
+pub use optimize::OptimizeOptions;
+
+// Параметры подкоманды `backtest`
+#[derive(Debug, Clone)]
+pub struct BacktestOptions {
+    // CSV-файл или записи бота
+    pub data: PathBuf,
+    // Каталог для кривой капитала, сделок и статистики
+    pub output: Option<PathBuf>,
+    pub exchange: ExchangeConfig,
+    // Период доходностей для Sharpe и Sortino
+    pub period_secs: i64,
+    // Настройки Monte Carlo по сделкам бэктеста
+    pub monte_carlo: Option<MonteCarloConfig>,
+}
+
+// Бэктест стратегий из конфигурации на исторических данных; печатает статистику
+pub fn run(options: &BacktestOptions, markets_config_path: &str, strategies_config_path: &str) -> Result<BacktestReport, Box<dyn Error>> {
+    let markets = Arc::new(MarketRegistry::load(markets_config_path)?);
+    let config = RunnerConfig::load(strategies_config_path)?;
+    let events = data::load(&options.data, &markets)?;
+    println!("Бэктест на {} событиях из {}", events.len(), options.data.display());
+
+    let backtest = Backtest::new(&config, &StrategyRegistry::with_builtin(), markets, options.exchange.clone())?;
+    let report = backtest.run(&events, options.period_secs)?;
+    println!("{}", report.stats);
+    if let Some(output) = &options.output {
+        report.write(output)?;
+        println!("Результаты сохранены в {}", output.display());
+    }
+    if let Some(config) = &options.monte_carlo {
+        let monte_carlo = monte_carlo::simulate(&report.trades, report.stats.initial_equity, config)?;
+        println!("\n{}", monte_carlo);
+        if let Some(output) = &options.output {
+            monte_carlo.write(&output.join("monte_carlo.json"))?;
+        }
+    }
+    Ok(report)
+}
@@ -0,0 +1,379 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::rng::Rng;
+use super::stats::Trade;
+
+// Как из сделок бэктеста собираются последовательности прогонов
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Resampling {
+    // Выборка с возвращением: меняются и состав, и порядок сделок
+    Bootstrap,
+    // Перестановка: те же сделки в случайном порядке
+    Shuffle,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct MonteCarloConfig {
+    pub runs: usize,
+    pub resampling: Resampling,
+    pub seed: u64,
+    // Доля капитала в каждой сделке; результат сделки масштабируется ее доходностью
+    pub position_sizes: Vec<f64>,
+    // Дополнительное проскальзывание на вход и на выход, bps: равномерно от 0 до значения
+    pub slippage_bps: f64,
+    // Множитель комиссий сделки: равномерно в [min, max]
+    pub fee_multiplier: (f64, f64),
+    // Разорение - капитал хотя бы раз опустился до этой доли начального
+    pub ruin_equity: f64,
+    // Уровень доверительных интервалов
+    pub confidence: f64,
+}
+
+impl Default for MonteCarloConfig {
+    fn default() -> Self {
+        Self {
+            runs: 1000,
+            resampling: Resampling::Bootstrap,
+            seed: 0,
+            position_sizes: vec![0.1, 0.25, 0.5, 1.0],
+            slippage_bps: 0.0,
+            fee_multiplier: (1.0, 1.0),
+            ruin_equity: 0.5,
+            confidence: 0.95,
+        }
+    }
+}
+
+impl MonteCarloConfig {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read Monte Carlo config {}: {}", path.display(), e))?;
+        Self::from_json(&contents)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, Box<dyn Error>> {
+        let config: Self = serde_json::from_str(json)?;
+        if config.runs == 0 || config.position_sizes.is_empty() {
+            return Err("Monte Carlo needs runs >= 1 and at least one position size".into());
+        }
+        if config.position_sizes.iter().any(|&size| size <= 0.0) {
+            return Err("position sizes must be positive".into());
+        }
+        if config.confidence <= 0.0 || config.confidence >= 1.0 || !(0.0..1.0).contains(&config.ruin_equity) {
+            return Err("confidence must be in (0, 1) and ruin_equity in [0, 1)".into());
+        }
+        if config.slippage_bps < 0.0 || config.fee_multiplier.0 < 0.0 || config.fee_multiplier.1 < config.fee_multiplier.0 {
+            return Err("slippage_bps must be non-negative and fee_multiplier a [min, max] range".into());
+        }
+        Ok(config)
+    }
+}
+
+// Среднее, медиана и доверительный интервал по перцентилям
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct Distribution {
+    pub mean: f64,
+    pub median: f64,
+    pub lower: f64,
+    pub upper: f64,
+}
+
+impl Distribution {
+    pub fn from_samples(samples: &mut [f64], confidence: f64) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        samples.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        let tail = (1.0 - confidence) / 2.0;
+        Self {
+            mean: samples.iter().sum::<f64>() / samples.len() as f64,
+            median: percentile(samples, 0.5),
+            lower: percentile(samples, tail),
+            upper: percentile(samples, 1.0 - tail),
+        }
+    }
+}
+
+// Перцентиль отсортированной выборки с линейной интерполяцией
+pub fn percentile(sorted: &[f64], quantile: f64) -> f64 {
+    let position = quantile.clamp(0.0, 1.0) * (sorted.len() - 1) as f64;
+    let index = position.floor() as usize;
+    let fraction = position - index as f64;
+    match sorted.get(index + 1) {
+        Some(next) => sorted[index] + (next - sorted[index]) * fraction,
+        None => sorted[index],
+    }
+}
+
+// Итоги прогонов при одной доле капитала на сделку
+#[derive(Debug, Clone, Serialize)]
+pub struct SizeReport {
+    pub position_size: f64,
+    // Конечный результат в котируемом токене от начального капитала бэктеста
+    pub pnl: Distribution,
+    // Максимальная просадка от пика, доля
+    pub max_drawdown: Distribution,
+    // Доля прогонов с разорением и ее интервал Уилсона
+    pub risk_of_ruin: f64,
+    pub risk_of_ruin_interval: (f64, f64),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MonteCarloReport {
+    pub runs: usize,
+    // Ставки - позиции, собранные из частичных закрытий
+    pub trades: usize,
+    // Позиции, открытые до закрытия предыдущих: прогоны считают их
+    // последовательными, поэтому совокупный риск одновременных позиций занижен
+    pub overlapping: usize,
+    pub resampling: Resampling,
+    pub initial_equity: f64,
+    pub confidence: f64,
+    pub ruin_equity: f64,
+    pub sizes: Vec<SizeReport>,
+}
+
+impl MonteCarloReport {
+    pub fn write(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+impl fmt::Display for MonteCarloReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f, "Monte Carlo: {} прогонов по {} сделкам ({:?}), интервалы {:.0}%, разорение - капитал ниже {:.0}% начального",
+            self.runs, self.trades, self.resampling, self.confidence * 100.0, self.ruin_equity * 100.0
+        )?;
+        if self.overlapping > 0 {
+            writeln!(
+                f, "Внимание: {} позиций открыты одновременно с другими; прогоны считают их последовательными, риск занижен",
+                self.overlapping
+            )?;
+        }
+        write!(f, "Доля   PnL медиана  PnL интервал            Просадка медиана  Просадка интервал  Риск разорения")?;
+        for size in &self.sizes {
+            write!(
+                f, "\n{:>4.0}%  {:>12.2}  [{:>9.2}, {:>9.2}]  {:>15.2}%  [{:>5.1}%, {:>5.1}%]   {:>5.1}% [{:.1}%, {:.1}%]",
+                size.position_size * 100.0, size.pnl.median, size.pnl.lower, size.pnl.upper,
+                size.max_drawdown.median * 100.0, size.max_drawdown.lower * 100.0, size.max_drawdown.upper * 100.0,
+                size.risk_of_ruin * 100.0, size.risk_of_ruin_interval.0 * 100.0, size.risk_of_ruin_interval.1 * 100.0
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// Позиция экземпляра по паре от входа до последнего закрытия. Журнал
+// записывает каждое частичное закрытие отдельной сделкой с общим временем
+// входа; по отдельности они были бы несколькими ставками одного капитала.
+#[derive(Debug, Clone, Copy, Default)]
+struct ClosedPosition {
+    entry_time: i64,
+    exit_time: i64,
+    entry_notional: f64,
+    exit_notional: f64,
+    fees: f64,
+    pnl: f64,
+}
+
+// Сделки, собранные по позициям, в порядке первого закрытия
+fn closed_positions(trades: &[Trade]) -> Vec<ClosedPosition> {
+    let mut positions: Vec<ClosedPosition> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    for trade in trades {
+        let key = format!("{} {} {:?} {}", trade.strategy_id, trade.pair, trade.side, trade.entry_time);
+        let slot = *index.entry(key).or_insert_with(|| {
+            positions.push(ClosedPosition { entry_time: trade.entry_time, exit_time: trade.exit_time, ..ClosedPosition::default() });
+            positions.len() - 1
+        });
+        let position = &mut positions[slot];
+        position.exit_time = position.exit_time.max(trade.exit_time);
+        position.entry_notional += trade.notional();
+        position.exit_notional += trade.exit_price * trade.size;
+        position.fees += trade.fees;
+        position.pnl += trade.pnl;
+    }
+    positions
+}
+
+// Сколько позиций открыто, пока еще не закрыта одна из более ранних
+fn overlapping(positions: &[ClosedPosition]) -> usize {
+    let mut spans: Vec<(i64, i64)> = positions.iter().map(|position| (position.entry_time, position.exit_time)).collect();
+    spans.sort_unstable();
+    let mut open_until = i64::MIN;
+    let mut count = 0;
+    for (entry_time, exit_time) in spans {
+        if entry_time < open_until {
+            count += 1;
+        }
+        open_until = open_until.max(exit_time);
+    }
+    count
+}
+
+// Позиция в виде доходности на вложенную сумму; издержки считаются отдельно,
+// чтобы их можно было возмущать
+#[derive(Debug, Clone, Copy)]
+struct TradeReturn {
+    // Результат до комиссий на сумму входа
+    gross: f64,
+    // Комиссии на сумму входа
+    fees: f64,
+    // Оборот входа и выхода на сумму входа - база для проскальзывания
+    turnover: f64,
+}
+
+impl TradeReturn {
+    fn from_position(position: &ClosedPosition) -> Option<Self> {
+        let notional = position.entry_notional;
+        if notional <= 0.0 {
+            return None;
+        }
+        Some(Self {
+            gross: (position.pnl + position.fees) / notional,
+            fees: position.fees / notional,
+            turnover: (notional + position.exit_notional) / notional,
+        })
+    }
+}
+
+// Прогоны по сделкам бэктеста: каждая последовательность с возмущенными
+// издержками проходит через все доли капитала, так что доли сравниваются
+// на одних и тех же путях. Ставка - позиция целиком, и ставки идут одна за
+// другой: позиции, которые в бэктесте были открыты одновременно, прогон
+// проигрывает последовательно, и их число выводится в отчете.
+pub fn simulate(trades: &[Trade], initial_equity: f64, config: &MonteCarloConfig) -> Result<MonteCarloReport, Box<dyn Error>> {
+    let positions = closed_positions(trades);
+    let returns: Vec<TradeReturn> = positions.iter().filter_map(TradeReturn::from_position).collect();
+    if returns.is_empty() {
+        return Err("no closed trades to resample".into());
+    }
+
+    let mut rng = Rng::new(config.seed);
+    let mut order: Vec<usize> = (0..returns.len()).collect();
+    let mut path = Vec::with_capacity(returns.len());
+    let sizes = config.position_sizes.len();
+    let mut pnl = vec![Vec::with_capacity(config.runs); sizes];
+    let mut drawdowns = vec![Vec::with_capacity(config.runs); sizes];
+    let mut ruins = vec![0usize; sizes];
+
+    for _ in 0..config.runs {
+        match config.resampling {
+            Resampling::Bootstrap => {
+                for index in order.iter_mut() {
+                    *index = rng.below(returns.len());
+                }
+            }
+            Resampling::Shuffle => {
+                // Фишер-Йетс поверх прошлой перестановки
+                for i in (1..order.len()).rev() {
+                    order.swap(i, rng.below(i + 1));
+                }
+            }
+        }
+
+        path.clear();
+        for &index in &order {
+            let trade = returns[index];
+            let slippage = rng.next_f64() * config.slippage_bps / 10_000.0;
+            let fee_multiplier = config.fee_multiplier.0 + rng.next_f64() * (config.fee_multiplier.1 - config.fee_multiplier.0);
+            path.push(trade.gross - trade.fees * fee_multiplier - slippage * trade.turnover);
+        }
+
+        for (slot, &size) in config.position_sizes.iter().enumerate() {
+            let mut equity: f64 = 1.0;
+            let mut peak: f64 = 1.0;
+            let mut max_drawdown: f64 = 0.0;
+            let mut ruined = false;
+            for &trade_return in &path {
+                // Потеря больше вложенного капитала оставляет счет пустым
+                equity = (equity * (1.0 + size * trade_return)).max(0.0);
+                peak = peak.max(equity);
+                max_drawdown = max_drawdown.max((peak - equity) / peak);
+                if equity <= config.ruin_equity {
+                    ruined = true;
+                }
+                if equity == 0.0 {
+                    break;
+                }
+            }
+            pnl[slot].push(initial_equity * (equity - 1.0));
+            drawdowns[slot].push(max_drawdown);
+            if ruined {
+                ruins[slot] += 1;
+            }
+        }
+    }
+
+    let sizes = config.position_sizes.iter()
+        .zip(pnl.iter_mut().zip(drawdowns.iter_mut()))
+        .zip(&ruins)
+        .map(|((&position_size, (pnl, drawdowns)), &ruins)| SizeReport {
+            position_size,
+            pnl: Distribution::from_samples(pnl, config.confidence),
+            max_drawdown: Distribution::from_samples(drawdowns, config.confidence),
+            risk_of_ruin: ruins as f64 / config.runs as f64,
+            risk_of_ruin_interval: wilson_interval(ruins, config.runs, config.confidence),
+        })
+        .collect();
+
+    Ok(MonteCarloReport {
+        runs: config.runs,
+        trades: returns.len(),
+        overlapping: overlapping(&positions),
+        resampling: config.resampling,
+        initial_equity,
+        confidence: config.confidence,
+        ruin_equity: config.ruin_equity,
+        sizes,
+    })
+}
+
+// Интервал Уилсона для доли успехов: не схлопывается в точку при 0 и 100%
+pub fn wilson_interval(successes: usize, trials: usize, confidence: f64) -> (f64, f64) {
+    if trials == 0 {
+        return (0.0, 1.0);
+    }
+    let z = normal_quantile(1.0 - (1.0 - confidence) / 2.0);
+    let n = trials as f64;
+    let p = successes as f64 / n;
+    let denominator = 1.0 + z * z / n;
+    let center = (p + z * z / (2.0 * n)) / denominator;
+    let half_width = z * (p * (1.0 - p) / n + z * z / (4.0 * n * n)).sqrt() / denominator;
+    ((center - half_width).max(0.0), (center + half_width).min(1.0))
+}
+
+// Квантиль стандартного нормального распределения (аппроксимация Acklam)
+fn normal_quantile(p: f64) -> f64 {
+    const A: [f64; 6] = [-3.969683028665376e1, 2.209460984245205e2, -2.759285104469687e2, 1.38357751867269e2, -3.066479806614716e1, 2.506628277459239];
+    const B: [f64; 5] = [-5.447609879822406e1, 1.615858368580409e2, -1.556989798598866e2, 6.680131188771972e1, -1.328068155288572e1];
+    const C: [f64; 6] = [-7.784894002430293e-3, -3.223964580411365e-1, -2.400758277161838, -2.549732539343734, 4.374664141464968, 2.938163982698783];
+    const D: [f64; 4] = [7.784695709041462e-3, 3.224671290700398e-1, 2.445134137142996, 3.754408661907416];
+    const LOW: f64 = 0.02425;
+
+    let tail = |q: f64| (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5]) / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0);
+    if p < LOW {
+        tail((-2.0 * p.ln()).sqrt())
+    } else if p > 1.0 - LOW {
+        -tail((-2.0 * (1.0 - p).ln()).sqrt())
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    }
+}
+
+#[cfg(test)]
+#[path = "monte_carlo_test.rs"]
+mod tests;
@@ -0,0 +1,112 @@
+use super::*;
+use crate::jito_integration::TradeAction;
+
+// Длинная сделка на `size` SOL с входом по 100
+fn trade(entry_time: i64, exit_time: i64, size: f64, exit_price: f64, fees: f64) -> Trade {
+    Trade {
+        strategy_id: "swing".to_string(),
+        pair: "SOL/USDC".to_string(),
+        side: TradeAction::Buy,
+        size,
+        entry_price: 100.0,
+        exit_price,
+        entry_time,
+        exit_time,
+        fees,
+        pnl: (exit_price - 100.0) * size - fees,
+    }
+}
+
+// Позиции на 1 SOL одна за другой по минуте
+fn trades(exits: &[(f64, f64)]) -> Vec<Trade> {
+    exits.iter()
+        .enumerate()
+        .map(|(i, &(exit_price, fees))| trade(i as i64 * 60, i as i64 * 60 + 60, 1.0, exit_price, fees))
+        .collect()
+}
+
+fn config(json: &str) -> MonteCarloConfig {
+    MonteCarloConfig::from_json(json).unwrap()
+}
+
+#[test]
+fn test_shuffle_keeps_final_pnl_and_varies_drawdown() {
+    let trades = trades(&[(110.0, 0.0), (90.0, 0.0), (105.0, 0.0), (95.0, 0.0)]);
+    let report = simulate(&trades, 1000.0, &config(r#"{"runs": 200, "resampling": "shuffle", "position_sizes": [1.0]}"#)).unwrap();
+
+    // Порядок не меняет произведение доходностей: 1.1 * 0.9 * 1.05 * 0.95
+    let expected = 1000.0 * (1.1 * 0.9 * 1.05 * 0.95 - 1.0);
+    let pnl = report.sizes[0].pnl;
+    assert!((pnl.lower - expected).abs() < 1e-9 && (pnl.upper - expected).abs() < 1e-9);
+    // А просадка зависит от того, идут ли убытки подряд
+    let drawdown = report.sizes[0].max_drawdown;
+    assert!(drawdown.lower < drawdown.upper);
+    assert!((drawdown.upper - (1.0 - 0.9 * 0.95)).abs() < 1e-9);
+    assert_eq!(report.sizes[0].risk_of_ruin, 0.0);
+}
+
+#[test]
+fn test_risk_of_ruin_grows_with_position_size() {
+    let trades = trades(&[(140.0, 0.0), (140.0, 0.0), (50.0, 0.0), (50.0, 0.0)]);
+    let report = simulate(&trades, 1000.0, &config(r#"{"runs": 500, "seed": 3, "position_sizes": [0.1, 1.0]}"#)).unwrap();
+
+    let (small, full) = (&report.sizes[0], &report.sizes[1]);
+    assert_eq!(small.risk_of_ruin, 0.0);
+    assert!(full.risk_of_ruin > 0.3);
+    assert!(full.risk_of_ruin_interval.0 < full.risk_of_ruin && full.risk_of_ruin < full.risk_of_ruin_interval.1);
+    // Даже без разорений в выборке интервал оставляет место для риска
+    assert!(small.risk_of_ruin_interval.1 > 0.0);
+    assert!(full.max_drawdown.median > small.max_drawdown.median);
+}
+
+#[test]
+fn test_cost_perturbations_lower_pnl() {
+    let trades = trades(&[(102.0, 0.2), (101.0, 0.2), (99.5, 0.2)]);
+    let base = simulate(&trades, 1000.0, &config(r#"{"runs": 300, "seed": 1, "position_sizes": [0.5]}"#)).unwrap();
+    let costly = simulate(&trades, 1000.0, &config(r#"{
+        "runs": 300, "seed": 1, "position_sizes": [0.5],
+        "slippage_bps": 20.0, "fee_multiplier": [2.0, 3.0]
+    }"#)).unwrap();
+
+    // Те же последовательности, но издержки выше в каждой сделке
+    assert!(costly.sizes[0].pnl.mean < base.sizes[0].pnl.mean);
+    assert!(costly.sizes[0].pnl.upper < base.sizes[0].pnl.upper);
+}
+
+#[test]
+fn test_partial_closes_are_one_bet() {
+    // Позиция 2 SOL закрыта двумя частями по 110 и 90 - одна ставка с нулевым результатом
+    let partial = vec![trade(0, 60, 1.0, 110.0, 0.0), trade(0, 120, 1.0, 90.0, 0.0)];
+    let report = simulate(&partial, 1000.0, &config(r#"{"runs": 50, "position_sizes": [1.0]}"#)).unwrap();
+    assert_eq!((report.trades, report.overlapping), (1, 0));
+    assert!(report.sizes[0].pnl.upper.abs() < 1e-9);
+    assert_eq!(report.sizes[0].max_drawdown.upper, 0.0);
+}
+
+#[test]
+fn test_overlapping_positions_are_reported() {
+    // Вторая позиция открыта до закрытия первой, третья - после
+    let mut overlapping = vec![trade(0, 120, 1.0, 110.0, 0.0), trade(60, 180, 1.0, 90.0, 0.0), trade(180, 240, 1.0, 105.0, 0.0)];
+    overlapping[1].pair = "RAY/USDC".to_string();
+    let report = simulate(&overlapping, 1000.0, &config(r#"{"runs": 50, "position_sizes": [1.0]}"#)).unwrap();
+    assert_eq!((report.trades, report.overlapping), (3, 1));
+    assert!(report.to_string().contains("1 позиций открыты одновременно"));
+
+    let sequential = simulate(&trades(&[(110.0, 0.0), (90.0, 0.0)]), 1000.0, &MonteCarloConfig::default()).unwrap();
+    assert_eq!(sequential.overlapping, 0);
+}
+
+#[test]
+fn test_percentile_and_wilson_interval() {
+    let sorted = [1.0, 2.0, 3.0, 4.0, 5.0];
+    assert_eq!(percentile(&sorted, 0.5), 3.0);
+    assert_eq!(percentile(&sorted, 0.125), 1.5);
+    assert_eq!(percentile(&sorted, 1.0), 5.0);
+
+    let (lower, upper) = wilson_interval(50, 100, 0.95);
+    assert!((lower - 0.4038).abs() < 1e-3 && (upper - 0.5962).abs() < 1e-3);
+    assert_eq!(wilson_interval(0, 100, 0.95).0, 0.0);
+
+    assert!(MonteCarloConfig::from_json(r#"{"position_sizes": []}"#).is_err());
+    assert!(simulate(&[], 1000.0, &MonteCarloConfig::default()).is_err());
+}
@@ -0,0 +1,670 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use log::warn;
+use serde::Deserialize;
+use serde_json::{Number, Value};
+
+use super::data::{self, BacktestEvent};
+use super::engine::Backtest;
+use super::exchange::ExchangeConfig;
+use super::rng::Rng;
+use super::stats::Stats;
+use crate::market_registry::MarketRegistry;
+use crate::strategies::registry::StrategyInstanceConfig;
+use crate::strategies::{RunnerConfig, StrategyRegistry};
+
+// Значения параметров стратегии по имени, как в `params` конфигурации
+pub type Params = BTreeMap<String, Value>;
+
+// Кандидатов на одно предложение Bayesian-lite
+const BAYESIAN_CANDIDATES: usize = 24;
+// Доля лучших оценок, задающая "хорошую" область пространства
+const BAYESIAN_GOOD_FRACTION: f64 = 0.25;
+// Ширина ядра в нормированном пространстве параметров
+const BAYESIAN_BANDWIDTH: f64 = 0.15;
+// Случайных точек до того, как поиск начнет опираться на историю
+const BAYESIAN_MIN_RANDOM: usize = 8;
+
+// Параметры подкоманды `optimize`
+#[derive(Debug, Clone)]
+pub struct OptimizeOptions {
+    pub data: PathBuf,
+    // Файл с пространством параметров и настройками поиска
+    pub spec: PathBuf,
+    // Каталог для таблиц результатов и выбранной конфигурации
+    pub output: PathBuf,
+    pub exchange: ExchangeConfig,
+    pub period_secs: i64,
+    // Число потоков; по умолчанию - по числу ядер
+    pub threads: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchMethod {
+    // Все сочетания значений
+    Grid,
+    Random,
+    // Случайный старт, затем точки, похожие на лучшие и непохожие на худшие
+    Bayesian,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Objective {
+    Sharpe,
+    Sortino,
+    TotalReturn,
+}
+
+impl Objective {
+    pub fn score(&self, stats: &Stats) -> f64 {
+        match self {
+            Objective::Sharpe => stats.sharpe,
+            Objective::Sortino => stats.sortino,
+            Objective::TotalReturn => stats.total_return,
+        }
+    }
+}
+
+// Значения одного параметра: перечисление или диапазон. Диапазон с целыми
+// границами и шагом дает целые значения.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum ParamRange {
+    Values { values: Vec<Value> },
+    Range { min: Number, max: Number, #[serde(default)] step: Option<Number> },
+}
+
+impl ParamRange {
+    // Пустое перечисление и перевернутый диапазон не дают ни одного значения
+    fn validate(&self) -> Result<(), Box<dyn Error>> {
+        match self {
+            ParamRange::Values { values } if values.is_empty() => Err("values must not be empty".into()),
+            ParamRange::Values { .. } => Ok(()),
+            ParamRange::Range { min, max, step } => {
+                let (min, max) = (as_f64(min), as_f64(max));
+                if !(min.is_finite() && max.is_finite()) || min > max {
+                    return Err(format!("invalid range {}..{}", min, max).into());
+                }
+                match step {
+                    Some(step) if as_f64(step) <= 0.0 => Err(format!("step {} must be positive", step).into()),
+                    _ => Ok(()),
+                }
+            }
+        }
+    }
+
+    // Все значения для поиска по сетке; диапазону нужен шаг
+    pub fn grid(&self) -> Result<Vec<Value>, Box<dyn Error>> {
+        match self {
+            ParamRange::Values { values } => Ok(values.clone()),
+            ParamRange::Range { step: None, .. } => Err("grid search needs a step for every range".into()),
+            ParamRange::Range { min, max, step: Some(step) } => {
+                let (min, max, step) = (as_f64(min), as_f64(max), as_f64(step));
+                if step <= 0.0 || max < min {
+                    return Err(format!("invalid range {}..{} step {}", min, max, step).into());
+                }
+                let count = ((max - min) / step + 1e-9).floor() as usize + 1;
+                Ok((0..count).map(|index| self.value(min + index as f64 * step)).collect())
+            }
+        }
+    }
+
+    // Значение по координате в [0, 1]
+    pub fn value_at(&self, unit: f64) -> Value {
+        let unit = unit.clamp(0.0, 1.0);
+        match self {
+            ParamRange::Values { values } => values[((unit * values.len() as f64) as usize).min(values.len() - 1)].clone(),
+            ParamRange::Range { min, max, step } => {
+                let (min, max) = (as_f64(min), as_f64(max));
+                let mut value = min + unit * (max - min);
+                if let Some(step) = step {
+                    let step = as_f64(step);
+                    if step > 0.0 {
+                        value = (min + ((value - min) / step).round() * step).min(max);
+                    }
+                }
+                self.value(value)
+            }
+        }
+    }
+
+    // Координата значения в [0, 1]; значения перечисления - середины своих долей
+    pub fn unit_of(&self, value: &Value) -> f64 {
+        match self {
+            ParamRange::Values { values } => {
+                let index = values.iter().position(|candidate| candidate == value).unwrap_or(0);
+                (index as f64 + 0.5) / values.len() as f64
+            }
+            ParamRange::Range { min, max, .. } => {
+                let (min, max) = (as_f64(min), as_f64(max));
+                let value = value.as_f64().unwrap_or(min);
+                if max > min { (value - min) / (max - min) } else { 0.0 }
+            }
+        }
+    }
+
+    fn is_integer(&self) -> bool {
+        match self {
+            ParamRange::Values { .. } => false,
+            ParamRange::Range { min, max, step } => {
+                let integer = |number: &Number| number.is_i64() || number.is_u64();
+                integer(min) && integer(max) && step.iter().all(integer)
+            }
+        }
+    }
+
+    fn value(&self, value: f64) -> Value {
+        if self.is_integer() {
+            Value::from(value.round() as i64)
+        } else {
+            // Убираем хвосты вида 0.30000000000000004
+            Value::from((value * 1e10).round() / 1e10)
+        }
+    }
+}
+
+fn as_f64(number: &Number) -> f64 {
+    number.as_f64().unwrap_or(0.0)
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct WalkForward {
+    // Число окон; каждое следующее сдвинуто на длину out-of-sample
+    pub folds: usize,
+    // Доля окна для подбора параметров, остаток - для проверки
+    pub in_sample: f64,
+}
+
+impl Default for WalkForward {
+    fn default() -> Self {
+        Self { folds: 1, in_sample: 0.7 }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct OptimizeSpec {
+    // Экземпляр из strategies.json, параметры которого подбираются
+    pub strategy_id: String,
+    #[serde(default = "default_method")]
+    pub method: SearchMethod,
+    // Число оценок для random и bayesian
+    #[serde(default = "default_samples")]
+    pub samples: usize,
+    #[serde(default)]
+    pub seed: u64,
+    #[serde(default = "default_objective")]
+    pub objective: Objective,
+    // Наборы с меньшим числом сделок ранжируются последними
+    #[serde(default = "default_min_trades")]
+    pub min_trades: usize,
+    // Сколько лучших наборов каждого окна проверяется на out-of-sample
+    #[serde(default = "default_top")]
+    pub top: usize,
+    #[serde(default)]
+    pub walk_forward: WalkForward,
+    pub parameters: BTreeMap<String, ParamRange>,
+}
+
+fn default_method() -> SearchMethod {
+    SearchMethod::Grid
+}
+
+fn default_samples() -> usize {
+    50
+}
+
+fn default_objective() -> Objective {
+    Objective::Sharpe
+}
+
+fn default_min_trades() -> usize {
+    1
+}
+
+fn default_top() -> usize {
+    10
+}
+
+impl OptimizeSpec {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read optimization spec {}: {}", path.display(), e))?;
+        Self::from_json(&contents)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, Box<dyn Error>> {
+        let spec: Self = serde_json::from_str(json)?;
+        if spec.parameters.is_empty() {
+            return Err("optimization spec has no parameters".into());
+        }
+        for (name, range) in &spec.parameters {
+            range.validate().map_err(|e| format!("{}: {}", name, e))?;
+        }
+        if spec.walk_forward.folds == 0 || !(spec.walk_forward.in_sample > 0.0 && spec.walk_forward.in_sample < 1.0) {
+            return Err("walk_forward needs folds >= 1 and in_sample between 0 and 1".into());
+        }
+        Ok(spec)
+    }
+
+    // Декартово произведение значений всех параметров
+    pub fn grid(&self) -> Result<Vec<Params>, Box<dyn Error>> {
+        let mut grid = vec![Params::new()];
+        for (name, range) in &self.parameters {
+            let values = range.grid().map_err(|e| format!("{}: {}", name, e))?;
+            grid = grid.into_iter()
+                .flat_map(|params| values.iter().map(move |value| {
+                    let mut params = params.clone();
+                    params.insert(name.clone(), value.clone());
+                    params
+                }))
+                .collect();
+        }
+        Ok(grid)
+    }
+
+    pub fn sample(&self, rng: &mut Rng) -> Params {
+        self.parameters.iter()
+            .map(|(name, range)| (name.clone(), range.value_at(rng.next_f64())))
+            .collect()
+    }
+
+    // Следующие `count` точек Bayesian-lite: из случайных кандидатов и
+    // возмущений лучших точек выбираются те, у которых плотность лучших
+    // оценок выше всего относительно плотности остальных
+    pub fn propose(&self, history: &[Evaluation], count: usize, rng: &mut Rng) -> Vec<Params> {
+        let mut proposals: Vec<Params> = Vec::with_capacity(count);
+        if history.len() < BAYESIAN_MIN_RANDOM.min(self.samples) {
+            proposals.extend((0..count).map(|_| self.sample(rng)));
+            return proposals;
+        }
+
+        let mut ranked: Vec<&Evaluation> = history.iter().collect();
+        ranked.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+        let good_count = ((ranked.len() as f64 * BAYESIAN_GOOD_FRACTION).ceil() as usize).max(1);
+        let good: Vec<Vec<f64>> = ranked[..good_count].iter().map(|evaluation| self.units(&evaluation.params)).collect();
+        let bad: Vec<Vec<f64>> = ranked[good_count..].iter().map(|evaluation| self.units(&evaluation.params)).collect();
+
+        for _ in 0..count {
+            let mut best: Option<(f64, Params)> = None;
+            for candidate in 0..BAYESIAN_CANDIDATES {
+                let params = if candidate % 2 == 0 {
+                    self.sample(rng)
+                } else {
+                    let center = &good[rng.below(good.len())];
+                    self.parameters.values()
+                        .zip(center)
+                        .map(|(range, &unit)| range.value_at(unit + rng.normal() * BAYESIAN_BANDWIDTH))
+                        .zip(self.parameters.keys())
+                        .map(|(value, name)| (name.clone(), value))
+                        .collect()
+                };
+                // Уже оцененные и предложенные точки пропускаются
+                let seen = history.iter().any(|evaluation| evaluation.params == params) || proposals.contains(&params);
+                let unit = self.units(&params);
+                let score = if seen { f64::NEG_INFINITY } else { density(&unit, &good) / (density(&unit, &bad) + 1e-9) };
+                match &best {
+                    Some((best_score, _)) if score <= *best_score => {}
+                    _ => best = Some((score, params)),
+                }
+            }
+            if let Some((_, params)) = best {
+                proposals.push(params);
+            }
+        }
+        proposals
+    }
+
+    fn units(&self, params: &Params) -> Vec<f64> {
+        self.parameters.iter()
+            .map(|(name, range)| params.get(name).map_or(0.5, |value| range.unit_of(value)))
+            .collect()
+    }
+}
+
+// Гауссова оценка плотности точек в нормированном пространстве
+fn density(unit: &[f64], points: &[Vec<f64>]) -> f64 {
+    if points.is_empty() {
+        return 0.0;
+    }
+    let sum: f64 = points.iter()
+        .map(|point| {
+            let distance: f64 = point.iter().zip(unit).map(|(a, b)| (a - b) * (a - b)).sum();
+            (-distance / (2.0 * BAYESIAN_BANDWIDTH * BAYESIAN_BANDWIDTH)).exp()
+        })
+        .sum();
+    sum / points.len() as f64
+}
+
+// Результат бэктеста одного набора параметров
+#[derive(Debug, Clone)]
+pub struct Evaluation {
+    pub params: Params,
+    // None, если стратегия не создалась с этими параметрами
+    pub stats: Option<Stats>,
+    // Значение цели; -inf для неудачных наборов и наборов с малым числом сделок
+    pub score: f64,
+}
+
+// Окно walk-forward: поиск на in-sample и проверка лучших наборов на out-of-sample
+#[derive(Debug, Clone)]
+pub struct Fold {
+    pub index: usize,
+    // Границы [начало, конец) в миллисекундах
+    pub in_sample: (i64, i64),
+    pub out_of_sample: (i64, i64),
+    // Все оценки in-sample по убыванию цели
+    pub evaluations: Vec<Evaluation>,
+    // Оценки первых `top` наборов на out-of-sample в том же порядке
+    pub out_of_sample_evaluations: Vec<Evaluation>,
+}
+
+#[derive(Debug, Clone)]
+pub struct OptimizeReport {
+    pub folds: Vec<Fold>,
+    // Лучший набор последнего окна - подобранный на самых свежих данных
+    pub best: Params,
+}
+
+impl OptimizeReport {
+    // Таблица всех оценок, итоги окон и конфигурация стратегий с выбранными
+    // параметрами в каталог `dir`
+    pub fn write(&self, dir: &Path, config: &RunnerConfig, strategy_id: &str) -> Result<(), Box<dyn Error>> {
+        fs::create_dir_all(dir)?;
+
+        let mut results = BufWriter::new(File::create(dir.join("results.csv"))?);
+        writeln!(results, "fold,rank,params,score,total_return,max_drawdown,trades,oos_score,oos_return,oos_max_drawdown")?;
+        for fold in &self.folds {
+            for (rank, evaluation) in fold.evaluations.iter().enumerate() {
+                let oos = fold.out_of_sample_evaluations.get(rank);
+                writeln!(
+                    results, "{},{},{},{},{}",
+                    fold.index, rank + 1, format_params(&evaluation.params),
+                    stats_columns(evaluation), oos.map_or(",,".to_string(), oos_columns)
+                )?;
+            }
+        }
+        results.flush()?;
+
+        let mut walk_forward = BufWriter::new(File::create(dir.join("walk_forward.csv"))?);
+        writeln!(walk_forward, "fold,is_start_ms,is_end_ms,oos_start_ms,oos_end_ms,params,is_score,oos_score,oos_return,oos_max_drawdown")?;
+        for fold in &self.folds {
+            if let (Some(best), Some(oos)) = (fold.evaluations.first(), fold.out_of_sample_evaluations.first()) {
+                writeln!(
+                    walk_forward, "{},{},{},{},{},{},{:.6},{}",
+                    fold.index, fold.in_sample.0, fold.in_sample.1, fold.out_of_sample.0, fold.out_of_sample.1,
+                    format_params(&best.params), best.score, oos_columns(oos)
+                )?;
+            }
+        }
+        walk_forward.flush()?;
+
+        let mut config = config.clone();
+        let instance = config.strategies.iter_mut()
+            .find(|instance| instance.id == strategy_id)
+            .ok_or_else(|| format!("Unknown strategy id '{}'", strategy_id))?;
+        instance.params = with_params(&instance.params, &self.best)?;
+        fs::write(dir.join("strategies.json"), serde_json::to_string_pretty(&config)?)?;
+        Ok(())
+    }
+}
+
+fn stats_columns(evaluation: &Evaluation) -> String {
+    match &evaluation.stats {
+        Some(stats) => format!("{:.6},{:.6},{:.6},{}", evaluation.score, stats.total_return, stats.max_drawdown, stats.trades),
+        None => format!("{},,,", evaluation.score),
+    }
+}
+
+fn oos_columns(evaluation: &Evaluation) -> String {
+    match &evaluation.stats {
+        Some(stats) => format!("{:.6},{:.6},{:.6}", evaluation.score, stats.total_return, stats.max_drawdown),
+        None => format!("{},,", evaluation.score),
+    }
+}
+
+// Параметры через пробел: "ma_periods=20 min_price_change=0.02"
+pub fn format_params(params: &Params) -> String {
+    params.iter()
+        .map(|(name, value)| match value {
+            Value::String(value) => format!("{}={}", name, value),
+            value => format!("{}={}", name, value),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Параметры экземпляра из конфигурации, поверх которых записаны подбираемые
+fn with_params(base: &Value, params: &Params) -> Result<Value, Box<dyn Error>> {
+    let mut merged = match base {
+        Value::Object(object) => object.clone(),
+        Value::Null => serde_json::Map::new(),
+        _ => return Err("strategy params must be an object".into()),
+    };
+    for (name, value) in params {
+        merged.insert(name.clone(), value.clone());
+    }
+    Ok(Value::Object(merged))
+}
+
+// Границы окон walk-forward на интервале [start_ms, end_ms): окна одинаковой
+// длины сдвигаются на длину out-of-sample, так что out-of-sample окон идут
+// подряд и вместе покрывают конец интервала
+pub fn walk_forward_windows(start_ms: i64, end_ms: i64, walk_forward: &WalkForward) -> Vec<((i64, i64), (i64, i64))> {
+    let duration = (end_ms - start_ms) as f64;
+    let in_sample = walk_forward.in_sample;
+    let out_of_sample_ms = duration / (1.0 / (1.0 - in_sample) + walk_forward.folds as f64 - 1.0);
+    let in_sample_ms = out_of_sample_ms * in_sample / (1.0 - in_sample);
+    (0..walk_forward.folds)
+        .map(|fold| {
+            let start = start_ms as f64 + fold as f64 * out_of_sample_ms;
+            let split = (start + in_sample_ms).round() as i64;
+            let end = if fold + 1 == walk_forward.folds { end_ms } else { (start + in_sample_ms + out_of_sample_ms).round() as i64 };
+            ((start.round() as i64, split), (split, end))
+        })
+        .collect()
+}
+
+// Подбор параметров одного экземпляра стратегии бэктестами на исторических данных
+pub struct Optimizer<'a> {
+    spec: &'a OptimizeSpec,
+    instance: StrategyInstanceConfig,
+    strategies: &'a StrategyRegistry,
+    markets: Arc<MarketRegistry>,
+    exchange: ExchangeConfig,
+    period_secs: i64,
+    threads: usize,
+}
+
+impl<'a> Optimizer<'a> {
+    pub fn new(
+        spec: &'a OptimizeSpec,
+        config: &RunnerConfig,
+        strategies: &'a StrategyRegistry,
+        markets: Arc<MarketRegistry>,
+        exchange: ExchangeConfig,
+        period_secs: i64,
+        threads: usize,
+    ) -> Result<Self, Box<dyn Error>> {
+        let instance = config.strategies.iter()
+            .find(|instance| instance.id == spec.strategy_id)
+            .ok_or_else(|| format!("Unknown strategy id '{}'", spec.strategy_id))?
+            .clone();
+        Ok(Self {
+            spec,
+            instance,
+            strategies,
+            markets,
+            exchange,
+            period_secs,
+            threads: threads.max(1),
+        })
+    }
+
+    // Поиск в пространстве параметров; оценки по убыванию цели
+    pub fn search(&self, events: &[BacktestEvent], seed: u64) -> Result<Vec<Evaluation>, Box<dyn Error>> {
+        let mut rng = Rng::new(seed);
+        let mut evaluations = match self.spec.method {
+            SearchMethod::Grid => self.evaluate_all(&self.spec.grid()?, events),
+            SearchMethod::Random => {
+                let candidates: Vec<Params> = (0..self.spec.samples).map(|_| self.spec.sample(&mut rng)).collect();
+                self.evaluate_all(&candidates, events)
+            }
+            SearchMethod::Bayesian => {
+                // Точки предлагаются пачками по числу потоков
+                let mut evaluations = Vec::with_capacity(self.spec.samples);
+                while evaluations.len() < self.spec.samples {
+                    let count = self.threads.min(self.spec.samples - evaluations.len());
+                    let candidates = self.spec.propose(&evaluations, count, &mut rng);
+                    evaluations.extend(self.evaluate_all(&candidates, events));
+                }
+                evaluations
+            }
+        };
+        evaluations.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+        Ok(evaluations)
+    }
+
+    // Поиск на in-sample каждого окна и проверка лучших наборов на его out-of-sample
+    pub fn walk_forward(&self, events: &[BacktestEvent]) -> Result<OptimizeReport, Box<dyn Error>> {
+        let (start_ms, end_ms) = match (events.first(), events.last()) {
+            (Some(first), Some(last)) => (first.timestamp_ms(), last.timestamp_ms() + 1),
+            _ => return Err("no events to optimize on".into()),
+        };
+
+        let mut folds = Vec::new();
+        for (index, (in_sample, out_of_sample)) in walk_forward_windows(start_ms, end_ms, &self.spec.walk_forward).into_iter().enumerate() {
+            let evaluations = self.search(window(events, in_sample), self.spec.seed.wrapping_add(index as u64))?;
+            let top: Vec<Params> = evaluations.iter()
+                .take(self.spec.top.max(1))
+                .map(|evaluation| evaluation.params.clone())
+                .collect();
+            let out_of_sample_evaluations = self.evaluate_all(&top, window(events, out_of_sample));
+            folds.push(Fold { index: index + 1, in_sample, out_of_sample, evaluations, out_of_sample_evaluations });
+        }
+
+        let best = folds.last()
+            .and_then(|fold| fold.evaluations.first())
+            .filter(|evaluation| evaluation.score > f64::NEG_INFINITY)
+            .ok_or_else(|| format!("no parameter set made at least {} trades", self.spec.min_trades))?
+            .params
+            .clone();
+        Ok(OptimizeReport { folds, best })
+    }
+
+    pub fn evaluate(&self, params: &Params, events: &[BacktestEvent]) -> Evaluation {
+        let result = self.backtest(params, events);
+        match result {
+            Ok(stats) => {
+                let score = self.spec.objective.score(&stats);
+                let score = if stats.trades < self.spec.min_trades || score.is_nan() { f64::NEG_INFINITY } else { score };
+                Evaluation { params: params.clone(), stats: Some(stats), score }
+            }
+            Err(e) => {
+                warn!("Optimize: {} failed: {}", format_params(params), e);
+                Evaluation { params: params.clone(), stats: None, score: f64::NEG_INFINITY }
+            }
+        }
+    }
+
+    fn backtest(&self, params: &Params, events: &[BacktestEvent]) -> Result<Stats, Box<dyn Error>> {
+        let mut instance = self.instance.clone();
+        instance.params = with_params(&instance.params, params)?;
+        let config = RunnerConfig { strategies: vec![instance] };
+        let backtest = Backtest::new(&config, self.strategies, self.markets.clone(), self.exchange.clone())?;
+        Ok(backtest.run(events, self.period_secs)?.stats)
+    }
+
+    // Оценивает наборы параллельно; порядок результатов - порядок наборов
+    fn evaluate_all(&self, candidates: &[Params], events: &[BacktestEvent]) -> Vec<Evaluation> {
+        let next = AtomicUsize::new(0);
+        let mut results: Vec<(usize, Evaluation)> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..self.threads.min(candidates.len()))
+                .map(|_| scope.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        match candidates.get(index) {
+                            Some(params) => done.push((index, self.evaluate(params, events))),
+                            None => return done,
+                        }
+                    }
+                }))
+                .collect();
+            handles.into_iter()
+                .flat_map(|handle| handle.join().unwrap_or_else(|_| {
+                    warn!("Optimize: worker thread panicked");
+                    Vec::new()
+                }))
+                .collect()
+        });
+        results.sort_by_key(|(index, _)| *index);
+        results.into_iter().map(|(_, evaluation)| evaluation).collect()
+    }
+}
+
+// События внутри [start_ms, end_ms); события упорядочены по времени
+fn window(events: &[BacktestEvent], (start_ms, end_ms): (i64, i64)) -> &[BacktestEvent] {
+    let start = events.partition_point(|event| event.timestamp_ms() < start_ms);
+    let end = events.partition_point(|event| event.timestamp_ms() < end_ms);
+    &events[start..end]
+}
+
+// Подбор параметров по спецификации; печатает итоги окон и таблицу последнего окна
+pub fn run(options: &OptimizeOptions, markets_config_path: &str, strategies_config_path: &str) -> Result<OptimizeReport, Box<dyn Error>> {
+    let markets = Arc::new(MarketRegistry::load(markets_config_path)?);
+    let config = RunnerConfig::load(strategies_config_path)?;
+    let spec = OptimizeSpec::load(&options.spec)?;
+    let events = data::load(&options.data, &markets)?;
+    let threads = options.threads
+        .or_else(|| thread::available_parallelism().ok().map(|threads| threads.get()))
+        .unwrap_or(1);
+    println!(
+        "Оптимизация {} ({:?}, цель {:?}, окон {}, потоков {}) на {} событиях из {}",
+        spec.strategy_id, spec.method, spec.objective, spec.walk_forward.folds, threads, events.len(), options.data.display()
+    );
+
+    let strategies = StrategyRegistry::with_builtin();
+    let optimizer = Optimizer::new(&spec, &config, &strategies, markets, options.exchange.clone(), options.period_secs, threads)?;
+    let report = optimizer.walk_forward(&events)?;
+
+    println!("\nОкно  In-sample  Out-of-sample  Доходность OOS  Параметры");
+    for fold in &report.folds {
+        if let (Some(best), Some(oos)) = (fold.evaluations.first(), fold.out_of_sample_evaluations.first()) {
+            let oos_return = oos.stats.as_ref().map_or(0.0, |stats| stats.total_return);
+            println!(
+                "{:>5}  {:>9.3}  {:>13.3}  {:>13.2}%  {}",
+                fold.index, best.score, oos.score, oos_return * 100.0, format_params(&best.params)
+            );
+        }
+    }
+    if let Some(fold) = report.folds.last() {
+        println!("\nРейтинг последнего окна:");
+        println!("Место  In-sample  Out-of-sample  Сделок  Параметры");
+        for (rank, (evaluation, oos)) in fold.evaluations.iter().zip(&fold.out_of_sample_evaluations).enumerate() {
+            let trades = evaluation.stats.as_ref().map_or(0, |stats| stats.trades);
+            println!(
+                "{:>5}  {:>9.3}  {:>13.3}  {:>6}  {}",
+                rank + 1, evaluation.score, oos.score, trades, format_params(&evaluation.params)
+            );
+        }
+    }
+    println!("\nВыбранные параметры: {}", format_params(&report.best));
+
+    report.write(&options.output, &config, &spec.strategy_id)?;
+    println!("Результаты и конфигурация сохранены в {}", options.output.display());
+    Ok(report)
+}
+
+#[cfg(test)]
+#[path = "optimize_test.rs"]
+mod tests;
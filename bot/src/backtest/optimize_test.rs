@@ -0,0 +1,141 @@
+use super::*;
+use crate::backtest::data::parse_csv;
+use crate::jito_integration::{TradeAction, TradingSignal};
+use crate::market_registry::test_registry;
+use crate::price_feed::PriceData;
+use crate::strategies::registry::parse_params;
+use crate::strategies::{Strategy, StrategyContext};
+
+// Покупает 1 SOL без позиции и продает позицию, когда цена дошла до цели
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct BuyThenSell {
+    target: f64,
+}
+
+impl Strategy for BuyThenSell {
+    fn name(&self) -> &str {
+        "buy_then_sell"
+    }
+
+    fn on_tick(&mut self, ctx: &StrategyContext, price_data: &PriceData) -> Result<Vec<TradingSignal>, Box<dyn Error>> {
+        let pair = &ctx.pairs[0];
+        let price = price_data.get_price(pair)?;
+        let position = ctx.position(pair);
+        let signal = if position <= 0.0 {
+            ctx.build_signal(pair, TradeAction::Buy, 1.0, price * 1.01)?
+        } else if price >= self.target {
+            ctx.build_signal(pair, TradeAction::Sell, position, price * 0.99)?
+        } else {
+            None
+        };
+        Ok(signal.into_iter().collect())
+    }
+}
+
+fn strategies() -> StrategyRegistry {
+    let mut strategies = StrategyRegistry::new();
+    strategies.register("buy_then_sell", |params: &Value, _| Ok(Box::new(parse_params::<BuyThenSell>(params)?)));
+    strategies
+}
+
+fn config() -> RunnerConfig {
+    RunnerConfig::from_json(r#"{"strategies": [
+        {"id": "swing", "strategy": "buy_then_sell", "pairs": ["SOL/USDC"], "capital": 100.0, "params": {"target": 22.0}}
+    ]}"#).unwrap()
+}
+
+#[test]
+fn test_param_ranges() {
+    let spec = OptimizeSpec::from_json(r#"{
+        "strategy_id": "swing",
+        "parameters": {
+            "period": {"min": 10, "max": 30, "step": 10},
+            "threshold": {"min": 0.1, "max": 0.3, "step": 0.1},
+            "mode": {"values": ["fast", "slow"]}
+        }
+    }"#).unwrap();
+    assert_eq!(spec.method, SearchMethod::Grid);
+
+    // Целые границы и шаг дают целые значения, дробные округляются от хвостов
+    let period = &spec.parameters["period"];
+    assert_eq!(period.grid().unwrap(), vec![Value::from(10), Value::from(20), Value::from(30)]);
+    assert_eq!(spec.parameters["threshold"].grid().unwrap(), vec![Value::from(0.1), Value::from(0.2), Value::from(0.3)]);
+    assert_eq!(period.value_at(0.6), Value::from(20));
+    assert_eq!(period.unit_of(&Value::from(20)), 0.5);
+
+    let grid = spec.grid().unwrap();
+    assert_eq!(grid.len(), 18);
+    assert_eq!(format_params(&grid[0]), "mode=fast period=10 threshold=0.1");
+
+    let no_step = OptimizeSpec::from_json(r#"{"strategy_id": "swing", "parameters": {"x": {"min": 0, "max": 1}}}"#).unwrap();
+    assert!(no_step.grid().is_err());
+
+    // Пустые перечисления и перевернутые диапазоны отклоняются при загрузке
+    for parameters in [r#"{"x": {"values": []}}"#, r#"{"x": {"min": 2, "max": 1}}"#, r#"{"x": {"min": 0, "max": 1, "step": 0}}"#] {
+        let json = format!(r#"{{"strategy_id": "swing", "parameters": {}}}"#, parameters);
+        assert!(OptimizeSpec::from_json(&json).is_err(), "{}", parameters);
+    }
+}
+
+#[test]
+fn test_walk_forward_windows() {
+    // 3 окна с долей in-sample 0.5: out-of-sample по 250 мс идут подряд до конца интервала
+    let windows = walk_forward_windows(0, 1_000, &WalkForward { folds: 3, in_sample: 0.5 });
+    assert_eq!(windows, vec![((0, 250), (250, 500)), ((250, 500), (500, 750)), ((500, 750), (750, 1_000))]);
+
+    let windows = walk_forward_windows(0, 1_000, &WalkForward::default());
+    assert_eq!(windows, vec![((0, 700), (700, 1_000))]);
+}
+
+#[test]
+fn test_grid_search_ranks_by_objective() {
+    let events = parse_csv("timestamp,pair,price\n\
+        0,SOL/USDC,20\n\
+        1,SOL/USDC,21\n\
+        2,SOL/USDC,22\n\
+        3,SOL/USDC,23\n\
+        4,SOL/USDC,10\n").unwrap();
+    let spec = OptimizeSpec::from_json(r#"{
+        "strategy_id": "swing",
+        "objective": "total_return",
+        "parameters": {"target": {"values": [21.0, 22.0, 23.0, 30.0]}}
+    }"#).unwrap();
+    let strategies = strategies();
+    let markets = Arc::new(test_registry());
+    let exchange = ExchangeConfig { latency_ms: 0, slippage_bps: 0.0, ..ExchangeConfig::default() };
+    let optimizer = Optimizer::new(&spec, &config(), &strategies, markets, exchange, 1, 2).unwrap();
+
+    let evaluations = optimizer.search(&events, 0).unwrap();
+    assert_eq!(evaluations.len(), 4);
+    // Продажа на 23 успевает до обвала, цель 30 не продает ни разу и уходит в конец
+    assert_eq!(evaluations[0].params["target"], Value::from(23.0));
+    assert!(evaluations[0].score > evaluations[1].score);
+    assert_eq!(evaluations[3].params["target"], Value::from(30.0));
+    assert_eq!(evaluations[3].score, f64::NEG_INFINITY);
+}
+
+#[test]
+fn test_bayesian_proposals_skip_evaluated_points() {
+    let spec = OptimizeSpec::from_json(r#"{
+        "strategy_id": "swing",
+        "method": "bayesian",
+        "samples": 20,
+        "parameters": {"x": {"min": 0, "max": 100, "step": 1}}
+    }"#).unwrap();
+    let history: Vec<Evaluation> = (0..10)
+        .map(|index| {
+            let x = index * 10;
+            let params = Params::from([("x".to_string(), Value::from(x))]);
+            Evaluation { params, stats: None, score: -((x - 80) * (x - 80)) as f64 }
+        })
+        .collect();
+
+    let proposals = spec.propose(&history, 4, &mut Rng::new(7));
+    assert_eq!(proposals.len(), 4);
+    for params in &proposals {
+        assert!(!history.iter().any(|evaluation| &evaluation.params == params));
+        let x = params["x"].as_i64().unwrap();
+        assert!((0..=100).contains(&x));
+    }
+}
@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::jito_integration::TradeAction;
+use crate::strategies::Fill;
+
+// Остаток позиции меньше этого считается закрытой позицией
+const POSITION_EPSILON: f64 = 1e-9;
+const SECONDS_PER_YEAR: f64 = 365.0 * 24.0 * 60.0 * 60.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct EquityPoint {
+    pub timestamp_ms: i64,
+    // Капитал всех экземпляров по текущим ценам
+    pub equity: f64,
+    // Есть ли открытая позиция хотя бы у одного экземпляра
+    pub in_market: bool,
+}
+
+// Закрытая сделка: вход и выход (полный или частичный) одной позиции
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Trade {
+    pub strategy_id: String,
+    pub pair: String,
+    // Buy - длинная позиция, Sell - короткая
+    pub side: TradeAction,
+    pub size: f64,
+    pub entry_price: f64,
+    pub exit_price: f64,
+    // Unix-время в секундах
+    pub entry_time: i64,
+    pub exit_time: i64,
+    // Комиссии входа (пропорционально закрытому объему) и выхода
+    pub fees: f64,
+    // Результат после комиссий
+    pub pnl: f64,
+}
+
+impl Trade {
+    // Сумма входа в котируемом токене
+    pub fn notional(&self) -> f64 {
+        self.entry_price * self.size
+    }
+}
+
+// Открытая позиция экземпляра по паре со средней ценой входа
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpenPosition {
+    // Отрицательная - короткая
+    pub size: f64,
+    pub entry_price: f64,
+    pub entry_time: i64,
+    // Комиссии входа, еще не отнесенные на закрытые сделки
+    pub fees: f64,
+}
+
+// Собирает закрытые сделки из потока исполнений по средней цене входа
+#[derive(Debug, Default)]
+pub struct TradeLedger {
+    // Позиции по ключу "экземпляр пара"
+    positions: HashMap<String, OpenPosition>,
+    trades: Vec<Trade>,
+}
+
+impl TradeLedger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn trades(&self) -> &[Trade] {
+        &self.trades
+    }
+
+    pub fn into_trades(self) -> Vec<Trade> {
+        self.trades
+    }
+
+    // Открытые позиции по ключу "экземпляр пара": бумажная торговля сохраняет
+    // их, чтобы сделки после перезапуска закрывались по прежней цене входа
+    pub fn open_positions(&self) -> &HashMap<String, OpenPosition> {
+        &self.positions
+    }
+
+    pub fn with_positions(positions: HashMap<String, OpenPosition>) -> Self {
+        Self { positions, trades: Vec::new() }
+    }
+
+    pub fn record(&mut self, strategy_id: &str, fill: &Fill) {
+        let key = format!("{} {}", strategy_id, fill.pair);
+        let signed = match fill.action {
+            TradeAction::Buy => fill.size,
+            TradeAction::Sell => -fill.size,
+        };
+        let mut remaining = fill.size;
+
+        if let Some(position) = self.positions.get_mut(&key) {
+            if position.size * signed < 0.0 {
+                // Сделка против позиции закрывает ее полностью или частично
+                let closed = remaining.min(position.size.abs());
+                let entry_fees = position.fees * closed / position.size.abs();
+                let exit_fee = fill.fee * closed / fill.size;
+                let direction = position.size.signum();
+                self.trades.push(Trade {
+                    strategy_id: strategy_id.to_string(),
+                    pair: fill.pair.to_string(),
+                    side: if direction > 0.0 { TradeAction::Buy } else { TradeAction::Sell },
+                    size: closed,
+                    entry_price: position.entry_price,
+                    exit_price: fill.price,
+                    entry_time: position.entry_time,
+                    exit_time: fill.timestamp,
+                    fees: entry_fees + exit_fee,
+                    pnl: (fill.price - position.entry_price) * closed * direction - entry_fees - exit_fee,
+                });
+                position.fees -= entry_fees;
+                position.size -= closed * direction;
+                remaining -= closed;
+                if position.size.abs() <= POSITION_EPSILON {
+                    self.positions.remove(&key);
+                }
+            }
+        }
+        if remaining <= POSITION_EPSILON {
+            return;
+        }
+
+        // Остаток открывает позицию или увеличивает ее
+        let fee = fill.fee * remaining / fill.size;
+        let added = remaining * signed.signum();
+        let position = self.positions.entry(key).or_insert(OpenPosition {
+            size: 0.0,
+            entry_price: fill.price,
+            entry_time: fill.timestamp,
+            fees: 0.0,
+        });
+        position.entry_price = (position.entry_price * position.size.abs() + fill.price * remaining) / (position.size.abs() + remaining);
+        position.size += added;
+        position.fees += fee;
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Stats {
+    pub initial_equity: f64,
+    pub final_equity: f64,
+    // Доходность за весь период, доля
+    pub total_return: f64,
+    // Годовые Sharpe и Sortino по доходностям за период `period_secs`, без безрисковой ставки
+    pub sharpe: f64,
+    pub sortino: f64,
+    // Максимальная просадка от пика, доля
+    pub max_drawdown: f64,
+    pub trades: usize,
+    pub win_rate: f64,
+    // Прибыль сделок к убыткам; None, если убыточных сделок нет
+    pub profit_factor: Option<f64>,
+    // Доля времени с открытой позицией
+    pub exposure: f64,
+    pub fees: f64,
+}
+
+impl Stats {
+    pub fn compute(equity: &[EquityPoint], trades: &[Trade], fees: f64, period_secs: i64) -> Self {
+        let (first, last) = match (equity.first(), equity.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return Self::default(),
+        };
+
+        let returns = period_returns(equity, period_secs);
+        let periods_per_year = SECONDS_PER_YEAR / period_secs as f64;
+        let mean = mean(&returns);
+        let deviation = mean_square(returns.iter().map(|&r| r - mean)).sqrt();
+        let downside = mean_square(returns.iter().map(|&r| r.min(0.0))).sqrt();
+        let annualized = |risk: f64| if risk > 0.0 { mean / risk * periods_per_year.sqrt() } else { 0.0 };
+
+        let mut peak = first.equity;
+        let mut max_drawdown: f64 = 0.0;
+        for point in equity {
+            peak = peak.max(point.equity);
+            if peak > 0.0 {
+                max_drawdown = max_drawdown.max((peak - point.equity) / peak);
+            }
+        }
+
+        // Время в рынке: интервал до следующей точки считается по состоянию в начале интервала
+        let mut in_market_ms = 0;
+        for window in equity.windows(2) {
+            if window[0].in_market {
+                in_market_ms += window[1].timestamp_ms - window[0].timestamp_ms;
+            }
+        }
+        let duration_ms = last.timestamp_ms - first.timestamp_ms;
+
+        let gross_profit: f64 = trades.iter().map(|trade| trade.pnl.max(0.0)).sum();
+        let gross_loss: f64 = trades.iter().map(|trade| (-trade.pnl).max(0.0)).sum();
+        let wins = trades.iter().filter(|trade| trade.pnl > 0.0).count();
+
+        Self {
+            initial_equity: first.equity,
+            final_equity: last.equity,
+            total_return: if first.equity > 0.0 { last.equity / first.equity - 1.0 } else { 0.0 },
+            sharpe: annualized(deviation),
+            sortino: annualized(downside),
+            max_drawdown,
+            trades: trades.len(),
+            win_rate: if trades.is_empty() { 0.0 } else { wins as f64 / trades.len() as f64 },
+            profit_factor: if gross_loss > 0.0 { Some(gross_profit / gross_loss) } else { None },
+            exposure: if duration_ms > 0 { in_market_ms as f64 / duration_ms as f64 } else { 0.0 },
+            fees,
+        }
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let profit_factor = match self.profit_factor {
+            Some(profit_factor) => format!("{:.2}", profit_factor),
+            None => "-".to_string(),
+        };
+        writeln!(f, "Начальный капитал:   {:.2}", self.initial_equity)?;
+        writeln!(f, "Конечный капитал:    {:.2}", self.final_equity)?;
+        writeln!(f, "Доходность:          {:.2}%", self.total_return * 100.0)?;
+        writeln!(f, "Sharpe:              {:.2}", self.sharpe)?;
+        writeln!(f, "Sortino:             {:.2}", self.sortino)?;
+        writeln!(f, "Макс. просадка:      {:.2}%", self.max_drawdown * 100.0)?;
+        writeln!(f, "Сделок:              {}", self.trades)?;
+        writeln!(f, "Прибыльных:          {:.1}%", self.win_rate * 100.0)?;
+        writeln!(f, "Profit factor:       {}", profit_factor)?;
+        writeln!(f, "Время в рынке:       {:.1}%", self.exposure * 100.0)?;
+        write!(f, "Комиссии:            {:.2}", self.fees)
+    }
+}
+
+// Доходности между концами периодов; начальная точка кривой открывает первый период
+fn period_returns(equity: &[EquityPoint], period_secs: i64) -> Vec<f64> {
+    let period_ms = period_secs.max(1) * 1000;
+    let mut closes = Vec::new();
+    let mut current_period = None;
+    for point in equity {
+        let period = point.timestamp_ms.div_euclid(period_ms);
+        if current_period == Some(period) {
+            if let Some(close) = closes.last_mut() {
+                *close = point.equity;
+            }
+        } else {
+            closes.push(point.equity);
+            current_period = Some(period);
+        }
+    }
+    let mut returns = Vec::with_capacity(closes.len());
+    let mut previous = equity[0].equity;
+    for close in closes {
+        if previous > 0.0 {
+            returns.push(close / previous - 1.0);
+        }
+        previous = close;
+    }
+    returns
+}
+
+fn mean(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        0.0
+    } else {
+        values.iter().sum::<f64>() / values.len() as f64
+    }
+}
+
+fn mean_square(values: impl Iterator<Item = f64>) -> f64 {
+    let (sum, count) = values.fold((0.0, 0usize), |(sum, count), value| (sum + value * value, count + 1));
+    if count == 0 { 0.0 } else { sum / count as f64 }
+}
+
+#[cfg(test)]
+#[path = "stats_test.rs"]
+mod tests;
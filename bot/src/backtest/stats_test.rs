@@ -0,0 +1,73 @@
+use super::*;
+use crate::price_feed::TokenPair;
+
+fn fill(action: TradeAction, size: f64, price: f64, fee: f64, timestamp: i64) -> Fill {
+    Fill {
+        pair: TokenPair::new("SOL", "USDC"),
+        client_order_id: 0,
+        action,
+        price,
+        size,
+        fee,
+        timestamp,
+    }
+}
+
+fn point(timestamp_ms: i64, equity: f64, in_market: bool) -> EquityPoint {
+    EquityPoint { timestamp_ms, equity, in_market }
+}
+
+#[test]
+fn test_ledger_closes_partially_and_flips() {
+    let mut ledger = TradeLedger::new();
+    ledger.record("ma", &fill(TradeAction::Buy, 1.0, 10.0, 0.2, 1));
+    ledger.record("ma", &fill(TradeAction::Buy, 1.0, 12.0, 0.2, 2));
+    ledger.record("ma", &fill(TradeAction::Sell, 1.0, 13.0, 0.1, 3));
+    // Продажа 2 закрывает остаток длинной позиции и открывает короткую на 1
+    ledger.record("ma", &fill(TradeAction::Sell, 2.0, 10.0, 0.4, 4));
+    ledger.record("ma", &fill(TradeAction::Buy, 1.0, 9.0, 0.1, 5));
+
+    let trades = ledger.trades();
+    assert_eq!(trades.len(), 3);
+    // Средняя цена входа 11, комиссия входа 0.2 на каждую единицу
+    assert_eq!((trades[0].side, trades[0].entry_price, trades[0].entry_time), (TradeAction::Buy, 11.0, 1));
+    assert!((trades[0].pnl - (2.0 - 0.2 - 0.1)).abs() < 1e-9);
+    assert!((trades[1].pnl - (-1.0 - 0.2 - 0.2)).abs() < 1e-9);
+    assert_eq!((trades[2].side, trades[2].entry_time, trades[2].exit_time), (TradeAction::Sell, 4, 5));
+    assert!((trades[2].pnl - (1.0 - 0.2 - 0.1)).abs() < 1e-9);
+}
+
+#[test]
+fn test_stats_from_equity_and_trades() {
+    let equity = vec![
+        point(0, 100.0, false),
+        point(1_000, 110.0, true),
+        point(2_000, 99.0, true),
+        point(3_000, 120.0, false),
+        point(4_000, 120.0, false),
+    ];
+    let trade = |pnl: f64| Trade {
+        strategy_id: "ma".to_string(),
+        pair: "SOL/USDC".to_string(),
+        side: TradeAction::Buy,
+        size: 1.0,
+        entry_price: 10.0,
+        exit_price: 10.0 + pnl,
+        entry_time: 0,
+        exit_time: 1,
+        fees: 0.0,
+        pnl,
+    };
+    let stats = Stats::compute(&equity, &[trade(30.0), trade(-10.0), trade(-5.0)], 1.5, 1);
+
+    assert!((stats.total_return - 0.2).abs() < 1e-9);
+    assert!((stats.max_drawdown - 0.1).abs() < 1e-9);
+    assert!((stats.win_rate - 1.0 / 3.0).abs() < 1e-9);
+    assert_eq!(stats.profit_factor, Some(2.0));
+    assert!((stats.exposure - 0.5).abs() < 1e-9);
+    assert!(stats.sharpe > 0.0 && stats.sortino > stats.sharpe);
+
+    // Без убыточных сделок profit factor не определен, ровная кривая - нулевой Sharpe
+    let flat = Stats::compute(&[point(0, 100.0, false), point(1_000, 100.0, false)], &[trade(1.0)], 0.0, 1);
+    assert_eq!((flat.profit_factor, flat.sharpe, flat.max_drawdown), (None, 0.0, 0.0));
+}
@@ -0,0 +1,190 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+use serde::Deserialize;
+
+// Сколько закрытых баров хранится на каждый таймфрейм
+const CANDLES_CAPACITY: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub enum Timeframe {
+    #[serde(rename = "1s")]
+    Sec1,
+    #[serde(rename = "1m")]
+    Min1,
+    #[serde(rename = "5m")]
+    Min5,
+    #[serde(rename = "1h")]
+    Hour1,
+}
+
+impl Timeframe {
+    pub const ALL: [Timeframe; 4] = [Timeframe::Sec1, Timeframe::Min1, Timeframe::Min5, Timeframe::Hour1];
+
+    pub fn seconds(&self) -> i64 {
+        match self {
+            Timeframe::Sec1 => 1,
+            Timeframe::Min1 => 60,
+            Timeframe::Min5 => 5 * 60,
+            Timeframe::Hour1 => 60 * 60,
+        }
+    }
+
+    // Начало бара, в который попадает момент времени
+    pub fn bucket_start(&self, timestamp: i64) -> i64 {
+        timestamp - timestamp.rem_euclid(self.seconds())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Candle {
+    // Unix-время начала бара в секундах
+    pub open_time: i64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    // Объем сделок в котируемом токене
+    pub volume: f64,
+}
+
+impl Candle {
+    fn new(open_time: i64, price: f64) -> Self {
+        Self {
+            open_time,
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            volume: 0.0,
+        }
+    }
+
+    fn update(&mut self, price: f64, volume: f64) {
+        self.high = self.high.max(price);
+        self.low = self.low.min(price);
+        self.close = price;
+        self.volume += volume;
+    }
+}
+
+// Закрытые бары и текущий незакрытый бар одного таймфрейма. Закрытые бары лежат
+// в общем `Arc`: клон серии для снимка не копирует их, пока бар не закроется.
+#[derive(Debug, Clone)]
+pub struct CandleSeries {
+    timeframe: Timeframe,
+    closed: Arc<VecDeque<Candle>>,
+    current: Option<Candle>,
+}
+
+impl CandleSeries {
+    pub fn new(timeframe: Timeframe) -> Self {
+        Self {
+            timeframe,
+            closed: Arc::new(VecDeque::with_capacity(CANDLES_CAPACITY)),
+            current: None,
+        }
+    }
+
+    pub fn closed(&self) -> &VecDeque<Candle> {
+        &self.closed
+    }
+
+    pub fn current(&self) -> Option<&Candle> {
+        self.current.as_ref()
+    }
+
+    pub fn update(&mut self, timestamp: i64, price: f64, volume: f64) {
+        self.advance(timestamp);
+
+        let bucket = self.timeframe.bucket_start(timestamp);
+        match self.current.as_mut() {
+            Some(candle) if candle.open_time == bucket => candle.update(price, volume),
+            // Запоздавшее обновление для уже закрытого бара игнорируем
+            Some(candle) if candle.open_time > bucket => {}
+            _ => {
+                let mut candle = Candle::new(bucket, price);
+                candle.volume = volume;
+                self.current = Some(candle);
+            }
+        }
+    }
+
+    // Закрывает бары, время которых истекло к моменту `timestamp`. Интервалы без
+    // обновлений заполняются плоскими барами по цене последнего закрытия.
+    pub fn advance(&mut self, timestamp: i64) {
+        let bucket = self.timeframe.bucket_start(timestamp);
+        let current = match self.current {
+            Some(candle) if candle.open_time < bucket => candle,
+            _ => return,
+        };
+
+        self.push_closed(current);
+        // После долгого простоя в буфере останутся только последние CANDLES_CAPACITY
+        // баров, поэтому более ранние плоские бары не строим вовсе
+        let seconds = self.timeframe.seconds();
+        let mut open_time = (current.open_time + seconds).max(bucket - CANDLES_CAPACITY as i64 * seconds);
+        while open_time < bucket {
+            self.push_closed(Candle::new(open_time, current.close));
+            open_time += seconds;
+        }
+
+        // Новый бар открывается по последней известной цене
+        self.current = Some(Candle::new(bucket, current.close));
+    }
+
+    fn push_closed(&mut self, candle: Candle) {
+        let closed = Arc::make_mut(&mut self.closed);
+        if closed.len() == CANDLES_CAPACITY {
+            closed.pop_front();
+        }
+        closed.push_back(candle);
+    }
+}
+
+// Строит бары всех таймфреймов по тикам оракула и сделкам DEX
+pub struct CandleBuilder {
+    series: HashMap<String, HashMap<Timeframe, CandleSeries>>,
+}
+
+impl CandleBuilder {
+    pub fn new() -> Self {
+        Self {
+            series: HashMap::new(),
+        }
+    }
+
+    pub fn on_tick(&mut self, pair: &str, timestamp: i64, price: f64) {
+        self.update(pair, timestamp, price, 0.0);
+    }
+
+    pub fn on_fill(&mut self, pair: &str, timestamp: i64, price: f64, volume: f64) {
+        self.update(pair, timestamp, price, volume);
+    }
+
+    // Закрывает истекшие бары по всем парам, даже если обновлений не было
+    pub fn advance(&mut self, timestamp: i64) {
+        for series in self.series.values_mut().flat_map(|by_timeframe| by_timeframe.values_mut()) {
+            series.advance(timestamp);
+        }
+    }
+
+    // Дешевый снимок: закрытые бары разделяются с построителем, а не копируются
+    pub fn snapshot(&self) -> HashMap<String, HashMap<Timeframe, CandleSeries>> {
+        self.series.clone()
+    }
+
+    fn update(&mut self, pair: &str, timestamp: i64, price: f64, volume: f64) {
+        let by_timeframe = self.series.entry(pair.to_string()).or_insert_with(|| {
+            Timeframe::ALL.iter().map(|&timeframe| (timeframe, CandleSeries::new(timeframe))).collect()
+        });
+
+        for series in by_timeframe.values_mut() {
+            series.update(timestamp, price, volume);
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "candles_test.rs"]
+mod tests;
@@ -0,0 +1,80 @@
+use super::*;
+
+fn closed(series: &CandleSeries) -> Vec<(i64, f64)> {
+    series.closed().iter().map(|candle| (candle.open_time, candle.close)).collect()
+}
+
+#[test]
+fn test_gap_is_filled_with_flat_bars() {
+    let mut series = CandleSeries::new(Timeframe::Min1);
+    series.update(0, 20.0, 0.0);
+    series.update(30, 21.0, 0.0);
+    // Две минуты без обновлений
+    series.update(185, 22.0, 0.0);
+
+    assert_eq!(closed(&series), vec![(0, 21.0), (60, 21.0), (120, 21.0)]);
+    let flat = series.closed()[1];
+    assert_eq!((flat.open, flat.high, flat.low, flat.volume), (21.0, 21.0, 21.0, 0.0));
+    // Новый бар открывается по последнему закрытию
+    let current = series.current().unwrap();
+    assert_eq!((current.open_time, current.open, current.close), (180, 21.0, 22.0));
+}
+
+#[test]
+fn test_long_gap_keeps_only_capacity() {
+    let mut series = CandleSeries::new(Timeframe::Sec1);
+    series.update(0, 20.0, 0.0);
+    // Год простоя: заполняются только бары, которые поместятся в буфер
+    let later = 365 * 24 * 60 * 60;
+    series.advance(later);
+
+    let closed = series.closed();
+    assert_eq!(closed.len(), CANDLES_CAPACITY);
+    assert_eq!(closed.front().unwrap().open_time, later - CANDLES_CAPACITY as i64);
+    assert_eq!(closed.back().unwrap().open_time, later - 1);
+    assert!(closed.iter().all(|candle| candle.close == 20.0));
+    assert_eq!(series.current().unwrap().open_time, later);
+}
+
+#[test]
+fn test_late_and_out_of_order_fills() {
+    let mut series = CandleSeries::new(Timeframe::Min1);
+    series.update(10, 20.0, 1.0);
+    // Сделка раньше последнего тика, но в том же баре, учитывается
+    series.update(50, 21.0, 1.0);
+    series.update(20, 19.0, 2.0);
+    let current = *series.current().unwrap();
+    assert_eq!((current.high, current.low, current.close, current.volume), (21.0, 19.0, 19.0, 4.0));
+
+    // Сделка из уже закрытого бара не меняет ни его, ни текущий
+    series.update(70, 22.0, 1.0);
+    series.update(30, 30.0, 5.0);
+    assert_eq!(series.closed()[0], current);
+    let current = series.current().unwrap();
+    assert_eq!((current.open_time, current.high, current.volume), (60, 22.0, 1.0));
+}
+
+#[test]
+fn test_builder_rolls_up_timeframes() {
+    let mut builder = CandleBuilder::new();
+    for second in 0..300 {
+        builder.on_tick("SOL/USDC", second, 20.0 + (second % 60) as f64);
+    }
+    builder.on_fill("SOL/USDC", 299, 20.0, 150.0);
+    builder.advance(300);
+
+    let series = &builder.snapshot()["SOL/USDC"];
+    assert_eq!(series[&Timeframe::Sec1].closed().len(), 300);
+    let minutes = series[&Timeframe::Min1].closed();
+    assert_eq!(minutes.len(), 5);
+    assert!(minutes.iter().all(|candle| candle.high == 79.0 && candle.low == 20.0));
+    assert_eq!((minutes[0].open, minutes[1].open, minutes[4].close), (20.0, 79.0, 20.0));
+
+    // Пятиминутный бар собран из тех же тиков, что и минутные
+    let five = series[&Timeframe::Min5].closed();
+    assert_eq!(five.len(), 1);
+    assert_eq!((five[0].open_time, five[0].high, five[0].low, five[0].volume), (0, 79.0, 20.0, 150.0));
+    // Часовой бар еще открыт
+    assert!(series[&Timeframe::Hour1].closed().is_empty());
+    assert_eq!(series[&Timeframe::Hour1].current().unwrap().volume, 150.0);
+}
@@ -0,0 +1,187 @@
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+use crate::backtest::{BacktestOptions, ExchangeConfig, MonteCarloConfig, OptimizeOptions};
+use crate::recorder;
+use crate::replay::ReplayConfig;
+
+// Период доходностей для Sharpe и Sortino по умолчанию
+const DEFAULT_PERIOD_SECS: i64 = 60 * 60;
+// Каталог результатов оптимизации по умолчанию
+const DEFAULT_OPTIMIZE_OUTPUT: &str = "optimize-results";
+
+// Параметры командной строки:
+//   --replay <файл или каталог>  воспроизвести записанные рыночные данные
+//   --replay-speed <x>           ускорение воспроизведения (0 - без пауз)
+//   --paper                      бумажная торговля на виртуальном счете вместо Jito
+//
+// Подкоманда `backtest` прогоняет стратегии на исторических данных:
+//   --data <файл или каталог>    CSV с ценами или барами либо записи бота
+//   --output <каталог>           сохранить кривую капитала, сделки и статистику
+//   --latency-ms <мс>            задержка от сигнала до исполнения
+//   --slippage-bps <bps>         проскальзывание тейкера
+//   --fee-bps <bps>              комиссия тейкера вместо комиссии рынка
+//   --maker-fee-bps <bps>        комиссия мейкера (отрицательная - ребейт)
+//   --period-secs <с>            период доходностей для Sharpe и Sortino
+//   --monte-carlo <файл>         Monte Carlo по сделкам бэктеста с настройками из файла
+//
+// Подкоманда `optimize` подбирает параметры стратегии бэктестами:
+//   --data <файл или каталог>    исторические данные, как у `backtest`
+//   --spec <файл>                пространство параметров и настройки поиска
+//   --output <каталог>           таблицы результатов и strategies.json (optimize-results)
+//   --threads <n>                число потоков (по умолчанию - по числу ядер)
+//   флаги биржи и --period-secs - как у `backtest`
+#[derive(Debug, Clone, Default)]
+pub struct CliOptions {
+    pub replay: Option<ReplayConfig>,
+    pub backtest: Option<BacktestOptions>,
+    pub optimize: Option<OptimizeOptions>,
+    // Включает бумажную торговлю независимо от конфигурации
+    pub paper: bool,
+}
+
+impl CliOptions {
+    pub fn parse(args: impl Iterator<Item = String>) -> Result<Self, Box<dyn Error>> {
+        let mut args = args.peekable();
+        match args.peek().map(String::as_str) {
+            Some("backtest") => {
+                args.next();
+                return Ok(Self {
+                    backtest: Some(parse_backtest(args)?),
+                    ..Self::default()
+                });
+            }
+            Some("optimize") => {
+                args.next();
+                return Ok(Self {
+                    optimize: Some(parse_optimize(args)?),
+                    ..Self::default()
+                });
+            }
+            _ => {}
+        }
+
+        let mut replay_path = None;
+        let mut speed = 1.0;
+        let mut paper = false;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--replay" => replay_path = Some(next_value(&mut args, &arg)?),
+                "--paper" => paper = true,
+                "--replay-speed" => {
+                    speed = next_value(&mut args, &arg)?.parse()
+                        .map_err(|e| format!("Invalid --replay-speed: {}", e))?;
+                }
+                _ => return Err(format!("Unknown argument '{}'", arg).into()),
+            }
+        }
+
+        let replay = match replay_path {
+            Some(path) => Some(ReplayConfig {
+                files: recorder::record_files(Path::new(&path))?,
+                speed,
+            }),
+            None => None,
+        };
+
+        Ok(Self { replay, paper, ..Self::default() })
+    }
+}
+
+fn parse_backtest(mut args: impl Iterator<Item = String>) -> Result<BacktestOptions, Box<dyn Error>> {
+    let mut data = None;
+    let mut output = None;
+    let mut exchange = ExchangeConfig::default();
+    let mut period_secs = DEFAULT_PERIOD_SECS;
+    let mut monte_carlo = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--data" => data = Some(PathBuf::from(next_value(&mut args, &arg)?)),
+            "--output" => output = Some(PathBuf::from(next_value(&mut args, &arg)?)),
+            "--period-secs" => period_secs = parse_value(&mut args, &arg)?,
+            "--monte-carlo" => monte_carlo = Some(MonteCarloConfig::load(Path::new(&next_value(&mut args, &arg)?))?),
+            _ => {
+                if !parse_exchange_flag(&arg, &mut args, &mut exchange)? {
+                    return Err(format!("Unknown backtest argument '{}'", arg).into());
+                }
+            }
+        }
+    }
+    if period_secs <= 0 {
+        return Err("--period-secs must be positive".into());
+    }
+
+    Ok(BacktestOptions {
+        data: data.ok_or("backtest requires --data")?,
+        output,
+        exchange,
+        period_secs,
+        monte_carlo,
+    })
+}
+
+fn parse_optimize(mut args: impl Iterator<Item = String>) -> Result<OptimizeOptions, Box<dyn Error>> {
+    let mut data = None;
+    let mut spec = None;
+    let mut output = PathBuf::from(DEFAULT_OPTIMIZE_OUTPUT);
+    let mut exchange = ExchangeConfig::default();
+    let mut period_secs = DEFAULT_PERIOD_SECS;
+    let mut threads = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--data" => data = Some(PathBuf::from(next_value(&mut args, &arg)?)),
+            "--spec" => spec = Some(PathBuf::from(next_value(&mut args, &arg)?)),
+            "--output" => output = PathBuf::from(next_value(&mut args, &arg)?),
+            "--threads" => threads = Some(parse_value(&mut args, &arg)?),
+            "--period-secs" => period_secs = parse_value(&mut args, &arg)?,
+            _ => {
+                if !parse_exchange_flag(&arg, &mut args, &mut exchange)? {
+                    return Err(format!("Unknown optimize argument '{}'", arg).into());
+                }
+            }
+        }
+    }
+    if period_secs <= 0 {
+        return Err("--period-secs must be positive".into());
+    }
+    if threads == Some(0) {
+        return Err("--threads must be positive".into());
+    }
+
+    Ok(OptimizeOptions {
+        data: data.ok_or("optimize requires --data")?,
+        spec: spec.ok_or("optimize requires --spec")?,
+        output,
+        exchange,
+        period_secs,
+        threads,
+    })
+}
+
+// Флаги симулятора биржи, общие для `backtest` и `optimize`; false - флаг не биржевой
+fn parse_exchange_flag(flag: &str, args: &mut impl Iterator<Item = String>, exchange: &mut ExchangeConfig) -> Result<bool, Box<dyn Error>> {
+    match flag {
+        "--latency-ms" => exchange.latency_ms = parse_value(args, flag)?,
+        "--slippage-bps" => exchange.slippage_bps = parse_value(args, flag)?,
+        "--fee-bps" => exchange.taker_fee_bps = Some(parse_value(args, flag)?),
+        "--maker-fee-bps" => exchange.maker_fee_bps = parse_value(args, flag)?,
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
+
+fn parse_value<T>(args: &mut impl Iterator<Item = String>, flag: &str) -> Result<T, Box<dyn Error>>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    next_value(args, flag)?.parse()
+        .map_err(|e: T::Err| format!("Invalid {}: {}", flag, e).into())
+}
+
+fn next_value(args: &mut impl Iterator<Item = String>, flag: &str) -> Result<String, Box<dyn Error>> {
+    args.next().ok_or_else(|| format!("{} requires a value", flag).into())
+}
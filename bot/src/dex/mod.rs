@@ -0,0 +1,80 @@
+use crate::jito_integration::TradeAction;
+
+pub mod orca;
+pub mod raydium;
+pub mod serum;
+
+use orca::OrcaWhirlpool;
+use raydium::RaydiumPool;
+
+// Общий интерфейс площадки, способной оценить обмен по снимку аккаунтов.
+// Для покупки `amount_in` задается в котируемом токене и возвращается
+// количество базового токена, для продажи - наоборот. Все суммы в UI-единицах.
+pub trait LiquiditySource {
+    fn name(&self) -> &str;
+
+    fn quote(&self, action: TradeAction, amount_in: f64) -> Option<f64>;
+}
+
+// Снимок AMM-пула, который можно передать стратегиям вместе с остальными данными тика
+#[derive(Debug, Clone)]
+pub enum AmmPool {
+    Raydium(RaydiumPool),
+    Orca(OrcaWhirlpool),
+}
+
+impl LiquiditySource for AmmPool {
+    fn name(&self) -> &str {
+        match self {
+            AmmPool::Raydium(pool) => pool.name(),
+            AmmPool::Orca(pool) => pool.name(),
+        }
+    }
+
+    fn quote(&self, action: TradeAction, amount_in: f64) -> Option<f64> {
+        match self {
+            AmmPool::Raydium(pool) => pool.quote(action, amount_in),
+            AmmPool::Orca(pool) => pool.quote(action, amount_in),
+        }
+    }
+}
+
+// Количество токенов в SPL token account хранится по смещению 64
+pub fn decode_token_amount(data: &[u8]) -> Option<u64> {
+    read_u64(data, 64)
+}
+
+pub(crate) fn read_u64(data: &[u8], offset: usize) -> Option<u64> {
+    let bytes = data.get(offset..offset + 8)?;
+    Some(u64::from_le_bytes(bytes.try_into().ok()?))
+}
+
+pub(crate) fn read_i64(data: &[u8], offset: usize) -> Option<i64> {
+    let bytes = data.get(offset..offset + 8)?;
+    Some(i64::from_le_bytes(bytes.try_into().ok()?))
+}
+
+pub(crate) fn read_u128(data: &[u8], offset: usize) -> Option<u128> {
+    let bytes = data.get(offset..offset + 16)?;
+    Some(u128::from_le_bytes(bytes.try_into().ok()?))
+}
+
+pub(crate) fn read_i128(data: &[u8], offset: usize) -> Option<i128> {
+    let bytes = data.get(offset..offset + 16)?;
+    Some(i128::from_le_bytes(bytes.try_into().ok()?))
+}
+
+pub(crate) fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes(bytes.try_into().ok()?))
+}
+
+pub(crate) fn read_i32(data: &[u8], offset: usize) -> Option<i32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(i32::from_le_bytes(bytes.try_into().ok()?))
+}
+
+pub(crate) fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset + 2)?;
+    Some(u16::from_le_bytes(bytes.try_into().ok()?))
+}
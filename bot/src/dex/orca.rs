@@ -0,0 +1,249 @@
+use std::error::Error;
+
+use super::{read_i128, read_i32, read_u128, read_u16, LiquiditySource};
+use crate::jito_integration::TradeAction;
+
+// Смещения полей аккаунта Whirlpool (после 8 байт дискриминатора Anchor)
+const WHIRLPOOL_MIN_LEN: usize = 261;
+const TICK_SPACING_OFFSET: usize = 41;
+const FEE_RATE_OFFSET: usize = 45;
+const LIQUIDITY_OFFSET: usize = 49;
+const SQRT_PRICE_OFFSET: usize = 65;
+const TICK_CURRENT_INDEX_OFFSET: usize = 81;
+
+// Комиссия задается в сотых долях базисного пункта
+const FEE_RATE_DENOMINATOR: f64 = 1_000_000.0;
+
+const TICK_ARRAY_SIZE: usize = 88;
+const TICK_ARRAY_START_OFFSET: usize = 8;
+const TICK_ARRAY_TICKS_OFFSET: usize = 12;
+const TICK_LEN: usize = 113;
+
+#[derive(Debug, Clone)]
+pub struct WhirlpoolState {
+    pub tick_spacing: u16,
+    pub fee_rate: u16,
+    pub liquidity: u128,
+    // Q64.64
+    pub sqrt_price: u128,
+    pub tick_current_index: i32,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct InitializedTick {
+    pub index: i32,
+    pub liquidity_net: i128,
+}
+
+pub fn decode_whirlpool(data: &[u8]) -> Result<WhirlpoolState, Box<dyn Error>> {
+    if data.len() < WHIRLPOOL_MIN_LEN {
+        return Err("Whirlpool account is too small".into());
+    }
+
+    Ok(WhirlpoolState {
+        tick_spacing: read_u16(data, TICK_SPACING_OFFSET).ok_or("Invalid tick spacing")?,
+        fee_rate: read_u16(data, FEE_RATE_OFFSET).ok_or("Invalid fee rate")?,
+        liquidity: read_u128(data, LIQUIDITY_OFFSET).ok_or("Invalid liquidity")?,
+        sqrt_price: read_u128(data, SQRT_PRICE_OFFSET).ok_or("Invalid sqrt price")?,
+        tick_current_index: read_i32(data, TICK_CURRENT_INDEX_OFFSET).ok_or("Invalid tick index")?,
+    })
+}
+
+// Возвращает только инициализированные тики из аккаунта TickArray
+pub fn decode_tick_array(data: &[u8], tick_spacing: u16) -> Result<Vec<InitializedTick>, Box<dyn Error>> {
+    if data.len() < TICK_ARRAY_TICKS_OFFSET + TICK_ARRAY_SIZE * TICK_LEN {
+        return Err("Tick array account is too small".into());
+    }
+
+    let start_tick_index = read_i32(data, TICK_ARRAY_START_OFFSET).ok_or("Invalid tick array")?;
+    let mut ticks = Vec::new();
+
+    for i in 0..TICK_ARRAY_SIZE {
+        let offset = TICK_ARRAY_TICKS_OFFSET + i * TICK_LEN;
+        if data[offset] == 0 {
+            continue;
+        }
+
+        ticks.push(InitializedTick {
+            index: start_tick_index + i as i32 * tick_spacing as i32,
+            liquidity_net: read_i128(data, offset + 1).ok_or("Invalid tick")?,
+        });
+    }
+
+    Ok(ticks)
+}
+
+pub fn tick_to_sqrt_price(tick: i32) -> f64 {
+    1.0001f64.powf(tick as f64 / 2.0)
+}
+
+#[derive(Debug, Clone)]
+pub struct OrcaWhirlpool {
+    name: String,
+    base_is_a: bool,
+    decimals_a: u8,
+    decimals_b: u8,
+    fee: f64,
+    liquidity: f64,
+    sqrt_price: f64,
+    tick_current_index: i32,
+    // Отсортированы по возрастанию индекса
+    ticks: Vec<InitializedTick>,
+}
+
+impl OrcaWhirlpool {
+    pub fn from_accounts(
+        name: &str,
+        whirlpool_data: &[u8],
+        tick_arrays: &[Vec<u8>],
+        base_is_a: bool,
+        decimals_a: u8,
+        decimals_b: u8,
+    ) -> Result<Self, Box<dyn Error>> {
+        let state = decode_whirlpool(whirlpool_data)?;
+
+        let mut ticks = Vec::new();
+        for data in tick_arrays {
+            ticks.extend(decode_tick_array(data, state.tick_spacing)?);
+        }
+        ticks.sort_by_key(|tick| tick.index);
+        ticks.dedup_by_key(|tick| tick.index);
+
+        Ok(Self {
+            name: name.to_string(),
+            base_is_a,
+            decimals_a,
+            decimals_b,
+            fee: state.fee_rate as f64 / FEE_RATE_DENOMINATOR,
+            liquidity: state.liquidity as f64,
+            sqrt_price: state.sqrt_price as f64 / 2f64.powi(64),
+            tick_current_index: state.tick_current_index,
+            ticks,
+        })
+    }
+
+    // Обмен A -> B: цена (B за A) снижается, тики пересекаются вниз
+    fn swap_a_to_b(&self, amount_in: f64) -> Option<f64> {
+        let mut remaining = amount_in * (1.0 - self.fee);
+        let mut liquidity = self.liquidity;
+        let mut sqrt_price = self.sqrt_price;
+        let mut amount_out = 0.0;
+        let mut ticks = self.ticks.iter().rev().filter(|tick| tick.index <= self.tick_current_index);
+
+        while remaining > 0.0 {
+            let next_tick = ticks.next();
+            let target = match next_tick {
+                Some(tick) => tick_to_sqrt_price(tick.index),
+                None => 0.0,
+            };
+
+            if liquidity > 0.0 {
+                let max_in = if target > 0.0 {
+                    liquidity * (1.0 / target - 1.0 / sqrt_price)
+                } else {
+                    f64::INFINITY
+                };
+
+                if remaining < max_in {
+                    let new_sqrt_price = 1.0 / (1.0 / sqrt_price + remaining / liquidity);
+                    amount_out += liquidity * (sqrt_price - new_sqrt_price);
+                    remaining = 0.0;
+                    break;
+                }
+
+                amount_out += liquidity * (sqrt_price - target);
+                remaining -= max_in;
+            }
+
+            match next_tick {
+                Some(tick) => {
+                    sqrt_price = target;
+                    liquidity -= tick.liquidity_net as f64;
+                }
+                None => break,
+            }
+        }
+
+        if remaining > 0.0 {
+            return None;
+        }
+        Some(amount_out)
+    }
+
+    // Обмен B -> A: цена растет, тики пересекаются вверх
+    fn swap_b_to_a(&self, amount_in: f64) -> Option<f64> {
+        let mut remaining = amount_in * (1.0 - self.fee);
+        let mut liquidity = self.liquidity;
+        let mut sqrt_price = self.sqrt_price;
+        let mut amount_out = 0.0;
+        let mut ticks = self.ticks.iter().filter(|tick| tick.index > self.tick_current_index);
+
+        while remaining > 0.0 {
+            let next_tick = ticks.next();
+            let target = match next_tick {
+                Some(tick) => tick_to_sqrt_price(tick.index),
+                None => f64::INFINITY,
+            };
+
+            if liquidity > 0.0 {
+                let max_in = liquidity * (target - sqrt_price);
+
+                if remaining < max_in {
+                    let new_sqrt_price = sqrt_price + remaining / liquidity;
+                    amount_out += liquidity * (1.0 / sqrt_price - 1.0 / new_sqrt_price);
+                    remaining = 0.0;
+                    break;
+                }
+
+                amount_out += liquidity * (1.0 / sqrt_price - 1.0 / target);
+                remaining -= max_in;
+            }
+
+            match next_tick {
+                Some(tick) => {
+                    sqrt_price = target;
+                    liquidity += tick.liquidity_net as f64;
+                }
+                None => break,
+            }
+        }
+
+        if remaining > 0.0 {
+            return None;
+        }
+        Some(amount_out)
+    }
+}
+
+impl LiquiditySource for OrcaWhirlpool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn quote(&self, action: TradeAction, amount_in: f64) -> Option<f64> {
+        if amount_in <= 0.0 {
+            return None;
+        }
+
+        // Направление обмена в терминах токенов A/B пула
+        let a_to_b = match action {
+            TradeAction::Buy => !self.base_is_a,
+            TradeAction::Sell => self.base_is_a,
+        };
+
+        let (decimals_in, decimals_out) = if a_to_b {
+            (self.decimals_a, self.decimals_b)
+        } else {
+            (self.decimals_b, self.decimals_a)
+        };
+
+        let native_in = amount_in * 10f64.powi(decimals_in as i32);
+        let native_out = if a_to_b {
+            self.swap_a_to_b(native_in)?
+        } else {
+            self.swap_b_to_a(native_in)?
+        };
+
+        Some(native_out / 10f64.powi(decimals_out as i32))
+    }
+}
@@ -0,0 +1,101 @@
+use std::error::Error;
+
+use solana_sdk::pubkey::Pubkey;
+
+use super::{decode_token_amount, read_u64, LiquiditySource};
+use crate::jito_integration::TradeAction;
+
+// Смещения полей AmmInfo (Raydium AMM v4, 752 байта)
+const AMM_INFO_LEN: usize = 752;
+const SWAP_FEE_NUMERATOR_OFFSET: usize = 176;
+const SWAP_FEE_DENOMINATOR_OFFSET: usize = 184;
+const NEED_TAKE_PNL_COIN_OFFSET: usize = 192;
+const NEED_TAKE_PNL_PC_OFFSET: usize = 200;
+const POOL_COIN_VAULT_OFFSET: usize = 336;
+const POOL_PC_VAULT_OFFSET: usize = 368;
+
+#[derive(Debug, Clone)]
+pub struct AmmInfo {
+    pub swap_fee_numerator: u64,
+    pub swap_fee_denominator: u64,
+    pub need_take_pnl_coin: u64,
+    pub need_take_pnl_pc: u64,
+    pub coin_vault: Pubkey,
+    pub pc_vault: Pubkey,
+}
+
+pub fn decode_amm_info(data: &[u8]) -> Result<AmmInfo, Box<dyn Error>> {
+    if data.len() < AMM_INFO_LEN {
+        return Err("Raydium AMM account is too small".into());
+    }
+
+    let pubkey_at = |offset: usize| Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap());
+
+    Ok(AmmInfo {
+        swap_fee_numerator: read_u64(data, SWAP_FEE_NUMERATOR_OFFSET).ok_or("Invalid AMM fees")?,
+        swap_fee_denominator: read_u64(data, SWAP_FEE_DENOMINATOR_OFFSET).ok_or("Invalid AMM fees")?,
+        need_take_pnl_coin: read_u64(data, NEED_TAKE_PNL_COIN_OFFSET).ok_or("Invalid AMM pnl")?,
+        need_take_pnl_pc: read_u64(data, NEED_TAKE_PNL_PC_OFFSET).ok_or("Invalid AMM pnl")?,
+        coin_vault: pubkey_at(POOL_COIN_VAULT_OFFSET),
+        pc_vault: pubkey_at(POOL_PC_VAULT_OFFSET),
+    })
+}
+
+// Пул постоянного произведения: base = coin, quote = pc
+#[derive(Debug, Clone)]
+pub struct RaydiumPool {
+    name: String,
+    base_reserve: f64,
+    quote_reserve: f64,
+    fee: f64,
+}
+
+impl RaydiumPool {
+    pub fn from_accounts(
+        name: &str,
+        amm_data: &[u8],
+        coin_vault_data: &[u8],
+        pc_vault_data: &[u8],
+        base_decimals: u8,
+        quote_decimals: u8,
+    ) -> Result<Self, Box<dyn Error>> {
+        let amm = decode_amm_info(amm_data)?;
+        if amm.swap_fee_denominator == 0 {
+            return Err("Raydium AMM has zero fee denominator".into());
+        }
+
+        let coin_amount = decode_token_amount(coin_vault_data).ok_or("Invalid coin vault")?;
+        let pc_amount = decode_token_amount(pc_vault_data).ok_or("Invalid pc vault")?;
+
+        // Невыведенный PnL пула лежит в хранилищах, но в резервы не входит
+        let base_reserve = coin_amount.saturating_sub(amm.need_take_pnl_coin);
+        let quote_reserve = pc_amount.saturating_sub(amm.need_take_pnl_pc);
+
+        Ok(Self {
+            name: name.to_string(),
+            base_reserve: base_reserve as f64 / 10f64.powi(base_decimals as i32),
+            quote_reserve: quote_reserve as f64 / 10f64.powi(quote_decimals as i32),
+            fee: amm.swap_fee_numerator as f64 / amm.swap_fee_denominator as f64,
+        })
+    }
+}
+
+impl LiquiditySource for RaydiumPool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn quote(&self, action: TradeAction, amount_in: f64) -> Option<f64> {
+        if amount_in <= 0.0 || self.base_reserve <= 0.0 || self.quote_reserve <= 0.0 {
+            return None;
+        }
+
+        let (reserve_in, reserve_out) = match action {
+            TradeAction::Buy => (self.quote_reserve, self.base_reserve),
+            TradeAction::Sell => (self.base_reserve, self.quote_reserve),
+        };
+
+        let amount_in_after_fee = amount_in * (1.0 - self.fee);
+        Some(reserve_out * amount_in_after_fee / (reserve_in + amount_in_after_fee))
+    }
+}
@@ -0,0 +1,376 @@
+use std::error::Error;
+
+use solana_sdk::pubkey::Pubkey;
+
+use super::{read_u128, read_u32, read_u64, LiquiditySource};
+use crate::jito_integration::TradeAction;
+
+// Аккаунты Serum/OpenBook обрамлены строкой "serum" и 7 байтами "padding"
+const ACCOUNT_HEAD_PADDING: usize = 5;
+const ACCOUNT_TAIL_PADDING: usize = 7;
+const ACCOUNT_FLAGS_LEN: usize = 8;
+
+const SLAB_HEADER_LEN: usize = 32;
+const SLAB_NODE_LEN: usize = 72;
+const LEAF_NODE_TAG: u32 = 2;
+
+const MARKET_STATE_LEN: usize = 388;
+const MARKET_EVENT_QUEUE_OFFSET: usize = 253;
+const MARKET_BIDS_OFFSET: usize = 285;
+const MARKET_ASKS_OFFSET: usize = 317;
+const MARKET_COIN_LOT_SIZE_OFFSET: usize = 349;
+const MARKET_PC_LOT_SIZE_OFFSET: usize = 357;
+
+const EVENT_QUEUE_HEADER_LEN: usize = 24;
+const EVENT_LEN: usize = 88;
+const EVENT_FLAG_FILL: u8 = 0x01;
+const EVENT_FLAG_OUT: u8 = 0x02;
+const EVENT_FLAG_BID: u8 = 0x04;
+const EVENT_FLAG_MAKER: u8 = 0x08;
+
+const NEW_ORDER_V3_TAG: u32 = 10;
+const CANCEL_ORDER_BY_CLIENT_ID_V2_TAG: u32 = 12;
+// Сколько встречных ордеров может съесть одна инструкция
+const NEW_ORDER_MATCH_LIMIT: u16 = 65535;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SerumMarketParams {
+    pub base_lot_size: u64,
+    pub quote_lot_size: u64,
+    pub base_decimals: u8,
+    pub quote_decimals: u8,
+    pub taker_fee_bps: u64,
+}
+
+impl SerumMarketParams {
+    // Цена в лотах - количество quote-лотов за один base-лот
+    pub fn price_lots_to_ui(&self, price_lots: u64) -> f64 {
+        price_lots as f64 * self.quote_lot_size as f64 * 10f64.powi(self.base_decimals as i32)
+            / (self.base_lot_size as f64 * 10f64.powi(self.quote_decimals as i32))
+    }
+
+    pub fn size_lots_to_ui(&self, size_lots: u64) -> f64 {
+        size_lots as f64 * self.base_lot_size as f64 / 10f64.powi(self.base_decimals as i32)
+    }
+
+    pub fn taker_fee(&self) -> f64 {
+        self.taker_fee_bps as f64 / 10_000.0
+    }
+}
+
+// Поля MarketState, нужные для чтения стакана
+#[derive(Debug, Clone, Copy)]
+pub struct MarketState {
+    pub event_queue: Pubkey,
+    pub bids: Pubkey,
+    pub asks: Pubkey,
+    pub base_lot_size: u64,
+    pub quote_lot_size: u64,
+}
+
+pub fn decode_market_state(data: &[u8]) -> Result<MarketState, Box<dyn Error>> {
+    if data.len() < MARKET_STATE_LEN || &data[..ACCOUNT_HEAD_PADDING] != b"serum" {
+        return Err("Invalid Serum market account".into());
+    }
+
+    let pubkey_at = |offset: usize| Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap());
+
+    Ok(MarketState {
+        event_queue: pubkey_at(MARKET_EVENT_QUEUE_OFFSET),
+        bids: pubkey_at(MARKET_BIDS_OFFSET),
+        asks: pubkey_at(MARKET_ASKS_OFFSET),
+        base_lot_size: read_u64(data, MARKET_COIN_LOT_SIZE_OFFSET).ok_or("Invalid market lot size")?,
+        quote_lot_size: read_u64(data, MARKET_PC_LOT_SIZE_OFFSET).ok_or("Invalid market lot size")?,
+    })
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SlabOrder {
+    pub order_id: u128,
+    pub price_lots: u64,
+    pub quantity_lots: u64,
+    pub owner_slot: u8,
+    pub client_order_id: u64,
+}
+
+// Достаем все листья (ордера) из slab-аккаунта bids или asks.
+// Обходить дерево не нужно: листья лежат в массиве узлов до bump_index.
+pub fn decode_slab(data: &[u8]) -> Result<Vec<SlabOrder>, Box<dyn Error>> {
+    if data.len() < ACCOUNT_HEAD_PADDING + ACCOUNT_FLAGS_LEN + SLAB_HEADER_LEN + ACCOUNT_TAIL_PADDING {
+        return Err("Slab account is too small".into());
+    }
+    if &data[..ACCOUNT_HEAD_PADDING] != b"serum" {
+        return Err("Slab account has invalid head padding".into());
+    }
+
+    let header = ACCOUNT_HEAD_PADDING + ACCOUNT_FLAGS_LEN;
+    let bump_index = read_u64(data, header).ok_or("Invalid slab header")? as usize;
+    let nodes_start = header + SLAB_HEADER_LEN;
+    let nodes_end = data.len() - ACCOUNT_TAIL_PADDING;
+    let capacity = (nodes_end - nodes_start) / SLAB_NODE_LEN;
+
+    let mut orders = Vec::new();
+    for index in 0..bump_index.min(capacity) {
+        let node = nodes_start + index * SLAB_NODE_LEN;
+        if read_u32(data, node) != Some(LEAF_NODE_TAG) {
+            continue;
+        }
+
+        let key = read_u128(data, node + 8).ok_or("Invalid leaf node")?;
+        orders.push(SlabOrder {
+            order_id: key,
+            price_lots: (key >> 64) as u64,
+            quantity_lots: read_u64(data, node + 56).ok_or("Invalid leaf node")?,
+            owner_slot: data[node + 4],
+            client_order_id: read_u64(data, node + 64).ok_or("Invalid leaf node")?,
+        });
+    }
+
+    Ok(orders)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EventKind {
+    Fill,
+    Out,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SerumEvent {
+    pub seq_num: u64,
+    pub kind: EventKind,
+    pub is_bid: bool,
+    pub is_maker: bool,
+    pub owner_slot: u8,
+    pub native_qty_released: u64,
+    pub native_qty_paid: u64,
+    pub native_fee_or_rebate: u64,
+    pub order_id: u128,
+    // OpenOrders аккаунт владельца ордера
+    pub owner: Pubkey,
+    pub client_order_id: u64,
+}
+
+impl SerumEvent {
+    pub fn price_lots(&self) -> u64 {
+        (self.order_id >> 64) as u64
+    }
+}
+
+// Декодирует кольцевой буфер event queue. Возвращает события в порядке
+// поступления вместе с их порядковыми номерами.
+pub fn decode_event_queue(data: &[u8]) -> Result<Vec<SerumEvent>, Box<dyn Error>> {
+    if data.len() < ACCOUNT_HEAD_PADDING + ACCOUNT_FLAGS_LEN + EVENT_QUEUE_HEADER_LEN + ACCOUNT_TAIL_PADDING {
+        return Err("Event queue account is too small".into());
+    }
+    if &data[..ACCOUNT_HEAD_PADDING] != b"serum" {
+        return Err("Event queue account has invalid head padding".into());
+    }
+
+    let header = ACCOUNT_HEAD_PADDING + ACCOUNT_FLAGS_LEN;
+    let head = read_u64(data, header).ok_or("Invalid event queue header")? as usize;
+    let count = read_u64(data, header + 8).ok_or("Invalid event queue header")? as usize;
+    let seq_num = read_u64(data, header + 16).ok_or("Invalid event queue header")?;
+
+    let events_start = header + EVENT_QUEUE_HEADER_LEN;
+    let capacity = (data.len() - ACCOUNT_TAIL_PADDING - events_start) / EVENT_LEN;
+    if capacity == 0 || count > capacity {
+        return Err("Event queue header is inconsistent".into());
+    }
+
+    // seq_num в заголовке - номер следующего события; на поврежденной или
+    // только что созданной очереди он может быть меньше числа событий
+    let first_seq_num = seq_num.checked_sub(count as u64)
+        .ok_or("Event queue sequence number is smaller than event count")?;
+
+    let mut events = Vec::with_capacity(count);
+    for i in 0..count {
+        let offset = events_start + ((head + i) % capacity) * EVENT_LEN;
+        let flags = data[offset];
+        let kind = if flags & EVENT_FLAG_FILL != 0 {
+            EventKind::Fill
+        } else if flags & EVENT_FLAG_OUT != 0 {
+            EventKind::Out
+        } else {
+            return Err("Unknown event type in event queue".into());
+        };
+
+        events.push(SerumEvent {
+            seq_num: first_seq_num + i as u64,
+            kind,
+            is_bid: flags & EVENT_FLAG_BID != 0,
+            is_maker: flags & EVENT_FLAG_MAKER != 0,
+            owner_slot: data[offset + 1],
+            native_qty_released: read_u64(data, offset + 8).ok_or("Invalid event")?,
+            native_qty_paid: read_u64(data, offset + 16).ok_or("Invalid event")?,
+            native_fee_or_rebate: read_u64(data, offset + 24).ok_or("Invalid event")?,
+            order_id: read_u128(data, offset + 32).ok_or("Invalid event")?,
+            owner: Pubkey::new_from_array(data[offset + 48..offset + 80].try_into().unwrap()),
+            client_order_id: read_u64(data, offset + 80).ok_or("Invalid event")?,
+        });
+    }
+
+    Ok(events)
+}
+
+// Агрегирует ордера по цене: (цена, объем) в UI-единицах
+pub fn aggregate_levels(orders: &[SlabOrder], params: &SerumMarketParams, descending: bool) -> Vec<(f64, f64)> {
+    let mut by_price: Vec<(u64, u64)> = Vec::new();
+    let mut sorted = orders.to_vec();
+    sorted.sort_by_key(|order| order.price_lots);
+    if descending {
+        sorted.reverse();
+    }
+
+    for order in sorted {
+        match by_price.last_mut() {
+            Some((price, quantity)) if *price == order.price_lots => *quantity += order.quantity_lots,
+            _ => by_price.push((order.price_lots, order.quantity_lots)),
+        }
+    }
+
+    by_price
+        .into_iter()
+        .map(|(price, quantity)| (params.price_lots_to_ui(price), params.size_lots_to_ui(quantity)))
+        .collect()
+}
+
+// OrderType инструкции NewOrderV3
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderType {
+    Limit = 0,
+    ImmediateOrCancel = 1,
+    PostOnly = 2,
+}
+
+// Параметры нового ордера в лотах рынка
+#[derive(Debug, Clone, Copy)]
+pub struct NewOrder {
+    pub side: TradeAction,
+    pub limit_price_lots: u64,
+    pub max_coin_qty_lots: u64,
+    // Предел по котируемому токену в нативных единицах, включая комиссию
+    pub max_native_pc_qty: u64,
+    pub order_type: OrderType,
+    pub client_order_id: u64,
+}
+
+// Данные инструкции NewOrderV3: версия 0, тег 10 и поля в little-endian
+pub fn encode_new_order(order: &NewOrder) -> Vec<u8> {
+    let mut data = Vec::with_capacity(51);
+    data.push(0);
+    data.extend_from_slice(&NEW_ORDER_V3_TAG.to_le_bytes());
+    let side: u32 = match order.side {
+        TradeAction::Buy => 0,
+        TradeAction::Sell => 1,
+    };
+    data.extend_from_slice(&side.to_le_bytes());
+    data.extend_from_slice(&order.limit_price_lots.to_le_bytes());
+    data.extend_from_slice(&order.max_coin_qty_lots.to_le_bytes());
+    data.extend_from_slice(&order.max_native_pc_qty.to_le_bytes());
+    // Самоисполнение: уменьшить встречный ордер
+    data.extend_from_slice(&0u32.to_le_bytes());
+    data.extend_from_slice(&(order.order_type as u32).to_le_bytes());
+    data.extend_from_slice(&order.client_order_id.to_le_bytes());
+    data.extend_from_slice(&NEW_ORDER_MATCH_LIMIT.to_le_bytes());
+    data
+}
+
+// Данные инструкции CancelOrderByClientIdV2: версия 0, тег 12 и client_order_id
+pub fn encode_cancel_by_client_id(client_order_id: u64) -> Vec<u8> {
+    let mut data = Vec::with_capacity(13);
+    data.push(0);
+    data.extend_from_slice(&CANCEL_ORDER_BY_CLIENT_ID_V2_TAG.to_le_bytes());
+    data.extend_from_slice(&client_order_id.to_le_bytes());
+    data
+}
+
+pub struct SerumBook {
+    name: String,
+    taker_fee: f64,
+    // Лучшая цена первой
+    bids: Vec<(f64, f64)>,
+    asks: Vec<(f64, f64)>,
+}
+
+impl SerumBook {
+    pub fn from_levels(name: &str, taker_fee: f64, bids: Vec<(f64, f64)>, asks: Vec<(f64, f64)>) -> Self {
+        Self {
+            name: name.to_string(),
+            taker_fee,
+            bids,
+            asks,
+        }
+    }
+
+    pub fn from_slabs(
+        name: &str,
+        params: SerumMarketParams,
+        bids_data: &[u8],
+        asks_data: &[u8],
+    ) -> Result<Self, Box<dyn Error>> {
+        Ok(Self {
+            name: name.to_string(),
+            taker_fee: params.taker_fee(),
+            bids: aggregate_levels(&decode_slab(bids_data)?, &params, true),
+            asks: aggregate_levels(&decode_slab(asks_data)?, &params, false),
+        })
+    }
+}
+
+impl LiquiditySource for SerumBook {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn quote(&self, action: TradeAction, amount_in: f64) -> Option<f64> {
+        if amount_in <= 0.0 {
+            return None;
+        }
+
+        match action {
+            TradeAction::Buy => {
+                // Комиссия тейкера списывается сверх стоимости, поэтому
+                // на сам обмен уходит amount_in / (1 + fee)
+                let mut remaining = amount_in / (1.0 + self.taker_fee);
+                let mut bought = 0.0;
+                for &(price, size) in &self.asks {
+                    let cost = price * size;
+                    if remaining <= cost {
+                        bought += remaining / price;
+                        remaining = 0.0;
+                        break;
+                    }
+                    bought += size;
+                    remaining -= cost;
+                }
+                // Глубины не хватило - маршрут через эту площадку невозможен
+                if remaining > 0.0 {
+                    return None;
+                }
+                Some(bought)
+            }
+            TradeAction::Sell => {
+                let mut remaining = amount_in;
+                let mut proceeds = 0.0;
+                for &(price, size) in &self.bids {
+                    if remaining <= size {
+                        proceeds += remaining * price;
+                        remaining = 0.0;
+                        break;
+                    }
+                    proceeds += size * price;
+                    remaining -= size;
+                }
+                if remaining > 0.0 {
+                    return None;
+                }
+                Some(proceeds * (1.0 - self.taker_fee))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "serum_test.rs"]
+mod tests;
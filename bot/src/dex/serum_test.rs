@@ -0,0 +1,32 @@
+use super::*;
+
+#[test]
+fn test_encode_new_order_layout() {
+    let data = encode_new_order(&NewOrder {
+        side: TradeAction::Sell,
+        limit_price_lots: 20_500,
+        max_coin_qty_lots: 15,
+        max_native_pc_qty: 30_780_750,
+        order_type: OrderType::PostOnly,
+        client_order_id: 0x0102_0304_0506_0708,
+    });
+
+    assert_eq!(data.len(), 51);
+    assert_eq!(data[0], 0);
+    assert_eq!(data[1..5], 10u32.to_le_bytes());
+    assert_eq!(data[5..9], 1u32.to_le_bytes());
+    assert_eq!(data[9..17], 20_500u64.to_le_bytes());
+    assert_eq!(data[17..25], 15u64.to_le_bytes());
+    assert_eq!(data[25..33], 30_780_750u64.to_le_bytes());
+    // Самоисполнение и тип ордера
+    assert_eq!(data[33..37], 0u32.to_le_bytes());
+    assert_eq!(data[37..41], 2u32.to_le_bytes());
+    assert_eq!(data[41..49], 0x0102_0304_0506_0708u64.to_le_bytes());
+    assert_eq!(data[49..51], u16::MAX.to_le_bytes());
+}
+
+#[test]
+fn test_encode_cancel_by_client_id_layout() {
+    let data = encode_cancel_by_client_id(0x0102_0304_0506_0708);
+    assert_eq!(data, vec![0, 12, 0, 0, 0, 8, 7, 6, 5, 4, 3, 2, 1]);
+}
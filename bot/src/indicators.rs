@@ -0,0 +1,505 @@
+use std::collections::VecDeque;
+
+use crate::candles::Candle;
+
+// Потоковые индикаторы: каждое обновление - O(1) (для стохастика - O(1) в среднем).
+// `update` возвращает значение, как только накоплено достаточно данных,
+// `value` - последнее рассчитанное значение.
+
+// Простая скользящая средняя
+#[derive(Debug, Clone)]
+pub struct Sma {
+    period: usize,
+    window: VecDeque<f64>,
+    sum: f64,
+}
+
+impl Sma {
+    pub fn new(period: usize) -> Self {
+        assert!(period > 0, "SMA period must be positive");
+        Self {
+            period,
+            window: VecDeque::with_capacity(period + 1),
+            sum: 0.0,
+        }
+    }
+
+    pub fn update(&mut self, value: f64) -> Option<f64> {
+        self.window.push_back(value);
+        self.sum += value;
+        if self.window.len() > self.period {
+            self.sum -= self.window.pop_front().unwrap_or(0.0);
+        }
+        self.value()
+    }
+
+    pub fn value(&self) -> Option<f64> {
+        if self.window.len() < self.period {
+            return None;
+        }
+        Some(self.sum / self.period as f64)
+    }
+}
+
+// Экспоненциальная скользящая средняя; первое значение - SMA за период
+#[derive(Debug, Clone)]
+pub struct Ema {
+    period: usize,
+    alpha: f64,
+    count: usize,
+    seed_sum: f64,
+    value: Option<f64>,
+}
+
+impl Ema {
+    pub fn new(period: usize) -> Self {
+        assert!(period > 0, "EMA period must be positive");
+        Self {
+            period,
+            alpha: 2.0 / (period as f64 + 1.0),
+            count: 0,
+            seed_sum: 0.0,
+            value: None,
+        }
+    }
+
+    pub fn update(&mut self, value: f64) -> Option<f64> {
+        self.value = match self.value {
+            Some(previous) => Some(previous + self.alpha * (value - previous)),
+            None => {
+                self.count += 1;
+                self.seed_sum += value;
+                if self.count < self.period {
+                    None
+                } else {
+                    Some(self.seed_sum / self.period as f64)
+                }
+            }
+        };
+        self.value
+    }
+
+    pub fn value(&self) -> Option<f64> {
+        self.value
+    }
+}
+
+// Сглаживание Уайлдера: среднее первых `period` значений, затем
+// avg = (avg * (period - 1) + value) / period
+#[derive(Debug, Clone)]
+struct WilderAverage {
+    period: usize,
+    count: usize,
+    seed_sum: f64,
+    value: Option<f64>,
+}
+
+impl WilderAverage {
+    fn new(period: usize) -> Self {
+        Self {
+            period,
+            count: 0,
+            seed_sum: 0.0,
+            value: None,
+        }
+    }
+
+    fn update(&mut self, value: f64) -> Option<f64> {
+        let period = self.period as f64;
+        self.value = match self.value {
+            Some(previous) => Some((previous * (period - 1.0) + value) / period),
+            None => {
+                self.count += 1;
+                self.seed_sum += value;
+                if self.count < self.period {
+                    None
+                } else {
+                    Some(self.seed_sum / period)
+                }
+            }
+        };
+        self.value
+    }
+}
+
+// RSI со сглаживанием Уайлдера по последним изменениям цены
+#[derive(Debug, Clone)]
+pub struct Rsi {
+    previous: Option<f64>,
+    gains: WilderAverage,
+    losses: WilderAverage,
+    value: Option<f64>,
+}
+
+impl Rsi {
+    pub fn new(period: usize) -> Self {
+        assert!(period > 0, "RSI period must be positive");
+        Self {
+            previous: None,
+            gains: WilderAverage::new(period),
+            losses: WilderAverage::new(period),
+            value: None,
+        }
+    }
+
+    pub fn update(&mut self, close: f64) -> Option<f64> {
+        let previous = self.previous.replace(close)?;
+        let change = close - previous;
+        let gain = self.gains.update(change.max(0.0));
+        let loss = self.losses.update((-change).max(0.0));
+
+        if let (Some(gain), Some(loss)) = (gain, loss) {
+            self.value = Some(if loss == 0.0 {
+                // Цена не менялась - нейтральное значение
+                if gain == 0.0 { 50.0 } else { 100.0 }
+            } else {
+                100.0 - 100.0 / (1.0 + gain / loss)
+            });
+        }
+        self.value
+    }
+
+    pub fn value(&self) -> Option<f64> {
+        self.value
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MacdValue {
+    pub macd: f64,
+    pub signal: f64,
+    pub histogram: f64,
+}
+
+// MACD: разница быстрой и медленной EMA и сигнальная EMA этой разницы
+#[derive(Debug, Clone)]
+pub struct Macd {
+    fast: Ema,
+    slow: Ema,
+    signal: Ema,
+    value: Option<MacdValue>,
+}
+
+impl Macd {
+    pub fn new(fast: usize, slow: usize, signal: usize) -> Self {
+        assert!(fast < slow, "MACD fast period must be shorter than slow");
+        Self {
+            fast: Ema::new(fast),
+            slow: Ema::new(slow),
+            signal: Ema::new(signal),
+            value: None,
+        }
+    }
+
+    pub fn update(&mut self, close: f64) -> Option<MacdValue> {
+        let fast = self.fast.update(close);
+        let slow = self.slow.update(close);
+        if let (Some(fast), Some(slow)) = (fast, slow) {
+            let macd = fast - slow;
+            if let Some(signal) = self.signal.update(macd) {
+                self.value = Some(MacdValue {
+                    macd,
+                    signal,
+                    histogram: macd - signal,
+                });
+            }
+        }
+        self.value
+    }
+
+    pub fn value(&self) -> Option<MacdValue> {
+        self.value
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BollingerValue {
+    pub middle: f64,
+    pub upper: f64,
+    pub lower: f64,
+}
+
+// Полосы Боллинджера: SMA +/- k стандартных отклонений (по генеральной совокупности)
+#[derive(Debug, Clone)]
+pub struct Bollinger {
+    period: usize,
+    k: f64,
+    window: VecDeque<f64>,
+    sum: f64,
+    sum_squares: f64,
+}
+
+impl Bollinger {
+    pub fn new(period: usize, k: f64) -> Self {
+        assert!(period > 0, "Bollinger period must be positive");
+        Self {
+            period,
+            k,
+            window: VecDeque::with_capacity(period + 1),
+            sum: 0.0,
+            sum_squares: 0.0,
+        }
+    }
+
+    pub fn update(&mut self, close: f64) -> Option<BollingerValue> {
+        self.window.push_back(close);
+        self.sum += close;
+        self.sum_squares += close * close;
+        if self.window.len() > self.period {
+            let removed = self.window.pop_front().unwrap_or(0.0);
+            self.sum -= removed;
+            self.sum_squares -= removed * removed;
+        }
+        self.value()
+    }
+
+    pub fn value(&self) -> Option<BollingerValue> {
+        if self.window.len() < self.period {
+            return None;
+        }
+        let n = self.period as f64;
+        let mean = self.sum / n;
+        // Ошибки округления могут дать слегка отрицательную дисперсию
+        let deviation = (self.sum_squares / n - mean * mean).max(0.0).sqrt();
+        Some(BollingerValue {
+            middle: mean,
+            upper: mean + self.k * deviation,
+            lower: mean - self.k * deviation,
+        })
+    }
+}
+
+// Истинный диапазон бара с учетом закрытия предыдущего
+fn true_range(candle: &Candle, previous_close: Option<f64>) -> f64 {
+    let range = candle.high - candle.low;
+    match previous_close {
+        Some(close) => range
+            .max((candle.high - close).abs())
+            .max((candle.low - close).abs()),
+        None => range,
+    }
+}
+
+// Средний истинный диапазон со сглаживанием Уайлдера
+#[derive(Debug, Clone)]
+pub struct Atr {
+    previous_close: Option<f64>,
+    average: WilderAverage,
+}
+
+impl Atr {
+    pub fn new(period: usize) -> Self {
+        assert!(period > 0, "ATR period must be positive");
+        Self {
+            previous_close: None,
+            average: WilderAverage::new(period),
+        }
+    }
+
+    pub fn update(&mut self, candle: &Candle) -> Option<f64> {
+        let range = true_range(candle, self.previous_close);
+        self.previous_close = Some(candle.close);
+        self.average.update(range)
+    }
+
+    pub fn value(&self) -> Option<f64> {
+        self.average.value
+    }
+}
+
+// Средневзвешенная по объему цена с начала сессии
+#[derive(Debug, Clone, Default)]
+pub struct Vwap {
+    price_volume: f64,
+    volume: f64,
+}
+
+impl Vwap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update(&mut self, price: f64, volume: f64) -> Option<f64> {
+        if volume > 0.0 {
+            self.price_volume += price * volume;
+            self.volume += volume;
+        }
+        self.value()
+    }
+
+    // Бар учитывается по типичной цене (high + low + close) / 3
+    pub fn update_candle(&mut self, candle: &Candle) -> Option<f64> {
+        self.update((candle.high + candle.low + candle.close) / 3.0, candle.volume)
+    }
+
+    pub fn value(&self) -> Option<f64> {
+        if self.volume > 0.0 {
+            Some(self.price_volume / self.volume)
+        } else {
+            None
+        }
+    }
+
+    // Начало новой сессии
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StochasticValue {
+    pub k: f64,
+    pub d: f64,
+}
+
+// Стохастический осциллятор: %K за `k_period` баров и %D как SMA от %K.
+// Максимумы и минимумы окна хранятся в монотонных очередях.
+#[derive(Debug, Clone)]
+pub struct Stochastic {
+    k_period: usize,
+    index: usize,
+    highs: VecDeque<(usize, f64)>,
+    lows: VecDeque<(usize, f64)>,
+    d: Sma,
+    value: Option<StochasticValue>,
+}
+
+impl Stochastic {
+    pub fn new(k_period: usize, d_period: usize) -> Self {
+        assert!(k_period > 0, "Stochastic period must be positive");
+        Self {
+            k_period,
+            index: 0,
+            highs: VecDeque::new(),
+            lows: VecDeque::new(),
+            d: Sma::new(d_period),
+            value: None,
+        }
+    }
+
+    pub fn update(&mut self, candle: &Candle) -> Option<StochasticValue> {
+        let index = self.index;
+        self.index += 1;
+
+        while matches!(self.highs.back(), Some(&(_, high)) if high <= candle.high) {
+            self.highs.pop_back();
+        }
+        self.highs.push_back((index, candle.high));
+        while matches!(self.lows.back(), Some(&(_, low)) if low >= candle.low) {
+            self.lows.pop_back();
+        }
+        self.lows.push_back((index, candle.low));
+
+        // Удаляем значения, вышедшие из окна
+        let oldest = (index + 1).saturating_sub(self.k_period);
+        while matches!(self.highs.front(), Some(&(i, _)) if i < oldest) {
+            self.highs.pop_front();
+        }
+        while matches!(self.lows.front(), Some(&(i, _)) if i < oldest) {
+            self.lows.pop_front();
+        }
+
+        if self.index < self.k_period {
+            return None;
+        }
+
+        let highest = self.highs.front().map(|&(_, high)| high).unwrap_or(candle.high);
+        let lowest = self.lows.front().map(|&(_, low)| low).unwrap_or(candle.low);
+        let k = if highest > lowest {
+            100.0 * (candle.close - lowest) / (highest - lowest)
+        } else {
+            50.0
+        };
+        if let Some(d) = self.d.update(k) {
+            self.value = Some(StochasticValue { k, d });
+        }
+        self.value
+    }
+
+    pub fn value(&self) -> Option<StochasticValue> {
+        self.value
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdxValue {
+    pub adx: f64,
+    pub plus_di: f64,
+    pub minus_di: f64,
+}
+
+// Индекс направленного движения Уайлдера. Сглаженные суммы TR и DM:
+// первая - сумма за период, далее s = s - s / period + value.
+#[derive(Debug, Clone)]
+pub struct Adx {
+    period: usize,
+    previous: Option<Candle>,
+    count: usize,
+    true_range: f64,
+    plus_dm: f64,
+    minus_dm: f64,
+    dx: WilderAverage,
+    value: Option<AdxValue>,
+}
+
+impl Adx {
+    pub fn new(period: usize) -> Self {
+        assert!(period > 0, "ADX period must be positive");
+        Self {
+            period,
+            previous: None,
+            count: 0,
+            true_range: 0.0,
+            plus_dm: 0.0,
+            minus_dm: 0.0,
+            dx: WilderAverage::new(period),
+            value: None,
+        }
+    }
+
+    pub fn update(&mut self, candle: &Candle) -> Option<AdxValue> {
+        let previous = self.previous.replace(*candle)?;
+
+        let up = candle.high - previous.high;
+        let down = previous.low - candle.low;
+        let plus_dm = if up > down && up > 0.0 { up } else { 0.0 };
+        let minus_dm = if down > up && down > 0.0 { down } else { 0.0 };
+        let range = true_range(candle, Some(previous.close));
+
+        let period = self.period as f64;
+        if self.count < self.period {
+            self.count += 1;
+            self.true_range += range;
+            self.plus_dm += plus_dm;
+            self.minus_dm += minus_dm;
+            if self.count < self.period {
+                return None;
+            }
+        } else {
+            self.true_range += range - self.true_range / period;
+            self.plus_dm += plus_dm - self.plus_dm / period;
+            self.minus_dm += minus_dm - self.minus_dm / period;
+        }
+
+        let (plus_di, minus_di) = if self.true_range > 0.0 {
+            (100.0 * self.plus_dm / self.true_range, 100.0 * self.minus_dm / self.true_range)
+        } else {
+            (0.0, 0.0)
+        };
+        let di_sum = plus_di + minus_di;
+        let dx = if di_sum > 0.0 { 100.0 * (plus_di - minus_di).abs() / di_sum } else { 0.0 };
+
+        if let Some(adx) = self.dx.update(dx) {
+            self.value = Some(AdxValue { adx, plus_di, minus_di });
+        }
+        self.value
+    }
+
+    pub fn value(&self) -> Option<AdxValue> {
+        self.value
+    }
+}
+
+#[cfg(test)]
+#[path = "indicators_test.rs"]
+mod tests;
@@ -0,0 +1,184 @@
+use super::*;
+
+const EPSILON: f64 = 1e-6;
+
+fn candle(high: f64, low: f64, close: f64) -> Candle {
+    Candle {
+        open_time: 0,
+        open: close,
+        high,
+        low,
+        close,
+        volume: 0.0,
+    }
+}
+
+// Набор баров для ATR, стохастика и ADX; эталон посчитан независимой реализацией
+fn bars() -> Vec<Candle> {
+    [
+        (10.0, 8.0, 9.0), (11.0, 9.0, 10.5), (12.0, 10.0, 11.0), (11.5, 9.5, 10.0), (13.0, 10.0, 12.5),
+        (14.0, 12.0, 13.0), (13.5, 11.0, 11.5), (12.0, 10.0, 10.5), (12.5, 10.5, 12.0), (15.0, 12.0, 14.5),
+    ]
+    .iter()
+    .map(|&(high, low, close)| candle(high, low, close))
+    .collect()
+}
+
+fn assert_close(actual: Option<f64>, expected: f64) {
+    let actual = actual.expect("indicator should be ready");
+    assert!((actual - expected).abs() < EPSILON, "expected {}, got {}", expected, actual);
+}
+
+#[test]
+fn test_sma_window() {
+    let mut sma = Sma::new(3);
+    assert_eq!(sma.update(1.0), None);
+    assert_eq!(sma.update(2.0), None);
+    assert_close(sma.update(3.0), 2.0);
+    assert_close(sma.update(10.0), 5.0);
+    assert_close(sma.value(), 5.0);
+}
+
+#[test]
+fn test_ema_seeded_with_sma() {
+    let mut ema = Ema::new(3);
+    assert_eq!(ema.update(1.0), None);
+    assert_eq!(ema.update(2.0), None);
+    assert_close(ema.update(3.0), 2.0);
+    // На линейном ряду EMA отстает на (period - 1) / 2
+    for value in 4..=10 {
+        assert_close(ema.update(value as f64), value as f64 - 1.0);
+    }
+}
+
+#[test]
+fn test_wilder_rsi_reference_values() {
+    // Классический пример Уайлдера (RSI 14) без промежуточного округления
+    let closes = [
+        44.34, 44.09, 44.15, 43.61, 44.33, 44.83, 45.10, 45.42, 45.84, 46.08,
+        45.89, 46.03, 45.61, 46.28, 46.28, 46.00, 46.03, 46.41, 46.22, 45.64,
+    ];
+    let expected = [70.4641, 66.2496, 66.4809, 69.3469, 66.2947, 57.9150];
+
+    let mut rsi = Rsi::new(14);
+    let values: Vec<f64> = closes.iter().filter_map(|&close| rsi.update(close)).collect();
+    assert_eq!(values.len(), expected.len());
+    for (value, expected) in values.iter().zip(expected.iter()) {
+        assert!((value - expected).abs() < 1e-3, "expected {}, got {}", expected, value);
+    }
+}
+
+#[test]
+fn test_rsi_flat_and_rising_prices() {
+    let mut flat = Rsi::new(3);
+    let mut rising = Rsi::new(3);
+    for i in 0..5 {
+        flat.update(10.0);
+        rising.update(10.0 + i as f64);
+    }
+    assert_close(flat.value(), 50.0);
+    assert_close(rising.value(), 100.0);
+}
+
+#[test]
+fn test_macd_on_linear_series() {
+    // EMA(3) и EMA(6) линейного ряда отстают на 1 и 2.5 - MACD постоянен
+    let mut macd = Macd::new(3, 6, 3);
+    let values: Vec<MacdValue> = (1..=20).filter_map(|value| macd.update(value as f64)).collect();
+    assert_eq!(values.len(), 20 - 6 - 3 + 2);
+    for value in values {
+        assert!((value.macd - 1.5).abs() < EPSILON);
+        assert!((value.signal - 1.5).abs() < EPSILON);
+        assert!(value.histogram.abs() < EPSILON);
+    }
+}
+
+#[test]
+fn test_bollinger_bands() {
+    let mut bollinger = Bollinger::new(8, 2.0);
+    let mut value = None;
+    for close in [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0] {
+        value = bollinger.update(close);
+    }
+    // Среднее 5, стандартное отклонение 2
+    let value = value.unwrap();
+    assert!((value.middle - 5.0).abs() < EPSILON);
+    assert!((value.upper - 9.0).abs() < EPSILON);
+    assert!((value.lower - 1.0).abs() < EPSILON);
+
+    // Окно сдвигается: [4, 4, 4, 5, 5, 7, 9, 4]
+    let value = bollinger.update(4.0).unwrap();
+    assert!((value.middle - 5.25).abs() < EPSILON);
+}
+
+#[test]
+fn test_atr_reference_values() {
+    let expected = [2.0, 2.0, 2.333333, 2.222222, 2.314815, 2.209877, 2.139918, 2.426612];
+    let mut atr = Atr::new(3);
+    let values: Vec<f64> = bars().iter().filter_map(|bar| atr.update(bar)).collect();
+    assert_eq!(values.len(), expected.len());
+    for (value, expected) in values.iter().zip(expected.iter()) {
+        assert_close(Some(*value), *expected);
+    }
+}
+
+#[test]
+fn test_vwap() {
+    let mut vwap = Vwap::new();
+    assert_eq!(vwap.update(10.0, 0.0), None);
+    vwap.update(10.0, 1.0);
+    assert_close(vwap.update(13.0, 2.0), 12.0);
+
+    let mut bar = candle(12.0, 9.0, 12.0);
+    bar.volume = 3.0;
+    // Типичная цена бара 11
+    assert_close(vwap.update_candle(&bar), 11.5);
+
+    vwap.reset();
+    assert_eq!(vwap.value(), None);
+}
+
+#[test]
+fn test_stochastic_reference_values() {
+    let expected_k = [33.333333, 85.714286, 77.777778, 37.5, 12.5, 57.142857, 90.0];
+    let expected_d = [54.166667, 59.52381, 81.746032, 57.638889, 25.0, 34.821429, 73.571429];
+    let mut stochastic = Stochastic::new(3, 2);
+    let values: Vec<StochasticValue> = bars().iter().filter_map(|bar| stochastic.update(bar)).collect();
+    assert_eq!(values.len(), expected_k.len());
+    for (i, value) in values.iter().enumerate() {
+        assert_close(Some(value.k), expected_k[i]);
+        assert_close(Some(value.d), expected_d[i]);
+    }
+}
+
+#[test]
+fn test_adx_reference_values() {
+    let expected = [
+        (74.887218, 43.333333, 3.333333),
+        (58.358547, 27.733333, 16.533333),
+        (44.168856, 19.366853, 26.629423),
+        (31.801712, 21.121795, 18.333333),
+        (42.046074, 46.758998, 10.778217),
+    ];
+    let mut adx = Adx::new(3);
+    let values: Vec<AdxValue> = bars().iter().filter_map(|bar| adx.update(bar)).collect();
+    assert_eq!(values.len(), expected.len());
+    for (value, &(expected_adx, plus_di, minus_di)) in values.iter().zip(expected.iter()) {
+        assert_close(Some(value.adx), expected_adx);
+        assert_close(Some(value.plus_di), plus_di);
+        assert_close(Some(value.minus_di), minus_di);
+    }
+}
+
+#[test]
+fn test_adx_strong_trend() {
+    // Устойчивый рост: -DM нулевой, ADX стремится к 100
+    let mut adx = Adx::new(5);
+    for i in 0..30 {
+        let close = 10.0 + i as f64;
+        adx.update(&candle(close + 1.0, close - 1.0, close));
+    }
+    let value = adx.value().unwrap();
+    assert!((value.adx - 100.0).abs() < EPSILON);
+    assert!(value.minus_di.abs() < EPSILON);
+}
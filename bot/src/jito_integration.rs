@@ -1,20 +1,34 @@
 use jito_block_engine::client::BlockEngineClient;
+use log::warn;
+use serde::{Deserialize, Serialize};
 use solana_sdk::{
     hash::Hash,
     instruction::Instruction,
     message::Message,
+    pubkey::Pubkey,
     signature::{Keypair, Signature},
     transaction::Transaction,
 };
+use std::collections::BTreeMap;
 use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::dex::serum::{encode_cancel_by_client_id, encode_new_order, NewOrder, OrderType};
+use crate::market_registry::MarketRegistry;
+use crate::quantization::Rounding;
 
 pub struct JitoClient {
     block_engine: BlockEngineClient,
     tip_account: Keypair,
+    // Параметры рынков для перевода сигналов в лоты
+    markets: Arc<MarketRegistry>,
 }
 
 impl JitoClient {
-    pub async fn new() -> Result<Self, Box<dyn Error>> {
+    pub async fn new(markets: Arc<MarketRegistry>) -> Result<Self, Box<dyn Error>> {
         let block_engine = BlockEngineClient::new(
             "https://jito-block-engine.devnet.solana.com",
             None,
@@ -23,29 +37,48 @@ impl JitoClient {
         Ok(Self {
             block_engine,
             tip_account: Keypair::new(),
+            markets,
         })
     }
 
-    pub async fn execute_trade(&self, signal: TradingSignal) -> Result<Signature, Box<dyn Error>> {
-        // Создаем транзакцию на основе торгового сигнала
-        let instructions = self.create_trade_instructions(&signal)?;
-        
-        // Добавляем MEV-tip для приоритетного исполнения
-        let tip_instruction = self.create_tip_instruction()?;
-        let mut all_instructions = vec![tip_instruction];
-        all_instructions.extend(instructions);
-
-        // Создаем и подписываем транзакцию
-        let message = Message::new(&all_instructions, Some(&signal.payer));
-        let mut tx = Transaction::new_unsigned(message);
-        tx.sign(&[&self.tip_account], tx.message.recent_blockhash);
-
-        // Отправляем транзакцию через Jito Block Engine
+    // Отправляет сигналы одним бандлом: Jito включает в блок либо
+    // все транзакции бандла, либо ни одной. None - бандл не отправлен,
+    // потому что срок одного из сигналов истек к слоту `current_slot`.
+    pub async fn execute_bundle(&self, signals: Vec<TradingSignal>, current_slot: u64) -> Result<Option<Signature>, Box<dyn Error>> {
+        if signals.is_empty() {
+            return Err("Empty bundle".into());
+        }
+        if let Some(expired) = signals.iter().find(|signal| signal.is_expired(current_slot)) {
+            warn!(
+                "Signal {} of {} expired at slot {:?} (current {}), bundle of {} dropped",
+                expired.client_order_id, expired.strategy_id, expired.expiry_slot, current_slot, signals.len()
+            );
+            return Ok(None);
+        }
+
+        let mut transactions = Vec::with_capacity(signals.len());
+        for (i, signal) in signals.iter().enumerate() {
+            // Создаем транзакцию на основе торгового сигнала
+            let mut instructions = self.create_trade_instructions(signal)?;
+
+            // MEV-tip добавляется в последнюю транзакцию бандла
+            if i + 1 == signals.len() {
+                instructions.push(self.create_tip_instruction()?);
+            }
+
+            // Создаем и подписываем транзакцию
+            let message = Message::new(&instructions, Some(&signal.payer));
+            let mut tx = Transaction::new_unsigned(message);
+            tx.sign(&[&self.tip_account], tx.message.recent_blockhash);
+            transactions.push(tx);
+        }
+
+        // Отправляем транзакции через Jito Block Engine
         let signature = self.block_engine
-            .send_bundle(vec![tx])
+            .send_bundle(transactions)
             .await?;
 
-        Ok(signature)
+        Ok(Some(signature))
     }
 
     fn create_tip_instruction(&self) -> Result<Instruction, Box<dyn Error>> {
@@ -62,39 +95,235 @@ impl JitoClient {
         // Конвертируем торговый сигнал в инструкции Solana
         let mut instructions = Vec::new();
 
-        match signal.action {
-            TradeAction::Buy => {
-                // Создаем инструкцию для покупки
-                instructions.push(Instruction::new_with_bytes(
-                    signal.market,
-                    &[],
-                    vec![],
-                ));
-            }
-            TradeAction::Sell => {
-                // Создаем инструкцию для продажи
-                instructions.push(Instruction::new_with_bytes(
-                    signal.market,
-                    &[],
-                    vec![],
-                ));
+        match signal.order_action {
+            OrderAction::Place => instructions.push(self.create_order_instruction(signal)?),
+            OrderAction::Cancel => instructions.push(self.create_cancel_instruction(signal, signal.client_order_id)),
+            // Отмена и новый ордер в одной транзакции: котировка не остается без замены
+            OrderAction::Replace { cancel_client_order_id } => {
+                instructions.push(self.create_cancel_instruction(signal, cancel_client_order_id));
+                instructions.push(self.create_order_instruction(signal)?);
             }
         }
 
         Ok(instructions)
     }
+
+    fn create_order_instruction(&self, signal: &TradingSignal) -> Result<Instruction, Box<dyn Error>> {
+        // Обмен в пуле требует аккаунтов пула, его рынка Serum и токен-аккаунтов
+        // бота, которых нет в реестре: такие ноги исполняются только на
+        // виртуальной бирже, а в бандл не попадают
+        if signal.venue != Venue::Serum {
+            return Err(format!("{:?} swaps are not supported by the Jito client", signal.venue).into());
+        }
+
+        let market = self.markets.by_dex_market(&signal.market)
+            .ok_or_else(|| format!("Market {} is not in registry", signal.market))?;
+        let quantizer = market.quantizer();
+
+        // Предельная цена округляется внутрь допуска: покупка не дороже, продажа не дешевле
+        let rounding = match signal.action {
+            TradeAction::Buy => Rounding::Down,
+            TradeAction::Sell => Rounding::Up,
+        };
+        let limit_price = quantizer.price_from_ui(quantizer.price_native_to_ui(signal.limit_price()), rounding)?;
+        let size = quantizer.size_from_native(signal.amount, Rounding::Down);
+        let quote = quantizer.quote_native(limit_price, size)? as u128;
+        let max_native_pc_qty = (quote * (10_000 + market.taker_fee_bps as u128) / 10_000) as u64;
+
+        // Создаем инструкцию NewOrderV3 с типом ордера из сигнала
+        Ok(Instruction::new_with_bytes(
+            signal.market,
+            &encode_new_order(&NewOrder {
+                side: signal.action,
+                limit_price_lots: limit_price.0,
+                max_coin_qty_lots: size.0,
+                max_native_pc_qty,
+                order_type: signal.order_type(),
+                client_order_id: signal.client_order_id,
+            }),
+            vec![],
+        ))
+    }
+
+    fn create_cancel_instruction(&self, signal: &TradingSignal, client_order_id: u64) -> Instruction {
+        // Создаем инструкцию CancelOrderByClientIdV2
+        Instruction::new_with_bytes(
+            signal.market,
+            &encode_cancel_by_client_id(client_order_id),
+            vec![],
+        )
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TradingSignal {
     pub market: Pubkey,
     pub action: TradeAction,
     pub amount: u64,
+    // Цена в нативных единицах; для рыночного ордера - ожидаемая цена
     pub price: u64,
     pub payer: Pubkey,
+    // Идентификатор ордера на Serum; для отмены - идентификатор отменяемого ордера
+    pub client_order_id: u64,
+    pub order_action: OrderAction,
+    #[serde(default)]
+    pub time_in_force: TimeInForce,
+    // Где исполняется сигнал; `market` всегда указывает рынок Serum пары
+    #[serde(default)]
+    pub venue: Venue,
+    // Сигналы с одним идентификатором исполняются атомарно одним бандлом
+    #[serde(default)]
+    pub bundle_id: Option<u64>,
+    // Экземпляр стратегии, выдавший сигнал
+    #[serde(default)]
+    pub strategy_id: String,
+    // Уверенность стратегии в сигнале от 0 до 1
+    #[serde(default = "default_confidence")]
+    pub confidence: f64,
+    // Сигнал не исполняется после этого слота
+    #[serde(default)]
+    pub expiry_slot: Option<u64>,
+    #[serde(default)]
+    pub order_kind: OrderKind,
+    // Допуск проскальзывания рыночного ордера от `price`
+    #[serde(default)]
+    pub slippage_bps: f64,
+    // Чем стратегия обосновала сигнал: индикаторы, пороги, голоса
+    #[serde(default)]
+    pub reason: BTreeMap<String, String>,
+}
+
+fn default_confidence() -> f64 {
+    1.0
+}
+
+impl TradingSignal {
+    pub fn with_reason(mut self, key: &str, value: impl fmt::Display) -> Self {
+        self.reason.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    // Предельная цена исполнения: для рыночного ордера - цена сигнала с допуском проскальзывания
+    pub fn limit_price(&self) -> u64 {
+        match self.order_kind {
+            OrderKind::Limit => self.price,
+            OrderKind::Market => {
+                let slippage = self.slippage_bps / 10_000.0;
+                match self.action {
+                    TradeAction::Buy => (self.price as f64 * (1.0 + slippage)).ceil() as u64,
+                    TradeAction::Sell => (self.price as f64 * (1.0 - slippage)).max(0.0).floor() as u64,
+                }
+            }
+        }
+    }
+
+    // Рыночный ордер на Serum - IOC по предельной цене
+    pub fn order_type(&self) -> OrderType {
+        match self.order_kind {
+            OrderKind::Market => OrderType::ImmediateOrCancel,
+            OrderKind::Limit => self.time_in_force.order_type(),
+        }
+    }
+
+    // Ордер остается в стакане и исполняется по событиям мейкера
+    pub fn is_resting(&self) -> bool {
+        self.order_kind == OrderKind::Limit && self.time_in_force != TimeInForce::Ioc
+    }
+
+    pub fn is_expired(&self, current_slot: u64) -> bool {
+        match self.expiry_slot {
+            Some(expiry_slot) => current_slot > expiry_slot,
+            None => false,
+        }
+    }
+}
+
+impl fmt::Display for TradingSignal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{}] {:?} {:?} {} @ {}", self.strategy_id, self.order_action, self.action, self.amount, self.price)?;
+        match self.order_kind {
+            OrderKind::Limit => write!(f, " limit {:?}", self.time_in_force)?,
+            OrderKind::Market => write!(f, " market ±{}bps", self.slippage_bps)?,
+        }
+        if self.venue != Venue::Serum {
+            write!(f, " via {:?}", self.venue)?;
+        }
+        write!(f, ", confidence {:.2}", self.confidence)?;
+        if let Some(expiry_slot) = self.expiry_slot {
+            write!(f, ", expires after slot {}", expiry_slot)?;
+        }
+        if !self.reason.is_empty() {
+            let reason: Vec<String> = self.reason.iter().map(|(key, value)| format!("{}={}", key, value)).collect();
+            write!(f, ", reason: {}", reason.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimeInForce {
+    // Остается в стакане до исполнения или отмены
+    Gtc,
+    // Исполняется сразу по доступной ликвидности, остаток отменяется
+    #[default]
+    Ioc,
+    // Только встает в стакан и исполняется как мейкер
+    PostOnly,
+}
+
+impl TimeInForce {
+    pub fn order_type(&self) -> OrderType {
+        match self {
+            TimeInForce::Gtc => OrderType::Limit,
+            TimeInForce::Ioc => OrderType::ImmediateOrCancel,
+            TimeInForce::PostOnly => OrderType::PostOnly,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrderKind {
+    #[default]
+    Limit,
+    // Исполнение по любой цене в пределах `slippage_bps`
+    Market,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Venue {
+    // Ордер в стакане Serum/OpenBook
+    #[default]
+    Serum,
+    // Обмен в AMM-пуле Raydium
+    Raydium { amm: Pubkey },
+    // Обмен в Whirlpool Orca
+    Orca { whirlpool: Pubkey },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrderAction {
+    Place,
+    Cancel,
+    // Отменить ордер и выставить новый
+    Replace { cancel_client_order_id: u64 },
+}
+
+static NEXT_CLIENT_ORDER_ID: AtomicU64 = AtomicU64::new(0);
+
+// Уникальный client_order_id. Счетчик начинается с текущего времени в микросекундах,
+// чтобы не пересекаться с ордерами предыдущих запусков бота.
+pub fn next_client_order_id() -> u64 {
+    if NEXT_CLIENT_ORDER_ID.load(Ordering::Relaxed) == 0 {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_micros() as u64)
+            .unwrap_or(1);
+        let _ = NEXT_CLIENT_ORDER_ID.compare_exchange(0, now, Ordering::Relaxed, Ordering::Relaxed);
+    }
+    NEXT_CLIENT_ORDER_ID.fetch_add(1, Ordering::Relaxed)
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum TradeAction {
     Buy,
     Sell,
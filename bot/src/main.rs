@@ -1,19 +1,68 @@
 use anchor_client::{Client, Cluster};
+use solana_client::rpc_client::RpcClient;
 use solana_sdk::{
     commitment_config::CommitmentConfig,
     signature::{Keypair, Signer},
 };
+use std::path::Path;
 use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use dotenv::dotenv;
+use log::{error, info, warn};
 use std::env;
+use account_stream::FeedEvent;
+use recorder::RecordedEvent;
 
 mod trading_strategy;
+mod strategies;
 mod price_feed;
+mod account_stream;
+mod recorder;
+mod replay;
+mod cli;
 mod jito_integration;
+mod dex;
+mod router;
+mod orderbook;
+mod matching;
+mod pools;
+mod market_registry;
+mod quantization;
+mod price_aggregation;
+mod pricing_graph;
+mod switchboard;
+mod price_history;
+mod candles;
+mod indicators;
+mod error_handling;
+mod monitoring;
+mod backtest;
+mod paper;
+mod telegram_bot;
+mod telegram_commands;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     dotenv().ok();
+    let options = cli::CliOptions::parse(env::args().skip(1))?;
+    let markets_config_path = env::var("MARKETS_CONFIG_PATH")
+        .unwrap_or_else(|_| "markets.json".to_string());
+    let strategies_config_path = env::var("STRATEGIES_CONFIG_PATH")
+        .unwrap_or_else(|_| "strategies.json".to_string());
+    let paper_config_path = env::var("PAPER_CONFIG_PATH")
+        .unwrap_or_else(|_| "paper.json".to_string());
+
+    // Бэктест не подключается к кластеру
+    if let Some(backtest_options) = &options.backtest {
+        backtest::run(backtest_options, &markets_config_path, &strategies_config_path)?;
+        return Ok(());
+    }
+    if let Some(optimize_options) = &options.optimize {
+        backtest::optimize::run(optimize_options, &markets_config_path, &strategies_config_path)?;
+        return Ok(());
+    }
     
     let cluster_url = env::var("SOLANA_CLUSTER_URL")
         .expect("SOLANA_CLUSTER_URL must be set");
@@ -31,26 +80,216 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let program = client.program(trading_program::ID);
     
+    // Реестр рынков - единственный источник адресов и параметров торговых пар
+    let registry = Arc::new(market_registry::MarketRegistry::load(&markets_config_path)?);
+
     // Инициализация компонентов бота
-    let price_feed = price_feed::PriceFeed::new().await?;
-    let trading_strategy = trading_strategy::TradingStrategy::new(program.clone());
-    let jito_client = jito_integration::JitoClient::new().await?;
+    let mut price_feed = price_feed::PriceFeed::new(&registry).await?;
+    // Экземпляры стратегий с выделенным капиталом из конфигурации
+    let runner_config = strategies::RunnerConfig::load(&strategies_config_path)?;
+    let mut strategy_runner = strategies::StrategyRunner::new(
+        &runner_config,
+        &strategies::StrategyRegistry::with_builtin(),
+        registry.clone(),
+        program.payer(),
+    )?;
+    let mut order_books = orderbook::OrderBookMirror::new();
+    let mut amm_pools = pools::PoolMirror::new();
+    // Монитор разделен с ботом Telegram: основной цикл пишет, команды читают
+    let monitor = Arc::new(Mutex::new(monitoring::Monitor::new()));
+    let trading_enabled = Arc::new(AtomicBool::new(true));
+    match (env::var("TELEGRAM_BOT_TOKEN"), env::var("TELEGRAM_CHAT_ID")) {
+        (Ok(token), Ok(chat_id)) if !token.is_empty() => {
+            let ctx = telegram_commands::CommandContext {
+                monitor: monitor.clone(),
+                markets: registry.clone(),
+                trading_enabled: trading_enabled.clone(),
+            };
+            let chat_id = chat_id.parse().map_err(|e| format!("Invalid TELEGRAM_CHAT_ID: {}", e))?;
+            tokio::spawn(telegram_bot::TelegramBot::new(&token, chat_id, ctx).run());
+            info!("Telegram bot started for chat {}", chat_id);
+        }
+        (Ok(token), Err(_)) if !token.is_empty() => warn!("TELEGRAM_CHAT_ID is not set, Telegram bot is disabled"),
+        _ => {}
+    }
 
-    println!("Торговый бот запущен");
+    // Бумажная торговля: сигналы исполняются на виртуальном счете, а не через Jito
+    let paper_config = paper::PaperConfig::load(&paper_config_path)?;
+    let mut paper_trader = if options.paper || paper_config.enabled {
+        Some(paper::PaperTrader::new(paper_config, registry.clone(), &mut strategy_runner)?)
+    } else {
+        None
+    };
+
+    // Запись рыночных данных и сигналов для последующего воспроизведения
+    let mut recorder = match env::var("RECORD_DIR") {
+        Ok(dir) if !dir.is_empty() => Some(recorder::Recorder::create(Path::new(&dir), account_stream::now_ms())?),
+        _ => None,
+    };
+
+    let (mut events, jito_client) = match options.replay {
+        Some(replay_config) => {
+            // При воспроизведении рынки подключаются по записанным MarketState,
+            // а сигналы исполняются только на виртуальном счете с --paper
+            order_books.watch_registry(&registry);
+            amm_pools.watch_registry(&registry);
+            (replay::spawn_replay(replay_config), None)
+        }
+        None => {
+            // Локальные стаканы рынков из реестра
+            let rpc_client = RpcClient::new(cluster_url.clone());
+            let mut initial_accounts = order_books.track_registry(&registry, &rpc_client)?;
+            // AMM-пулы реестра для арбитража
+            initial_accounts.extend(amm_pools.track_registry(&registry, &rpc_client)?);
+            if let Some(recorder) = recorder.as_mut() {
+                for update in initial_accounts {
+                    recorder.record(account_stream::now_ms(), RecordedEvent::Account(update))?;
+                }
+            }
 
-    // Основной цикл бота
-    loop {
-        // Получаем текущие цены
-        let price_data = price_feed.get_latest_prices().await?;
-        
-        // Анализируем рынок и генерируем сигналы
-        let signals = trading_strategy.analyze_market(&price_data).await?;
-        
-        // Исполняем сигналы через Jito MEV
-        for signal in signals {
-            jito_client.execute_trade(signal).await?;
+            // Обновления аккаунтов оракулов и рынков приходят по подписке в канал
+            let mut accounts = price_feed.oracle_accounts();
+            accounts.extend(order_books.tracked_accounts());
+            accounts.extend(amm_pools.tracked_accounts());
+            let stream_config = account_stream::AccountStreamConfig::from_env(&cluster_url);
+            let events = account_stream::spawn_live_feed(stream_config, accounts, Duration::from_secs(1));
+            let jito_client = if paper_trader.is_some() {
+                None
+            } else {
+                // Исполнения на Serum учитываются только по OpenOrders аккаунту бота
+                if let Some(market) = registry.markets().find(|market| market.open_orders.is_none()) {
+                    return Err(format!("{}: open_orders must be set in market registry for live trading", market.pair).into());
+                }
+                // Ноги в AMM-пулах Jito-клиент не отправляет, а одна нога Serum - уже не арбитраж
+                if let Some(instance) = runner_config.strategies.iter().find(|instance| instance.strategy == "arbitrage") {
+                    return Err(format!("{}: arbitrage runs only with --paper or in backtest", instance.id).into());
+                }
+                Some(jito_integration::JitoClient::new(registry.clone()).await?)
+            };
+            (events, jito_client)
         }
+    };
 
-        tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+    println!("Торговый бот запущен");
+    if let Some(paper_trader) = &paper_trader {
+        println!("Бумажная торговля, виртуальные балансы: {:?}", paper_trader.portfolio().balances);
     }
+
+    // Основной цикл бота. Ошибка одного события или бандла логируется и не
+    // останавливает бота.
+    while let Some(event) = events.recv().await {
+        match event {
+            FeedEvent::Account { timestamp_ms, update } => {
+                let result = (|| -> Result<(), Box<dyn std::error::Error>> {
+                    if let Some(recorder) = recorder.as_mut() {
+                        recorder.record(timestamp_ms, RecordedEvent::Account(update.clone()))?;
+                    }
+                    if amm_pools.is_tracked(&update.pubkey) {
+                        amm_pools.on_account_update(&update.pubkey, &update.data, update.slot)?;
+                    } else if !price_feed.on_account_update(&update.pubkey, &update.data, update.slot)? {
+                        let fills = order_books.on_account_update(&update.pubkey, &update.data, update.slot)?;
+                        let outs = order_books.take_outs();
+                        price_feed.record_fills(&fills, timestamp_ms / 1000);
+                        match paper_trader.as_mut() {
+                            // Стакан и сделки рынка исполняют виртуальные ордера
+                            Some(paper_trader) => paper_trader.on_market_update(
+                                &order_books, &update.pubkey, &fills, timestamp_ms, &mut strategy_runner, &mut monitor.lock().unwrap(),
+                            )?,
+                            // Исполнения стоящих ордеров стратегий, затем подтверждения их отмен
+                            None => {
+                                strategy_runner.on_market_fills(&fills, timestamp_ms / 1000)?;
+                                strategy_runner.on_market_outs(&outs);
+                            }
+                        }
+                    }
+                    Ok(())
+                })();
+                if let Err(e) = result {
+                    error!("Failed to process account {}: {}", update.pubkey, e);
+                }
+            }
+            FeedEvent::Tick { timestamp_ms } => {
+                let price_data = price_feed.snapshot(order_books.snapshot(), amm_pools.snapshot(), timestamp_ms / 1000);
+                if let Some(paper_trader) = paper_trader.as_mut() {
+                    if let Err(e) = paper_trader.on_prices(&price_data.prices(), price_data.slot(), timestamp_ms, &mut strategy_runner, &mut monitor.lock().unwrap()) {
+                        error!("Paper trader failed on prices: {}", e);
+                    }
+                }
+
+                // Анализируем рынок всеми стратегиями и генерируем сигналы
+                let mut signals = strategy_runner.on_tick(&price_data);
+                signals.extend(strategy_runner.on_timer(timestamp_ms / 1000));
+                {
+                    let mut monitor = monitor.lock().unwrap();
+                    for signal in &signals {
+                        monitor.record_signal(&signal.signal);
+                    }
+                }
+
+                if let Some(recorder) = recorder.as_mut() {
+                    let result = (|| -> Result<(), Box<dyn std::error::Error>> {
+                        recorder.record(timestamp_ms, RecordedEvent::Tick { prices: price_data.prices() })?;
+                        for signal in &signals {
+                            recorder.record(timestamp_ms, RecordedEvent::Signal(signal.signal.clone()))?;
+                        }
+                        recorder.flush()
+                    })();
+                    if let Err(e) = result {
+                        error!("Failed to record tick: {}", e);
+                    }
+                }
+
+                // Торговля остановлена командой Telegram: сигналы не отправляются,
+                // стратегии получают отказ и освобождают капитал
+                if !trading_enabled.load(Ordering::Relaxed) {
+                    for signal in &signals {
+                        strategy_runner.on_reject(signal);
+                    }
+                    continue;
+                }
+
+                // Исполняем сигналы через Jito MEV или на виртуальном счете
+                match (&jito_client, paper_trader.as_mut()) {
+                    (_, Some(paper_trader)) => {
+                        if let Err(e) = paper_trader.submit(signals, timestamp_ms, &mut strategy_runner, &mut monitor.lock().unwrap()) {
+                            error!("Paper trader failed to execute signals: {}", e);
+                        }
+                    }
+                    (Some(jito_client), None) => {
+                        // Ноги арбитража уходят одним бандлом
+                        for bundle in strategies::runner::bundles(signals) {
+                            let legs = bundle.iter().map(|signal| signal.signal.clone()).collect();
+                            // Просроченный или не отправленный бандл не учитывается в капитале
+                            let sent = match jito_client.execute_bundle(legs, price_data.slot()).await {
+                                Ok(signature) => signature.is_some(),
+                                Err(e) => {
+                                    error!("Failed to send bundle: {}", e);
+                                    false
+                                }
+                            };
+                            if !sent {
+                                for signal in &bundle {
+                                    strategy_runner.on_reject(signal);
+                                }
+                                continue;
+                            }
+                            for signal in &bundle {
+                                if let Err(e) = strategy_runner.on_executed(signal, timestamp_ms / 1000) {
+                                    error!("Failed to book signal of {}: {}", signal.strategy_id, e);
+                                }
+                            }
+                        }
+                    }
+                    (None, None) => {
+                        for signal in &signals {
+                            println!("Сигнал при воспроизведении: {}", signal.signal);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    println!("Источник данных завершил работу");
+    Ok(())
 } 
\ No newline at end of file
@@ -0,0 +1,383 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+
+use serde::Deserialize;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::dex::serum::SerumMarketParams;
+use crate::error_handling::TradingError;
+use crate::jito_integration::Venue;
+use crate::price_feed::TokenPair;
+use crate::quantization::Quantizer;
+
+// Допуск при проверке кратности цены и объема шагу рынка
+const STEP_EPSILON: f64 = 1e-9;
+
+#[derive(Deserialize)]
+struct RegistryFile {
+    markets: Vec<MarketConfig>,
+    #[serde(default)]
+    feeds: Vec<FeedConfig>,
+    #[serde(default)]
+    stablecoins: Vec<Stablecoin>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OracleKind {
+    Pyth,
+    Switchboard,
+}
+
+#[derive(Deserialize)]
+struct OracleConfig {
+    kind: OracleKind,
+    account: String,
+}
+
+// Ценовой фид оракулов, например SOL/USD. Фиды не обязаны совпадать с
+// торгуемыми рынками: цены рынков выводятся из них через граф курсов.
+#[derive(Deserialize)]
+struct FeedConfig {
+    base: String,
+    quote: String,
+    // Оракулы в порядке приоритета: первый считается основным
+    oracles: Vec<OracleConfig>,
+}
+
+#[derive(Debug, Clone)]
+pub struct OracleFeed {
+    pub pair: TokenPair,
+    pub oracles: Vec<OracleAccount>,
+}
+
+// Стейблкоин, курс которого к `peg` проверяется на отвязку
+#[derive(Debug, Clone, Deserialize)]
+pub struct Stablecoin {
+    pub symbol: String,
+    pub peg: String,
+    pub max_depeg_bps: f64,
+}
+
+// Аккаунт оракула с типом, определяющим формат данных
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OracleAccount {
+    pub kind: OracleKind,
+    pub account: Pubkey,
+}
+
+// Описание рынка в конфигурационном файле. Адреса задаются в base58.
+#[derive(Deserialize)]
+struct MarketConfig {
+    base: String,
+    quote: String,
+    base_mint: String,
+    quote_mint: String,
+    base_decimals: u8,
+    quote_decimals: u8,
+    dex_market: String,
+    // Размеры лотов в нативных единицах токенов, как в MarketState
+    base_lot_size: u64,
+    quote_lot_size: u64,
+    taker_fee_bps: u64,
+    // Минимальный объем ордера в базовом токене
+    min_order_size: f64,
+    // AMM-пулы той же пары
+    #[serde(default)]
+    pools: Vec<PoolConfig>,
+    // OpenOrders аккаунт бота на рынке
+    #[serde(default)]
+    open_orders: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+enum PoolConfig {
+    Raydium {
+        amm: String,
+    },
+    Orca {
+        whirlpool: String,
+        tick_arrays: Vec<String>,
+        // Базовый токен пары - токен A пула
+        base_is_a: bool,
+    },
+}
+
+// AMM-пул, на котором торгуется пара рынка
+#[derive(Debug, Clone)]
+pub enum PoolInfo {
+    Raydium {
+        amm: Pubkey,
+    },
+    Orca {
+        whirlpool: Pubkey,
+        tick_arrays: Vec<Pubkey>,
+        base_is_a: bool,
+    },
+}
+
+impl PoolInfo {
+    // Площадка для сигналов, исполняемых обменом в этом пуле
+    pub fn venue(&self) -> Venue {
+        match self {
+            PoolInfo::Raydium { amm } => Venue::Raydium { amm: *amm },
+            PoolInfo::Orca { whirlpool, .. } => Venue::Orca { whirlpool: *whirlpool },
+        }
+    }
+
+    pub fn name(&self, pair: &TokenPair) -> String {
+        match self {
+            PoolInfo::Raydium { amm } => format!("raydium:{}:{}", pair, amm),
+            PoolInfo::Orca { whirlpool, .. } => format!("orca:{}:{}", pair, whirlpool),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MarketInfo {
+    pub pair: TokenPair,
+    pub base_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    pub base_decimals: u8,
+    pub quote_decimals: u8,
+    pub dex_market: Pubkey,
+    pub base_lot_size: u64,
+    pub quote_lot_size: u64,
+    pub taker_fee_bps: u64,
+    pub min_order_size: f64,
+    pub pools: Vec<PoolInfo>,
+    // События event queue с другим владельцем не относятся к ордерам бота
+    pub open_orders: Option<Pubkey>,
+}
+
+impl MarketInfo {
+    pub fn serum_params(&self) -> SerumMarketParams {
+        SerumMarketParams {
+            base_lot_size: self.base_lot_size,
+            quote_lot_size: self.quote_lot_size,
+            base_decimals: self.base_decimals,
+            quote_decimals: self.quote_decimals,
+            taker_fee_bps: self.taker_fee_bps,
+        }
+    }
+
+    pub fn quantizer(&self) -> Quantizer {
+        Quantizer::from_market(self)
+    }
+
+    // Шаг цены в котируемом токене за единицу базового
+    pub fn tick_size(&self) -> f64 {
+        self.quantizer().tick_size()
+    }
+
+    // Шаг объема в базовом токене
+    pub fn lot_size(&self) -> f64 {
+        self.quantizer().lot_size()
+    }
+
+    // Проверяет, что ордер можно выставить на рынок как есть
+    pub fn validate_order(&self, price: f64, size: f64) -> Result<(), TradingError> {
+        if price <= 0.0 || size <= 0.0 {
+            return Err(TradingError::InvalidOrder(format!(
+                "{}: price and size must be positive (price={}, size={})",
+                self.pair, price, size
+            )));
+        }
+        if size < self.min_order_size {
+            return Err(TradingError::InvalidOrder(format!(
+                "{}: size {} is below minimum {}",
+                self.pair, size, self.min_order_size
+            )));
+        }
+        if !is_multiple_of(price, self.tick_size()) {
+            return Err(TradingError::InvalidOrder(format!(
+                "{}: price {} is not a multiple of tick size {}",
+                self.pair, price, self.tick_size()
+            )));
+        }
+        if !is_multiple_of(size, self.lot_size()) {
+            return Err(TradingError::InvalidOrder(format!(
+                "{}: size {} is not a multiple of lot size {}",
+                self.pair, size, self.lot_size()
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn is_multiple_of(value: f64, step: f64) -> bool {
+    let steps = value / step;
+    (steps - steps.round()).abs() < STEP_EPSILON * steps.abs().max(1.0)
+}
+
+pub struct MarketRegistry {
+    // Упорядочены по паре, чтобы обход рынков был одинаковым от запуска к запуску
+    markets: BTreeMap<String, MarketInfo>,
+    feeds: Vec<OracleFeed>,
+    stablecoins: Vec<Stablecoin>,
+}
+
+impl MarketRegistry {
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read market registry {}: {}", path, e))?;
+        Self::from_json(&contents)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, Box<dyn Error>> {
+        let file: RegistryFile = serde_json::from_str(json)?;
+
+        let mut markets = BTreeMap::new();
+        for config in file.markets {
+            let pair = TokenPair::new(&config.base, &config.quote);
+            if config.base_lot_size == 0 || config.quote_lot_size == 0 {
+                return Err(format!("{}: lot sizes must be non-zero", pair).into());
+            }
+
+            let info = MarketInfo {
+                base_mint: parse_pubkey(&pair, "base_mint", &config.base_mint)?,
+                quote_mint: parse_pubkey(&pair, "quote_mint", &config.quote_mint)?,
+                base_decimals: config.base_decimals,
+                quote_decimals: config.quote_decimals,
+                dex_market: parse_pubkey(&pair, "dex_market", &config.dex_market)?,
+                base_lot_size: config.base_lot_size,
+                quote_lot_size: config.quote_lot_size,
+                taker_fee_bps: config.taker_fee_bps,
+                min_order_size: config.min_order_size,
+                pools: config.pools.iter()
+                    .map(|pool| parse_pool(&pair, pool))
+                    .collect::<Result<Vec<_>, Box<dyn Error>>>()?,
+                open_orders: config.open_orders.as_ref()
+                    .map(|account| parse_pubkey(&pair, "open_orders", account))
+                    .transpose()?,
+                pair,
+            };
+
+            if markets.insert(info.pair.to_string(), info).is_some() {
+                return Err(format!("Duplicate market {}/{} in registry", config.base, config.quote).into());
+            }
+        }
+
+        let mut feeds = Vec::with_capacity(file.feeds.len());
+        for config in file.feeds {
+            let pair = TokenPair::new(&config.base, &config.quote);
+            if config.oracles.is_empty() {
+                return Err(format!("No oracle configured for feed {}", pair).into());
+            }
+            let oracles = config.oracles.iter()
+                .map(|oracle| Ok(OracleAccount {
+                    kind: oracle.kind,
+                    account: parse_pubkey(&pair, "oracles", &oracle.account)?,
+                }))
+                .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+            feeds.push(OracleFeed { pair, oracles });
+        }
+
+        Ok(Self {
+            markets,
+            feeds,
+            stablecoins: file.stablecoins,
+        })
+    }
+
+    pub fn get(&self, pair: &TokenPair) -> Result<&MarketInfo, Box<dyn Error>> {
+        self.markets.get(&pair.to_string())
+            .ok_or_else(|| format!("Market {} is not in registry", pair).into())
+    }
+
+    // Рынок по адресу Serum market из сигнала
+    pub fn by_dex_market(&self, dex_market: &Pubkey) -> Option<&MarketInfo> {
+        self.markets.values().find(|info| info.dex_market == *dex_market)
+    }
+
+    pub fn markets(&self) -> impl Iterator<Item = &MarketInfo> {
+        self.markets.values()
+    }
+
+    pub fn pairs(&self) -> Vec<TokenPair> {
+        self.markets.values().map(|info| info.pair.clone()).collect()
+    }
+
+    pub fn feeds(&self) -> &[OracleFeed] {
+        &self.feeds
+    }
+
+    pub fn stablecoins(&self) -> &[Stablecoin] {
+        &self.stablecoins
+    }
+}
+
+fn parse_pubkey(pair: &TokenPair, field: &str, value: &str) -> Result<Pubkey, Box<dyn Error>> {
+    value.parse()
+        .map_err(|e| format!("{}: invalid {} '{}': {}", pair, field, value, e).into())
+}
+
+fn parse_pool(pair: &TokenPair, config: &PoolConfig) -> Result<PoolInfo, Box<dyn Error>> {
+    Ok(match config {
+        PoolConfig::Raydium { amm } => PoolInfo::Raydium {
+            amm: parse_pubkey(pair, "pools.amm", amm)?,
+        },
+        PoolConfig::Orca { whirlpool, tick_arrays, base_is_a } => PoolInfo::Orca {
+            whirlpool: parse_pubkey(pair, "pools.whirlpool", whirlpool)?,
+            tick_arrays: tick_arrays.iter()
+                .map(|account| parse_pubkey(pair, "pools.tick_arrays", account))
+                .collect::<Result<Vec<_>, _>>()?,
+            base_is_a: *base_is_a,
+        },
+    })
+}
+
+// Реестр с рынками SOL, RAY и mSOL к USDC для тестов модулей
+#[cfg(test)]
+pub(crate) fn test_registry() -> MarketRegistry {
+    MarketRegistry::from_json(r#"{
+        "markets": [
+            {
+                "base": "SOL",
+                "quote": "USDC",
+                "base_mint": "So11111111111111111111111111111111111111112",
+                "quote_mint": "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+                "base_decimals": 9,
+                "quote_decimals": 6,
+                "dex_market": "9wFFyRfZBsuAha4YcuxcXLKwMxJR43S7fPfQLusDBzvT",
+                "base_lot_size": 100000000,
+                "quote_lot_size": 100,
+                "taker_fee_bps": 10,
+                "min_order_size": 0.1,
+                "open_orders": "CwLm7jteztZW9Jw2ipbTUB6VA7iNDFrN8Mh9fdg2Ktw7"
+            },
+            {
+                "base": "RAY",
+                "quote": "USDC",
+                "base_mint": "4k3Dyjzvzp8eMZWUXbBCjEvwSkkk59S5iCNLY3QrkX6R",
+                "quote_mint": "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+                "base_decimals": 6,
+                "quote_decimals": 6,
+                "dex_market": "2xiv8A5xrJ7RnGdxXB42uFEkYHJjszEhaJyKKt4WaLep",
+                "base_lot_size": 100000,
+                "quote_lot_size": 100,
+                "taker_fee_bps": 10,
+                "min_order_size": 0.1
+            },
+            {
+                "base": "mSOL",
+                "quote": "USDC",
+                "base_mint": "mSoLzYCxHdYgdzU16g5QSh3i5K3z3KZK7ytfqcJm7So",
+                "quote_mint": "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+                "base_decimals": 9,
+                "quote_decimals": 6,
+                "dex_market": "6oGsL2puUgySccKzn9XA9afqF217LfxP5ocq4B3LWsjy",
+                "base_lot_size": 100000000,
+                "quote_lot_size": 100,
+                "taker_fee_bps": 10,
+                "min_order_size": 0.1
+            }
+        ]
+    }"#).unwrap()
+}
+
+#[cfg(test)]
+#[path = "market_registry_test.rs"]
+mod tests;
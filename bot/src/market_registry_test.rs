@@ -0,0 +1,77 @@
+use super::*;
+
+fn market(base: &str, base_lot_size: u64, quote_lot_size: u64) -> String {
+    format!(r#"{{
+        "base": "{}",
+        "quote": "USDC",
+        "base_mint": "So11111111111111111111111111111111111111112",
+        "quote_mint": "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+        "base_decimals": 9,
+        "quote_decimals": 6,
+        "dex_market": "9wFFyRfZBsuAha4YcuxcXLKwMxJR43S7fPfQLusDBzvT",
+        "base_lot_size": {},
+        "quote_lot_size": {},
+        "taker_fee_bps": 4,
+        "min_order_size": 0.1
+    }}"#, base, base_lot_size, quote_lot_size)
+}
+
+fn registry(markets: &[String]) -> Result<MarketRegistry, Box<dyn Error>> {
+    MarketRegistry::from_json(&format!(r#"{{"markets": [{}]}}"#, markets.join(",")))
+}
+
+#[test]
+fn test_bundled_registry_loads() {
+    let registry = MarketRegistry::from_json(include_str!("../markets.json")).unwrap();
+    let sol = registry.get(&TokenPair::new("SOL", "USDC")).unwrap();
+    assert_eq!(sol.pools.len(), 1);
+    assert!(registry.by_dex_market(&sol.dex_market).is_some());
+    assert!(!registry.feeds().is_empty());
+    assert!(registry.feeds().iter().all(|feed| !feed.oracles.is_empty()));
+}
+
+#[test]
+fn test_from_json_steps_and_lookup() {
+    let registry = registry(&[market("SOL", 100_000_000, 100)]).unwrap();
+    let sol = registry.get(&TokenPair::new("SOL", "USDC")).unwrap();
+
+    // 100 нативных USDC на лот 0.1 SOL - шаг цены 0.001 USDC
+    assert!((sol.tick_size() - 0.001).abs() < 1e-12);
+    assert!((sol.lot_size() - 0.1).abs() < 1e-12);
+    assert_eq!(sol.serum_params().base_lot_size, 100_000_000);
+    assert!(sol.open_orders.is_none());
+    assert_eq!(registry.pairs(), vec![TokenPair::new("SOL", "USDC")]);
+    assert!(registry.get(&TokenPair::new("RAY", "USDC")).is_err());
+}
+
+#[test]
+fn test_from_json_rejects_invalid_markets() {
+    let error = registry(&[market("SOL", 100_000_000, 100), market("SOL", 100_000_000, 100)]).err().unwrap();
+    assert!(error.to_string().contains("Duplicate market SOL/USDC"));
+
+    assert!(registry(&[market("SOL", 0, 100)]).is_err());
+    assert!(registry(&[market("SOL", 100_000_000, 0)]).is_err());
+
+    let no_oracles = r#"{"markets": [], "feeds": [{"base": "SOL", "quote": "USD", "oracles": []}]}"#;
+    assert!(MarketRegistry::from_json(no_oracles).is_err());
+}
+
+#[test]
+fn test_validate_order() {
+    let registry = registry(&[market("SOL", 100_000_000, 100)]).unwrap();
+    let sol = registry.get(&TokenPair::new("SOL", "USDC")).unwrap();
+
+    assert!(sol.validate_order(20.125, 1.5).is_ok());
+    // Ошибки округления f64 не считаются нарушением шага
+    assert!(sol.validate_order(0.1 + 0.2, 0.3).is_ok());
+
+    let rejected = |price: f64, size: f64| match sol.validate_order(price, size) {
+        Err(TradingError::InvalidOrder(message)) => message,
+        other => panic!("expected InvalidOrder, got {:?}", other.err()),
+    };
+    assert!(rejected(0.0, 1.0).contains("must be positive"));
+    assert!(rejected(20.0, -1.0).contains("must be positive"));
+    assert!(rejected(20.0, 0.05).contains("below minimum"));
+    assert!(rejected(20.0005, 1.0).contains("tick size"));
+    assert!(rejected(20.0, 1.25).contains("lot size"));
+}
@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+use std::error::Error;
+
+use crate::jito_integration::TradeAction;
+use crate::orderbook::{BookSide, L2Book, MarketFill};
+
+// Остаток меньше этого считается исполненным
+const SIZE_EPSILON: f64 = 1e-9;
+// Допуск при сравнении цены сделки с ценой ордера
+const PRICE_TOLERANCE: f64 = 1e-9;
+
+// Ордер, отправленный в симулятор, в UI-единицах
+#[derive(Debug, Clone)]
+pub struct SimOrder {
+    pub client_order_id: u64,
+    pub action: TradeAction,
+    // Предельная цена; у рыночного ордера - цена с допуском проскальзывания
+    pub limit: f64,
+    pub size: f64,
+    // Неисполненный остаток встает в стакан
+    pub resting: bool,
+    // Ордер, пересекающий спред, отклоняется целиком
+    pub post_only: bool,
+}
+
+// Исполнение ордера симулятором
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimFill {
+    pub client_order_id: u64,
+    pub action: TradeAction,
+    pub price: f64,
+    pub size: f64,
+    // Комиссия в котируемом токене; отрицательная - ребейт мейкера
+    pub fee: f64,
+    pub maker: bool,
+}
+
+// Что стало с ордером при отправке
+#[derive(Debug, Clone, Default)]
+pub struct Placement {
+    // Исполнение тейкера по средневзвешенной цене пройденных уровней
+    pub taker: Option<SimFill>,
+    // Объем, вставший в стакан
+    pub rested: f64,
+    // Причина, по которой ордер не исполнен вовсе
+    pub rejected: Option<String>,
+}
+
+// Стоящий ордер с позицией в очереди своего уровня
+#[derive(Debug, Clone)]
+struct QueuedOrder {
+    pair: String,
+    client_order_id: u64,
+    action: TradeAction,
+    price: f64,
+    size: f64,
+    // Объем уровня, стоявший перед ордером
+    queue_ahead: f64,
+}
+
+// Симулятор исполнения против L2-стаканов Serum. Тейкер проходит уровни
+// встречной стороны до своего лимита и может исполниться частично; взятый
+// объем снимается с копии стакана до следующего снимка. Стоящий ордер встает
+// в конец очереди своего уровня: объем перед ним уменьшают сделки по этой цене
+// и сокращение уровня в новых снимках, исполняется он только после того, как
+// очередь пройдена, или когда сделки и встречные заявки проходят сквозь его цену.
+// Используется бэктестом на записанных снимках и бумажной торговлей на живых.
+#[derive(Debug, Clone)]
+pub struct MatchingEngine {
+    // Комиссия тейкера; по умолчанию - комиссия рынка из параметров стакана
+    taker_fee_bps: Option<f64>,
+    // Комиссия мейкера; отрицательная - ребейт
+    maker_fee_bps: f64,
+    // Последние снимки стаканов по ключу пары
+    books: HashMap<String, L2Book>,
+    // Стоящие ордера в порядке выставления
+    orders: Vec<QueuedOrder>,
+}
+
+impl MatchingEngine {
+    pub fn new(taker_fee_bps: Option<f64>, maker_fee_bps: f64) -> Self {
+        Self {
+            taker_fee_bps,
+            maker_fee_bps,
+            books: HashMap::new(),
+            orders: Vec::new(),
+        }
+    }
+
+    pub fn book(&self, pair: &str) -> Option<&L2Book> {
+        self.books.get(pair)
+    }
+
+    pub fn books(&self) -> &HashMap<String, L2Book> {
+        &self.books
+    }
+
+    pub fn has_order(&self, client_order_id: u64) -> bool {
+        self.orders.iter().any(|order| order.client_order_id == client_order_id)
+    }
+
+    // Новый снимок стакана пары. Встречные заявки по цене стоящего ордера или
+    // лучше исполняют его; сократившийся уровень сокращает очередь перед ордером.
+    pub fn on_book(&mut self, pair: &str, book: L2Book) -> Vec<SimFill> {
+        let mut book = book;
+        let mut fills = Vec::new();
+        for order in self.orders.iter_mut().filter(|order| order.pair == pair) {
+            let (own_side, opposite_side) = sides(order.action);
+            let crossed: f64 = book.take_liquidity(opposite_side, order.size, order.price)
+                .iter()
+                .map(|&(_, size)| size)
+                .sum();
+            if crossed > SIZE_EPSILON {
+                order.queue_ahead = 0.0;
+                fills.push(maker_fill(order, crossed, self.maker_fee_bps));
+                order.size -= crossed;
+            }
+            order.queue_ahead = order.queue_ahead.min(book.size_at(own_side, order.price));
+        }
+        self.books.insert(pair.to_string(), book);
+        self.remove_filled();
+        fills
+    }
+
+    // Сделка из event queue. Учитываются только события мейкера: у каждого своя
+    // цена уровня, а событие тейкера дублирует тот же объем.
+    pub fn on_trade(&mut self, trade: &MarketFill) -> Vec<SimFill> {
+        if !trade.maker {
+            return Vec::new();
+        }
+        let pair = trade.pair.to_string();
+        let mut left = trade.size;
+        let mut fills = Vec::new();
+        for order in self.orders.iter_mut().filter(|order| order.pair == pair) {
+            // Продающий тейкер забирает bids, то есть стоящие покупки
+            if order.action == trade.taker_action || left <= SIZE_EPSILON {
+                continue;
+            }
+            let through = match order.action {
+                TradeAction::Buy => trade.price < order.price * (1.0 - PRICE_TOLERANCE),
+                TradeAction::Sell => trade.price > order.price * (1.0 + PRICE_TOLERANCE),
+            };
+            let at_level = (trade.price - order.price).abs() <= order.price * PRICE_TOLERANCE;
+
+            let size = if through {
+                // Сделка хуже цены ордера прошла бы сначала через него
+                order.size.min(left)
+            } else if at_level {
+                let past_queue = (left - order.queue_ahead).max(0.0);
+                order.queue_ahead = (order.queue_ahead - left).max(0.0);
+                order.size.min(past_queue)
+            } else {
+                continue;
+            };
+            if size <= SIZE_EPSILON {
+                continue;
+            }
+            fills.push(maker_fill(order, size, self.maker_fee_bps));
+            order.size -= size;
+            left -= size;
+        }
+        self.remove_filled();
+        fills
+    }
+
+    // Исполняет ордер против стакана пары. Тейкерская часть снимает объем со
+    // стакана, остаток лимитного ордера встает в конец очереди своего уровня.
+    pub fn place(&mut self, pair: &str, order: &SimOrder) -> Result<Placement, Box<dyn Error>> {
+        let taker_fee_bps = self.taker_fee_bps;
+        let book = self.books.get_mut(pair)
+            .ok_or_else(|| format!("No order book for {}", pair))?;
+        let (own_side, opposite_side) = sides(order.action);
+
+        let crosses = match (order.action, book.levels(opposite_side).first()) {
+            (TradeAction::Buy, Some(&(best, _))) => best <= order.limit,
+            (TradeAction::Sell, Some(&(best, _))) => best >= order.limit,
+            (_, None) => false,
+        };
+        if order.post_only && crosses {
+            return Ok(Placement {
+                rejected: Some("post-only order would cross the spread".to_string()),
+                ..Placement::default()
+            });
+        }
+
+        let taken = book.take_liquidity(opposite_side, order.size, order.limit);
+        let size: f64 = taken.iter().map(|&(_, size)| size).sum();
+        let taker = if size > SIZE_EPSILON {
+            let price = taken.iter().map(|&(price, size)| price * size).sum::<f64>() / size;
+            let fee_bps = taker_fee_bps.unwrap_or(book.params().taker_fee_bps as f64);
+            Some(SimFill {
+                client_order_id: order.client_order_id,
+                action: order.action,
+                price,
+                size,
+                fee: price * size * fee_bps / 10_000.0,
+                maker: false,
+            })
+        } else {
+            None
+        };
+
+        let remaining = order.size - size;
+        if !order.resting || remaining <= SIZE_EPSILON {
+            let rejected = if taker.is_none() {
+                Some(format!("no liquidity for {} within limit {:.6}", pair, order.limit))
+            } else {
+                None
+            };
+            return Ok(Placement { taker, rested: 0.0, rejected });
+        }
+
+        self.orders.push(QueuedOrder {
+            pair: pair.to_string(),
+            client_order_id: order.client_order_id,
+            action: order.action,
+            price: order.limit,
+            size: remaining,
+            queue_ahead: book.size_at(own_side, order.limit),
+        });
+        Ok(Placement { taker, rested: remaining, rejected: None })
+    }
+
+    // Снимает стоящий ордер; false, если такого нет
+    pub fn cancel(&mut self, client_order_id: u64) -> bool {
+        let before = self.orders.len();
+        self.orders.retain(|order| order.client_order_id != client_order_id);
+        self.orders.len() != before
+    }
+
+    fn remove_filled(&mut self) {
+        self.orders.retain(|order| order.size > SIZE_EPSILON);
+    }
+}
+
+// Своя и встречная стороны стакана для направления ордера
+fn sides(action: TradeAction) -> (BookSide, BookSide) {
+    match action {
+        TradeAction::Buy => (BookSide::Bids, BookSide::Asks),
+        TradeAction::Sell => (BookSide::Asks, BookSide::Bids),
+    }
+}
+
+fn maker_fill(order: &QueuedOrder, size: f64, fee_bps: f64) -> SimFill {
+    SimFill {
+        client_order_id: order.client_order_id,
+        action: order.action,
+        price: order.price,
+        size,
+        fee: order.price * size * fee_bps / 10_000.0,
+        maker: true,
+    }
+}
+
+#[cfg(test)]
+#[path = "matching_test.rs"]
+mod tests;
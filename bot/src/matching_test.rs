@@ -0,0 +1,106 @@
+use super::*;
+use crate::dex::serum::SerumMarketParams;
+use crate::price_feed::TokenPair;
+use solana_sdk::pubkey::Pubkey;
+
+// SOL/USDC: шаг цены 0.001, лот 0.1 SOL, комиссия тейкера 10 bps
+const PARAMS: SerumMarketParams = SerumMarketParams {
+    base_lot_size: 100_000_000,
+    quote_lot_size: 100,
+    base_decimals: 9,
+    quote_decimals: 6,
+    taker_fee_bps: 10,
+};
+
+fn engine(bids: &[(u64, u64)], asks: &[(u64, u64)]) -> MatchingEngine {
+    let mut engine = MatchingEngine::new(None, -2.0);
+    engine.on_book("SOL/USDC", L2Book::with_levels(PARAMS, bids, asks));
+    engine
+}
+
+fn order(client_order_id: u64, action: TradeAction, limit: f64, size: f64, resting: bool) -> SimOrder {
+    SimOrder { client_order_id, action, limit, size, resting, post_only: false }
+}
+
+// Событие мейкера из event queue по уровню `price`
+fn trade(price: f64, size: f64, taker_action: TradeAction) -> MarketFill {
+    MarketFill {
+        pair: TokenPair::new("SOL", "USDC"),
+        market: Pubkey::default(),
+        seq_num: 0,
+        slot: 0,
+        price,
+        size,
+        taker_action,
+        maker: true,
+        owner: Pubkey::default(),
+        client_order_id: 0,
+        fee: 0.0,
+    }
+}
+
+#[test]
+fn test_taker_walks_levels_and_fills_partially() {
+    // asks: 20.000 x 1 SOL, 20.010 x 2 SOL, 20.050 x 5 SOL
+    let mut engine = engine(&[], &[(20_000, 10), (20_010, 20), (20_050, 50)]);
+
+    let placement = engine.place("SOL/USDC", &order(1, TradeAction::Buy, 20.02, 2.0, false)).unwrap();
+    let fill = placement.taker.unwrap();
+    assert!((fill.price - 20.005).abs() < 1e-9 && (fill.size - 2.0).abs() < 1e-9);
+    assert!((fill.fee - 20.005 * 2.0 * 0.001).abs() < 1e-9 && !fill.maker);
+
+    // Взятый объем снят со стакана: до лимита остался 1 SOL
+    let placement = engine.place("SOL/USDC", &order(2, TradeAction::Buy, 20.02, 3.0, false)).unwrap();
+    let fill = placement.taker.unwrap();
+    assert!((fill.price - 20.01).abs() < 1e-9 && (fill.size - 1.0).abs() < 1e-9);
+    assert_eq!(placement.rested, 0.0);
+
+    let placement = engine.place("SOL/USDC", &order(3, TradeAction::Buy, 20.02, 1.0, false)).unwrap();
+    assert!(placement.taker.is_none() && placement.rejected.is_some());
+}
+
+#[test]
+fn test_resting_order_waits_for_queue_ahead() {
+    // bids: 19.990 x 3 SOL
+    let mut engine = engine(&[(19_990, 30)], &[(20_000, 10)]);
+    let placement = engine.place("SOL/USDC", &order(1, TradeAction::Buy, 19.99, 1.0, true)).unwrap();
+    assert!(placement.taker.is_none() && (placement.rested - 1.0).abs() < 1e-9);
+
+    // Продажи по цене ордера сначала съедают 3 SOL, стоявшие перед ним
+    assert!(engine.on_trade(&trade(19.99, 2.0, TradeAction::Sell)).is_empty());
+    // Уровень сократился до 0.5 SOL: отмены впереди продвигают очередь
+    assert!(engine.on_book("SOL/USDC", L2Book::with_levels(PARAMS, &[(19_990, 5)], &[(20_000, 10)])).is_empty());
+
+    let fills = engine.on_trade(&trade(19.99, 1.0, TradeAction::Sell));
+    assert_eq!(fills.len(), 1);
+    assert!((fills[0].size - 0.5).abs() < 1e-9 && fills[0].maker);
+    assert!((fills[0].fee + 19.99 * 0.5 * 0.0002).abs() < 1e-9);
+
+    // Покупки тейкеров ордер на покупку не трогают, продажа ниже его цены исполняет остаток
+    assert!(engine.on_trade(&trade(20.0, 1.0, TradeAction::Buy)).is_empty());
+    let fills = engine.on_trade(&trade(19.98, 2.0, TradeAction::Sell));
+    assert!((fills[0].price - 19.99).abs() < 1e-9 && (fills[0].size - 0.5).abs() < 1e-9);
+    assert!(!engine.has_order(1));
+}
+
+#[test]
+fn test_crossing_limit_order_takes_then_rests() {
+    let mut engine = engine(&[(19_990, 10)], &[(20_000, 10), (20_020, 10)]);
+
+    let post_only = SimOrder { post_only: true, ..order(1, TradeAction::Buy, 20.0, 1.0, true) };
+    assert!(engine.place("SOL/USDC", &post_only).unwrap().rejected.is_some());
+
+    // Покупка 3 SOL по 20.01 забирает 1 SOL по 20.00, остаток встает новым уровнем без очереди
+    let placement = engine.place("SOL/USDC", &order(2, TradeAction::Buy, 20.01, 3.0, true)).unwrap();
+    assert!((placement.taker.unwrap().size - 1.0).abs() < 1e-9);
+    assert!((placement.rested - 2.0).abs() < 1e-9);
+
+    // Новый снимок с продажей по цене ордера исполняет его на объем этой продажи
+    let fills = engine.on_book("SOL/USDC", L2Book::with_levels(PARAMS, &[(19_990, 10)], &[(20_010, 5), (20_020, 10)]));
+    assert_eq!(fills.len(), 1);
+    assert!((fills[0].price - 20.01).abs() < 1e-9 && (fills[0].size - 0.5).abs() < 1e-9);
+    assert!(engine.has_order(2));
+
+    assert!(engine.cancel(2));
+    assert!(!engine.has_order(2));
+}
@@ -1,7 +1,14 @@
 use log::{info, warn, error};
+use std::collections::VecDeque;
 use std::time::{Duration, Instant};
 use tokio::time::interval;
 
+use crate::jito_integration::TradingSignal;
+use crate::paper::PaperSummary;
+
+// Сколько последних сигналов хранится для отчетов
+const RECENT_SIGNALS: usize = 20;
+
 pub struct PerformanceMetrics {
     pub trades_executed: u64,
     pub successful_trades: u64,
@@ -13,6 +20,9 @@ pub struct PerformanceMetrics {
 pub struct Monitor {
     start_time: Instant,
     metrics: PerformanceMetrics,
+    recent_signals: VecDeque<TradingSignal>,
+    // Виртуальный счет при бумажной торговле
+    paper_portfolio: Option<PaperSummary>,
 }
 
 impl Monitor {
@@ -26,7 +36,39 @@ impl Monitor {
                 average_execution_time: Duration::new(0, 0),
                 total_profit_loss: 0.0,
             },
+            recent_signals: VecDeque::with_capacity(RECENT_SIGNALS),
+            paper_portfolio: None,
+        }
+    }
+
+    // Сигнал с метаданными стратегии: уверенность, срок действия, обоснование
+    pub fn record_signal(&mut self, signal: &TradingSignal) {
+        info!("Signal: {}", signal);
+        if self.recent_signals.len() == RECENT_SIGNALS {
+            self.recent_signals.pop_front();
         }
+        self.recent_signals.push_back(signal.clone());
+    }
+
+    // Последние сигналы, от новых к старым
+    pub fn recent_signals(&self) -> impl Iterator<Item = &TradingSignal> {
+        self.recent_signals.iter().rev()
+    }
+
+    pub fn update_paper_portfolio(&mut self, summary: PaperSummary) {
+        self.paper_portfolio = Some(summary);
+    }
+
+    pub fn paper_portfolio(&self) -> Option<&PaperSummary> {
+        self.paper_portfolio.as_ref()
+    }
+
+    pub fn metrics(&self) -> &PerformanceMetrics {
+        &self.metrics
+    }
+
+    pub fn uptime(&self) -> Duration {
+        self.start_time.elapsed()
     }
 
     pub fn record_trade(&mut self, success: bool, execution_time: Duration, profit_loss: f64) {
@@ -68,6 +110,12 @@ impl Monitor {
         );
         info!("Average execution time: {:?}", self.metrics.average_execution_time);
         info!("Total PnL: {:.2} USDC", self.metrics.total_profit_loss);
+        if let Some(paper) = &self.paper_portfolio {
+            info!(
+                "Paper equity: {:.2} {} (PnL {:.2}, fees {:.2}, fills {})",
+                paper.equity, paper.quote, paper.pnl, paper.fees, paper.fills
+            );
+        }
         info!("========================");
     }
 } 
\ No newline at end of file
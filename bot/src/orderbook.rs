@@ -0,0 +1,504 @@
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::account_stream::AccountUpdate;
+use crate::dex::serum::{self, EventKind, SerumBook, SerumMarketParams};
+use crate::jito_integration::TradeAction;
+use crate::market_registry::{MarketInfo, MarketRegistry};
+use crate::price_feed::TokenPair;
+use crate::quantization::Rounding;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BookSide {
+    Bids,
+    Asks,
+}
+
+// Сделка из event queue, приведенная к UI-единицам
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarketFill {
+    pub pair: TokenPair,
+    pub market: Pubkey,
+    pub seq_num: u64,
+    pub slot: u64,
+    pub price: f64,
+    pub size: f64,
+    // Сторона тейкера: покупка, если тейкер забрал asks
+    pub taker_action: TradeAction,
+    // Событие мейкера: сделка по стоявшему в стакане ордеру
+    pub maker: bool,
+    // OpenOrders аккаунт и client_order_id ордера, которому принадлежит событие.
+    // client_order_id выбирает сам владелец, поэтому он уникален только вместе с owner.
+    pub owner: Pubkey,
+    pub client_order_id: u64,
+    // Комиссия в котируемом токене; для мейкера - отрицательная (ребейт)
+    pub fee: f64,
+}
+
+// Ордер ушел из стакана: отменен или исполнен полностью
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderOut {
+    pub pair: TokenPair,
+    pub owner: Pubkey,
+    pub client_order_id: u64,
+}
+
+// L2-стакан одного рынка: цена и объем хранятся в лотах, наружу отдаются в UI-единицах
+#[derive(Debug, Clone, PartialEq)]
+pub struct L2Book {
+    params: SerumMarketParams,
+    bids: BTreeMap<u64, u64>,
+    asks: BTreeMap<u64, u64>,
+    last_update_slot: u64,
+}
+
+impl L2Book {
+    pub fn new(params: SerumMarketParams) -> Self {
+        Self {
+            params,
+            bids: BTreeMap::new(),
+            asks: BTreeMap::new(),
+            last_update_slot: 0,
+        }
+    }
+
+    pub fn params(&self) -> &SerumMarketParams {
+        &self.params
+    }
+
+    pub fn last_update_slot(&self) -> u64 {
+        self.last_update_slot
+    }
+
+    // Полностью заменяет сторону стакана содержимым slab-аккаунта
+    pub fn apply_slab(&mut self, side: BookSide, data: &[u8], slot: u64) -> Result<(), Box<dyn Error>> {
+        let orders = serum::decode_slab(data)?;
+        let mut levels = BTreeMap::new();
+        for order in orders {
+            *levels.entry(order.price_lots).or_insert(0) += order.quantity_lots;
+        }
+
+        match side {
+            BookSide::Bids => self.bids = levels,
+            BookSide::Asks => self.asks = levels,
+        }
+        self.last_update_slot = self.last_update_slot.max(slot);
+        Ok(())
+    }
+
+    pub fn best_bid(&self) -> Option<(f64, f64)> {
+        self.bids.iter().next_back().map(|(&price, &size)| self.to_ui(price, size))
+    }
+
+    pub fn best_ask(&self) -> Option<(f64, f64)> {
+        self.asks.iter().next().map(|(&price, &size)| self.to_ui(price, size))
+    }
+
+    pub fn mid_price(&self) -> Option<f64> {
+        Some((self.best_bid()?.0 + self.best_ask()?.0) / 2.0)
+    }
+
+    pub fn spread(&self) -> Option<f64> {
+        Some(self.best_ask()?.0 - self.best_bid()?.0)
+    }
+
+    // Уровни стороны стакана, лучшая цена первой
+    pub fn levels(&self, side: BookSide) -> Vec<(f64, f64)> {
+        match side {
+            BookSide::Bids => self.bids.iter().rev().map(|(&price, &size)| self.to_ui(price, size)).collect(),
+            BookSide::Asks => self.asks.iter().map(|(&price, &size)| self.to_ui(price, size)).collect(),
+        }
+    }
+
+    // Суммарный объем на уровнях не хуже указанной цены
+    pub fn depth_at_price(&self, side: BookSide, price: f64) -> f64 {
+        self.levels(side)
+            .into_iter()
+            .take_while(|&(level_price, _)| match side {
+                BookSide::Bids => level_price >= price,
+                BookSide::Asks => level_price <= price,
+            })
+            .map(|(_, size)| size)
+            .sum()
+    }
+
+    // Средневзвешенная цена исполнения рыночного ордера заданного объема.
+    // Покупка идет по asks, продажа - по bids. None, если глубины не хватает.
+    pub fn vwap(&self, action: TradeAction, size: f64) -> Option<f64> {
+        if size <= 0.0 {
+            return None;
+        }
+
+        let side = match action {
+            TradeAction::Buy => BookSide::Asks,
+            TradeAction::Sell => BookSide::Bids,
+        };
+
+        let mut remaining = size;
+        let mut notional = 0.0;
+        for (price, level_size) in self.levels(side) {
+            let take = remaining.min(level_size);
+            notional += take * price;
+            remaining -= take;
+            if remaining <= 0.0 {
+                return Some(notional / size);
+            }
+        }
+
+        None
+    }
+
+    // Объем уровня с указанной ценой; 0, если такого уровня нет
+    pub fn size_at(&self, side: BookSide, price: f64) -> f64 {
+        let price_lots = self.price_to_lots(price, Rounding::Nearest);
+        let levels = match side {
+            BookSide::Bids => &self.bids,
+            BookSide::Asks => &self.asks,
+        };
+        levels.get(&price_lots).map_or(0.0, |&size| self.params.size_lots_to_ui(size))
+    }
+
+    // Снимает объем со стороны стакана, начиная с лучшей цены и не дальше `limit`.
+    // Возвращает снятые части уровней (цена, объем) в UI-единицах.
+    pub fn take_liquidity(&mut self, side: BookSide, size: f64, limit: f64) -> Vec<(f64, f64)> {
+        // Уровни за пределом не трогаются: лимит покупки округляется вниз, продажи - вверх
+        let limit_lots = match side {
+            BookSide::Bids => self.price_to_lots(limit, Rounding::Up),
+            BookSide::Asks => self.price_to_lots(limit, Rounding::Down),
+        };
+        let mut remaining = (size / self.params.size_lots_to_ui(1)).round() as u64;
+        let mut taken = Vec::new();
+        while remaining > 0 {
+            let best = match side {
+                BookSide::Bids => self.bids.range(limit_lots..).next_back(),
+                BookSide::Asks => self.asks.range(..=limit_lots).next(),
+            };
+            let (price, level) = match best {
+                Some((&price, &level)) => (price, level),
+                None => break,
+            };
+            let take = remaining.min(level);
+            let levels = match side {
+                BookSide::Bids => &mut self.bids,
+                BookSide::Asks => &mut self.asks,
+            };
+            if take == level {
+                levels.remove(&price);
+            } else {
+                levels.insert(price, level - take);
+            }
+            remaining -= take;
+            taken.push(self.to_ui(price, take));
+        }
+        taken
+    }
+
+    pub fn to_liquidity_source(&self, name: &str) -> SerumBook {
+        SerumBook::from_levels(name, self.params.taker_fee(), self.levels(BookSide::Bids), self.levels(BookSide::Asks))
+    }
+
+    fn to_ui(&self, price_lots: u64, size_lots: u64) -> (f64, f64) {
+        (self.params.price_lots_to_ui(price_lots), self.params.size_lots_to_ui(size_lots))
+    }
+
+    fn price_to_lots(&self, price: f64, rounding: Rounding) -> u64 {
+        rounding.apply(price / self.params.price_lots_to_ui(1)).max(0.0) as u64
+    }
+}
+
+#[cfg(test)]
+impl L2Book {
+    // Стакан с уровнями в лотах - для тестов стратегий
+    pub fn with_levels(params: SerumMarketParams, bids: &[(u64, u64)], asks: &[(u64, u64)]) -> Self {
+        Self {
+            params,
+            bids: bids.iter().copied().collect(),
+            asks: asks.iter().copied().collect(),
+            last_update_slot: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum TrackedAccount {
+    // MarketState рынка из реестра, адреса стакана которого еще не известны
+    MarketState(Pubkey),
+    Bids(Pubkey),
+    Asks(Pubkey),
+    EventQueue(Pubkey),
+}
+
+struct MarketAccounts {
+    pair: TokenPair,
+    bids: Pubkey,
+    asks: Pubkey,
+    event_queue: Pubkey,
+    // Номер следующего необработанного события
+    next_seq_num: Option<u64>,
+}
+
+// Локальное зеркало стаканов. Обновляется уведомлениями об изменении
+// аккаунтов bids/asks/event queue (или их периодическим опросом).
+pub struct OrderBookMirror {
+    books: HashMap<Pubkey, L2Book>,
+    markets: HashMap<Pubkey, MarketAccounts>,
+    accounts: HashMap<Pubkey, TrackedAccount>,
+    // Рынки реестра, ожидающие содержимого MarketState
+    pending: HashMap<Pubkey, MarketInfo>,
+    // События Out из event queue, еще не забранные через `take_outs`
+    outs: Vec<OrderOut>,
+}
+
+impl OrderBookMirror {
+    pub fn new() -> Self {
+        Self {
+            books: HashMap::new(),
+            markets: HashMap::new(),
+            accounts: HashMap::new(),
+            pending: HashMap::new(),
+            outs: Vec::new(),
+        }
+    }
+
+    pub fn track_market(
+        &mut self,
+        pair: &TokenPair,
+        market: Pubkey,
+        bids: Pubkey,
+        asks: Pubkey,
+        event_queue: Pubkey,
+        params: SerumMarketParams,
+    ) {
+        self.books.insert(market, L2Book::new(params));
+        self.markets.insert(market, MarketAccounts {
+            pair: pair.clone(),
+            bids,
+            asks,
+            event_queue,
+            next_seq_num: None,
+        });
+        self.accounts.insert(bids, TrackedAccount::Bids(market));
+        self.accounts.insert(asks, TrackedAccount::Asks(market));
+        self.accounts.insert(event_queue, TrackedAccount::EventQueue(market));
+    }
+
+    // Ожидает MarketState рынков реестра: рынок начнет отслеживаться, когда
+    // содержимое его аккаунта придет в `on_account_update`
+    pub fn watch_registry(&mut self, registry: &MarketRegistry) {
+        for market in registry.markets() {
+            self.pending.insert(market.dex_market, market.clone());
+            self.accounts.insert(market.dex_market, TrackedAccount::MarketState(market.dex_market));
+        }
+    }
+
+    // Подключает все рынки реестра, читая адреса bids/asks/event queue из MarketState.
+    // Возвращает прочитанные аккаунты MarketState, чтобы их можно было записать.
+    pub fn track_registry(&mut self, registry: &MarketRegistry, rpc_client: &RpcClient) -> Result<Vec<AccountUpdate>, Box<dyn Error>> {
+        self.watch_registry(registry);
+
+        let slot = rpc_client.get_slot()?;
+        let mut market_states = Vec::new();
+        for market in registry.markets() {
+            let data = rpc_client.get_account_data(&market.dex_market)?;
+            self.on_account_update(&market.dex_market, &data, slot)?;
+            market_states.push(AccountUpdate {
+                pubkey: market.dex_market,
+                slot,
+                data,
+            });
+        }
+        Ok(market_states)
+    }
+
+    // Все аккаунты, на изменения которых нужно подписаться
+    pub fn tracked_accounts(&self) -> Vec<Pubkey> {
+        self.markets
+            .values()
+            .flat_map(|accounts| [accounts.bids, accounts.asks, accounts.event_queue])
+            .collect()
+    }
+
+    pub fn book(&self, market: &Pubkey) -> Option<&L2Book> {
+        self.books.get(market)
+    }
+
+    pub fn is_tracked(&self, account: &Pubkey) -> bool {
+        self.accounts.contains_key(account)
+    }
+
+    // Пара и стакан рынка, которому принадлежит аккаунт bids или asks
+    pub fn book_of_account(&self, account: &Pubkey) -> Option<(&TokenPair, &L2Book)> {
+        let market = match self.accounts.get(account)? {
+            TrackedAccount::Bids(market) | TrackedAccount::Asks(market) => market,
+            TrackedAccount::MarketState(_) | TrackedAccount::EventQueue(_) => return None,
+        };
+        Some((&self.markets.get(market)?.pair, self.books.get(market)?))
+    }
+
+    // Копия всех стаканов по ключу торговой пары для передачи стратегиям
+    pub fn snapshot(&self) -> HashMap<String, L2Book> {
+        self.markets
+            .iter()
+            .filter_map(|(market, accounts)| {
+                self.books.get(market).map(|book| (accounts.pair.to_string(), book.clone()))
+            })
+            .collect()
+    }
+
+    // Обрабатывает новое содержимое аккаунта. Для event queue возвращает
+    // сделки, появившиеся с предыдущего обновления.
+    pub fn on_account_update(&mut self, account: &Pubkey, data: &[u8], slot: u64) -> Result<Vec<MarketFill>, Box<dyn Error>> {
+        let tracked = *self.accounts.get(account)
+            .ok_or_else(|| format!("Account {} is not tracked", account))?;
+
+        match tracked {
+            TrackedAccount::MarketState(market) => {
+                self.apply_market_state(market, data)?;
+                Ok(Vec::new())
+            }
+            TrackedAccount::Bids(market) => {
+                self.book_mut(&market)?.apply_slab(BookSide::Bids, data, slot)?;
+                Ok(Vec::new())
+            }
+            TrackedAccount::Asks(market) => {
+                self.book_mut(&market)?.apply_slab(BookSide::Asks, data, slot)?;
+                Ok(Vec::new())
+            }
+            TrackedAccount::EventQueue(market) => self.apply_event_queue(market, data, slot),
+        }
+    }
+
+    // Опрос всех отслеживаемых аккаунтов одним RPC-запросом
+    pub fn poll(&mut self, rpc_client: &RpcClient) -> Result<Vec<MarketFill>, Box<dyn Error>> {
+        let slot = rpc_client.get_slot()?;
+        let keys = self.tracked_accounts();
+        let accounts = rpc_client.get_multiple_accounts(&keys)?;
+
+        let mut fills = Vec::new();
+        for (key, account) in keys.iter().zip(accounts) {
+            if let Some(account) = account {
+                fills.extend(self.on_account_update(key, &account.data, slot)?);
+            }
+        }
+
+        Ok(fills)
+    }
+
+    // Ордера, ушедшие из стакана с прошлого вызова, в порядке событий. Забирать
+    // их нужно после сделок того же обновления: Out ордера идет после его исполнений.
+    pub fn take_outs(&mut self) -> Vec<OrderOut> {
+        std::mem::take(&mut self.outs)
+    }
+
+    fn book_mut(&mut self, market: &Pubkey) -> Result<&mut L2Book, Box<dyn Error>> {
+        self.books.get_mut(market).ok_or_else(|| format!("Market {} is not tracked", market).into())
+    }
+
+    // Адреса стакана и размеры лотов меняются только при пересоздании рынка,
+    // поэтому MarketState применяется один раз
+    fn apply_market_state(&mut self, market: Pubkey, data: &[u8]) -> Result<(), Box<dyn Error>> {
+        let info = match self.pending.remove(&market) {
+            Some(info) => info,
+            None => return Ok(()),
+        };
+
+        let state = serum::decode_market_state(data)?;
+        if state.base_lot_size != info.base_lot_size || state.quote_lot_size != info.quote_lot_size {
+            return Err(format!("{}: lot sizes in registry do not match market account", info.pair).into());
+        }
+
+        self.track_market(
+            &info.pair,
+            market,
+            state.bids,
+            state.asks,
+            state.event_queue,
+            info.serum_params(),
+        );
+        Ok(())
+    }
+
+    fn apply_event_queue(&mut self, market: Pubkey, data: &[u8], slot: u64) -> Result<Vec<MarketFill>, Box<dyn Error>> {
+        let params = *self.book_mut(&market)?.params();
+        let events = serum::decode_event_queue(data)?;
+        let accounts = self.markets.get_mut(&market)
+            .ok_or_else(|| format!("Market {} is not tracked", market))?;
+
+        // При первом снимке просто запоминаем позицию, чтобы не выдать старые сделки
+        let next_seq_num = match accounts.next_seq_num {
+            Some(seq_num) => seq_num,
+            None => {
+                accounts.next_seq_num = Some(events.last().map_or(0, |event| event.seq_num + 1));
+                return Ok(Vec::new());
+            }
+        };
+
+        let mut fills = Vec::new();
+        for event in events.iter().filter(|event| event.seq_num >= next_seq_num) {
+            if event.kind == EventKind::Out {
+                self.outs.push(OrderOut {
+                    pair: accounts.pair.clone(),
+                    owner: event.owner,
+                    client_order_id: event.client_order_id,
+                });
+                continue;
+            }
+
+            // Каждая сделка порождает событие для мейкера и тейкера. Комиссия тейкера
+            // входит в оплаченную (или вычтена из полученной) сумму, ребейт мейкера
+            // начисляется отдельно.
+            let (base_native, quote_native, fee) = match (event.is_bid, event.is_maker) {
+                (true, false) => (
+                    event.native_qty_released,
+                    event.native_qty_paid.saturating_sub(event.native_fee_or_rebate),
+                    event.native_fee_or_rebate as f64,
+                ),
+                (false, false) => (
+                    event.native_qty_paid,
+                    event.native_qty_released + event.native_fee_or_rebate,
+                    event.native_fee_or_rebate as f64,
+                ),
+                (true, true) => (event.native_qty_released, event.native_qty_paid, -(event.native_fee_or_rebate as f64)),
+                (false, true) => (event.native_qty_paid, event.native_qty_released, -(event.native_fee_or_rebate as f64)),
+            };
+            if base_native == 0 {
+                continue;
+            }
+
+            // Мейкер на стороне bid означает, что тейкер продавал
+            let taker_action = if event.is_bid != event.is_maker {
+                TradeAction::Buy
+            } else {
+                TradeAction::Sell
+            };
+            let size = base_native as f64 / 10f64.powi(params.base_decimals as i32);
+            let quote_scale = 10f64.powi(params.quote_decimals as i32);
+            fills.push(MarketFill {
+                pair: accounts.pair.clone(),
+                market,
+                seq_num: event.seq_num,
+                slot,
+                price: quote_native as f64 / quote_scale / size,
+                size,
+                taker_action,
+                maker: event.is_maker,
+                owner: event.owner,
+                client_order_id: event.client_order_id,
+                fee: fee / quote_scale,
+            });
+        }
+
+        if let Some(last) = events.last() {
+            accounts.next_seq_num = Some(next_seq_num.max(last.seq_num + 1));
+        }
+
+        Ok(fills)
+    }
+}
+
+#[cfg(test)]
+#[path = "orderbook_test.rs"]
+mod tests;
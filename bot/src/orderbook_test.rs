@@ -0,0 +1,190 @@
+use super::*;
+
+// Снимки аккаунтов Serum собираются в тех же бинарных раскладках, что и в сети
+
+// SOL/USDC: шаг цены 0.001, лот 0.1 SOL, комиссия тейкера 10 bps
+const PARAMS: SerumMarketParams = SerumMarketParams {
+    base_lot_size: 100_000_000,
+    quote_lot_size: 100,
+    base_decimals: 9,
+    quote_decimals: 6,
+    taker_fee_bps: 10,
+};
+
+fn slab(orders: &[(u64, u64)]) -> Vec<u8> {
+    let mut data = b"serum".to_vec();
+    data.extend_from_slice(&0u64.to_le_bytes()); // account flags
+    data.extend_from_slice(&(orders.len() as u64 + 1).to_le_bytes()); // bump_index
+    data.extend_from_slice(&[0u8; 24]);
+    // Внутренний узел дерева перед листьями декодер пропускает
+    let mut inner = vec![0u8; 72];
+    inner[0..4].copy_from_slice(&1u32.to_le_bytes());
+    data.extend_from_slice(&inner);
+    for (i, &(price_lots, quantity_lots)) in orders.iter().enumerate() {
+        let mut node = vec![0u8; 72];
+        node[0..4].copy_from_slice(&2u32.to_le_bytes());
+        let key = ((price_lots as u128) << 64) | i as u128;
+        node[8..24].copy_from_slice(&key.to_le_bytes());
+        node[56..64].copy_from_slice(&quantity_lots.to_le_bytes());
+        node[64..72].copy_from_slice(&(100 + i as u64).to_le_bytes());
+        data.extend_from_slice(&node);
+    }
+    data.extend_from_slice(b"padding");
+    data
+}
+
+// Событие event queue: флаги, нативные суммы, цена в order_id, владелец и client_order_id
+struct Event {
+    flags: u8,
+    released: u64,
+    paid: u64,
+    fee: u64,
+    price_lots: u64,
+    owner: Pubkey,
+    client_order_id: u64,
+}
+
+const TAKER: Pubkey = Pubkey::new_from_array([1; 32]);
+const MAKER: Pubkey = Pubkey::new_from_array([2; 32]);
+
+fn event_queue(capacity: usize, head: usize, seq_num: u64, events: &[Event]) -> Vec<u8> {
+    let mut data = b"serum".to_vec();
+    data.extend_from_slice(&0u64.to_le_bytes()); // account flags
+    data.extend_from_slice(&(head as u64).to_le_bytes());
+    data.extend_from_slice(&(events.len() as u64).to_le_bytes());
+    data.extend_from_slice(&seq_num.to_le_bytes());
+    let mut slots = vec![vec![0u8; 88]; capacity];
+    for (i, event) in events.iter().enumerate() {
+        let slot = &mut slots[(head + i) % capacity];
+        slot[0] = event.flags;
+        slot[8..16].copy_from_slice(&event.released.to_le_bytes());
+        slot[16..24].copy_from_slice(&event.paid.to_le_bytes());
+        slot[24..32].copy_from_slice(&event.fee.to_le_bytes());
+        let order_id = (event.price_lots as u128) << 64;
+        slot[32..48].copy_from_slice(&order_id.to_le_bytes());
+        slot[48..80].copy_from_slice(&event.owner.to_bytes());
+        slot[80..88].copy_from_slice(&event.client_order_id.to_le_bytes());
+    }
+    for slot in slots {
+        data.extend_from_slice(&slot);
+    }
+    data.extend_from_slice(b"padding");
+    data
+}
+
+// Покупка тейкером 1 SOL по 20.000 у мейкера на asks: по событию на каждую сторону
+fn buy_fill(client_order_id: u64) -> [Event; 2] {
+    [
+        // Тейкер-покупатель получил 1 SOL, заплатил 20 USDC и 0.02 комиссии
+        Event { flags: 0x01 | 0x04, released: 1_000_000_000, paid: 20_020_000, fee: 20_000, price_lots: 20_000, owner: TAKER, client_order_id },
+        // Мейкер-продавец отдал 1 SOL, получил 20 USDC и ребейт 0.004
+        Event { flags: 0x01 | 0x08, released: 20_000_000, paid: 1_000_000_000, fee: 4_000, price_lots: 20_000, owner: MAKER, client_order_id: 7 },
+    ]
+}
+
+fn out_event() -> Event {
+    Event { flags: 0x02, released: 0, paid: 0, fee: 0, price_lots: 20_000, owner: MAKER, client_order_id: 0 }
+}
+
+#[test]
+fn test_decode_event_queue_wraps_around() {
+    let [taker, maker] = buy_fill(1);
+    // Три события с головой у конца буфера на 4 события: номера 7, 8, 9
+    let data = event_queue(4, 3, 10, &[taker, out_event(), maker]);
+    let events = serum::decode_event_queue(&data).unwrap();
+
+    assert_eq!(events.iter().map(|event| event.seq_num).collect::<Vec<_>>(), vec![7, 8, 9]);
+    assert_eq!(events[0].kind, EventKind::Fill);
+    assert!(events[0].is_bid && !events[0].is_maker);
+    assert_eq!((events[0].price_lots(), events[0].client_order_id), (20_000, 1));
+    assert_eq!((events[0].owner, events[2].owner), (TAKER, MAKER));
+    assert_eq!(events[1].kind, EventKind::Out);
+    assert!(!events[2].is_bid && events[2].is_maker);
+}
+
+#[test]
+fn test_decode_event_queue_rejects_inconsistent_header() {
+    // Номер следующего события меньше числа событий
+    let data = event_queue(4, 0, 1, &[out_event(), out_event()]);
+    assert!(serum::decode_event_queue(&data).is_err());
+
+    let mut data = event_queue(2, 0, 10, &[out_event(), out_event()]);
+    data[21..29].copy_from_slice(&3u64.to_le_bytes());
+    assert!(serum::decode_event_queue(&data).is_err());
+}
+
+#[test]
+fn test_l2_book_depth_and_vwap() {
+    let mut book = L2Book::new(PARAMS);
+    // Два ордера на 19.990 складываются в один уровень
+    book.apply_slab(BookSide::Bids, &slab(&[(19_990, 10), (19_980, 20), (19_990, 5)]), 5).unwrap();
+    book.apply_slab(BookSide::Asks, &slab(&[(20_010, 10), (20_030, 30)]), 7).unwrap();
+
+    assert_eq!(book.last_update_slot(), 7);
+    assert_eq!(book.levels(BookSide::Bids).len(), 2);
+    let (bid, bid_size) = book.best_bid().unwrap();
+    assert!((bid - 19.99).abs() < 1e-9 && (bid_size - 1.5).abs() < 1e-9);
+    assert!((book.mid_price().unwrap() - 20.0).abs() < 1e-9);
+    assert!((book.spread().unwrap() - 0.02).abs() < 1e-9);
+
+    assert!((book.depth_at_price(BookSide::Bids, 19.985) - 1.5).abs() < 1e-9);
+    assert!((book.depth_at_price(BookSide::Asks, 20.03) - 4.0).abs() < 1e-9);
+
+    // 2 SOL: 1 по 20.010 и 1 по 20.030
+    assert!((book.vwap(TradeAction::Buy, 2.0).unwrap() - 20.02).abs() < 1e-9);
+    assert!(book.vwap(TradeAction::Buy, 5.0).is_none());
+    assert!((book.vwap(TradeAction::Sell, 1.5).unwrap() - 19.99).abs() < 1e-9);
+
+    // Новый снимок стороны полностью заменяет прежний
+    book.apply_slab(BookSide::Asks, &slab(&[(20_050, 10)]), 8).unwrap();
+    assert!((book.best_ask().unwrap().0 - 20.05).abs() < 1e-9);
+}
+
+#[test]
+fn test_mirror_emits_only_new_fills() {
+    let market = Pubkey::new_unique();
+    let (bids, asks, queue) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    let mut mirror = OrderBookMirror::new();
+    mirror.track_market(&TokenPair::new("SOL", "USDC"), market, bids, asks, queue, PARAMS);
+    assert!(mirror.is_tracked(&queue) && !mirror.is_tracked(&market));
+
+    mirror.on_account_update(&bids, &slab(&[(19_990, 10)]), 10).unwrap();
+    mirror.on_account_update(&asks, &slab(&[(20_000, 10)]), 10).unwrap();
+    let (pair, book) = mirror.book_of_account(&asks).unwrap();
+    assert_eq!(pair.to_string(), "SOL/USDC");
+    assert!((book.mid_price().unwrap() - 19.995).abs() < 1e-9);
+    assert!(mirror.book_of_account(&queue).is_none());
+    assert!(mirror.snapshot().contains_key("SOL/USDC"));
+
+    // Первый снимок очереди только запоминает позицию: старые сделки не выдаются
+    let [taker, maker] = buy_fill(1);
+    assert!(mirror.on_account_update(&queue, &event_queue(8, 0, 2, &[taker, maker]), 11).unwrap().is_empty());
+
+    // Во втором снимке событие 1 уже видели, новые - 2..4
+    let [taker, maker] = buy_fill(2);
+    let [old, _] = buy_fill(1);
+    let fills = mirror.on_account_update(&queue, &event_queue(8, 1, 5, &[old, taker, out_event(), maker]), 12).unwrap();
+    assert_eq!(fills.len(), 2);
+
+    let taker = &fills[0];
+    assert_eq!((taker.seq_num, taker.slot, taker.owner, taker.client_order_id), (2, 12, TAKER, 2));
+    assert!(!taker.maker && taker.taker_action == TradeAction::Buy);
+    assert!((taker.price - 20.0).abs() < 1e-9 && (taker.size - 1.0).abs() < 1e-9);
+    assert!((taker.fee - 0.02).abs() < 1e-9);
+
+    let maker = &fills[1];
+    assert!(maker.maker && maker.taker_action == TradeAction::Buy);
+    assert!((maker.price - 20.0).abs() < 1e-9 && (maker.fee + 0.004).abs() < 1e-9);
+
+    // Событие Out забирается отдельно и только один раз
+    let out = OrderOut { pair: TokenPair::new("SOL", "USDC"), owner: MAKER, client_order_id: 0 };
+    assert_eq!(mirror.take_outs(), vec![out]);
+    assert!(mirror.take_outs().is_empty());
+
+    // Повтор того же снимка ничего не выдает
+    let [taker, maker] = buy_fill(2);
+    let [old, _] = buy_fill(1);
+    assert!(mirror.on_account_update(&queue, &event_queue(8, 1, 5, &[old, taker, out_event(), maker]), 13).unwrap().is_empty());
+    assert!(mirror.take_outs().is_empty());
+    assert!(mirror.on_account_update(&Pubkey::new_unique(), &[], 13).is_err());
+}
@@ -0,0 +1,421 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::error::Error;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::backtest::engine::dispatch_event;
+use crate::backtest::exchange::{ExchangeConfig, ExchangeEvent, RestingOrder, SimulatedExchange};
+use crate::backtest::stats::{OpenPosition, TradeLedger};
+use crate::jito_integration::{OrderAction, TradeAction};
+use crate::market_registry::MarketRegistry;
+use crate::monitoring::Monitor;
+use crate::orderbook::{MarketFill, OrderBookMirror};
+use crate::strategies::runner::{self, StrategyRunner, StrategySignal};
+use crate::strategies::{Allocation, Fill};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct PaperConfig {
+    // Включает бумажную торговлю без флага --paper
+    pub enabled: bool,
+    // Токен, в котором оценивается портфель
+    pub quote: String,
+    // Начальные виртуальные балансы, пока сохраненного портфеля нет
+    pub balances: BTreeMap<String, f64>,
+    // Файл виртуального портфеля
+    pub portfolio_path: PathBuf,
+    // Исполнять ордера против зеркала стаканов; иначе - по ценам фида с проскальзыванием
+    pub use_order_book: bool,
+    // Задержка, проскальзывание и комиссии виртуальной биржи
+    pub exchange: ExchangeConfig,
+}
+
+impl Default for PaperConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            quote: "USDC".to_string(),
+            balances: BTreeMap::from([("USDC".to_string(), 10_000.0)]),
+            portfolio_path: PathBuf::from("paper-portfolio.json"),
+            use_order_book: true,
+            exchange: ExchangeConfig::default(),
+        }
+    }
+}
+
+impl PaperConfig {
+    // Конфигурация из файла; если файла нет - значения по умолчанию
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Self::from_json(&contents),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("Failed to read paper trading config {}: {}", path, e).into()),
+        }
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, Box<dyn Error>> {
+        Ok(serde_json::from_str(json)?)
+    }
+}
+
+// Виртуальный счет: балансы токенов по символу и состояние стратегий на нем.
+// Сохраняется после каждого события биржи, чтобы обкатка стратегии переживала
+// перезапуски бота.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PaperPortfolio {
+    // Балансы на момент создания счета - база для PnL
+    pub initial_balances: BTreeMap<String, f64>,
+    pub balances: BTreeMap<String, f64>,
+    // Уплаченные комиссии в котируемых токенах
+    pub fees: f64,
+    pub fills: u64,
+    // Unix-время последней сделки в секундах
+    pub updated_at: i64,
+    // Капитал и позиции экземпляров стратегий по id, чтобы после перезапуска
+    // они сходились с балансами
+    #[serde(default)]
+    pub allocations: BTreeMap<String, Allocation>,
+    // Открытые позиции журнала сделок для реализованного PnL
+    #[serde(default)]
+    pub open_positions: HashMap<String, OpenPosition>,
+    // Стоящие ордера виртуальной биржи
+    #[serde(default)]
+    pub resting_orders: Vec<RestingOrder>,
+}
+
+impl PaperPortfolio {
+    pub fn new(balances: BTreeMap<String, f64>) -> Self {
+        Self {
+            initial_balances: balances.clone(),
+            balances,
+            fees: 0.0,
+            fills: 0,
+            updated_at: 0,
+            allocations: BTreeMap::new(),
+            open_positions: HashMap::new(),
+            resting_orders: Vec::new(),
+        }
+    }
+
+    // Сохраненный портфель; None, если файла еще нет
+    pub fn load(path: &Path) -> Result<Option<Self>, Box<dyn Error>> {
+        match fs::read_to_string(path) {
+            Ok(contents) => serde_json::from_str(&contents)
+                .map(Some)
+                .map_err(|e| format!("Invalid paper portfolio {}: {}", path.display(), e).into()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(format!("Failed to read paper portfolio {}: {}", path.display(), e).into()),
+        }
+    }
+
+    // Запись через временный файл, чтобы прерванная запись не испортила портфель
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, serde_json::to_string_pretty(self)?)?;
+        fs::rename(&tmp_path, path)
+            .map_err(|e| format!("Failed to save paper portfolio {}: {}", path.display(), e))?;
+        Ok(())
+    }
+
+    pub fn balance(&self, token: &str) -> f64 {
+        self.balances.get(token).copied().unwrap_or(0.0)
+    }
+
+    pub fn apply_fill(&mut self, fill: &Fill) {
+        let (base, quote) = match fill.action {
+            TradeAction::Buy => (fill.size, -(fill.notional() + fill.fee)),
+            TradeAction::Sell => (-fill.size, fill.notional() - fill.fee),
+        };
+        *self.balances.entry(fill.pair.base().to_string()).or_insert(0.0) += base;
+        *self.balances.entry(fill.pair.quote().to_string()).or_insert(0.0) += quote;
+        self.fees += fill.fee;
+        self.fills += 1;
+        self.updated_at = fill.timestamp;
+    }
+
+    // Оценка портфеля в токене `quote` по ценам пар "TOKEN/QUOTE" и PnL
+    // относительно начальных балансов по тем же ценам
+    pub fn summary(&self, prices: &HashMap<String, f64>, quote: &str) -> PaperSummary {
+        let equity = valuation(&self.balances, prices, quote);
+        let initial_equity = valuation(&self.initial_balances, prices, quote);
+        PaperSummary {
+            quote: quote.to_string(),
+            balances: self.balances.clone(),
+            equity,
+            initial_equity,
+            pnl: equity - initial_equity,
+            fees: self.fees,
+            fills: self.fills,
+        }
+    }
+}
+
+// Состояние виртуального счета для мониторинга и Telegram
+#[derive(Debug, Clone, Serialize)]
+pub struct PaperSummary {
+    pub quote: String,
+    pub balances: BTreeMap<String, f64>,
+    pub equity: f64,
+    // Стоимость начальных балансов по текущим ценам
+    pub initial_equity: f64,
+    // Результат торговли по сравнению с удержанием начальных балансов
+    pub pnl: f64,
+    pub fees: f64,
+    pub fills: u64,
+}
+
+// Токены без цены к `quote` в оценку не входят
+fn valuation(balances: &BTreeMap<String, f64>, prices: &HashMap<String, f64>, quote: &str) -> f64 {
+    balances.iter()
+        .map(|(token, &amount)| {
+            if token == quote {
+                amount
+            } else {
+                prices.get(&format!("{}/{}", token, quote)).map_or(0.0, |price| amount * price)
+            }
+        })
+        .sum()
+}
+
+// Исполнитель бумажной торговли: вместо бандлов Jito сигналы уходят на
+// симулированную биржу бэктеста, которая исполняет их против зеркала стаканов
+// (с очередью стоящих ордеров) или по ценам фида с проскальзыванием. Сделки
+// меняют виртуальные балансы и передаются стратегиям так же, как живые.
+pub struct PaperTrader {
+    config: PaperConfig,
+    markets: Arc<MarketRegistry>,
+    exchange: SimulatedExchange,
+    portfolio: PaperPortfolio,
+    // Закрытые сделки для реализованного PnL в мониторинге
+    ledger: TradeLedger,
+    seq_num: u64,
+}
+
+impl PaperTrader {
+    // Сохраненный портфель возвращает экземплярам стратегий их капитал, позиции
+    // и стоящие ордера. Состояние экземпляров, которых больше нет в конфигурации,
+    // отбрасывается; их токены остаются на балансах.
+    pub fn new(config: PaperConfig, markets: Arc<MarketRegistry>, runner: &mut StrategyRunner) -> Result<Self, Box<dyn Error>> {
+        let portfolio = match PaperPortfolio::load(&config.portfolio_path)? {
+            Some(portfolio) => {
+                info!("Paper portfolio loaded from {}: {:?}", config.portfolio_path.display(), portfolio.balances);
+                portfolio
+            }
+            None => PaperPortfolio::new(config.balances.clone()),
+        };
+
+        for (strategy_id, allocation) in &portfolio.allocations {
+            if !runner.restore_allocation(strategy_id, allocation.clone()) {
+                warn!("Paper: strategy {} is no longer configured, its allocation is dropped", strategy_id);
+            }
+        }
+        let mut exchange = SimulatedExchange::new(config.exchange.clone(), markets.clone());
+        exchange.restore_resting(portfolio.resting_orders.iter()
+            .filter(|order| runner.restore_resting(
+                &order.strategy_id, order.client_order_id, &order.pair, order.action, order.price, order.size,
+            ))
+            .cloned()
+            .collect());
+        let ledger = TradeLedger::with_positions(portfolio.open_positions.clone());
+
+        Ok(Self {
+            exchange,
+            config,
+            markets,
+            portfolio,
+            ledger,
+            seq_num: 0,
+        })
+    }
+
+    pub fn portfolio(&self) -> &PaperPortfolio {
+        &self.portfolio
+    }
+
+    // Обновление аккаунта рынка: новый снимок стакана и сделки event queue
+    // продвигают очередь виртуальных стоящих ордеров
+    pub fn on_market_update(
+        &mut self,
+        order_books: &OrderBookMirror,
+        account: &Pubkey,
+        fills: &[MarketFill],
+        timestamp_ms: i64,
+        runner: &mut StrategyRunner,
+        monitor: &mut Monitor,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut events = self.exchange.execute_due(timestamp_ms)?;
+        if self.config.use_order_book {
+            if let Some((pair, book)) = order_books.book_of_account(account) {
+                events.extend(self.exchange.on_book(&pair.to_string(), book.clone(), timestamp_ms));
+            }
+            for fill in fills {
+                events.extend(self.exchange.on_trade(fill, timestamp_ms));
+            }
+        }
+        self.apply(events, timestamp_ms, runner, monitor)
+    }
+
+    // Цены тика: по ним исполняются ордера пар без стакана
+    pub fn on_prices(
+        &mut self,
+        prices: &[(String, f64)],
+        slot: u64,
+        timestamp_ms: i64,
+        runner: &mut StrategyRunner,
+        monitor: &mut Monitor,
+    ) -> Result<(), Box<dyn Error>> {
+        self.exchange.sync_slot(slot, timestamp_ms);
+        // Ордера, дошедшие до биржи раньше новых цен, исполняются по предыдущим
+        let mut events = self.exchange.execute_due(timestamp_ms - 1)?;
+        for (pair, price) in prices {
+            events.extend(self.exchange.on_price(pair, *price, timestamp_ms));
+        }
+        self.apply(events, timestamp_ms, runner, monitor)
+    }
+
+    // Отправляет одобренные сигналы на виртуальную биржу. Бандл, которому не
+    // хватает виртуальных балансов, отклоняется целиком, как транзакция без средств.
+    pub fn submit(
+        &mut self,
+        signals: Vec<StrategySignal>,
+        timestamp_ms: i64,
+        runner: &mut StrategyRunner,
+        monitor: &mut Monitor,
+    ) -> Result<(), Box<dyn Error>> {
+        for bundle in runner::bundles(signals) {
+            if let Some(reason) = self.insufficient_funds(&bundle)? {
+                warn!("Paper: bundle of {} rejected: {}", bundle[0].strategy_id, reason);
+                monitor.record_trade(false, self.latency(), 0.0);
+                for leg in &bundle {
+                    runner.on_reject(leg);
+                }
+                continue;
+            }
+            for leg in &bundle {
+                runner.on_submitted(leg)?;
+            }
+            self.exchange.submit(bundle, timestamp_ms);
+        }
+        let events = self.exchange.execute_due(timestamp_ms)?;
+        self.apply(events, timestamp_ms, runner, monitor)
+    }
+
+    // Чего не хватает бандлу: покупки - котируемого токена по цене сигнала с
+    // комиссией рынка, продажи - базового токена. Средства под стоящие ордера
+    // и под бандлы, еще не дошедшие до биржи, заняты, кроме ордеров, которые
+    // эти бандлы отменяют.
+    fn insufficient_funds(&self, bundle: &[StrategySignal]) -> Result<Option<String>, Box<dyn Error>> {
+        let mut required: BTreeMap<&str, f64> = BTreeMap::new();
+        for leg in bundle {
+            self.add_leg_cost(&mut required, leg)?;
+        }
+
+        let mut locked: BTreeMap<&str, f64> = BTreeMap::new();
+        let mut canceled = HashSet::new();
+        for leg in self.exchange.pending_bundles().flatten().chain(bundle) {
+            match leg.signal.order_action {
+                OrderAction::Place => {}
+                OrderAction::Cancel => {
+                    canceled.insert(leg.signal.client_order_id);
+                }
+                OrderAction::Replace { cancel_client_order_id } => {
+                    canceled.insert(cancel_client_order_id);
+                }
+            }
+        }
+        for leg in self.exchange.pending_bundles().flatten() {
+            self.add_leg_cost(&mut locked, leg)?;
+        }
+        for order in self.exchange.resting_orders().iter().filter(|order| !canceled.contains(&order.client_order_id)) {
+            match order.action {
+                TradeAction::Buy => *locked.entry(order.pair.quote()).or_insert(0.0) += order.price * order.size * (1.0 + order.fee_bps / 10_000.0),
+                TradeAction::Sell => *locked.entry(order.pair.base()).or_insert(0.0) += order.size,
+            }
+        }
+
+        Ok(required.into_iter()
+            .map(|(token, amount)| (token, amount, self.portfolio.balance(token) - locked.get(token).copied().unwrap_or(0.0)))
+            .find(|&(_, amount, available)| amount > available + 1e-9)
+            .map(|(token, amount, available)| format!("{} {:.6} required, {:.6} available", token, amount, available)))
+    }
+
+    // Сколько токена нужно ноге: отмена средств не требует
+    fn add_leg_cost<'a>(&self, required: &mut BTreeMap<&'a str, f64>, leg: &'a StrategySignal) -> Result<(), Box<dyn Error>> {
+        if leg.signal.order_action == OrderAction::Cancel {
+            return Ok(());
+        }
+        let market = self.markets.get(&leg.pair)?;
+        let quantizer = market.quantizer();
+        let size = quantizer.size_native_to_ui(leg.signal.amount);
+        let price = quantizer.price_native_to_ui(leg.signal.limit_price());
+        match leg.signal.action {
+            TradeAction::Buy => {
+                let cost = price * size * (1.0 + market.taker_fee_bps as f64 / 10_000.0);
+                *required.entry(leg.pair.quote()).or_insert(0.0) += cost;
+            }
+            TradeAction::Sell => *required.entry(leg.pair.base()).or_insert(0.0) += size,
+        }
+        Ok(())
+    }
+
+    // Передает события биржи стратегиям, меняет балансы и отчитывается в мониторинг
+    fn apply(
+        &mut self,
+        events: Vec<ExchangeEvent>,
+        timestamp_ms: i64,
+        runner: &mut StrategyRunner,
+        monitor: &mut Monitor,
+    ) -> Result<(), Box<dyn Error>> {
+        let changed = !events.is_empty();
+        for event in events {
+            if let ExchangeEvent::Rejected { signal, reason } = &event {
+                warn!("Paper: {} signal rejected: {}", signal.strategy_id, reason);
+                monitor.record_trade(false, self.latency(), 0.0);
+            }
+            let fill = match dispatch_event(runner, &self.markets, event, &mut self.seq_num, timestamp_ms)? {
+                Some(fill) => fill,
+                None => continue,
+            };
+
+            // Реализованный результат сделок, закрытых этим исполнением
+            let closed = self.ledger.trades().len();
+            self.ledger.record(&fill.strategy_id, &fill.fill);
+            let pnl: f64 = self.ledger.trades()[closed..].iter().map(|trade| trade.pnl).sum();
+
+            info!(
+                "Paper fill: {} {:?} {} {} @ {:.6}, fee {:.6}{}",
+                fill.strategy_id, fill.fill.action, fill.fill.size, fill.fill.pair, fill.fill.price,
+                fill.fill.fee, if fill.maker { " (maker)" } else { "" }
+            );
+            self.portfolio.apply_fill(&fill.fill);
+            monitor.record_trade(true, self.latency(), pnl);
+        }
+
+        // Ордер мог встать, сняться или исполниться - сохраняется все состояние счета
+        if changed {
+            self.portfolio.allocations = runner.allocations()
+                .map(|(strategy_id, allocation)| (strategy_id.to_string(), allocation.clone()))
+                .collect();
+            self.portfolio.open_positions = self.ledger.open_positions().clone();
+            self.portfolio.resting_orders = self.exchange.resting_orders().to_vec();
+            self.portfolio.save(&self.config.portfolio_path)?;
+        }
+        monitor.update_paper_portfolio(self.portfolio.summary(self.exchange.prices(), &self.config.quote));
+        Ok(())
+    }
+
+    fn latency(&self) -> Duration {
+        Duration::from_millis(self.config.exchange.latency_ms.max(0) as u64)
+    }
+}
+
+#[cfg(test)]
+#[path = "paper_test.rs"]
+mod tests;
@@ -0,0 +1,178 @@
+use super::*;
+use crate::jito_integration::TradingSignal;
+use crate::market_registry::test_registry;
+use crate::price_feed::{PriceData, TokenPair};
+use crate::strategies::registry::parse_params;
+use crate::strategies::{Allocation, RunnerConfig, Strategy, StrategyContext, StrategyRegistry};
+use serde_json::Value;
+
+// Стратегия без собственных сигналов: сигналы в тестах собираются вручную
+#[derive(Default, Deserialize)]
+struct Idle {}
+
+impl Strategy for Idle {
+    fn name(&self) -> &str {
+        "idle"
+    }
+
+    fn on_tick(&mut self, _ctx: &StrategyContext, _price_data: &PriceData) -> Result<Vec<TradingSignal>, Box<dyn Error>> {
+        Ok(Vec::new())
+    }
+}
+
+fn runner(markets: Arc<MarketRegistry>) -> StrategyRunner {
+    let mut strategies = StrategyRegistry::new();
+    strategies.register("idle", |params: &Value, _| Ok(Box::new(parse_params::<Idle>(params)?)));
+    let config = RunnerConfig::from_json(r#"{"strategies": [
+        {"id": "test", "strategy": "idle", "pairs": ["SOL/USDC"], "capital": 1000.0, "params": {}}
+    ]}"#).unwrap();
+    StrategyRunner::new(&config, &strategies, markets, Pubkey::default()).unwrap()
+}
+
+fn signal(markets: &MarketRegistry, size: f64, price: f64, post_only: bool) -> StrategySignal {
+    let pair = TokenPair::new("SOL", "USDC");
+    let allocation = Allocation::new(1000.0);
+    let pairs = [pair.clone()];
+    let ctx = StrategyContext { id: "test", pairs: &pairs, markets, allocation: &allocation, payer: Pubkey::default() };
+    let signal = if post_only {
+        ctx.quote_signal(&pair, TradeAction::Buy, size, price)
+    } else {
+        ctx.build_signal(&pair, TradeAction::Buy, size, price)
+    };
+    StrategySignal {
+        strategy_id: "test".to_string(),
+        signal: signal.unwrap().unwrap(),
+        pair,
+    }
+}
+
+fn buy(markets: &MarketRegistry, size: f64, price: f64) -> StrategySignal {
+    signal(markets, size, price, false)
+}
+
+fn config(dir: &Path) -> PaperConfig {
+    PaperConfig {
+        balances: BTreeMap::from([("USDC".to_string(), 100.0)]),
+        portfolio_path: dir.join("portfolio.json"),
+        use_order_book: false,
+        exchange: ExchangeConfig { latency_ms: 0, slippage_bps: 10.0, ..ExchangeConfig::default() },
+        ..PaperConfig::default()
+    }
+}
+
+#[test]
+fn test_portfolio_fills_and_valuation() {
+    let mut portfolio = PaperPortfolio::new(BTreeMap::from([("USDC".to_string(), 100.0)]));
+    portfolio.apply_fill(&Fill {
+        pair: TokenPair::new("SOL", "USDC"),
+        client_order_id: 1,
+        action: TradeAction::Buy,
+        price: 20.0,
+        size: 2.0,
+        fee: 0.04,
+        timestamp: 10,
+    });
+    assert!((portfolio.balance("USDC") - 59.96).abs() < 1e-9);
+    assert_eq!(portfolio.balance("SOL"), 2.0);
+
+    // SOL подорожал до 21: стоимость 59.96 + 42, результат к удержанию 100 USDC
+    let prices = HashMap::from([("SOL/USDC".to_string(), 21.0)]);
+    let summary = portfolio.summary(&prices, "USDC");
+    assert!((summary.equity - 101.96).abs() < 1e-9);
+    assert!((summary.pnl - 1.96).abs() < 1e-9);
+    assert_eq!((summary.fills, portfolio.updated_at), (1, 10));
+}
+
+#[test]
+fn test_paper_trader_fills_and_persists_portfolio() {
+    let dir = std::env::temp_dir().join(format!("paper-test-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let config = config(&dir);
+    let markets = Arc::new(test_registry());
+    let mut runner = runner(markets.clone());
+    let mut monitor = Monitor::new();
+    let mut trader = PaperTrader::new(config.clone(), markets.clone(), &mut runner).unwrap();
+
+    trader.on_prices(&[("SOL/USDC".to_string(), 20.0)], 1_000, 0, &mut runner, &mut monitor).unwrap();
+    trader.submit(vec![buy(&markets, 1.0, 20.1)], 0, &mut runner, &mut monitor).unwrap();
+
+    // Покупка по цене фида с проскальзыванием 10 bps и комиссией рынка 10 bps
+    let price = 20.0 * 1.001;
+    let usdc = 100.0 - price * 1.001;
+    assert!((trader.portfolio().balance("USDC") - usdc).abs() < 1e-9);
+    assert_eq!(trader.portfolio().balance("SOL"), 1.0);
+    assert_eq!(runner.allocation("test").unwrap().position(&TokenPair::new("SOL", "USDC")), 1.0);
+    let summary = monitor.paper_portfolio().unwrap();
+    assert!((summary.pnl - (usdc + 20.0 - 100.0)).abs() < 1e-9);
+
+    // На 10 SOL виртуальных USDC не хватает - бандл отклоняется без исполнения
+    trader.submit(vec![buy(&markets, 10.0, 20.1)], 1_000, &mut runner, &mut monitor).unwrap();
+    assert_eq!(trader.portfolio().balance("SOL"), 1.0);
+
+    // Портфель переживает перезапуск
+    let restarted = PaperTrader::new(config, markets.clone(), &mut self::runner(markets)).unwrap();
+    assert_eq!(restarted.portfolio(), trader.portfolio());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_restart_restores_strategy_state() {
+    let dir = std::env::temp_dir().join(format!("paper-restart-test-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let config = config(&dir);
+    let markets = Arc::new(test_registry());
+    let pair = TokenPair::new("SOL", "USDC");
+    let mut runner = self::runner(markets.clone());
+    let mut monitor = Monitor::new();
+    let mut trader = PaperTrader::new(config.clone(), markets.clone(), &mut runner).unwrap();
+
+    // Покупка 1 SOL и post-only заявка на 1 SOL по 19 ниже рынка
+    trader.on_prices(&[("SOL/USDC".to_string(), 20.0)], 1_000, 0, &mut runner, &mut monitor).unwrap();
+    trader.submit(vec![buy(&markets, 1.0, 20.1)], 0, &mut runner, &mut monitor).unwrap();
+    trader.submit(vec![signal(&markets, 1.0, 19.0, true)], 1_000, &mut runner, &mut monitor).unwrap();
+    assert_eq!(trader.portfolio().resting_orders.len(), 1);
+    assert_eq!(trader.portfolio().open_positions.len(), 1);
+
+    // После перезапуска экземпляр получает прежние капитал и позицию, а не начальные
+    let mut restarted_runner = self::runner(markets.clone());
+    let mut restarted = PaperTrader::new(config, markets, &mut restarted_runner).unwrap();
+    assert_eq!(restarted_runner.allocation("test"), runner.allocation("test"));
+    assert_eq!(restarted_runner.allocation("test").unwrap().position(&pair), 1.0);
+
+    // Восстановленная заявка исполняется и доходит до экземпляра
+    restarted.on_prices(&[("SOL/USDC".to_string(), 18.5)], 1_010, 2_000, &mut restarted_runner, &mut monitor).unwrap();
+    assert_eq!(restarted.portfolio().balance("SOL"), 2.0);
+    assert_eq!(restarted_runner.allocation("test").unwrap().position(&pair), 2.0);
+    assert!(restarted.portfolio().resting_orders.is_empty());
+
+    // Средняя цена входа учитывает покупку до перезапуска
+    let position = &restarted.portfolio().open_positions["test SOL/USDC"];
+    assert_eq!(position, &restarted.ledger.open_positions()["test SOL/USDC"]);
+    assert!((position.entry_price - (20.0 * 1.001 + 19.0) / 2.0).abs() < 1e-9);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_funds_locked_by_resting_and_pending_orders() {
+    let dir = std::env::temp_dir().join(format!("paper-locked-test-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let mut config = config(&dir);
+    config.exchange.latency_ms = 1_000;
+    let markets = Arc::new(test_registry());
+    let mut runner = runner(markets.clone());
+    let mut monitor = Monitor::new();
+    let mut trader = PaperTrader::new(config, markets.clone(), &mut runner).unwrap();
+    trader.on_prices(&[("SOL/USDC".to_string(), 20.0)], 1_000, 0, &mut runner, &mut monitor).unwrap();
+
+    // Заявка на 4 SOL по 19 занимает 76 из 100 USDC
+    trader.submit(vec![signal(&markets, 4.0, 19.0, true)], 0, &mut runner, &mut monitor).unwrap();
+    trader.on_prices(&[("SOL/USDC".to_string(), 20.0)], 1_001, 1_001, &mut runner, &mut monitor).unwrap();
+    assert_eq!(trader.exchange.resting_orders().len(), 1);
+
+    // Покупке 1 SOL хватает остатка, и пока она в пути, вторая такая же отклоняется
+    trader.submit(vec![buy(&markets, 1.0, 20.1)], 1_001, &mut runner, &mut monitor).unwrap();
+    assert_eq!(trader.exchange.pending_bundles().count(), 1);
+    trader.submit(vec![buy(&markets, 1.0, 20.1)], 1_001, &mut runner, &mut monitor).unwrap();
+    assert_eq!(trader.exchange.pending_bundles().count(), 1);
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+use std::error::Error;
+
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::account_stream::AccountUpdate;
+use crate::dex::orca::OrcaWhirlpool;
+use crate::dex::raydium::{decode_amm_info, RaydiumPool};
+use crate::dex::AmmPool;
+use crate::jito_integration::Venue;
+use crate::market_registry::{MarketInfo, MarketRegistry, PoolInfo};
+use crate::price_feed::TokenPair;
+
+// Состояние AMM-пула на момент тика вместе с площадкой для сигналов
+#[derive(Debug, Clone)]
+pub struct PoolSnapshot {
+    pub venue: Venue,
+    pub pool: AmmPool,
+    // Слот последнего обновления аккаунтов пула
+    pub slot: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum TrackedAccount {
+    // AmmInfo Raydium: из него же берутся адреса хранилищ
+    RaydiumAmm(usize),
+    Vault(usize),
+    Whirlpool(usize),
+    TickArray(usize),
+}
+
+struct TrackedPool {
+    pair: TokenPair,
+    name: String,
+    info: PoolInfo,
+    base_decimals: u8,
+    quote_decimals: u8,
+    // Хранилища coin и pc пула Raydium, известные после первого AmmInfo
+    vaults: Option<(Pubkey, Pubkey)>,
+    // Последнее содержимое аккаунтов пула
+    data: HashMap<Pubkey, Vec<u8>>,
+    slot: u64,
+    pool: Option<AmmPool>,
+}
+
+impl TrackedPool {
+    // Пересобирает состояние пула, когда получены все нужные аккаунты
+    fn rebuild(&mut self) -> Result<(), Box<dyn Error>> {
+        self.pool = match &self.info {
+            PoolInfo::Raydium { amm } => {
+                let (coin_vault, pc_vault) = match self.vaults {
+                    Some(vaults) => vaults,
+                    None => return Ok(()),
+                };
+                match (self.data.get(amm), self.data.get(&coin_vault), self.data.get(&pc_vault)) {
+                    (Some(amm_data), Some(coin_data), Some(pc_data)) => Some(AmmPool::Raydium(RaydiumPool::from_accounts(
+                        &self.name,
+                        amm_data,
+                        coin_data,
+                        pc_data,
+                        self.base_decimals,
+                        self.quote_decimals,
+                    )?)),
+                    _ => None,
+                }
+            }
+            PoolInfo::Orca { whirlpool, tick_arrays, base_is_a } => {
+                let whirlpool_data = match self.data.get(whirlpool) {
+                    Some(data) => data,
+                    None => return Ok(()),
+                };
+                let tick_array_data: Vec<Vec<u8>> = tick_arrays.iter()
+                    .filter_map(|account| self.data.get(account).cloned())
+                    .collect();
+                let (decimals_a, decimals_b) = if *base_is_a {
+                    (self.base_decimals, self.quote_decimals)
+                } else {
+                    (self.quote_decimals, self.base_decimals)
+                };
+                Some(AmmPool::Orca(OrcaWhirlpool::from_accounts(
+                    &self.name,
+                    whirlpool_data,
+                    &tick_array_data,
+                    *base_is_a,
+                    decimals_a,
+                    decimals_b,
+                )?))
+            }
+        };
+        Ok(())
+    }
+}
+
+// Локальное зеркало AMM-пулов из реестра. Как и стаканы, обновляется
+// уведомлениями об изменении аккаунтов пулов.
+pub struct PoolMirror {
+    pools: Vec<TrackedPool>,
+    accounts: HashMap<Pubkey, TrackedAccount>,
+}
+
+impl PoolMirror {
+    pub fn new() -> Self {
+        Self {
+            pools: Vec::new(),
+            accounts: HashMap::new(),
+        }
+    }
+
+    pub fn track_pool(&mut self, market: &MarketInfo, info: &PoolInfo) {
+        let index = self.pools.len();
+        match info {
+            PoolInfo::Raydium { amm } => {
+                self.accounts.insert(*amm, TrackedAccount::RaydiumAmm(index));
+            }
+            PoolInfo::Orca { whirlpool, tick_arrays, .. } => {
+                self.accounts.insert(*whirlpool, TrackedAccount::Whirlpool(index));
+                for tick_array in tick_arrays {
+                    self.accounts.insert(*tick_array, TrackedAccount::TickArray(index));
+                }
+            }
+        }
+        self.pools.push(TrackedPool {
+            pair: market.pair.clone(),
+            name: info.name(&market.pair),
+            info: info.clone(),
+            base_decimals: market.base_decimals,
+            quote_decimals: market.quote_decimals,
+            vaults: None,
+            data: HashMap::new(),
+            slot: 0,
+            pool: None,
+        });
+    }
+
+    // Подключает пулы реестра; хранилища Raydium станут известны из AmmInfo
+    pub fn watch_registry(&mut self, registry: &MarketRegistry) {
+        for market in registry.markets() {
+            for pool in &market.pools {
+                self.track_pool(market, pool);
+            }
+        }
+    }
+
+    // Подключает пулы реестра и читает текущее содержимое их аккаунтов.
+    // Возвращает прочитанные аккаунты, чтобы их можно было записать.
+    pub fn track_registry(&mut self, registry: &MarketRegistry, rpc_client: &RpcClient) -> Result<Vec<AccountUpdate>, Box<dyn Error>> {
+        self.watch_registry(registry);
+
+        let slot = rpc_client.get_slot()?;
+        // Сначала AmmInfo: после них известны адреса хранилищ
+        let amms: Vec<Pubkey> = self.accounts.iter()
+            .filter(|(_, tracked)| matches!(tracked, TrackedAccount::RaydiumAmm(_)))
+            .map(|(account, _)| *account)
+            .collect();
+        let mut updates = self.fetch(rpc_client, &amms, slot)?;
+        let rest: Vec<Pubkey> = self.tracked_accounts()
+            .into_iter()
+            .filter(|account| !amms.contains(account))
+            .collect();
+        updates.extend(self.fetch(rpc_client, &rest, slot)?);
+        Ok(updates)
+    }
+
+    fn fetch(&mut self, rpc_client: &RpcClient, keys: &[Pubkey], slot: u64) -> Result<Vec<AccountUpdate>, Box<dyn Error>> {
+        let mut updates = Vec::new();
+        for (key, account) in keys.iter().zip(rpc_client.get_multiple_accounts(keys)?) {
+            if let Some(account) = account {
+                self.on_account_update(key, &account.data, slot)?;
+                updates.push(AccountUpdate {
+                    pubkey: *key,
+                    slot,
+                    data: account.data,
+                });
+            }
+        }
+        Ok(updates)
+    }
+
+    // Все аккаунты, на изменения которых нужно подписаться
+    pub fn tracked_accounts(&self) -> Vec<Pubkey> {
+        self.accounts.keys().copied().collect()
+    }
+
+    pub fn is_tracked(&self, account: &Pubkey) -> bool {
+        self.accounts.contains_key(account)
+    }
+
+    // Пулы с известным состоянием по ключу торговой пары для передачи стратегиям
+    pub fn snapshot(&self) -> HashMap<String, Vec<PoolSnapshot>> {
+        let mut snapshot: HashMap<String, Vec<PoolSnapshot>> = HashMap::new();
+        for tracked in &self.pools {
+            if let Some(pool) = &tracked.pool {
+                snapshot.entry(tracked.pair.to_string()).or_default().push(PoolSnapshot {
+                    venue: tracked.info.venue(),
+                    pool: pool.clone(),
+                    slot: tracked.slot,
+                });
+            }
+        }
+        snapshot
+    }
+
+    pub fn on_account_update(&mut self, account: &Pubkey, data: &[u8], slot: u64) -> Result<(), Box<dyn Error>> {
+        let tracked = *self.accounts.get(account)
+            .ok_or_else(|| format!("Account {} is not tracked", account))?;
+        let index = match tracked {
+            TrackedAccount::RaydiumAmm(index) => {
+                // Хранилища пула меняются только при его пересоздании
+                if self.pools[index].vaults.is_none() {
+                    let amm = decode_amm_info(data)?;
+                    self.pools[index].vaults = Some((amm.coin_vault, amm.pc_vault));
+                    self.accounts.insert(amm.coin_vault, TrackedAccount::Vault(index));
+                    self.accounts.insert(amm.pc_vault, TrackedAccount::Vault(index));
+                }
+                index
+            }
+            TrackedAccount::Vault(index) | TrackedAccount::Whirlpool(index) | TrackedAccount::TickArray(index) => index,
+        };
+
+        let pool = &mut self.pools[index];
+        pool.data.insert(*account, data.to_vec());
+        pool.slot = pool.slot.max(slot);
+        pool.rebuild()
+    }
+}
+
+#[cfg(test)]
+#[path = "pools_test.rs"]
+mod tests;
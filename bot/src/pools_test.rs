@@ -0,0 +1,68 @@
+use super::*;
+use crate::dex::LiquiditySource;
+use crate::jito_integration::TradeAction;
+
+const AMM: &str = "58oQChx4yWmvKdwLLZzBi4ChoCc2fqCUWBkwMihLYQo2";
+
+fn registry() -> MarketRegistry {
+    MarketRegistry::from_json(&format!(r#"{{
+        "markets": [{{
+            "base": "SOL",
+            "quote": "USDC",
+            "base_mint": "So11111111111111111111111111111111111111112",
+            "quote_mint": "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+            "base_decimals": 9,
+            "quote_decimals": 6,
+            "dex_market": "9wFFyRfZBsuAha4YcuxcXLKwMxJR43S7fPfQLusDBzvT",
+            "base_lot_size": 100000000,
+            "quote_lot_size": 100,
+            "taker_fee_bps": 10,
+            "min_order_size": 0.1,
+            "pools": [{{ "kind": "raydium", "amm": "{}" }}]
+        }}]
+    }}"#, AMM)).unwrap()
+}
+
+fn amm_info(coin_vault: Pubkey, pc_vault: Pubkey) -> Vec<u8> {
+    let mut data = vec![0u8; 752];
+    data[176..184].copy_from_slice(&25u64.to_le_bytes());
+    data[184..192].copy_from_slice(&10_000u64.to_le_bytes());
+    data[336..368].copy_from_slice(&coin_vault.to_bytes());
+    data[368..400].copy_from_slice(&pc_vault.to_bytes());
+    data
+}
+
+fn token_account(amount: u64) -> Vec<u8> {
+    let mut data = vec![0u8; 165];
+    data[64..72].copy_from_slice(&amount.to_le_bytes());
+    data
+}
+
+#[test]
+fn test_raydium_pool_built_from_streamed_accounts() {
+    let mut mirror = PoolMirror::new();
+    mirror.watch_registry(&registry());
+    let amm: Pubkey = AMM.parse().unwrap();
+    let (coin_vault, pc_vault) = (Pubkey::new_from_array([2; 32]), Pubkey::new_from_array([3; 32]));
+
+    assert!(mirror.is_tracked(&amm));
+    assert!(!mirror.is_tracked(&coin_vault));
+
+    // Хранилища становятся известны из AmmInfo, пул появляется после обоих хранилищ
+    mirror.on_account_update(&amm, &amm_info(coin_vault, pc_vault), 10).unwrap();
+    assert!(mirror.is_tracked(&coin_vault) && mirror.is_tracked(&pc_vault));
+    mirror.on_account_update(&coin_vault, &token_account(1_000_000_000_000), 11).unwrap();
+    assert!(mirror.snapshot().is_empty());
+    mirror.on_account_update(&pc_vault, &token_account(100_000_000_000), 12).unwrap();
+
+    let snapshot = mirror.snapshot();
+    let pools = &snapshot["SOL/USDC"];
+    assert_eq!(pools.len(), 1);
+    assert_eq!(pools[0].venue, Venue::Raydium { amm });
+    assert_eq!(pools[0].slot, 12);
+    // 1000 SOL и 100 000 USDC: 1 SOL продается чуть дешевле 100 USDC
+    let out = pools[0].pool.quote(TradeAction::Sell, 1.0).unwrap();
+    assert!(out > 99.0 && out < 100.0, "{}", out);
+
+    assert!(mirror.on_account_update(&Pubkey::new_from_array([4; 32]), &[], 13).is_err());
+}
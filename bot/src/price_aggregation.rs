@@ -0,0 +1,138 @@
+use std::env;
+use std::error::Error;
+use std::str::FromStr;
+
+// Откуда получена цена
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PriceSource {
+    Pyth,
+    Switchboard,
+    DexMid,
+}
+
+// Цена одного источника в UI-единицах
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SourcePrice {
+    pub source: PriceSource,
+    pub price: f64,
+    pub confidence: f64,
+}
+
+// Как сводить несколько источников в одну цену
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AggregationPolicy {
+    Median,
+    // Среднее с весами, обратными квадрату доверительного интервала
+    ConfidenceWeighted,
+    // Первый доступный источник в порядке приоритета
+    PrimaryWithFallback,
+}
+
+impl FromStr for AggregationPolicy {
+    type Err = Box<dyn Error>;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "median" => Ok(AggregationPolicy::Median),
+            "confidence_weighted" => Ok(AggregationPolicy::ConfidenceWeighted),
+            "primary" => Ok(AggregationPolicy::PrimaryWithFallback),
+            _ => Err(format!("Unknown price aggregation policy '{}'", value).into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AggregationConfig {
+    pub policy: AggregationPolicy,
+    // Максимальное расхождение источника с итоговой ценой в б.п.
+    pub max_divergence_bps: f64,
+}
+
+impl AggregationConfig {
+    pub fn from_env() -> Result<Self, Box<dyn Error>> {
+        let policy = match env::var("PRICE_AGGREGATION_POLICY") {
+            Ok(value) => value.parse()?,
+            Err(_) => AggregationPolicy::Median,
+        };
+
+        Ok(Self {
+            policy,
+            max_divergence_bps: env::var("PRICE_MAX_DIVERGENCE_BPS")
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(50.0),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AggregatedPrice {
+    pub price: f64,
+    pub confidence: f64,
+    pub sources: Vec<SourcePrice>,
+    // Наибольшее отклонение источника от итоговой цены в б.п.
+    pub divergence_bps: f64,
+}
+
+impl AggregatedPrice {
+    pub fn is_diverged(&self, config: &AggregationConfig) -> bool {
+        self.divergence_bps > config.max_divergence_bps
+    }
+}
+
+// Сводит цены источников (в порядке приоритета) по политике из конфигурации
+pub fn aggregate(sources: &[SourcePrice], policy: AggregationPolicy) -> Option<AggregatedPrice> {
+    let sources: Vec<SourcePrice> = sources.iter()
+        .filter(|source| source.price.is_finite() && source.price > 0.0)
+        .copied()
+        .collect();
+    let primary = *sources.first()?;
+
+    let (price, confidence) = match policy {
+        AggregationPolicy::Median => (
+            median(sources.iter().map(|source| source.price).collect()),
+            median(sources.iter().map(|source| source.confidence).collect()),
+        ),
+        AggregationPolicy::ConfidenceWeighted => confidence_weighted(&sources),
+        AggregationPolicy::PrimaryWithFallback => (primary.price, primary.confidence),
+    };
+
+    let divergence_bps = sources.iter()
+        .map(|source| (source.price - price).abs() / price * 10_000.0)
+        .fold(0.0, f64::max);
+
+    Some(AggregatedPrice {
+        price,
+        confidence,
+        sources,
+        divergence_bps,
+    })
+}
+
+fn median(mut values: Vec<f64>) -> f64 {
+    values.sort_by(|a, b| a.total_cmp(b));
+    // Для нечетной длины оба индекса совпадают
+    let lower = (values.len() - 1) / 2;
+    let upper = values.len() / 2;
+    (values[lower] + values[upper]) / 2.0
+}
+
+fn confidence_weighted(sources: &[SourcePrice]) -> (f64, f64) {
+    // Нулевой интервал не должен давать бесконечный вес
+    const MIN_CONFIDENCE: f64 = 1e-12;
+
+    let weights: Vec<f64> = sources.iter()
+        .map(|source| 1.0 / source.confidence.max(MIN_CONFIDENCE).powi(2))
+        .collect();
+    let total: f64 = weights.iter().sum();
+    let price = sources.iter()
+        .zip(&weights)
+        .map(|(source, weight)| source.price * weight)
+        .sum::<f64>() / total;
+
+    (price, (1.0 / total).sqrt())
+}
+
+#[cfg(test)]
+#[path = "price_aggregation_test.rs"]
+mod tests;
@@ -0,0 +1,56 @@
+use super::*;
+
+fn source(source: PriceSource, price: f64, confidence: f64) -> SourcePrice {
+    SourcePrice { source, price, confidence }
+}
+
+fn sources() -> Vec<SourcePrice> {
+    vec![
+        source(PriceSource::Pyth, 20.0, 0.01),
+        source(PriceSource::Switchboard, 20.2, 0.04),
+        source(PriceSource::DexMid, 20.1, 0.02),
+    ]
+}
+
+#[test]
+fn test_median_policy() {
+    let aggregated = aggregate(&sources(), AggregationPolicy::Median).unwrap();
+    assert!((aggregated.price - 20.1).abs() < 1e-9);
+    assert!((aggregated.confidence - 0.02).abs() < 1e-9);
+    // 20.0 и 20.2 отклоняются от 20.1 примерно на 50 б.п.
+    assert!((aggregated.divergence_bps - 0.1 / 20.1 * 10_000.0).abs() < 1e-6);
+}
+
+#[test]
+fn test_confidence_weighted_policy_prefers_tight_sources() {
+    let aggregated = aggregate(&sources(), AggregationPolicy::ConfidenceWeighted).unwrap();
+    // Веса 1/conf^2: 10000, 625, 2500
+    let expected = (20.0 * 10_000.0 + 20.2 * 625.0 + 20.1 * 2_500.0) / 13_125.0;
+    assert!((aggregated.price - expected).abs() < 1e-9);
+    assert!(aggregated.confidence < 0.01);
+}
+
+#[test]
+fn test_primary_policy_falls_back_to_next_source() {
+    let aggregated = aggregate(&sources(), AggregationPolicy::PrimaryWithFallback).unwrap();
+    assert_eq!(aggregated.price, 20.0);
+
+    let without_pyth = &sources()[1..];
+    let aggregated = aggregate(without_pyth, AggregationPolicy::PrimaryWithFallback).unwrap();
+    assert_eq!(aggregated.price, 20.2);
+    assert!(aggregate(&[], AggregationPolicy::PrimaryWithFallback).is_none());
+}
+
+#[test]
+fn test_divergence_flag() {
+    let config = AggregationConfig {
+        policy: AggregationPolicy::Median,
+        max_divergence_bps: 50.0,
+    };
+    let mut prices = sources();
+    assert!(!aggregate(&prices, config.policy).unwrap().is_diverged(&config));
+
+    prices[1].price = 21.0;
+    let aggregated = aggregate(&prices, config.policy).unwrap();
+    assert!(aggregated.is_diverged(&config));
+}
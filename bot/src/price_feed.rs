@@ -1,14 +1,77 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::env;
 use std::error::Error;
-use pyth_sdk_solana::PriceAccount;
-use solana_client::rpc_client::RpcClient;
+use std::fmt;
+use log::warn;
+use pyth_sdk_solana::state::{load_price_account, PriceStatus};
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use crate::candles::{Candle, CandleBuilder, CandleSeries, Timeframe};
+use crate::market_registry::{MarketRegistry, OracleFeed, OracleKind, Stablecoin};
+use crate::orderbook::{L2Book, MarketFill};
+use crate::pools::PoolSnapshot;
+use crate::price_aggregation::{self, AggregationConfig, PriceSource, SourcePrice};
+use crate::price_history::{PriceHistory, PricePoint};
+use crate::pricing_graph::{PricingGraph, Rate};
+use crate::switchboard;
+
+// Сколько точек на пару хранится между тиками
+const HISTORY_CAPACITY: usize = 1000;
+// Окно, за которое считается объем торгов
+const VOLUME_WINDOW_SECS: i64 = 24 * 60 * 60;
 
 pub struct PriceFeed {
-    rpc_client: RpcClient,
-    price_accounts: HashMap<String, Pubkey>,
+    // Торгуемые рынки, цены которых выводятся из фидов и стаканов
+    markets: Vec<TokenPair>,
+    feeds: Vec<OracleFeed>,
+    stablecoins: Vec<Stablecoin>,
+    oracle_kinds: HashMap<Pubkey, OracleKind>,
+    validation: PriceValidationConfig,
+    aggregation: AggregationConfig,
+    // Последние данные оракулов из потока обновлений аккаунтов
+    quotes: HashMap<Pubkey, OracleQuote>,
+    last_slot: u64,
+    history: HashMap<String, PriceHistory>,
+    // Объем сделок, накопленный с последнего тика
+    pending_volume: HashMap<String, f64>,
+    candles: CandleBuilder,
+}
+
+// Цена оракула в момент последнего обновления аккаунта
+#[derive(Debug, Clone, Copy)]
+struct OracleQuote {
+    source: PriceSource,
+    price: f64,
+    confidence: f64,
+    // Статус есть только у Pyth
+    status: Option<PriceStatus>,
+    publish_slot: u64,
+    publish_time: i64,
 }
 
+// Ограничения на свежесть цены Pyth
 #[derive(Debug, Clone)]
+pub struct PriceValidationConfig {
+    pub max_age_slots: u64,
+    pub max_age_secs: i64,
+}
+
+impl PriceValidationConfig {
+    pub fn from_env() -> Self {
+        Self {
+            max_age_slots: env::var("PYTH_MAX_AGE_SLOTS")
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(25),
+            max_age_secs: env::var("PYTH_MAX_AGE_SECS")
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(10),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TokenPair {
     base: String,
     quote: String,
@@ -21,70 +84,454 @@ impl TokenPair {
             quote: quote.to_string(),
         }
     }
+
+    pub fn base(&self) -> &str {
+        &self.base
+    }
+
+    pub fn quote(&self) -> &str {
+        &self.quote
+    }
 }
 
+impl fmt::Display for TokenPair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.base, self.quote)
+    }
+}
+
+#[derive(Default)]
 pub struct PriceData {
     prices: HashMap<String, f64>,
-    history: HashMap<String, Vec<f64>>,
+    confidences: HashMap<String, f64>,
+    // Пары, источники цены которых разошлись сильнее допустимого
+    // или котируемые в отвязавшемся стейблкоине
+    no_trade: HashSet<String>,
+    history: HashMap<String, PriceHistory>,
+    candles: HashMap<String, HashMap<Timeframe, CandleSeries>>,
+    order_books: HashMap<String, L2Book>,
+    pools: HashMap<String, Vec<PoolSnapshot>>,
+    // Последний слот обновлений оракулов на момент снимка
+    slot: u64,
 }
 
 impl PriceFeed {
-    pub async fn new() -> Result<Self, Box<dyn Error>> {
-        let rpc_client = RpcClient::new("https://api.devnet.solana.com".to_string());
-        
-        // Инициализация известных Pyth price accounts
-        let mut price_accounts = HashMap::new();
-        price_accounts.insert(
-            "SOL/USD".to_string(),
-            "H6ARHf6YXhGYeQfUzQNGk6rDNnLBQKrenN712K4AQJEG".parse()?,
-        );
+    pub async fn new(registry: &MarketRegistry) -> Result<Self, Box<dyn Error>> {
+        // Аккаунты оракулов (Pyth и Switchboard) берутся из фидов реестра
+        let oracle_kinds = registry.feeds().iter()
+            .flat_map(|feed| feed.oracles.iter())
+            .map(|oracle| (oracle.account, oracle.kind))
+            .collect();
         
         Ok(Self {
-            rpc_client,
-            price_accounts,
+            markets: registry.pairs(),
+            feeds: registry.feeds().to_vec(),
+            stablecoins: registry.stablecoins().to_vec(),
+            oracle_kinds,
+            validation: PriceValidationConfig::from_env(),
+            aggregation: AggregationConfig::from_env()?,
+            quotes: HashMap::new(),
+            last_slot: 0,
+            history: HashMap::new(),
+            pending_volume: HashMap::new(),
+            candles: CandleBuilder::new(),
         })
     }
 
-    pub async fn get_latest_prices(&self) -> Result<PriceData, Box<dyn Error>> {
+    // Учитывает сделки на DEX в объеме следующей точки истории и в барах.
+    // `now` - Unix-время в секундах, которое задает источник событий.
+    pub fn record_fills(&mut self, fills: &[MarketFill], now: i64) {
+        // Сделка попадает в event queue дважды - считаем ее только по событию тейкера
+        for fill in fills.iter().filter(|fill| !fill.maker) {
+            let pair = fill.pair.to_string();
+            let volume = fill.price * fill.size;
+            *self.pending_volume.entry(pair.clone()).or_insert(0.0) += volume;
+            self.candles.on_fill(&pair, now, fill.price, volume);
+        }
+    }
+
+    // Аккаунты оракулов, на которые нужно подписаться
+    pub fn oracle_accounts(&self) -> Vec<Pubkey> {
+        self.oracle_kinds.keys().copied().collect()
+    }
+
+    // Обрабатывает новое содержимое аккаунта. Возвращает false, если аккаунт
+    // не относится к оракулам этого фида.
+    pub fn on_account_update(&mut self, account: &Pubkey, data: &[u8], slot: u64) -> Result<bool, Box<dyn Error>> {
+        let quote = match self.oracle_kinds.get(account) {
+            Some(OracleKind::Pyth) => {
+                let price_account = load_price_account(data)
+                    .map_err(|e| format!("Invalid Pyth account {}: {:?}", account, e))?;
+                let scale = 10f64.powi(price_account.expo);
+                OracleQuote {
+                    source: PriceSource::Pyth,
+                    price: price_account.agg.price as f64 * scale,
+                    confidence: price_account.agg.conf as f64 * scale,
+                    status: Some(price_account.agg.status),
+                    publish_slot: price_account.agg.pub_slot,
+                    publish_time: price_account.timestamp,
+                }
+            }
+            Some(OracleKind::Switchboard) => {
+                let round = switchboard::decode_aggregator(data)
+                    .map_err(|e| format!("Invalid Switchboard account {}: {}", account, e))?;
+                OracleQuote {
+                    source: PriceSource::Switchboard,
+                    price: round.price,
+                    confidence: round.std_deviation,
+                    status: None,
+                    publish_slot: round.round_open_slot,
+                    publish_time: round.round_open_timestamp,
+                }
+            }
+            None => return Ok(false),
+        };
+
+        self.quotes.insert(*account, quote);
+        self.last_slot = self.last_slot.max(slot);
+        Ok(true)
+    }
+
+    // Сводит цены оракулов и середину стакана DEX, дописывает их в историю
+    // и бары и возвращает срез данных для стратегий на момент `now` (Unix-время в секундах)
+    pub fn snapshot(&mut self, order_books: HashMap<String, L2Book>, pools: HashMap<String, Vec<PoolSnapshot>>, now: i64) -> PriceData {
         let mut prices = HashMap::new();
-        let mut history = HashMap::new();
+        let mut confidences = HashMap::new();
+        let mut no_trade = HashSet::new();
 
-        for (pair, account) in &self.price_accounts {
-            let data = self.rpc_client.get_account_data(account)?;
-            let price_account: PriceAccount = bytemuck::from_bytes(&data);
-            
-            let price = price_account.agg.price as f64 / 10f64.powi(price_account.expo);
-            prices.insert(pair.clone(), price);
+        let current_slot = self.last_slot;
+
+        // Цены фидов (SOL/USD, USDC/USD, ...) образуют граф курсов
+        let mut graph = PricingGraph::new();
+        let mut feed_sources = HashMap::new();
+        for feed in &self.feeds {
+            let key = feed.pair.to_string();
+            let sources: Vec<SourcePrice> = feed.oracles.iter()
+                .filter_map(|oracle| self.quotes.get(&oracle.account))
+                .filter(|quote| self.is_valid_quote(&key, quote, current_slot, now))
+                .map(|quote| SourcePrice {
+                    source: quote.source,
+                    price: quote.price,
+                    confidence: quote.confidence,
+                })
+                .collect();
+            if let Some(aggregated) = price_aggregation::aggregate(&sources, self.aggregation.policy) {
+                graph.add_rate(feed.pair.base(), feed.pair.quote(), Rate {
+                    price: aggregated.price,
+                    confidence: aggregated.confidence,
+                });
+            }
+            feed_sources.insert(key, sources);
+        }
+        let depegged = self.check_stablecoins(&mut graph);
+
+        for market in &self.markets {
+            let pair = market.to_string();
+            let mut sources = self.normalized_sources(market, &feed_sources, &graph);
+            if let Some(book) = order_books.get(&pair) {
+                if let Some(mid) = self.dex_mid_price(book, current_slot) {
+                    sources.push(mid);
+                }
+            }
+
+            let aggregated = match price_aggregation::aggregate(&sources, self.aggregation.policy) {
+                Some(aggregated) => aggregated,
+                None => continue,
+            };
+            // Источники не согласны между собой - по паре не торгуем
+            if aggregated.is_diverged(&self.aggregation) {
+                warn!(
+                    "No trade on {}: price sources diverge by {:.1} bps ({:?})",
+                    pair, aggregated.divergence_bps, aggregated.sources
+                );
+                no_trade.insert(pair.clone());
+            }
+            if depegged.contains(market.base()) || depegged.contains(market.quote()) {
+                no_trade.insert(pair.clone());
+            }
+
+            prices.insert(pair.clone(), aggregated.price);
+            confidences.insert(pair.clone(), aggregated.confidence);
             
-            // Сохраняем историю цен
-            history.entry(pair.clone())
-                .or_insert_with(Vec::new)
-                .push(price);
+            // Сохраняем историю цен между тиками
+            self.history.entry(pair.clone())
+                .or_insert_with(|| PriceHistory::new(HISTORY_CAPACITY))
+                .push(PricePoint {
+                    price: aggregated.price,
+                    confidence: aggregated.confidence,
+                    slot: current_slot,
+                    timestamp: now,
+                    volume: self.pending_volume.remove(&pair).unwrap_or(0.0),
+                });
+            self.candles.on_tick(&pair, now, aggregated.price);
+        }
+        self.candles.advance(now);
+
+        PriceData {
+            prices,
+            confidences,
+            no_trade,
+            history: self.history.clone(),
+            candles: self.candles.snapshot(),
+            order_books,
+            pools,
+            slot: current_slot,
         }
+    }
 
-        Ok(PriceData { prices, history })
+    // Цены оракулов по базовому токену рынка, пересчитанные в его котируемый токен.
+    // Например, Pyth SOL/USD для рынка SOL/USDC делится на курс USDC/USD.
+    fn normalized_sources(
+        &self,
+        market: &TokenPair,
+        feed_sources: &HashMap<String, Vec<SourcePrice>>,
+        graph: &PricingGraph,
+    ) -> Vec<SourcePrice> {
+        let mut normalized = Vec::new();
+        for feed in self.feeds.iter().filter(|feed| feed.pair.base() == market.base()) {
+            let conversion = match graph.rate(feed.pair.quote(), market.quote()) {
+                Some(conversion) => conversion,
+                None => continue,
+            };
+            for source in &feed_sources[&feed.pair.to_string()] {
+                let price = source.price * conversion.price;
+                normalized.push(SourcePrice {
+                    source: source.source,
+                    price,
+                    confidence: price * (source.confidence / source.price + conversion.confidence / conversion.price),
+                });
+            }
+        }
+        normalized
+    }
+
+    // Стейблкоин без собственного фида считается равным привязке. Если фид есть,
+    // проверяем отвязку; возвращает символы отвязавшихся стейблкоинов.
+    fn check_stablecoins(&self, graph: &mut PricingGraph) -> HashSet<String> {
+        let mut depegged = HashSet::new();
+        for stablecoin in &self.stablecoins {
+            match graph.rate(&stablecoin.symbol, &stablecoin.peg) {
+                Some(rate) => {
+                    let deviation_bps = (rate.price - 1.0).abs() * 10_000.0;
+                    if deviation_bps > stablecoin.max_depeg_bps {
+                        warn!(
+                            "{} is depegged from {}: {:.4} ({:.1} bps)",
+                            stablecoin.symbol, stablecoin.peg, rate.price, deviation_bps
+                        );
+                        depegged.insert(stablecoin.symbol.clone());
+                    }
+                }
+                None => graph.add_rate(&stablecoin.symbol, &stablecoin.peg, Rate::identity()),
+            }
+        }
+        depegged
+    }
+
+    // Цена без статуса Trading или устаревшая в торговлю не идет
+    fn is_valid_quote(&self, pair: &str, quote: &OracleQuote, current_slot: u64, now: i64) -> bool {
+        if let Some(status) = quote.status {
+            if status != PriceStatus::Trading {
+                warn!("Skipping {:?} price for {}: status is {:?}", quote.source, pair, status);
+                return false;
+            }
+        }
+        let age_slots = current_slot.saturating_sub(quote.publish_slot);
+        let age_secs = now - quote.publish_time;
+        if age_slots > self.validation.max_age_slots || age_secs > self.validation.max_age_secs {
+            warn!("Skipping {:?} price for {}: stale ({} slots, {}s)", quote.source, pair, age_slots, age_secs);
+            return false;
+        }
+        true
+    }
+
+    // Середина стакана; доверительным интервалом служит половина спреда
+    fn dex_mid_price(&self, book: &L2Book, current_slot: u64) -> Option<SourcePrice> {
+        if current_slot.saturating_sub(book.last_update_slot()) > self.validation.max_age_slots {
+            return None;
+        }
+        Some(SourcePrice {
+            source: PriceSource::DexMid,
+            price: book.mid_price()?,
+            confidence: book.spread()? / 2.0,
+        })
     }
 }
 
+#[cfg(test)]
 impl PriceData {
+    // Снимок с ценами и нулевыми доверительными интервалами - для тестов стратегий
+    pub fn with_prices(prices: &[(&str, f64)]) -> Self {
+        Self {
+            prices: prices.iter().map(|&(pair, price)| (pair.to_string(), price)).collect(),
+            confidences: prices.iter().map(|&(pair, _)| (pair.to_string(), 0.0)).collect(),
+            ..Self::default()
+        }
+    }
+
+    pub fn set_confidence(&mut self, pair: &str, confidence: f64) {
+        self.confidences.insert(pair.to_string(), confidence);
+    }
+
+    pub fn set_candles(&mut self, candles: &CandleBuilder) {
+        self.candles = candles.snapshot();
+    }
+
+    pub fn set_order_book(&mut self, pair: &str, book: L2Book) {
+        self.order_books.insert(pair.to_string(), book);
+    }
+
+    pub fn set_pools(&mut self, pair: &str, pools: Vec<PoolSnapshot>) {
+        self.pools.insert(pair.to_string(), pools);
+    }
+
+    pub fn set_slot(&mut self, slot: u64) {
+        self.slot = slot;
+    }
+}
+
+impl PriceData {
+    // Снимок без оракулов: цены пар заданы напрямую, например при бэктесте
+    pub fn simulated(
+        prices: HashMap<String, f64>,
+        history: HashMap<String, PriceHistory>,
+        candles: &CandleBuilder,
+        order_books: HashMap<String, L2Book>,
+        slot: u64,
+    ) -> Self {
+        Self {
+            confidences: prices.keys().map(|pair| (pair.clone(), 0.0)).collect(),
+            prices,
+            history,
+            candles: candles.snapshot(),
+            order_books,
+            slot,
+            ..Self::default()
+        }
+    }
+
+    pub fn slot(&self) -> u64 {
+        self.slot
+    }
+
+    pub fn get_order_book(&self, pair: &TokenPair) -> Option<&L2Book> {
+        self.order_books.get(&pair.to_string())
+    }
+
+    // AMM-пулы пары с известным состоянием
+    pub fn get_pools(&self, pair: &TokenPair) -> &[PoolSnapshot] {
+        self.pools.get(&pair.to_string()).map(|pools| pools.as_slice()).unwrap_or(&[])
+    }
+
     pub fn get_price(&self, pair: &TokenPair) -> Result<f64, Box<dyn Error>> {
-        let key = format!("{}/{}", pair.base, pair.quote);
-        self.prices.get(&key)
+        self.prices.get(&pair.to_string())
             .copied()
             .ok_or_else(|| "Price not found".into())
     }
 
-    pub fn get_average_price(&self, pair: &TokenPair, periods: usize) -> Result<f64, Box<dyn Error>> {
-        let key = format!("{}/{}", pair.base, pair.quote);
-        let history = self.history.get(&key)
-            .ok_or_else(|| "Price history not found".into())?;
-        
-        let recent_prices = history.iter()
-            .rev()
-            .take(periods)
+    // Цены всех пар в порядке ключей
+    pub fn prices(&self) -> Vec<(String, f64)> {
+        let mut prices: Vec<_> = self.prices.iter().map(|(pair, &price)| (pair.clone(), price)).collect();
+        prices.sort_by(|a, b| a.0.cmp(&b.0));
+        prices
+    }
+
+    pub fn has_price(&self, pair: &TokenPair) -> bool {
+        self.prices.contains_key(&pair.to_string())
+    }
+
+    // false, если источники цены пары разошлись и торговать по ней нельзя
+    pub fn is_tradable(&self, pair: &TokenPair) -> bool {
+        self.has_price(pair) && !self.no_trade.contains(&pair.to_string())
+    }
+
+    // Доверительный интервал Pyth в тех же единицах, что и цена
+    pub fn get_confidence(&self, pair: &TokenPair) -> Result<f64, Box<dyn Error>> {
+        self.confidences.get(&pair.to_string())
             .copied()
-            .collect::<Vec<_>>();
+            .ok_or_else(|| "Price confidence not found".into())
+    }
+
+    // Отношение доверительного интервала к цене; чем больше, тем менее надежна цена
+    pub fn get_confidence_ratio(&self, pair: &TokenPair) -> Result<f64, Box<dyn Error>> {
+        Ok(self.get_confidence(pair)? / self.get_price(pair)?)
+    }
+
+    pub fn get_average_price(&self, pair: &TokenPair, periods: usize) -> Result<f64, Box<dyn Error>> {
+        let recent_prices = self.get_price_history(pair, periods)?;
+        if recent_prices.is_empty() {
+            return Err("Price history is empty".into());
+        }
             
         Ok(recent_prices.iter().sum::<f64>() / recent_prices.len() as f64)
     }
-} 
\ No newline at end of file
+
+    // Последние `periods` цен в хронологическом порядке
+    pub fn get_price_history(&self, pair: &TokenPair, periods: usize) -> Result<Vec<f64>, Box<dyn Error>> {
+        Ok(self.recent_points(pair, periods)?.iter().map(|point| point.price).collect())
+    }
+
+    pub fn get_points(&self, pair: &TokenPair) -> Result<&[PricePoint], Box<dyn Error>> {
+        self.history.get(&pair.to_string())
+            .map(|history| history.as_slice())
+            .ok_or_else(|| "Price history not found".into())
+    }
+
+    // Объем торгов на DEX за последние сутки в котируемом токене
+    pub fn get_volume(&self, pair: &TokenPair) -> Result<f64, Box<dyn Error>> {
+        let points = self.get_points(pair)?;
+        let since = match points.last() {
+            Some(last) => last.timestamp - VOLUME_WINDOW_SECS,
+            None => return Ok(0.0),
+        };
+
+        Ok(points.iter()
+            .filter(|point| point.timestamp > since)
+            .map(|point| point.volume)
+            .sum())
+    }
+
+    // Закрытые бары таймфрейма, самые старые первыми
+    pub fn get_candles(&self, pair: &TokenPair, timeframe: Timeframe) -> Result<Vec<Candle>, Box<dyn Error>> {
+        Ok(self.candle_series(pair, timeframe)?.closed().iter().copied().collect())
+    }
+
+    // Закрытые бары, открытые позже `after`, самые старые первыми. Позволяет
+    // индикаторам обрабатывать только новые бары.
+    pub fn get_candles_after(&self, pair: &TokenPair, timeframe: Timeframe, after: Option<i64>) -> Result<Vec<Candle>, Box<dyn Error>> {
+        let closed = self.candle_series(pair, timeframe)?.closed();
+        let new = match after {
+            Some(after) => closed.iter().rev().take_while(|candle| candle.open_time > after).count(),
+            None => closed.len(),
+        };
+        Ok(closed.iter().skip(closed.len() - new).copied().collect())
+    }
+
+    // Текущий незакрытый бар
+    pub fn get_current_candle(&self, pair: &TokenPair, timeframe: Timeframe) -> Option<Candle> {
+        self.candle_series(pair, timeframe).ok()?.current().copied()
+    }
+
+    // Цены закрытия последних `count` закрытых баров
+    pub fn get_closes(&self, pair: &TokenPair, timeframe: Timeframe, count: usize) -> Result<Vec<f64>, Box<dyn Error>> {
+        let closed = self.candle_series(pair, timeframe)?.closed();
+        Ok(closed.iter()
+            .skip(closed.len().saturating_sub(count))
+            .map(|candle| candle.close)
+            .collect())
+    }
+
+    fn candle_series(&self, pair: &TokenPair, timeframe: Timeframe) -> Result<&CandleSeries, Box<dyn Error>> {
+        self.candles.get(&pair.to_string())
+            .and_then(|by_timeframe| by_timeframe.get(&timeframe))
+            .ok_or_else(|| "Candles not found".into())
+    }
+
+    fn recent_points(&self, pair: &TokenPair, periods: usize) -> Result<&[PricePoint], Box<dyn Error>> {
+        let points = self.get_points(pair)?;
+        Ok(&points[points.len().saturating_sub(periods)..])
+    }
+} 
+
+#[cfg(test)]
+#[path = "price_feed_test.rs"]
+mod tests;
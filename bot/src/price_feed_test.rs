@@ -0,0 +1,96 @@
+use super::*;
+use crate::market_registry::OracleAccount;
+use crate::dex::serum::SerumMarketParams;
+use crate::price_aggregation::AggregationPolicy;
+
+const NOW: i64 = 1_700_000_000;
+const SLOT: u64 = 1_000;
+
+// Фид SOL/USD из одного оракула Pyth и рынок SOL/USDC; USDC без фида считается равным USD
+fn feed(pyth: Pubkey) -> PriceFeed {
+    let sol_usd = TokenPair::new("SOL", "USD");
+    PriceFeed {
+        markets: vec![TokenPair::new("SOL", "USDC")],
+        feeds: vec![OracleFeed {
+            pair: sol_usd,
+            oracles: vec![OracleAccount { kind: OracleKind::Pyth, account: pyth }],
+        }],
+        stablecoins: vec![Stablecoin { symbol: "USDC".to_string(), peg: "USD".to_string(), max_depeg_bps: 100.0 }],
+        oracle_kinds: HashMap::from([(pyth, OracleKind::Pyth)]),
+        validation: PriceValidationConfig { max_age_slots: 25, max_age_secs: 10 },
+        aggregation: AggregationConfig { policy: AggregationPolicy::Median, max_divergence_bps: 50.0 },
+        quotes: HashMap::new(),
+        last_slot: SLOT,
+        history: HashMap::new(),
+        pending_volume: HashMap::new(),
+        candles: CandleBuilder::new(),
+    }
+}
+
+fn pyth_quote(status: PriceStatus, confidence: f64, publish_slot: u64, publish_time: i64) -> OracleQuote {
+    OracleQuote {
+        source: PriceSource::Pyth,
+        price: 20.0,
+        confidence,
+        status: Some(status),
+        publish_slot,
+        publish_time,
+    }
+}
+
+// Цена SOL/USDC в снимке при единственной котировке Pyth
+fn snapshot_with(quote: OracleQuote) -> PriceData {
+    let pyth = Pubkey::new_unique();
+    let mut feed = feed(pyth);
+    feed.quotes.insert(pyth, quote);
+    feed.snapshot(HashMap::new(), HashMap::new(), NOW)
+}
+
+#[test]
+fn test_fresh_trading_quote_is_used() {
+    let price_data = snapshot_with(pyth_quote(PriceStatus::Trading, 0.02, SLOT - 25, NOW - 10));
+    let pair = TokenPair::new("SOL", "USDC");
+    assert_eq!(price_data.get_price(&pair).unwrap(), 20.0);
+    assert!((price_data.get_confidence(&pair).unwrap() - 0.02).abs() < 1e-12);
+    assert!(price_data.is_tradable(&pair));
+}
+
+#[test]
+fn test_non_trading_status_is_rejected() {
+    for status in [PriceStatus::Halted, PriceStatus::Unknown, PriceStatus::Auction] {
+        let price_data = snapshot_with(pyth_quote(status, 0.02, SLOT, NOW));
+        assert!(!price_data.has_price(&TokenPair::new("SOL", "USDC")), "{:?}", status);
+    }
+}
+
+#[test]
+fn test_stale_quote_is_rejected() {
+    // На слот старше допустимого
+    let price_data = snapshot_with(pyth_quote(PriceStatus::Trading, 0.02, SLOT - 26, NOW));
+    assert!(!price_data.has_price(&TokenPair::new("SOL", "USDC")));
+
+    // На секунду старше допустимого при свежем слоте
+    let price_data = snapshot_with(pyth_quote(PriceStatus::Trading, 0.02, SLOT, NOW - 11));
+    assert!(!price_data.has_price(&TokenPair::new("SOL", "USDC")));
+}
+
+#[test]
+fn test_wide_confidence_is_exposed_for_strategies() {
+    // Интервал 1 USDC при цене 20 - 5% цены: цена есть, но стратегии с порогом
+    // max_confidence_ratio (1% по умолчанию) по ней не торгуют
+    let price_data = snapshot_with(pyth_quote(PriceStatus::Trading, 1.0, SLOT, NOW));
+    let pair = TokenPair::new("SOL", "USDC");
+    assert!((price_data.get_confidence_ratio(&pair).unwrap() - 0.05).abs() < 1e-12);
+}
+
+#[test]
+fn test_stale_order_book_is_not_a_source() {
+    let pyth = Pubkey::new_unique();
+    let mut feed = feed(pyth);
+    feed.quotes.insert(pyth, pyth_quote(PriceStatus::Trading, 0.02, SLOT, NOW));
+    let params = SerumMarketParams { base_lot_size: 100_000_000, quote_lot_size: 100, base_decimals: 9, quote_decimals: 6, taker_fee_bps: 10 };
+    // Стакан со серединой 25 и слотом обновления 0 - на 1000 слотов старше фида
+    let book = L2Book::with_levels(params, &[(24_990, 10)], &[(25_010, 10)]);
+    let price_data = feed.snapshot(HashMap::from([("SOL/USDC".to_string(), book)]), HashMap::new(), NOW);
+    assert_eq!(price_data.get_price(&TokenPair::new("SOL", "USDC")).unwrap(), 20.0);
+}
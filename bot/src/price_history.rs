@@ -0,0 +1,76 @@
+use std::sync::Arc;
+
+// Одна точка ценового ряда: цена оракула на момент тика и объем сделок на DEX с прошлого тика
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PricePoint {
+    pub price: f64,
+    pub confidence: f64,
+    pub slot: u64,
+    // Unix-время в секундах
+    pub timestamp: i64,
+    // Объем в котируемом токене
+    pub volume: f64,
+}
+
+// Кольцевой буфер точек фиксированной емкости: при переполнении вытесняются самые старые.
+// Точки лежат в общем `Arc`, поэтому клон для снимка PriceData не копирует данные;
+// копия делается только при записи, пока снимок еще жив.
+#[derive(Debug, Clone)]
+pub struct PriceHistory {
+    // Вытесненные точки остаются в начале вектора до `start` и удаляются пачкой,
+    // когда их набирается `capacity` - так живые точки всегда лежат одним срезом
+    points: Arc<Vec<PricePoint>>,
+    start: usize,
+    capacity: usize,
+}
+
+impl PriceHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            points: Arc::new(Vec::with_capacity(capacity)),
+            start: 0,
+            capacity,
+        }
+    }
+
+    pub fn push(&mut self, point: PricePoint) {
+        // Буфер разделен со снимком - копируем только живые точки
+        if Arc::get_mut(&mut self.points).is_none() {
+            let mut points = Vec::with_capacity(self.capacity);
+            points.extend_from_slice(self.as_slice());
+            self.points = Arc::new(points);
+            self.start = 0;
+        }
+        let points = Arc::make_mut(&mut self.points);
+
+        if points.len() - self.start == self.capacity {
+            self.start += 1;
+        }
+        if self.start >= self.capacity {
+            points.drain(..self.start);
+            self.start = 0;
+        }
+        points.push(point);
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len() - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn last(&self) -> Option<&PricePoint> {
+        self.as_slice().last()
+    }
+
+    // Точки в хронологическом порядке
+    pub fn as_slice(&self) -> &[PricePoint] {
+        &self.points[self.start..]
+    }
+}
+
+#[cfg(test)]
+#[path = "price_history_test.rs"]
+mod tests;
@@ -0,0 +1,76 @@
+use super::*;
+use std::collections::HashMap;
+use crate::candles::CandleBuilder;
+use crate::price_feed::{PriceData, TokenPair};
+
+fn point(timestamp: i64, price: f64, volume: f64) -> PricePoint {
+    PricePoint { price, confidence: 0.0, slot: timestamp as u64, timestamp, volume }
+}
+
+fn prices(history: &PriceHistory) -> Vec<f64> {
+    history.as_slice().iter().map(|point| point.price).collect()
+}
+
+fn price_data(history: PriceHistory) -> PriceData {
+    let prices = HashMap::from([("SOL/USDC".to_string(), 20.0)]);
+    let history = HashMap::from([("SOL/USDC".to_string(), history)]);
+    PriceData::simulated(prices, history, &CandleBuilder::new(), HashMap::new(), 0)
+}
+
+#[test]
+fn test_ring_buffer_evicts_oldest() {
+    let mut history = PriceHistory::new(3);
+    assert!(history.is_empty() && history.last().is_none());
+
+    // Несколько полных оборотов буфера, включая удаление вытесненных точек пачкой
+    for i in 0..10 {
+        history.push(point(i, i as f64, 0.0));
+        assert_eq!(history.len(), (i as usize + 1).min(3));
+    }
+    assert_eq!(prices(&history), vec![7.0, 8.0, 9.0]);
+    assert_eq!(history.last().unwrap().price, 9.0);
+}
+
+#[test]
+fn test_snapshot_is_not_affected_by_later_pushes() {
+    let mut history = PriceHistory::new(3);
+    for i in 0..4 {
+        history.push(point(i, i as f64, 0.0));
+    }
+
+    // Клон разделяет точки с буфером, запись после него копирует только живые точки
+    let snapshot = history.clone();
+    history.push(point(4, 4.0, 0.0));
+    assert_eq!(prices(&snapshot), vec![1.0, 2.0, 3.0]);
+    assert_eq!(prices(&history), vec![2.0, 3.0, 4.0]);
+}
+
+#[test]
+fn test_get_price_history() {
+    let mut history = PriceHistory::new(1000);
+    for (i, &price) in [19.0, 19.5, 20.0, 20.5].iter().enumerate() {
+        history.push(point(i as i64, price, 0.0));
+    }
+    let price_data = price_data(history);
+    let pair = TokenPair::new("SOL", "USDC");
+
+    assert_eq!(price_data.get_price_history(&pair, 2).unwrap(), vec![20.0, 20.5]);
+    // Периодов больше, чем точек - возвращается вся история
+    assert_eq!(price_data.get_price_history(&pair, 10).unwrap(), vec![19.0, 19.5, 20.0, 20.5]);
+    assert!(price_data.get_price_history(&TokenPair::new("MSOL", "USDC"), 2).is_err());
+}
+
+#[test]
+fn test_get_volume_sums_last_day() {
+    let day = 24 * 60 * 60;
+    let mut history = PriceHistory::new(1000);
+    history.push(point(0, 20.0, 500.0));
+    history.push(point(100, 20.0, 100.0));
+    history.push(point(day / 2, 20.0, 200.0));
+    history.push(point(day + 100, 20.0, 300.0));
+    let pair = TokenPair::new("SOL", "USDC");
+
+    // Точка ровно сутки назад уже не входит в окно
+    assert_eq!(price_data(history).get_volume(&pair).unwrap(), 500.0);
+    assert_eq!(price_data(PriceHistory::new(10)).get_volume(&pair).unwrap(), 0.0);
+}
@@ -0,0 +1,91 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+// Курс одного токена в другом с доверительным интервалом в тех же единицах
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rate {
+    pub price: f64,
+    pub confidence: f64,
+}
+
+impl Rate {
+    pub fn identity() -> Self {
+        Self { price: 1.0, confidence: 0.0 }
+    }
+
+    fn relative_confidence(&self) -> f64 {
+        self.confidence / self.price
+    }
+
+    fn inverse(&self) -> Self {
+        Self {
+            price: 1.0 / self.price,
+            confidence: self.relative_confidence() / self.price,
+        }
+    }
+
+    // Произведение курсов A->B и B->C; относительные интервалы складываются
+    fn then(&self, next: &Rate) -> Self {
+        let price = self.price * next.price;
+        Self {
+            price,
+            confidence: price * (self.relative_confidence() + next.relative_confidence()),
+        }
+    }
+}
+
+// Граф известных курсов: вершины - токены, ребра - котировки фидов в обе стороны.
+// Кросс-курс ищется по кратчайшему пути, чтобы не накапливать лишнюю погрешность.
+#[derive(Debug, Clone, Default)]
+pub struct PricingGraph {
+    edges: HashMap<String, HashMap<String, Rate>>,
+}
+
+impl PricingGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Добавляет курс base/quote (цена одного base в quote) и обратный к нему
+    pub fn add_rate(&mut self, base: &str, quote: &str, rate: Rate) {
+        if !(rate.price.is_finite() && rate.price > 0.0) {
+            return;
+        }
+        self.edges.entry(base.to_string()).or_default().insert(quote.to_string(), rate);
+        self.edges.entry(quote.to_string()).or_default().insert(base.to_string(), rate.inverse());
+    }
+
+    // Цена одного `base` в `quote`, прямая или выведенная через промежуточные токены
+    pub fn rate(&self, base: &str, quote: &str) -> Option<Rate> {
+        if base == quote {
+            return Some(Rate::identity());
+        }
+
+        let mut visited = HashSet::new();
+        let mut queue = VecDeque::new();
+        visited.insert(base);
+        queue.push_back((base, Rate::identity()));
+
+        while let Some((token, rate)) = queue.pop_front() {
+            let edges = match self.edges.get(token) {
+                Some(edges) => edges,
+                None => continue,
+            };
+            for (next, edge) in edges {
+                if !visited.insert(next.as_str()) {
+                    continue;
+                }
+                let next_rate = rate.then(edge);
+                if next == quote {
+                    return Some(next_rate);
+                }
+                queue.push_back((next.as_str(), next_rate));
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+#[path = "pricing_graph_test.rs"]
+mod tests;
@@ -0,0 +1,48 @@
+use super::*;
+
+fn rate(price: f64, confidence: f64) -> Rate {
+    Rate { price, confidence }
+}
+
+fn usd_graph() -> PricingGraph {
+    let mut graph = PricingGraph::new();
+    graph.add_rate("SOL", "USD", rate(20.0, 0.02));
+    graph.add_rate("RAY", "USD", rate(0.5, 0.001));
+    graph.add_rate("USDC", "USD", rate(0.998, 0.0));
+    graph
+}
+
+#[test]
+fn test_direct_and_inverse_rates() {
+    let graph = usd_graph();
+    assert_eq!(graph.rate("SOL", "USD").unwrap(), rate(20.0, 0.02));
+    assert_eq!(graph.rate("SOL", "SOL").unwrap(), Rate::identity());
+
+    let inverse = graph.rate("USD", "SOL").unwrap();
+    assert!((inverse.price - 0.05).abs() < 1e-12);
+    // Относительный интервал сохраняется: 0.1%
+    assert!((inverse.confidence / inverse.price - 0.001).abs() < 1e-12);
+}
+
+#[test]
+fn test_cross_rate_through_usd() {
+    let graph = usd_graph();
+    // RAY/USDC = RAY/USD / USDC/USD
+    let ray_usdc = graph.rate("RAY", "USDC").unwrap();
+    assert!((ray_usdc.price - 0.5 / 0.998).abs() < 1e-12);
+    assert!((ray_usdc.confidence / ray_usdc.price - 0.002).abs() < 1e-12);
+
+    // RAY/SOL = RAY/USD / SOL/USD; относительные интервалы складываются
+    let ray_sol = graph.rate("RAY", "SOL").unwrap();
+    assert!((ray_sol.price - 0.025).abs() < 1e-12);
+    assert!((ray_sol.confidence / ray_sol.price - 0.003).abs() < 1e-12);
+}
+
+#[test]
+fn test_unknown_or_invalid_rates() {
+    let mut graph = usd_graph();
+    assert!(graph.rate("BTC", "USD").is_none());
+
+    graph.add_rate("BTC", "USD", rate(0.0, 1.0));
+    assert!(graph.rate("BTC", "USDC").is_none());
+}
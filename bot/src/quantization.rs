@@ -0,0 +1,153 @@
+use crate::error_handling::TradingError;
+use crate::market_registry::MarketInfo;
+
+// Поправка на погрешность f64 при делении на шаг (20.123 / 0.001 = 20122.999...)
+const ROUNDING_EPSILON: f64 = 1e-9;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rounding {
+    Down,
+    Up,
+    Nearest,
+}
+
+impl Rounding {
+    pub fn apply(&self, value: f64) -> f64 {
+        match self {
+            Rounding::Down => (value + ROUNDING_EPSILON).floor(),
+            Rounding::Up => (value - ROUNDING_EPSILON).ceil(),
+            Rounding::Nearest => value.round(),
+        }
+    }
+}
+
+// Цена в тиках рынка: количество quote-лотов за один base-лот
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PriceLots(pub u64);
+
+// Объем в base-лотах рынка
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SizeLots(pub u64);
+
+// Переводит цены и объемы между UI-единицами, нативными единицами токенов
+// и лотами/тиками рынка. Все преобразования из дробных значений требуют
+// явного направления округления.
+#[derive(Debug, Clone, Copy)]
+pub struct Quantizer {
+    base_decimals: u8,
+    quote_decimals: u8,
+    base_lot_size: u64,
+    quote_lot_size: u64,
+}
+
+impl Quantizer {
+    pub fn new(base_decimals: u8, quote_decimals: u8, base_lot_size: u64, quote_lot_size: u64) -> Self {
+        Self {
+            base_decimals,
+            quote_decimals,
+            base_lot_size,
+            quote_lot_size,
+        }
+    }
+
+    pub fn from_market(market: &MarketInfo) -> Self {
+        Self::new(market.base_decimals, market.quote_decimals, market.base_lot_size, market.quote_lot_size)
+    }
+
+    // Шаг цены в UI-единицах
+    pub fn tick_size(&self) -> f64 {
+        self.price_to_ui(PriceLots(1))
+    }
+
+    // Шаг объема в UI-единицах
+    pub fn lot_size(&self) -> f64 {
+        self.size_to_ui(SizeLots(1))
+    }
+
+    pub fn price_from_ui(&self, price: f64, rounding: Rounding) -> Result<PriceLots, TradingError> {
+        to_integer(price / self.tick_size(), rounding)
+            .map(PriceLots)
+            .ok_or_else(|| TradingError::InvalidOrder(format!("Price {} cannot be quantized", price)))
+    }
+
+    pub fn price_to_ui(&self, price: PriceLots) -> f64 {
+        price.0 as f64 * self.quote_lot_size as f64 * pow10(self.base_decimals)
+            / (self.base_lot_size as f64 * pow10(self.quote_decimals))
+    }
+
+    // Цена в нативных единицах котируемого токена за один целый базовый токен
+    // (например, микро-USDC за SOL)
+    pub fn price_to_native(&self, price: PriceLots) -> Result<u64, TradingError> {
+        (price.0 as u128)
+            .checked_mul(self.quote_lot_size as u128)
+            .and_then(|value| value.checked_mul(10u128.pow(self.base_decimals as u32)))
+            .map(|value| value / self.base_lot_size as u128)
+            .and_then(|value| u64::try_from(value).ok())
+            .ok_or_else(|| TradingError::InvalidOrder(format!("Price {:?} overflows native units", price)))
+    }
+
+    // Нативная цена за целый базовый токен в UI-единицах
+    pub fn price_native_to_ui(&self, price: u64) -> f64 {
+        price as f64 / pow10(self.quote_decimals)
+    }
+
+    pub fn size_native_to_ui(&self, amount: u64) -> f64 {
+        amount as f64 / pow10(self.base_decimals)
+    }
+
+    pub fn size_from_ui(&self, size: f64, rounding: Rounding) -> Result<SizeLots, TradingError> {
+        to_integer(size / self.lot_size(), rounding)
+            .map(SizeLots)
+            .ok_or_else(|| TradingError::InvalidOrder(format!("Size {} cannot be quantized", size)))
+    }
+
+    pub fn size_from_native(&self, amount: u64, rounding: Rounding) -> SizeLots {
+        let lots = amount / self.base_lot_size;
+        let remainder = amount % self.base_lot_size;
+        let round_up = match rounding {
+            Rounding::Down => false,
+            Rounding::Up => remainder > 0,
+            Rounding::Nearest => remainder * 2 >= self.base_lot_size,
+        };
+        SizeLots(if round_up { lots + 1 } else { lots })
+    }
+
+    pub fn size_to_ui(&self, size: SizeLots) -> f64 {
+        size.0 as f64 * self.base_lot_size as f64 / pow10(self.base_decimals)
+    }
+
+    pub fn size_to_native(&self, size: SizeLots) -> Result<u64, TradingError> {
+        size.0
+            .checked_mul(self.base_lot_size)
+            .ok_or_else(|| TradingError::InvalidOrder(format!("Size {:?} overflows native units", size)))
+    }
+
+    // Стоимость ордера в нативных единицах котируемого токена (без комиссий)
+    pub fn quote_native(&self, price: PriceLots, size: SizeLots) -> Result<u64, TradingError> {
+        (price.0 as u128)
+            .checked_mul(size.0 as u128)
+            .and_then(|value| value.checked_mul(self.quote_lot_size as u128))
+            .and_then(|value| u64::try_from(value).ok())
+            .ok_or_else(|| TradingError::InvalidOrder(format!("Notional of {:?} x {:?} overflows", size, price)))
+    }
+}
+
+fn to_integer(value: f64, rounding: Rounding) -> Option<u64> {
+    if !value.is_finite() || value < 0.0 {
+        return None;
+    }
+
+    let rounded = rounding.apply(value);
+    if rounded > u64::MAX as f64 {
+        return None;
+    }
+    Some(rounded as u64)
+}
+
+fn pow10(decimals: u8) -> f64 {
+    10f64.powi(decimals as i32)
+}
+
+#[cfg(test)]
+#[path = "quantization_test.rs"]
+mod tests;
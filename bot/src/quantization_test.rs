@@ -0,0 +1,52 @@
+use super::*;
+
+// SOL/USDC: лот 0.1 SOL, тик 0.001 USDC
+fn sol_usdc() -> Quantizer {
+    Quantizer::new(9, 6, 100_000_000, 100)
+}
+
+#[test]
+fn test_price_rounding_modes() {
+    let quantizer = sol_usdc();
+    assert_eq!(quantizer.price_from_ui(20.1234, Rounding::Down).unwrap(), PriceLots(20_123));
+    assert_eq!(quantizer.price_from_ui(20.1234, Rounding::Up).unwrap(), PriceLots(20_124));
+    assert_eq!(quantizer.price_from_ui(20.1236, Rounding::Nearest).unwrap(), PriceLots(20_124));
+
+    // Значение, ровно лежащее на шаге, не должно сдвигаться из-за погрешности f64
+    assert_eq!(quantizer.price_from_ui(20.123, Rounding::Down).unwrap(), PriceLots(20_123));
+    assert_eq!(quantizer.price_from_ui(20.123, Rounding::Up).unwrap(), PriceLots(20_123));
+}
+
+#[test]
+fn test_price_native_conversion() {
+    let quantizer = sol_usdc();
+    let price = quantizer.price_from_ui(20.123, Rounding::Nearest).unwrap();
+    assert_eq!(quantizer.price_to_native(price).unwrap(), 20_123_000);
+    assert!((quantizer.price_to_ui(price) - 20.123).abs() < 1e-9);
+}
+
+#[test]
+fn test_size_conversions() {
+    let quantizer = sol_usdc();
+    assert_eq!(quantizer.size_from_ui(1.25, Rounding::Down).unwrap(), SizeLots(12));
+    assert_eq!(quantizer.size_from_native(1_250_000_000, Rounding::Up), SizeLots(13));
+    assert_eq!(quantizer.size_from_native(1_250_000_000, Rounding::Nearest), SizeLots(13));
+    assert_eq!(quantizer.size_from_native(1_249_999_999, Rounding::Nearest), SizeLots(12));
+    assert_eq!(quantizer.size_to_native(SizeLots(12)).unwrap(), 1_200_000_000);
+}
+
+#[test]
+fn test_invalid_values_are_rejected() {
+    let quantizer = sol_usdc();
+    assert!(quantizer.price_from_ui(-1.0, Rounding::Down).is_err());
+    assert!(quantizer.size_from_ui(f64::NAN, Rounding::Down).is_err());
+    assert!(quantizer.quote_native(PriceLots(u64::MAX), SizeLots(u64::MAX)).is_err());
+}
+
+#[test]
+fn test_quote_native() {
+    let quantizer = sol_usdc();
+    // 1.2 SOL по 20.123 = 24.1476 USDC
+    let notional = quantizer.quote_native(PriceLots(20_123), SizeLots(12)).unwrap();
+    assert_eq!(notional, 24_147_600);
+}
@@ -0,0 +1,185 @@
+use std::error::Error;
+use std::ffi::OsStr;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::account_stream::AccountUpdate;
+use crate::jito_integration::TradingSignal;
+
+// Защита от чтения мусора при поврежденном префиксе длины
+const MAX_RECORD_LEN: u32 = 64 * 1024 * 1024;
+pub const RECORD_FILE_EXTENSION: &str = "bin";
+// Заголовок файла записи: магия и версия формата u32 LE. Версию нужно поднимать
+// при любом изменении bincode-раскладки `Record`, в том числе TradingSignal.
+const RECORD_MAGIC: [u8; 4] = *b"SREC";
+// 2: TradingSignal получил время жизни, срок действия и обоснование
+pub const RECORD_FORMAT_VERSION: u32 = 2;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum RecordedEvent {
+    // Новое содержимое аккаунта оракула или рынка (тик цены, обновление стакана)
+    Account(AccountUpdate),
+    // Тик стратегий и сведенные цены пар на этот момент
+    Tick { prices: Vec<(String, f64)> },
+    Signal(TradingSignal),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Record {
+    // Unix-время в миллисекундах
+    pub timestamp_ms: i64,
+    pub event: RecordedEvent,
+}
+
+// Пишет события в файл только на дозапись: после заголовка каждая запись -
+// длина u32 LE и bincode-представление `Record`
+pub struct Recorder {
+    path: PathBuf,
+    writer: BufWriter<File>,
+}
+
+impl Recorder {
+    // Создает новый файл записи в `dir`; имя содержит время начала записи
+    pub fn create(dir: &Path, started_at_ms: i64) -> Result<Self, Box<dyn Error>> {
+        fs::create_dir_all(dir)?;
+        let path = dir.join(format!("market-data-{}.{}", started_at_ms, RECORD_FILE_EXTENSION));
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| format!("Failed to open record file {}: {}", path.display(), e))?;
+
+        // Дописывать можно только в файл того же формата
+        if file.metadata()?.len() == 0 {
+            file.write_all(&RECORD_MAGIC)?;
+            file.write_all(&RECORD_FORMAT_VERSION.to_le_bytes())?;
+        } else {
+            read_header(&mut File::open(&path)?)
+                .map_err(|e| format!("Cannot append to {}: {}", path.display(), e))?;
+        }
+
+        Ok(Self {
+            path,
+            writer: BufWriter::new(file),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn record(&mut self, timestamp_ms: i64, event: RecordedEvent) -> Result<(), Box<dyn Error>> {
+        let bytes = bincode::serialize(&Record { timestamp_ms, event })?;
+        let len = u32::try_from(bytes.len())
+            .ok()
+            .filter(|&len| len <= MAX_RECORD_LEN)
+            .ok_or_else(|| format!("Record is too large: {} bytes", bytes.len()))?;
+
+        self.writer.write_all(&len.to_le_bytes())?;
+        self.writer.write_all(&bytes)?;
+        Ok(())
+    }
+
+    // Сбрасывает буфер на диск; вызывается на каждом тике стратегий
+    pub fn flush(&mut self) -> Result<(), Box<dyn Error>> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+pub struct RecordReader<R> {
+    reader: R,
+}
+
+impl RecordReader<BufReader<File>> {
+    pub fn open(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let file = File::open(path)
+            .map_err(|e| format!("Failed to open record file {}: {}", path.display(), e))?;
+        Self::new(BufReader::new(file))
+            .map_err(|e| format!("Failed to read record file {}: {}", path.display(), e).into())
+    }
+}
+
+impl<R: Read> RecordReader<R> {
+    // Проверяет заголовок: файл другой версии формата не читается как мусор
+    pub fn new(mut reader: R) -> Result<Self, Box<dyn Error>> {
+        read_header(&mut reader)?;
+        Ok(Self { reader })
+    }
+
+    // Следующая запись или None в конце файла. Недописанная последняя запись
+    // (бот остановлен во время записи) тоже считается концом файла.
+    pub fn next_record(&mut self) -> Result<Option<Record>, Box<dyn Error>> {
+        let mut len_bytes = [0u8; 4];
+        if !self.read_exact_or_eof(&mut len_bytes)? {
+            return Ok(None);
+        }
+        let len = u32::from_le_bytes(len_bytes);
+        if len > MAX_RECORD_LEN {
+            return Err(format!("Corrupted record file: record length {}", len).into());
+        }
+
+        let mut bytes = vec![0u8; len as usize];
+        if !self.read_exact_or_eof(&mut bytes)? {
+            return Ok(None);
+        }
+        Ok(Some(bincode::deserialize(&bytes)?))
+    }
+
+    fn read_exact_or_eof(&mut self, buf: &mut [u8]) -> Result<bool, Box<dyn Error>> {
+        match self.reader.read_exact(buf) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+fn read_header(reader: &mut impl Read) -> Result<(), Box<dyn Error>> {
+    let mut header = [0u8; 8];
+    match reader.read_exact(&mut header) {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Err("Not a record file: missing header".into()),
+        Err(e) => return Err(e.into()),
+    }
+    if header[..4] != RECORD_MAGIC {
+        return Err("Not a record file or recorded by a bot without format header".into());
+    }
+
+    let version = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+    if version != RECORD_FORMAT_VERSION {
+        return Err(format!(
+            "Unsupported record format version {} (this bot reads version {})",
+            version, RECORD_FORMAT_VERSION
+        ).into());
+    }
+    Ok(())
+}
+
+// Файлы записи по пути: сам файл или все файлы каталога в порядке имен,
+// то есть в порядке времени начала записи
+pub fn record_files(path: &Path) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    if path.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let mut files = Vec::new();
+    for entry in fs::read_dir(path)? {
+        let file = entry?.path();
+        if file.extension() == Some(OsStr::new(RECORD_FILE_EXTENSION)) {
+            files.push(file);
+        }
+    }
+    if files.is_empty() {
+        return Err(format!("No record files found in {}", path.display()).into());
+    }
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+#[path = "recorder_test.rs"]
+mod tests;
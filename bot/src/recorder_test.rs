@@ -0,0 +1,127 @@
+use super::*;
+use crate::dex::serum::OrderType;
+use crate::jito_integration::{OrderAction, OrderKind, TimeInForce, TradeAction, Venue};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::BTreeMap;
+
+fn account_update(slot: u64) -> RecordedEvent {
+    RecordedEvent::Account(AccountUpdate {
+        pubkey: Pubkey::new_from_array([7; 32]),
+        slot,
+        data: vec![1, 2, 3],
+    })
+}
+
+fn header(version: u32) -> Vec<u8> {
+    let mut bytes = RECORD_MAGIC.to_vec();
+    bytes.extend_from_slice(&version.to_le_bytes());
+    bytes
+}
+
+fn read_all(bytes: &[u8]) -> Vec<Record> {
+    let mut reader = RecordReader::new(bytes).unwrap();
+    let mut records = Vec::new();
+    while let Some(record) = reader.next_record().unwrap() {
+        records.push(record);
+    }
+    records
+}
+
+#[test]
+fn test_records_round_trip_through_file() {
+    let dir = std::env::temp_dir().join(format!("recorder-test-{}", std::process::id()));
+    let mut recorder = Recorder::create(&dir, 1_000).unwrap();
+    recorder.record(1_000, account_update(5)).unwrap();
+    recorder.record(1_500, RecordedEvent::Tick { prices: vec![("SOL/USDC".to_string(), 20.0)] }).unwrap();
+    recorder.flush().unwrap();
+
+    let files = record_files(&dir).unwrap();
+    assert_eq!(files, vec![recorder.path().to_path_buf()]);
+
+    let bytes = fs::read(recorder.path()).unwrap();
+    let records = read_all(&bytes);
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].timestamp_ms, 1_000);
+    match &records[0].event {
+        RecordedEvent::Account(update) => assert_eq!(update.slot, 5),
+        event => panic!("unexpected event {:?}", event),
+    }
+    match &records[1].event {
+        RecordedEvent::Tick { prices } => assert_eq!(prices, &vec![("SOL/USDC".to_string(), 20.0)]),
+        event => panic!("unexpected event {:?}", event),
+    }
+
+    // Повторное открытие того же файла дописывает записи без второго заголовка
+    let mut recorder = Recorder::create(&dir, 1_000).unwrap();
+    recorder.record(2_000, account_update(6)).unwrap();
+    recorder.flush().unwrap();
+    assert_eq!(read_all(&fs::read(recorder.path()).unwrap()).len(), 3);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_header_is_checked() {
+    let error = |bytes: &[u8]| RecordReader::new(bytes).err().unwrap().to_string();
+
+    assert!(error(&[]).contains("missing header"));
+    // Файл старого формата без заголовка начинается сразу с длины записи
+    assert!(error(&[12, 0, 0, 0, 1, 2, 3, 4]).contains("without format header"));
+    assert!(error(&header(RECORD_FORMAT_VERSION + 1)).contains("Unsupported record format version"));
+    assert!(RecordReader::new(&header(RECORD_FORMAT_VERSION)[..]).unwrap().next_record().unwrap().is_none());
+}
+
+#[test]
+fn test_truncated_tail_is_ignored() {
+    let mut bytes = header(RECORD_FORMAT_VERSION);
+    for slot in 0..2 {
+        let record = bincode::serialize(&Record { timestamp_ms: slot as i64, event: account_update(slot) }).unwrap();
+        bytes.extend_from_slice(&(record.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&record);
+    }
+    bytes.truncate(bytes.len() - 3);
+
+    let records = read_all(&bytes);
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].timestamp_ms, 0);
+}
+
+#[test]
+fn test_signal_metadata_round_trips() {
+    let signal = TradingSignal {
+        market: Pubkey::new_from_array([1; 32]),
+        action: TradeAction::Sell,
+        amount: 1_000_000_000,
+        price: 20_000_000,
+        payer: Pubkey::new_from_array([2; 32]),
+        client_order_id: 42,
+        order_action: OrderAction::Place,
+        time_in_force: TimeInForce::Gtc,
+        venue: Venue::Serum,
+        bundle_id: None,
+        strategy_id: "momentum-sol".to_string(),
+        confidence: 0.75,
+        expiry_slot: Some(1_005),
+        order_kind: OrderKind::Market,
+        slippage_bps: 30.0,
+        reason: BTreeMap::new(),
+    }
+    .with_reason("rsi", 74.5);
+
+    let record = bincode::serialize(&Record { timestamp_ms: 0, event: RecordedEvent::Signal(signal) }).unwrap();
+    let mut bytes = header(RECORD_FORMAT_VERSION);
+    bytes.extend_from_slice(&(record.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&record);
+
+    let records = read_all(&bytes);
+    let signal = match &records[0].event {
+        RecordedEvent::Signal(signal) => signal,
+        event => panic!("unexpected event {:?}", event),
+    };
+    assert_eq!((signal.strategy_id.as_str(), signal.expiry_slot), ("momentum-sol", Some(1_005)));
+    assert_eq!((signal.time_in_force, signal.order_kind), (TimeInForce::Gtc, OrderKind::Market));
+    assert_eq!(signal.reason.get("rsi").map(String::as_str), Some("74.5"));
+    // Рыночная продажа: предельная цена ниже ожидаемой на допуск проскальзывания
+    assert_eq!(signal.limit_price(), 19_940_000);
+    assert_eq!(signal.order_type(), OrderType::ImmediateOrCancel);
+}
@@ -0,0 +1,84 @@
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+
+use log::{info, warn};
+use tokio::sync::mpsc;
+
+use crate::account_stream::FeedEvent;
+use crate::recorder::{RecordReader, RecordedEvent};
+
+const CHANNEL_CAPACITY: usize = 1024;
+
+#[derive(Debug, Clone)]
+pub struct ReplayConfig {
+    pub files: Vec<PathBuf>,
+    // 1.0 - исходная скорость, 10.0 - в десять раз быстрее, 0 - без пауз
+    pub speed: f64,
+}
+
+// Воспроизводит записанные файлы как источник событий основного цикла.
+// Сигналы из записи не воспроизводятся: их заново генерируют стратегии.
+// Канал закрывается, когда записи закончились.
+pub fn spawn_replay(config: ReplayConfig) -> mpsc::Receiver<FeedEvent> {
+    let (sender, receiver) = mpsc::channel(CHANNEL_CAPACITY);
+    tokio::task::spawn_blocking(move || run_replay(&config, &sender));
+    receiver
+}
+
+fn run_replay(config: &ReplayConfig, sender: &mpsc::Sender<FeedEvent>) {
+    let mut previous_ms = None;
+    let mut replayed = 0u64;
+
+    for file in &config.files {
+        info!("Replaying {}", file.display());
+        let mut reader = match RecordReader::open(file) {
+            Ok(reader) => reader,
+            Err(e) => {
+                warn!("Replay stopped: {}", e);
+                return;
+            }
+        };
+
+        loop {
+            let record = match reader.next_record() {
+                Ok(Some(record)) => record,
+                Ok(None) => break,
+                Err(e) => {
+                    warn!("Replay stopped on corrupted record in {}: {}", file.display(), e);
+                    return;
+                }
+            };
+
+            let event = match record.event {
+                RecordedEvent::Account(update) => FeedEvent::Account { timestamp_ms: record.timestamp_ms, update },
+                RecordedEvent::Tick { .. } => FeedEvent::Tick { timestamp_ms: record.timestamp_ms },
+                RecordedEvent::Signal(_) => continue,
+            };
+
+            if let Some(delay) = replay_delay(previous_ms, record.timestamp_ms, config.speed) {
+                thread::sleep(delay);
+            }
+            previous_ms = Some(record.timestamp_ms);
+
+            if sender.blocking_send(event).is_err() {
+                return;
+            }
+            replayed += 1;
+        }
+    }
+
+    info!("Replay finished: {} events", replayed);
+}
+
+// Пауза между соседними событиями с учетом ускорения
+fn replay_delay(previous_ms: Option<i64>, timestamp_ms: i64, speed: f64) -> Option<Duration> {
+    if speed <= 0.0 {
+        return None;
+    }
+    let elapsed_ms = timestamp_ms - previous_ms?;
+    if elapsed_ms <= 0 {
+        return None;
+    }
+    Some(Duration::from_secs_f64(elapsed_ms as f64 / 1000.0 / speed))
+}
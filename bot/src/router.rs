@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+use std::error::Error;
+
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::dex::orca::OrcaWhirlpool;
+use crate::dex::raydium::{decode_amm_info, RaydiumPool};
+use crate::dex::serum::{self, SerumBook, SerumMarketParams};
+use crate::dex::LiquiditySource;
+use crate::jito_integration::TradeAction;
+use crate::market_registry::{MarketRegistry, PoolInfo};
+use crate::price_feed::TokenPair;
+
+// Маршрутизатор оценивает обмен по всем площадкам пары, в том числе с разбиением
+// объема между ними. Аккаунты площадок он читает сам по RPC (`load_sources`),
+// поэтому в основной цикл не встроен: стратегии получают стаканы и пулы из потока
+// аккаунтов через PriceData. Арбитраж сравнивает площадки попарно и не использует
+// разбиение, потому что его бандл - ровно одна покупка и одна продажа. Модуль
+// остается для разовой оценки маршрута крупной сделки вне цикла.
+
+// Количество частей, на которые делится объем при поиске разбиения
+const SPLIT_STEPS: usize = 20;
+
+#[derive(Debug, Clone)]
+pub enum VenueConfig {
+    Serum {
+        name: String,
+        market: Pubkey,
+        params: SerumMarketParams,
+    },
+    Raydium {
+        name: String,
+        amm: Pubkey,
+        base_decimals: u8,
+        quote_decimals: u8,
+    },
+    Orca {
+        name: String,
+        whirlpool: Pubkey,
+        tick_arrays: Vec<Pubkey>,
+        base_is_a: bool,
+        decimals_a: u8,
+        decimals_b: u8,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct RouteLeg {
+    pub venue: String,
+    pub amount_in: f64,
+    pub expected_out: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct Route {
+    pub action: TradeAction,
+    pub amount_in: f64,
+    pub expected_out: f64,
+    pub legs: Vec<RouteLeg>,
+}
+
+impl Route {
+    pub fn is_split(&self) -> bool {
+        self.legs.len() > 1
+    }
+}
+
+pub struct SmartOrderRouter {
+    rpc_client: RpcClient,
+    venues: HashMap<String, Vec<VenueConfig>>,
+}
+
+impl SmartOrderRouter {
+    // Рынки Serum и AMM-пулы из реестра подключаются автоматически,
+    // остальные площадки добавляются через add_venue
+    pub fn new(rpc_url: &str, registry: &MarketRegistry) -> Self {
+        let mut router = Self {
+            rpc_client: RpcClient::new(rpc_url.to_string()),
+            venues: HashMap::new(),
+        };
+
+        for market in registry.markets() {
+            router.add_venue(&market.pair, VenueConfig::Serum {
+                name: format!("serum:{}", market.pair),
+                market: market.dex_market,
+                params: market.serum_params(),
+            });
+            for pool in &market.pools {
+                let name = pool.name(&market.pair);
+                router.add_venue(&market.pair, match pool {
+                    PoolInfo::Raydium { amm } => VenueConfig::Raydium {
+                        name,
+                        amm: *amm,
+                        base_decimals: market.base_decimals,
+                        quote_decimals: market.quote_decimals,
+                    },
+                    PoolInfo::Orca { whirlpool, tick_arrays, base_is_a } => VenueConfig::Orca {
+                        name,
+                        whirlpool: *whirlpool,
+                        tick_arrays: tick_arrays.clone(),
+                        base_is_a: *base_is_a,
+                        decimals_a: if *base_is_a { market.base_decimals } else { market.quote_decimals },
+                        decimals_b: if *base_is_a { market.quote_decimals } else { market.base_decimals },
+                    },
+                });
+            }
+        }
+
+        router
+    }
+
+    pub fn add_venue(&mut self, pair: &TokenPair, venue: VenueConfig) {
+        self.venues.entry(pair.to_string()).or_default().push(venue);
+    }
+
+    pub async fn route(&self, pair: &TokenPair, action: TradeAction, amount_in: f64) -> Result<Route, Box<dyn Error>> {
+        let sources = self.load_sources(pair)?;
+        find_best_route(&sources, action, amount_in)
+            .ok_or_else(|| format!("No route found for {}", pair).into())
+    }
+
+    // Загружает снимки аккаунтов всех площадок пары и строит по ним источники ликвидности
+    pub fn load_sources(&self, pair: &TokenPair) -> Result<Vec<Box<dyn LiquiditySource>>, Box<dyn Error>> {
+        let venues = self.venues.get(&pair.to_string())
+            .ok_or_else(|| format!("No venues configured for {}", pair))?;
+
+        let mut sources: Vec<Box<dyn LiquiditySource>> = Vec::new();
+        for venue in venues {
+            match self.load_source(venue) {
+                Ok(source) => sources.push(source),
+                Err(e) => log::warn!("Failed to load venue for {}: {}", pair, e),
+            }
+        }
+
+        Ok(sources)
+    }
+
+    fn load_source(&self, venue: &VenueConfig) -> Result<Box<dyn LiquiditySource>, Box<dyn Error>> {
+        match venue {
+            VenueConfig::Serum { name, market, params } => {
+                let market_state = serum::decode_market_state(&self.rpc_client.get_account_data(market)?)?;
+                let bids_data = self.rpc_client.get_account_data(&market_state.bids)?;
+                let asks_data = self.rpc_client.get_account_data(&market_state.asks)?;
+                Ok(Box::new(SerumBook::from_slabs(name, *params, &bids_data, &asks_data)?))
+            }
+            VenueConfig::Raydium { name, amm, base_decimals, quote_decimals } => {
+                let amm_data = self.rpc_client.get_account_data(amm)?;
+                let amm_info = decode_amm_info(&amm_data)?;
+                let coin_vault_data = self.rpc_client.get_account_data(&amm_info.coin_vault)?;
+                let pc_vault_data = self.rpc_client.get_account_data(&amm_info.pc_vault)?;
+                Ok(Box::new(RaydiumPool::from_accounts(
+                    name,
+                    &amm_data,
+                    &coin_vault_data,
+                    &pc_vault_data,
+                    *base_decimals,
+                    *quote_decimals,
+                )?))
+            }
+            VenueConfig::Orca { name, whirlpool, tick_arrays, base_is_a, decimals_a, decimals_b } => {
+                let whirlpool_data = self.rpc_client.get_account_data(whirlpool)?;
+                let tick_array_data = self.rpc_client
+                    .get_multiple_accounts(tick_arrays)?
+                    .into_iter()
+                    .flatten()
+                    .map(|account| account.data)
+                    .collect::<Vec<_>>();
+                Ok(Box::new(OrcaWhirlpool::from_accounts(
+                    name,
+                    &whirlpool_data,
+                    &tick_array_data,
+                    *base_is_a,
+                    *decimals_a,
+                    *decimals_b,
+                )?))
+            }
+        }
+    }
+}
+
+// Выбирает лучший маршрут: целиком через одну площадку или с разбиением.
+// Разбиение строится жадно - каждая следующая часть объема уходит туда,
+// где она дает наибольший прирост выхода с учетом уже назначенного объема.
+pub fn find_best_route(sources: &[Box<dyn LiquiditySource>], action: TradeAction, amount_in: f64) -> Option<Route> {
+    if sources.is_empty() || amount_in <= 0.0 {
+        return None;
+    }
+
+    let best_single = sources.iter()
+        .filter_map(|source| source.quote(action, amount_in).map(|out| (source, out)))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(source, out)| Route {
+            action,
+            amount_in,
+            expected_out: out,
+            legs: vec![RouteLeg {
+                venue: source.name().to_string(),
+                amount_in,
+                expected_out: out,
+            }],
+        });
+
+    let split = split_route(sources, action, amount_in);
+
+    match (best_single, split) {
+        (Some(single), Some(split)) if split.expected_out > single.expected_out => Some(split),
+        (Some(single), _) => Some(single),
+        (None, split) => split,
+    }
+}
+
+fn split_route(sources: &[Box<dyn LiquiditySource>], action: TradeAction, amount_in: f64) -> Option<Route> {
+    let step = amount_in / SPLIT_STEPS as f64;
+    let mut allocated = vec![0.0; sources.len()];
+    let mut outputs = vec![0.0; sources.len()];
+
+    for _ in 0..SPLIT_STEPS {
+        let mut best: Option<(usize, f64)> = None;
+        for (i, source) in sources.iter().enumerate() {
+            if let Some(out) = source.quote(action, allocated[i] + step) {
+                let gain = out - outputs[i];
+                match best {
+                    Some((_, best_gain)) if best_gain >= gain => {}
+                    _ => best = Some((i, gain)),
+                }
+            }
+        }
+
+        let (i, gain) = best?;
+        allocated[i] += step;
+        outputs[i] += gain;
+    }
+
+    let legs = sources.iter()
+        .enumerate()
+        .filter(|(i, _)| allocated[*i] > 0.0)
+        .map(|(i, source)| RouteLeg {
+            venue: source.name().to_string(),
+            amount_in: allocated[i],
+            expected_out: outputs[i],
+        })
+        .collect::<Vec<_>>();
+
+    Some(Route {
+        action,
+        amount_in,
+        expected_out: outputs.iter().sum(),
+        legs,
+    })
+}
+
+#[cfg(test)]
+#[path = "router_test.rs"]
+mod tests;
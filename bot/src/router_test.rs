@@ -0,0 +1,146 @@
+use super::*;
+use crate::dex::orca;
+use crate::dex::serum;
+
+// Снимки аккаунтов собираются в тех же бинарных раскладках, что и в сети,
+// поэтому тестам не нужен RPC
+
+fn serum_slab(orders: &[(u64, u64)]) -> Vec<u8> {
+    let mut data = b"serum".to_vec();
+    data.extend_from_slice(&0u64.to_le_bytes()); // account flags
+    data.extend_from_slice(&(orders.len() as u64).to_le_bytes()); // bump_index
+    data.extend_from_slice(&[0u8; 24]);
+    for (i, &(price_lots, quantity_lots)) in orders.iter().enumerate() {
+        let mut node = vec![0u8; 72];
+        node[0..4].copy_from_slice(&2u32.to_le_bytes());
+        let key = ((price_lots as u128) << 64) | i as u128;
+        node[8..24].copy_from_slice(&key.to_le_bytes());
+        node[56..64].copy_from_slice(&quantity_lots.to_le_bytes());
+        data.extend_from_slice(&node);
+    }
+    data.extend_from_slice(b"padding");
+    data
+}
+
+fn serum_params() -> SerumMarketParams {
+    // SOL (9 знаков) / USDC (6 знаков), лот 0.1 SOL и 0.001 USDC
+    SerumMarketParams {
+        base_lot_size: 100_000_000,
+        quote_lot_size: 1_000,
+        base_decimals: 9,
+        quote_decimals: 6,
+        taker_fee_bps: 4,
+    }
+}
+
+fn token_account(amount: u64) -> Vec<u8> {
+    let mut data = vec![0u8; 165];
+    data[64..72].copy_from_slice(&amount.to_le_bytes());
+    data
+}
+
+fn raydium_amm(fee_numerator: u64, fee_denominator: u64) -> Vec<u8> {
+    let mut data = vec![0u8; 752];
+    data[176..184].copy_from_slice(&fee_numerator.to_le_bytes());
+    data[184..192].copy_from_slice(&fee_denominator.to_le_bytes());
+    data
+}
+
+fn raydium_pool(name: &str, base: u64, quote: u64) -> RaydiumPool {
+    RaydiumPool::from_accounts(name, &raydium_amm(25, 10_000), &token_account(base), &token_account(quote), 9, 6)
+        .unwrap()
+}
+
+fn whirlpool(liquidity: u128, price: f64, tick_current_index: i32) -> Vec<u8> {
+    let mut data = vec![0u8; 653];
+    data[41..43].copy_from_slice(&64u16.to_le_bytes());
+    data[45..47].copy_from_slice(&3000u16.to_le_bytes());
+    data[49..65].copy_from_slice(&liquidity.to_le_bytes());
+    let sqrt_price = (price.sqrt() * 2f64.powi(64)) as u128;
+    data[65..81].copy_from_slice(&sqrt_price.to_le_bytes());
+    data[81..85].copy_from_slice(&tick_current_index.to_le_bytes());
+    data
+}
+
+#[test]
+fn test_decode_serum_slab() {
+    let data = serum_slab(&[(2_000, 10), (1_990, 5), (2_000, 3)]);
+    let orders = serum::decode_slab(&data).unwrap();
+    assert_eq!(orders.len(), 3);
+
+    let levels = serum::aggregate_levels(&orders, &serum_params(), true);
+    assert_eq!(levels.len(), 2);
+    // 2_000 лотов цены = 20.0 USDC за SOL, 13 лотов = 1.3 SOL
+    assert!((levels[0].0 - 20.0).abs() < 1e-9);
+    assert!((levels[0].1 - 1.3).abs() < 1e-9);
+}
+
+#[test]
+fn test_serum_quote_walks_the_book() {
+    let book = SerumBook::from_slabs(
+        "serum",
+        serum_params(),
+        &serum_slab(&[(1_990, 10)]),
+        &serum_slab(&[(2_000, 10), (2_100, 10)]),
+    )
+    .unwrap();
+
+    // 1 SOL по 20 и еще 0.5 SOL по 21
+    let spend = (20.0 + 10.5) * (1.0 + 0.0004);
+    let bought = book.quote(TradeAction::Buy, spend).unwrap();
+    assert!((bought - 1.5).abs() < 1e-9);
+
+    // Объем больше глубины стакана не котируется
+    assert!(book.quote(TradeAction::Sell, 5.0).is_none());
+}
+
+#[test]
+fn test_raydium_constant_product() {
+    let pool = raydium_pool("raydium", 1_000_000_000_000, 20_000_000_000);
+    let out = pool.quote(TradeAction::Sell, 10.0).unwrap();
+
+    let in_after_fee = 10.0 * (1.0 - 0.0025);
+    let expected = 20_000.0 * in_after_fee / (1_000.0 + in_after_fee);
+    assert!((out - expected).abs() < 1e-9);
+}
+
+#[test]
+fn test_orca_quote_within_single_range() {
+    // Цена 0.02 USDC-native за lamport = 20 USDC за SOL
+    let pool = OrcaWhirlpool::from_accounts("orca", &whirlpool(1_000_000_000_000, 0.02, -39_120), &[], true, 9, 6)
+        .unwrap();
+
+    let out = pool.quote(TradeAction::Sell, 1.0).unwrap();
+    assert!(out > 19.8 && out < 20.0, "unexpected output {}", out);
+
+    let sqrt_price = orca::tick_to_sqrt_price(-39_120);
+    assert!((sqrt_price * sqrt_price - 0.02).abs() < 1e-3);
+}
+
+#[test]
+fn test_router_prefers_single_venue_when_it_dominates() {
+    let sources: Vec<Box<dyn LiquiditySource>> = vec![
+        Box::new(raydium_pool("deep", 100_000_000_000_000, 2_000_000_000_000)),
+        Box::new(raydium_pool("shallow", 1_000_000_000, 10_000_000)),
+    ];
+
+    let route = find_best_route(&sources, TradeAction::Sell, 1.0).unwrap();
+    assert!(!route.is_split());
+    assert_eq!(route.legs[0].venue, "deep");
+}
+
+#[test]
+fn test_router_splits_between_equal_pools() {
+    let sources: Vec<Box<dyn LiquiditySource>> = vec![
+        Box::new(raydium_pool("a", 1_000_000_000_000, 20_000_000_000)),
+        Box::new(raydium_pool("b", 1_000_000_000_000, 20_000_000_000)),
+    ];
+
+    let route = find_best_route(&sources, TradeAction::Sell, 100.0).unwrap();
+    assert!(route.is_split());
+
+    let single = sources[0].quote(TradeAction::Sell, 100.0).unwrap();
+    assert!(route.expected_out > single);
+    let total_in: f64 = route.legs.iter().map(|leg| leg.amount_in).sum();
+    assert!((total_in - 100.0).abs() < 1e-9);
+}
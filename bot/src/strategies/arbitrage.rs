@@ -0,0 +1,240 @@
+use std::error::Error;
+
+use log::info;
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::registry::parse_params;
+use super::{bundle, Strategy, StrategyContext};
+use crate::dex::LiquiditySource;
+use crate::jito_integration::{TradeAction, TradingSignal, Venue};
+use crate::orderbook::L2Book;
+use crate::pools::PoolSnapshot;
+use crate::price_feed::PriceData;
+
+// Итераций поиска суммы на входе AMM для покупки заданного объема
+const BISECTION_STEPS: usize = 60;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ArbitrageConfig {
+    // Наибольший объем сделки в базовом токене
+    pub max_size: f64,
+    // Сколько объемов пробуется: max_size / size_steps, 2 * max_size / size_steps, ...
+    pub size_steps: usize,
+    // Минимальная прибыль после комиссий, tip и проскальзывания в котируемом токене
+    pub min_profit: f64,
+    // Tip Jito за бандл в котируемом токене
+    pub tip: f64,
+    // Допуск проскальзывания каждой ноги: лимитные цены хуже ожидаемых на эту величину
+    pub slippage_bps: f64,
+}
+
+impl Default for ArbitrageConfig {
+    fn default() -> Self {
+        Self {
+            max_size: 1.0,
+            size_steps: 10,
+            min_profit: 0.1,
+            tip: 0.01,
+            slippage_bps: 10.0,
+        }
+    }
+}
+
+// Площадка, на которой можно купить или продать базовый токен пары
+#[derive(Clone, Copy)]
+enum Leg<'a> {
+    Book(&'a L2Book, f64),
+    Pool(&'a PoolSnapshot),
+}
+
+impl<'a> Leg<'a> {
+    fn venue(&self) -> Venue {
+        match self {
+            Leg::Book(..) => Venue::Serum,
+            Leg::Pool(pool) => pool.venue,
+        }
+    }
+
+    // Комиссия поверх цены; у AMM она уже входит в цену обмена
+    fn fee_rate(&self) -> f64 {
+        match self {
+            Leg::Book(_, fee_rate) => *fee_rate,
+            Leg::Pool(_) => 0.0,
+        }
+    }
+
+    // Средняя цена покупки или продажи объема `size` базового токена
+    fn average_price(&self, action: TradeAction, size: f64) -> Option<f64> {
+        match (self, action) {
+            (Leg::Book(book, _), _) => book.vwap(action, size),
+            (Leg::Pool(pool), TradeAction::Sell) => Some(pool.pool.quote(TradeAction::Sell, size)? / size),
+            (Leg::Pool(pool), TradeAction::Buy) => Some(quote_in_for_base(&pool.pool, size)? / size),
+        }
+    }
+}
+
+// Сумма в котируемом токене, за которую пул отдаст не меньше `size` базового токена
+fn quote_in_for_base(pool: &dyn LiquiditySource, size: f64) -> Option<f64> {
+    let mut high = 1.0;
+    while pool.quote(TradeAction::Buy, high)? < size {
+        high *= 2.0;
+        if !high.is_finite() {
+            return None;
+        }
+    }
+    let mut low = 0.0;
+    for _ in 0..BISECTION_STEPS {
+        let middle = (low + high) / 2.0;
+        match pool.quote(TradeAction::Buy, middle) {
+            Some(out) if out >= size => high = middle,
+            _ => low = middle,
+        }
+    }
+    Some(high)
+}
+
+// Лучшая найденная возможность: купить на одной площадке и продать на другой
+struct Opportunity {
+    buy: Venue,
+    sell: Venue,
+    size: f64,
+    buy_price: f64,
+    sell_price: f64,
+    profit: f64,
+}
+
+// Межплощадочный арбитраж пары между стаканом Serum/OpenBook и AMM-пулами
+// Raydium/Orca. Прибыль считается по худшим допустимым ценам после комиссий
+// и tip; обе ноги отправляются одним бандлом и исполняются атомарно.
+pub struct ArbitrageStrategy {
+    config: ArbitrageConfig,
+    // Слоты данных, по которым был отправлен последний бандл: пока площадки
+    // не обновились, та же возможность не отправляется повторно
+    last_slots: Option<Vec<u64>>,
+}
+
+impl ArbitrageStrategy {
+    pub fn new(config: ArbitrageConfig) -> Result<Self, Box<dyn Error>> {
+        if !(config.max_size > 0.0 && config.size_steps > 0) {
+            return Err("max_size and size_steps must be positive".into());
+        }
+        if !(config.tip >= 0.0 && config.slippage_bps >= 0.0) {
+            return Err("tip and slippage_bps must not be negative".into());
+        }
+        Ok(Self {
+            config,
+            last_slots: None,
+        })
+    }
+
+    pub fn from_params(params: &Value) -> Result<Self, Box<dyn Error>> {
+        Self::new(parse_params(params)?)
+    }
+
+    fn evaluate(&self, buy: Leg, sell: Leg, size: f64) -> Option<Opportunity> {
+        let slippage = self.config.slippage_bps / 10_000.0;
+        let buy_price = buy.average_price(TradeAction::Buy, size)? * (1.0 + slippage);
+        let sell_price = sell.average_price(TradeAction::Sell, size)? * (1.0 - slippage);
+        let fees = size * (buy_price * buy.fee_rate() + sell_price * sell.fee_rate());
+        Some(Opportunity {
+            buy: buy.venue(),
+            sell: sell.venue(),
+            size,
+            buy_price,
+            sell_price,
+            profit: size * (sell_price - buy_price) - fees - self.config.tip,
+        })
+    }
+}
+
+impl Strategy for ArbitrageStrategy {
+    fn name(&self) -> &str {
+        "arbitrage"
+    }
+
+    fn init(&mut self, ctx: &StrategyContext) -> Result<(), Box<dyn Error>> {
+        if ctx.pairs.len() != 1 {
+            return Err("arbitrage watches exactly one pair".into());
+        }
+        Ok(())
+    }
+
+    // Оракулы не используются: обе ноги оцениваются по состоянию самих площадок
+    fn on_tick(&mut self, ctx: &StrategyContext, price_data: &PriceData) -> Result<Vec<TradingSignal>, Box<dyn Error>> {
+        let pair = &ctx.pairs[0];
+        let market = ctx.markets.get(pair)?;
+        let pools = price_data.get_pools(pair);
+
+        // Ноги в пулах считаются исполненными, когда в event queue появляется
+        // сделка ноги Serum из того же бандла, поэтому без стакана арбитража нет
+        let book = match price_data.get_order_book(pair) {
+            Some(book) if !pools.is_empty() => book,
+            _ => return Ok(Vec::new()),
+        };
+        let mut legs: Vec<Leg> = pools.iter().map(Leg::Pool).collect();
+        let mut slots: Vec<u64> = pools.iter().map(|pool| pool.slot).collect();
+        legs.push(Leg::Book(book, market.taker_fee_bps as f64 / 10_000.0));
+        slots.push(book.last_update_slot());
+        if self.last_slots.as_ref() == Some(&slots) {
+            return Ok(Vec::new());
+        }
+
+        let lot_size = market.lot_size();
+        let mut best: Option<Opportunity> = None;
+        for step in 1..=self.config.size_steps {
+            let size = (self.config.max_size * step as f64 / self.config.size_steps as f64 / lot_size).floor() * lot_size;
+            if size < market.min_order_size {
+                continue;
+            }
+            for buy in &legs {
+                for sell in &legs {
+                    // Ровно одна нога на Serum: обмен между двумя пулами нечем подтвердить
+                    if (buy.venue() == Venue::Serum) == (sell.venue() == Venue::Serum) {
+                        continue;
+                    }
+                    let opportunity = match self.evaluate(*buy, *sell, size) {
+                        Some(opportunity) => opportunity,
+                        None => continue,
+                    };
+                    // Покупка должна укладываться в свободные средства
+                    if opportunity.size * opportunity.buy_price * (1.0 + buy.fee_rate()) > ctx.cash() {
+                        continue;
+                    }
+                    match &best {
+                        Some(best) if best.profit >= opportunity.profit => {}
+                        _ => best = Some(opportunity),
+                    }
+                }
+            }
+        }
+
+        let opportunity = match best {
+            Some(opportunity) if opportunity.profit >= self.config.min_profit => opportunity,
+            _ => return Ok(Vec::new()),
+        };
+
+        let buy = ctx.swap_signal(pair, TradeAction::Buy, opportunity.size, opportunity.buy_price, opportunity.buy)?;
+        let sell = ctx.swap_signal(pair, TradeAction::Sell, opportunity.size, opportunity.sell_price, opportunity.sell)?;
+        let legs = match (buy, sell) {
+            (Some(buy), Some(sell)) => vec![buy, sell],
+            _ => return Ok(Vec::new()),
+        };
+        let legs = legs.into_iter()
+            .map(|signal| signal.with_reason("expected_profit", format!("{:.4}", opportunity.profit)))
+            .collect();
+
+        info!(
+            "Arbitrage {}: buy {} {} on {:?} at {:.6}, sell on {:?} at {:.6}, expected profit {:.4}",
+            ctx.id, opportunity.size, pair.base(), opportunity.buy, opportunity.buy_price,
+            opportunity.sell, opportunity.sell_price, opportunity.profit
+        );
+        self.last_slots = Some(slots);
+        Ok(bundle(legs))
+    }
+}
+
+#[cfg(test)]
+#[path = "arbitrage_test.rs"]
+mod tests;
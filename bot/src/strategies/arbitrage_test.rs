@@ -0,0 +1,129 @@
+use super::*;
+use crate::dex::raydium::RaydiumPool;
+use crate::dex::AmmPool;
+use crate::market_registry::test_registry;
+use crate::price_feed::TokenPair;
+use crate::strategies::Allocation;
+use solana_sdk::pubkey::Pubkey;
+
+fn token_account(amount: u64) -> Vec<u8> {
+    let mut data = vec![0u8; 165];
+    data[64..72].copy_from_slice(&amount.to_le_bytes());
+    data
+}
+
+// Пул Raydium с комиссией 0.25% и резервами в UI-единицах
+fn raydium(amm: Pubkey, base: f64, quote: f64, slot: u64) -> PoolSnapshot {
+    let mut amm_data = vec![0u8; 752];
+    amm_data[176..184].copy_from_slice(&25u64.to_le_bytes());
+    amm_data[184..192].copy_from_slice(&10_000u64.to_le_bytes());
+    let pool = RaydiumPool::from_accounts(
+        "raydium",
+        &amm_data,
+        &token_account((base * 1e9) as u64),
+        &token_account((quote * 1e6) as u64),
+        9,
+        6,
+    ).unwrap();
+    PoolSnapshot { venue: Venue::Raydium { amm }, pool: AmmPool::Raydium(pool), slot }
+}
+
+// Стакан SOL/USDC: лучшие цены 99.9 / 100.0 по 10 SOL
+fn price_data(pool: PoolSnapshot) -> PriceData {
+    let markets = test_registry();
+    let params = markets.get(&TokenPair::new("SOL", "USDC")).unwrap().serum_params();
+    let mut price_data = PriceData::default();
+    price_data.set_order_book("SOL/USDC", L2Book::with_levels(params, &[(99_900, 100)], &[(100_000, 100)]));
+    price_data.set_pools("SOL/USDC", vec![pool]);
+    price_data
+}
+
+fn strategy() -> ArbitrageStrategy {
+    ArbitrageStrategy::from_params(&serde_json::json!({
+        "max_size": 2.0,
+        "size_steps": 4,
+        "min_profit": 0.5,
+        "tip": 0.05,
+        "slippage_bps": 10.0
+    })).unwrap()
+}
+
+fn tick(strategy: &mut ArbitrageStrategy, capital: f64, price_data: &PriceData) -> Vec<TradingSignal> {
+    let markets = test_registry();
+    let pairs = [TokenPair::new("SOL", "USDC")];
+    let allocation = Allocation::new(capital);
+    let ctx = StrategyContext { id: "arb-sol", pairs: &pairs, markets: &markets, allocation: &allocation, payer: Pubkey::new_unique() };
+    strategy.init(&ctx).unwrap();
+    strategy.on_tick(&ctx, price_data).unwrap()
+}
+
+#[test]
+fn test_buys_on_book_and_sells_to_pool() {
+    let amm = Pubkey::new_unique();
+    let mut strategy = strategy();
+    // В пуле SOL дороже на 3%
+    let price_data = price_data(raydium(amm, 1000.0, 103_000.0, 1));
+
+    let signals = tick(&mut strategy, 1000.0, &price_data);
+    assert_eq!(signals.len(), 2);
+    assert_eq!((signals[0].action, signals[0].venue), (TradeAction::Buy, Venue::Serum));
+    assert_eq!((signals[1].action, signals[1].venue), (TradeAction::Sell, Venue::Raydium { amm }));
+    // Обе ноги в одном бандле и на наибольший объем
+    assert!(signals[0].bundle_id.is_some());
+    assert_eq!(signals[0].bundle_id, signals[1].bundle_id);
+    assert_eq!(signals[0].amount, signals[1].amount);
+    assert_eq!(signals[0].amount, 2_000_000_000);
+    // Лимит покупки на 10 bps выше цены стакана
+    assert_eq!(signals[0].price, 100_100_000);
+
+    // Пока площадки не обновились, возможность не отправляется повторно
+    assert!(tick(&mut strategy, 1000.0, &price_data).is_empty());
+}
+
+#[test]
+fn test_buys_from_pool_and_sells_on_book() {
+    let amm = Pubkey::new_unique();
+    let mut strategy = strategy();
+    let signals = tick(&mut strategy, 1000.0, &price_data(raydium(amm, 1000.0, 97_000.0, 1)));
+
+    assert_eq!(signals.len(), 2);
+    assert_eq!((signals[0].action, signals[0].venue), (TradeAction::Buy, Venue::Raydium { amm }));
+    assert_eq!((signals[1].action, signals[1].venue), (TradeAction::Sell, Venue::Serum));
+}
+
+#[test]
+fn test_every_bundle_has_one_serum_leg() {
+    // Между пулами разница 6%, с каждым из них у стакана - около 3%
+    let cheap = raydium(Pubkey::new_unique(), 1000.0, 97_000.0, 1);
+    let dear = raydium(Pubkey::new_unique(), 1000.0, 103_000.0, 1);
+    let mut price_data = price_data(cheap.clone());
+    price_data.set_pools("SOL/USDC", vec![cheap.clone(), dear.clone()]);
+
+    let signals = tick(&mut strategy(), 1000.0, &price_data);
+    assert_eq!(signals.len(), 2);
+    assert_eq!(signals.iter().filter(|signal| signal.venue == Venue::Serum).count(), 1);
+
+    // Без стакана обмен между пулами не выставляется
+    let mut price_data = PriceData::default();
+    price_data.set_pools("SOL/USDC", vec![cheap, dear]);
+    assert!(tick(&mut strategy(), 1000.0, &price_data).is_empty());
+}
+
+#[test]
+fn test_edge_below_costs_is_ignored() {
+    // Разница 0.3% съедается комиссиями пула и стакана и проскальзыванием
+    let mut strategy = strategy();
+    assert!(tick(&mut strategy, 1000.0, &price_data(raydium(Pubkey::new_unique(), 1000.0, 100_300.0, 1))).is_empty());
+
+    // Без свободных средств на покупку
+    let mut strategy = self::strategy();
+    assert!(tick(&mut strategy, 50.0, &price_data(raydium(Pubkey::new_unique(), 1000.0, 103_000.0, 1))).is_empty());
+}
+
+#[test]
+fn test_quote_in_for_base() {
+    let pool = raydium(Pubkey::new_unique(), 1000.0, 100_000.0, 1).pool;
+    let quote_in = quote_in_for_base(&pool, 2.0).unwrap();
+    let out = pool.quote(TradeAction::Buy, quote_in).unwrap();
+    assert!(out >= 2.0 && out - 2.0 < 1e-9);
+}
@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+use std::error::Error;
+
+use log::{info, warn};
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::registry::parse_params;
+use super::schedule::{CronSchedule, ScheduleTracker};
+use super::{Strategy, StrategyContext};
+use crate::candles::Timeframe;
+use crate::jito_integration::{TradeAction, TradingSignal};
+use crate::price_feed::{PriceData, TokenPair};
+
+// Увеличенная покупка при просадке цены от недавнего максимума
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct DipLevel {
+    // Просадка как доля максимума, например 0.1 - на 10% ниже
+    pub drawdown: f64,
+    pub multiplier: f64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct DcaConfig {
+    // Расписание покупок в формате cron (UTC)
+    pub schedule: String,
+    // Сумма одной покупки в котируемом токене
+    pub quote_amount: f64,
+    // Применяется наибольший множитель среди достигнутых уровней
+    pub dips: Vec<DipLevel>,
+    // Максимум для просадки берется по последним `dip_lookback` закрытым барам
+    pub dip_timeframe: Timeframe,
+    pub dip_lookback: usize,
+    // Лимитная цена покупки выше текущей на этот допуск
+    pub max_slippage_bps: f64,
+    // Покупка пропускается, если интервал оракула шире этой доли цены
+    pub max_confidence_ratio: f64,
+}
+
+impl Default for DcaConfig {
+    fn default() -> Self {
+        Self {
+            schedule: "0 0 * * *".to_string(),
+            quote_amount: 10.0,
+            dips: Vec::new(),
+            dip_timeframe: Timeframe::Hour1,
+            dip_lookback: 24,
+            max_slippage_bps: 50.0,
+            max_confidence_ratio: 0.01,
+        }
+    }
+}
+
+// Усреднение: покупает фиксированную сумму каждой пары по расписанию.
+// При просадке от недавнего максимума сумма умножается на множитель уровня.
+pub struct DcaStrategy {
+    config: DcaConfig,
+    tracker: ScheduleTracker,
+    // Последняя пригодная для торговли цена и просадка по ключу пары
+    quotes: HashMap<String, (f64, f64)>,
+}
+
+impl DcaStrategy {
+    pub fn new(config: DcaConfig) -> Result<Self, Box<dyn Error>> {
+        if !(config.quote_amount.is_finite() && config.quote_amount > 0.0) {
+            return Err("quote_amount must be positive".into());
+        }
+        if config.dips.iter().any(|dip| !(dip.drawdown > 0.0 && dip.drawdown < 1.0 && dip.multiplier > 0.0)) {
+            return Err("dip levels need 0 < drawdown < 1 and a positive multiplier".into());
+        }
+        let tracker = ScheduleTracker::new(CronSchedule::parse(&config.schedule)?);
+        Ok(Self {
+            config,
+            tracker,
+            quotes: HashMap::new(),
+        })
+    }
+
+    pub fn from_params(params: &Value) -> Result<Self, Box<dyn Error>> {
+        Self::new(parse_params(params)?)
+    }
+
+    // Просадка текущей цены от максимума последних баров; 0, пока баров нет
+    fn drawdown(&self, pair: &TokenPair, price_data: &PriceData, price: f64) -> f64 {
+        let candles = price_data.get_candles(pair, self.config.dip_timeframe).unwrap_or_default();
+        let high = candles.iter()
+            .rev()
+            .take(self.config.dip_lookback)
+            .map(|candle| candle.high)
+            .fold(price, f64::max);
+        1.0 - price / high
+    }
+
+    fn multiplier(&self, drawdown: f64) -> f64 {
+        self.config.dips.iter()
+            .filter(|dip| drawdown >= dip.drawdown)
+            .map(|dip| dip.multiplier)
+            .fold(1.0, f64::max)
+    }
+}
+
+impl Strategy for DcaStrategy {
+    fn name(&self) -> &str {
+        "dca"
+    }
+
+    fn on_tick(&mut self, ctx: &StrategyContext, price_data: &PriceData) -> Result<Vec<TradingSignal>, Box<dyn Error>> {
+        for pair in ctx.pairs {
+            let usable = price_data.is_tradable(pair)
+                && price_data.get_confidence_ratio(pair)? <= self.config.max_confidence_ratio;
+            if usable {
+                let price = price_data.get_price(pair)?;
+                let drawdown = self.drawdown(pair, price_data, price);
+                self.quotes.insert(pair.to_string(), (price, drawdown));
+            } else {
+                // Устаревшая цена не должна попасть в покупку по расписанию
+                self.quotes.remove(&pair.to_string());
+            }
+        }
+        Ok(Vec::new())
+    }
+
+    fn timer_interval(&self) -> Option<i64> {
+        Some(60)
+    }
+
+    fn on_timer(&mut self, ctx: &StrategyContext, timestamp: i64) -> Result<Vec<TradingSignal>, Box<dyn Error>> {
+        if !self.tracker.due(timestamp) {
+            return Ok(Vec::new());
+        }
+
+        let mut cash = ctx.cash();
+        let mut signals = Vec::new();
+        for pair in ctx.pairs {
+            let (price, drawdown) = match self.quotes.get(&pair.to_string()) {
+                Some(&quote) => quote,
+                None => {
+                    warn!("DCA {}: skipping scheduled buy of {}: no tradable price", ctx.id, pair);
+                    continue;
+                }
+            };
+
+            let multiplier = self.multiplier(drawdown);
+            let amount = (self.config.quote_amount * multiplier).min(cash);
+            let limit_price = price * (1.0 + self.config.max_slippage_bps / 10_000.0);
+            if let Some(signal) = ctx.build_signal(pair, TradeAction::Buy, amount / limit_price, limit_price)? {
+                info!(
+                    "DCA {}: buying {:.2} of {} at {} (drawdown {:.4}, multiplier {})",
+                    ctx.id, amount, pair, price, drawdown, multiplier
+                );
+                cash -= amount;
+                signals.push(signal.with_reason("drawdown", format!("{:.4}", drawdown)).with_reason("multiplier", multiplier));
+            }
+        }
+        Ok(signals)
+    }
+}
+
+#[cfg(test)]
+#[path = "dca_test.rs"]
+mod tests;
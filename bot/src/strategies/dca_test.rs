@@ -0,0 +1,92 @@
+use super::*;
+use crate::candles::CandleBuilder;
+use crate::market_registry::test_registry;
+use crate::strategies::Allocation;
+use solana_sdk::pubkey::Pubkey;
+
+// 2024-01-01 00:00 UTC
+const NEW_YEAR: i64 = 1_704_067_200;
+
+fn strategy() -> DcaStrategy {
+    DcaStrategy::from_params(&serde_json::json!({
+        "schedule": "0 */6 * * *",
+        "quote_amount": 1000.0,
+        "dips": [
+            { "drawdown": 0.05, "multiplier": 2.0 },
+            { "drawdown": 0.1, "multiplier": 3.0 }
+        ],
+        "dip_timeframe": "1m",
+        "dip_lookback": 10,
+        "max_slippage_bps": 50.0
+    })).unwrap()
+}
+
+// Тик с ценой, после которого вызывается таймер в момент `timestamp`
+fn run(strategy: &mut DcaStrategy, allocation: &Allocation, price_data: &PriceData, timestamp: i64) -> Vec<TradingSignal> {
+    let markets = test_registry();
+    let pairs = [TokenPair::new("SOL", "USDC")];
+    let ctx = StrategyContext { id: "dca-sol", pairs: &pairs, markets: &markets, allocation, payer: Pubkey::new_unique() };
+    assert!(strategy.on_tick(&ctx, price_data).unwrap().is_empty());
+    strategy.on_timer(&ctx, timestamp).unwrap()
+}
+
+#[test]
+fn test_buys_fixed_amount_on_schedule() {
+    let mut strategy = strategy();
+    let allocation = Allocation::new(10_000.0);
+    let price_data = PriceData::with_prices(&[("SOL/USDC", 100.0)]);
+
+    // 1000 USDC по лимитной цене 100.5 - 9.9 SOL после округления до лота
+    let signals = run(&mut strategy, &allocation, &price_data, NEW_YEAR + 10);
+    assert_eq!(signals.len(), 1);
+    assert_eq!((signals[0].action, signals[0].price, signals[0].amount), (TradeAction::Buy, 100_500_000, 9_900_000_000));
+
+    // Та же минута и минуты вне расписания не повторяют покупку
+    assert!(run(&mut strategy, &allocation, &price_data, NEW_YEAR + 50).is_empty());
+    assert!(run(&mut strategy, &allocation, &price_data, NEW_YEAR + 3600).is_empty());
+    assert_eq!(run(&mut strategy, &allocation, &price_data, NEW_YEAR + 6 * 3600).len(), 1);
+}
+
+#[test]
+fn test_dip_multiplier() {
+    let mut strategy = strategy();
+    let allocation = Allocation::new(10_000.0);
+
+    // Максимум последних баров 100, текущая цена на 6% ниже
+    let mut candles = CandleBuilder::new();
+    for minute in 0..10 {
+        candles.on_tick("SOL/USDC", NEW_YEAR + minute * 60, if minute == 3 { 100.0 } else { 95.0 });
+    }
+    let mut price_data = PriceData::with_prices(&[("SOL/USDC", 94.0)]);
+    price_data.set_candles(&candles);
+
+    let signals = run(&mut strategy, &allocation, &price_data, NEW_YEAR + 6 * 3600);
+    assert_eq!(signals.len(), 1);
+    // 2000 USDC по 94.47 - 21.1 SOL
+    assert_eq!((signals[0].price, signals[0].amount), (94_470_000, 21_100_000_000));
+
+    assert_eq!(strategy.multiplier(0.04), 1.0);
+    assert_eq!(strategy.multiplier(0.12), 3.0);
+}
+
+#[test]
+fn test_skips_untrusted_price_and_limits_to_cash() {
+    let mut strategy = strategy();
+    let mut price_data = PriceData::with_prices(&[("SOL/USDC", 100.0)]);
+    price_data.set_confidence("SOL/USDC", 5.0);
+    assert!(run(&mut strategy, &Allocation::new(10_000.0), &price_data, NEW_YEAR).is_empty());
+
+    // Свободных средств меньше суммы покупки - покупаем на остаток
+    let mut strategy = self::strategy();
+    let price_data = PriceData::with_prices(&[("SOL/USDC", 100.0)]);
+    let signals = run(&mut strategy, &Allocation::new(300.0), &price_data, NEW_YEAR);
+    assert_eq!(signals[0].amount, 2_900_000_000);
+}
+
+#[test]
+fn test_invalid_params() {
+    assert!(DcaStrategy::from_params(&serde_json::json!({ "schedule": "every day" })).is_err());
+    assert!(DcaStrategy::from_params(&serde_json::json!({ "quote_amount": 0.0 })).is_err());
+    assert!(DcaStrategy::from_params(&serde_json::json!({ "dips": [{ "drawdown": 1.5, "multiplier": 2.0 }] })).is_err());
+    assert!(DcaStrategy::from_params(&Value::Null).is_ok());
+}
@@ -0,0 +1,285 @@
+use std::collections::HashMap;
+use std::error::Error;
+
+use log::info;
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::registry::{parse_params, StrategyRegistry};
+use super::{CandleCursor, Fill, Strategy, StrategyContext};
+use crate::candles::Timeframe;
+use crate::indicators::Adx;
+use crate::jito_integration::{OrderAction, TradeAction, TradingSignal};
+use crate::price_feed::{PriceData, TokenPair};
+
+// Правило объединения голосов дочерних стратегий
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CombineRule {
+    // Все участвующие стратегии голосуют за одно направление
+    Unanimous,
+    // За направление больше половины участвующих стратегий
+    #[default]
+    Majority,
+    // Взвешенная сумма голосов (+1 покупка, -1 продажа) не меньше порога
+    Weighted,
+}
+
+// Рыночный режим по ADX
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Regime {
+    #[default]
+    Any,
+    Trend,
+    Range,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChildConfig {
+    // Имя стратегии в реестре
+    pub strategy: String,
+    #[serde(default)]
+    pub params: Value,
+    #[serde(default = "default_weight")]
+    pub weight: f64,
+    // Режим, в котором голос стратегии учитывается
+    #[serde(default)]
+    pub regime: Regime,
+}
+
+fn default_weight() -> f64 {
+    1.0
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RegimeFilter {
+    pub timeframe: Timeframe,
+    pub period: usize,
+    // ADX не ниже порога - тренд, ниже - боковик
+    pub trend_adx: f64,
+}
+
+impl Default for RegimeFilter {
+    fn default() -> Self {
+        Self {
+            timeframe: Timeframe::Min5,
+            period: 14,
+            trend_adx: 25.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct EnsembleConfig {
+    pub children: Vec<ChildConfig>,
+    pub rule: CombineRule,
+    // Порог для правила weighted: доля суммы весов участвующих стратегий
+    pub threshold: f64,
+    // Без фильтра все стратегии участвуют в любом режиме
+    pub regime_filter: Option<RegimeFilter>,
+}
+
+struct Child {
+    name: String,
+    strategy: Box<dyn Strategy>,
+    weight: f64,
+    regime: Regime,
+}
+
+// Голос дочерней стратегии по паре на текущем тике
+struct Vote<'a> {
+    child: &'a Child,
+    // Сигнал стратегии; None - стратегия воздержалась
+    signal: Option<TradingSignal>,
+    // Голос учитывается только в режиме стратегии
+    active: bool,
+}
+
+impl<'a> Vote<'a> {
+    fn direction(&self) -> f64 {
+        match &self.signal {
+            Some(signal) if self.active && signal.action == TradeAction::Buy => 1.0,
+            Some(_) if self.active => -1.0,
+            _ => 0.0,
+        }
+    }
+}
+
+// Ансамбль: на каждом тике опрашивает дочерние стратегии и исполняет их
+// сигнал по паре, только если за него проголосовало достаточно стратегий.
+// Ордер берется у первой в порядке конфигурации стратегии, голосовавшей за
+// принятое направление. Фильтр режима по ADX исключает из голосования
+// трендовые стратегии в боковике и наоборот.
+pub struct EnsembleStrategy {
+    config: EnsembleConfig,
+    children: Vec<Child>,
+    // ADX по ключу пары для фильтра режима
+    adx: HashMap<String, (CandleCursor, Adx)>,
+}
+
+impl EnsembleStrategy {
+    pub fn new(config: EnsembleConfig, registry: &StrategyRegistry) -> Result<Self, Box<dyn Error>> {
+        if config.children.len() < 2 {
+            return Err("ensemble needs at least two children".into());
+        }
+        if config.children.iter().any(|child| child.weight <= 0.0) {
+            return Err("child weights must be positive".into());
+        }
+        if config.rule == CombineRule::Weighted && !(config.threshold > 0.0 && config.threshold <= 1.0) {
+            return Err("threshold must be between 0 and 1 for the weighted rule".into());
+        }
+        if let Some(filter) = &config.regime_filter {
+            if filter.period == 0 {
+                return Err("regime_filter.period must be positive".into());
+            }
+        }
+
+        let mut children = Vec::with_capacity(config.children.len());
+        for (index, child) in config.children.iter().enumerate() {
+            let strategy = registry.create(&child.strategy, &child.params)?;
+            // Голосует только направление новой сделки: таймеры, короткие позиции,
+            // ордера из on_fill и собственные стоящие ордера ребенка ансамбль не передает
+            if strategy.timer_interval().is_some()
+                || strategy.allows_short()
+                || strategy.trades_on_fill()
+                || strategy.manages_resting_orders()
+            {
+                return Err(format!("{} cannot be an ensemble child", child.strategy).into());
+            }
+            children.push(Child {
+                name: format!("{}#{}", child.strategy, index),
+                strategy,
+                weight: child.weight,
+                regime: child.regime,
+            });
+        }
+        Ok(Self {
+            config,
+            children,
+            adx: HashMap::new(),
+        })
+    }
+
+    pub fn from_params(params: &Value, registry: &StrategyRegistry) -> Result<Self, Box<dyn Error>> {
+        Self::new(parse_params(params)?, registry)
+    }
+
+    // Текущий режим пары; None, пока ADX не накопил достаточно баров
+    fn regime(&mut self, pair: &TokenPair, price_data: &PriceData) -> Result<Option<Regime>, Box<dyn Error>> {
+        let filter = match &self.config.regime_filter {
+            Some(filter) => filter,
+            None => return Ok(Some(Regime::Any)),
+        };
+        let period = filter.period;
+        let (cursor, adx) = self.adx.entry(pair.to_string())
+            .or_insert_with(|| (CandleCursor::default(), Adx::new(period)));
+        for candle in cursor.new_candles(price_data, pair, filter.timeframe)? {
+            adx.update(&candle);
+        }
+        Ok(adx.value().map(|value| if value.adx >= filter.trend_adx { Regime::Trend } else { Regime::Range }))
+    }
+
+    // Направление, принятое по правилу объединения, и итоговая оценка голосования
+    fn decide(&self, votes: &[Vote]) -> (Option<TradeAction>, f64) {
+        let active: Vec<&Vote> = votes.iter().filter(|vote| vote.active).collect();
+        if active.is_empty() {
+            return (None, 0.0);
+        }
+        let total_weight: f64 = active.iter().map(|vote| vote.child.weight).sum();
+        let score = active.iter().map(|vote| vote.child.weight * vote.direction()).sum::<f64>() / total_weight;
+        let buys = active.iter().filter(|vote| vote.direction() > 0.0).count();
+        let sells = active.iter().filter(|vote| vote.direction() < 0.0).count();
+
+        let action = match self.config.rule {
+            CombineRule::Unanimous if buys == active.len() => Some(TradeAction::Buy),
+            CombineRule::Unanimous if sells == active.len() => Some(TradeAction::Sell),
+            CombineRule::Majority if buys * 2 > active.len() => Some(TradeAction::Buy),
+            CombineRule::Majority if sells * 2 > active.len() => Some(TradeAction::Sell),
+            CombineRule::Weighted if score >= self.config.threshold => Some(TradeAction::Buy),
+            CombineRule::Weighted if score <= -self.config.threshold => Some(TradeAction::Sell),
+            _ => None,
+        };
+        (action, score)
+    }
+}
+
+impl Strategy for EnsembleStrategy {
+    fn name(&self) -> &str {
+        "ensemble"
+    }
+
+    fn init(&mut self, ctx: &StrategyContext) -> Result<(), Box<dyn Error>> {
+        for child in &mut self.children {
+            child.strategy.init(ctx).map_err(|e| format!("{}: {}", child.name, e))?;
+        }
+        Ok(())
+    }
+
+    fn on_tick(&mut self, ctx: &StrategyContext, price_data: &PriceData) -> Result<Vec<TradingSignal>, Box<dyn Error>> {
+        // Все стратегии получают каждый тик, чтобы их индикаторы не отставали
+        let mut child_signals = Vec::with_capacity(self.children.len());
+        for child in &mut self.children {
+            child_signals.push(child.strategy.on_tick(ctx, price_data).map_err(|e| format!("{}: {}", child.name, e))?);
+        }
+
+        let mut signals = Vec::new();
+        for pair in ctx.pairs {
+            let regime = match self.regime(pair, price_data)? {
+                Some(regime) => regime,
+                None => continue,
+            };
+            let market = ctx.markets.get(pair)?.dex_market;
+            let votes: Vec<Vote> = self.children.iter().zip(&child_signals)
+                .map(|(child, child_signals)| Vote {
+                    child,
+                    signal: child_signals.iter()
+                        .find(|signal| signal.market == market && signal.order_action == OrderAction::Place)
+                        .cloned(),
+                    active: child.regime == Regime::Any || regime == Regime::Any || child.regime == regime,
+                })
+                .collect();
+            if votes.iter().all(|vote| vote.signal.is_none()) {
+                continue;
+            }
+
+            let (action, score) = self.decide(&votes);
+            let contributions: Vec<String> = votes.iter()
+                .map(|vote| {
+                    let state = if vote.active { format!("{:+}", vote.direction()) } else { "off".to_string() };
+                    format!("{} {} x {}", vote.child.name, state, vote.child.weight)
+                })
+                .collect();
+            info!(
+                "Ensemble {} {}: {:?} rule, {:?} regime, score {:.2} -> {:?} [{}]",
+                ctx.id, pair, self.config.rule, regime, score, action, contributions.join(", ")
+            );
+
+            // Уверенность ансамбля - взвешенный перевес голосов за принятое направление
+            let signal = votes.into_iter()
+                .filter(|vote| vote.active)
+                .filter_map(|vote| vote.signal)
+                .find(|signal| Some(signal.action) == action)
+                .map(|signal| TradingSignal {
+                    confidence: score.abs().min(1.0),
+                    ..signal.with_reason("score", format!("{:.2}", score)).with_reason("votes", contributions.join(", "))
+                });
+            signals.extend(signal);
+        }
+        Ok(signals)
+    }
+
+    fn on_fill(&mut self, ctx: &StrategyContext, fill: &Fill) -> Result<Vec<TradingSignal>, Box<dyn Error>> {
+        // Сделки ансамбля видят все стратегии; их собственные сигналы из on_fill не исполняются
+        for child in &mut self.children {
+            child.strategy.on_fill(ctx, fill).map_err(|e| format!("{}: {}", child.name, e))?;
+        }
+        Ok(Vec::new())
+    }
+}
+
+#[cfg(test)]
+#[path = "ensemble_test.rs"]
+mod tests;
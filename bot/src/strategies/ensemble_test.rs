@@ -0,0 +1,138 @@
+use super::*;
+use crate::candles::CandleBuilder;
+use crate::market_registry::test_registry;
+use crate::strategies::Allocation;
+use solana_sdk::pubkey::Pubkey;
+
+// Голосует на каждом тике заданным направлением; по объему видно, чей ордер исполнен
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct FixedVote {
+    action: Option<TradeAction>,
+    size: f64,
+}
+
+impl Strategy for FixedVote {
+    fn name(&self) -> &str {
+        "fixed_vote"
+    }
+
+    fn on_tick(&mut self, ctx: &StrategyContext, _price_data: &PriceData) -> Result<Vec<TradingSignal>, Box<dyn Error>> {
+        match self.action {
+            Some(action) => Ok(ctx.build_signal(&ctx.pairs[0], action, self.size, 20.0)?.into_iter().collect()),
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+fn ensemble(config: Value) -> EnsembleStrategy {
+    let mut registry = StrategyRegistry::new();
+    registry.register("fixed_vote", |params, _| Ok(Box::new(parse_params::<FixedVote>(params)?)));
+    EnsembleStrategy::new(serde_json::from_value(config).unwrap(), &registry).unwrap()
+}
+
+fn tick(strategy: &mut EnsembleStrategy, price_data: &PriceData) -> Vec<TradingSignal> {
+    let markets = test_registry();
+    let pairs = [TokenPair::new("SOL", "USDC")];
+    let allocation = Allocation::new(1000.0);
+    let ctx = StrategyContext { id: "ensemble-sol", pairs: &pairs, markets: &markets, allocation: &allocation, payer: Pubkey::new_unique() };
+    strategy.init(&ctx).unwrap();
+    strategy.on_tick(&ctx, price_data).unwrap()
+}
+
+// Объемы и направления исполненных ордеров
+fn orders(signals: &[TradingSignal]) -> Vec<(TradeAction, u64)> {
+    signals.iter().map(|signal| (signal.action, signal.amount)).collect()
+}
+
+#[test]
+fn test_majority_and_unanimous_rules() {
+    let children = serde_json::json!([
+        { "strategy": "fixed_vote", "params": { "action": "Sell", "size": 3.0 } },
+        { "strategy": "fixed_vote", "params": { "action": "Buy", "size": 1.0 } },
+        { "strategy": "fixed_vote", "params": { "action": "Buy", "size": 2.0 } }
+    ]);
+
+    // Две покупки из трех: ордер первой купившей стратегии
+    let mut majority = ensemble(serde_json::json!({ "children": children, "rule": "majority" }));
+    assert_eq!(orders(&tick(&mut majority, &PriceData::default())), vec![(TradeAction::Buy, 1_000_000_000)]);
+
+    let mut unanimous = ensemble(serde_json::json!({ "children": children, "rule": "unanimous" }));
+    assert!(tick(&mut unanimous, &PriceData::default()).is_empty());
+}
+
+#[test]
+fn test_weighted_score_threshold() {
+    let config = |threshold: f64| serde_json::json!({
+        "children": [
+            { "strategy": "fixed_vote", "params": { "action": "Buy", "size": 1.0 }, "weight": 1.0 },
+            { "strategy": "fixed_vote", "params": { "action": "Sell", "size": 3.0 }, "weight": 3.0 },
+            { "strategy": "fixed_vote", "weight": 1.0 }
+        ],
+        "rule": "weighted",
+        "threshold": threshold
+    });
+
+    // Оценка (1 - 3) / 5 = -0.4
+    let signals = tick(&mut ensemble(config(0.4)), &PriceData::default());
+    assert_eq!(orders(&signals), vec![(TradeAction::Sell, 3_000_000_000)]);
+    assert!((signals[0].confidence - 0.4).abs() < 1e-9);
+    assert_eq!(signals[0].reason.get("score").map(String::as_str), Some("-0.40"));
+    assert!(tick(&mut ensemble(config(0.5)), &PriceData::default()).is_empty());
+}
+
+#[test]
+fn test_regime_filter_selects_children() {
+    let config = serde_json::json!({
+        "children": [
+            { "strategy": "fixed_vote", "params": { "action": "Buy", "size": 1.0 }, "regime": "trend" },
+            { "strategy": "fixed_vote", "params": { "action": "Sell", "size": 2.0 }, "regime": "range" }
+        ],
+        "rule": "unanimous",
+        "regime_filter": { "timeframe": "1m", "period": 5, "trend_adx": 25.0 }
+    });
+    let price_data = |prices: &[f64]| {
+        let mut candles = CandleBuilder::new();
+        for (minute, &price) in prices.iter().enumerate() {
+            candles.on_tick("SOL/USDC", minute as i64 * 60, price);
+        }
+        candles.advance(prices.len() as i64 * 60);
+        let mut price_data = PriceData::default();
+        price_data.set_candles(&candles);
+        price_data
+    };
+
+    // Пока ADX не готов, режим неизвестен и ансамбль не торгует
+    assert!(tick(&mut ensemble(config.clone()), &price_data(&[100.0, 101.0])).is_empty());
+
+    // Устойчивый рост - тренд: голосует только трендовая стратегия
+    let trend: Vec<f64> = (0..12).map(|minute| 100.0 + minute as f64).collect();
+    assert_eq!(orders(&tick(&mut ensemble(config.clone()), &price_data(&trend))), vec![(TradeAction::Buy, 1_000_000_000)]);
+
+    // Колебания на месте - боковик: голосует только стратегия для боковика
+    let range: Vec<f64> = (0..16).map(|minute| [101.0, 103.0, 100.0, 102.0][minute % 4]).collect();
+    assert_eq!(orders(&tick(&mut ensemble(config), &price_data(&range))), vec![(TradeAction::Sell, 2_000_000_000)]);
+}
+
+#[test]
+fn test_children_come_from_runner_registry() {
+    let mut registry = StrategyRegistry::with_builtin();
+    registry.register("fixed_vote", |params, _| Ok(Box::new(parse_params::<FixedVote>(params)?)));
+    let children = serde_json::json!({"children": [
+        { "strategy": "fixed_vote", "params": { "action": "Buy", "size": 1.0 } },
+        { "strategy": "fixed_vote", "params": { "action": "Buy", "size": 1.0 } }
+    ]});
+    // Стратегия, зарегистрированная только в этом реестре, доступна ансамблю
+    assert!(registry.create("ensemble", &children).is_ok());
+
+    // Дети со своими стоящими ордерами отменяют и заменяют их в обход голосования,
+    // а вторая нога pairs выставляется из on_fill, который ансамбль не исполняет
+    for strategy in ["grid", "market_maker", "pairs"] {
+        let params = serde_json::json!({"children": [
+            { "strategy": "fixed_vote" },
+            { "strategy": strategy, "params": { "lower": 10.0, "upper": 14.0, "levels": 5, "size": 1.0 } }
+        ]});
+        let error = registry.create("ensemble", &params).err().unwrap().to_string();
+        assert!(error.contains("cannot be an ensemble child"), "{}", error);
+    }
+}
@@ -0,0 +1,329 @@
+use std::error::Error;
+
+use log::info;
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::registry::parse_params;
+use super::{Fill, Strategy, StrategyContext};
+use crate::jito_integration::{TradeAction, TradingSignal};
+use crate::price_feed::{PriceData, TokenPair};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GridSpacing {
+    // Одинаковый шаг в котируемом токене
+    Arithmetic,
+    // Одинаковый шаг в процентах
+    Geometric,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GridConfig {
+    pub lower: f64,
+    pub upper: f64,
+    // Число ценовых уровней, включая границы диапазона
+    pub levels: usize,
+    #[serde(default = "default_spacing")]
+    pub spacing: GridSpacing,
+    // Объем ордера на каждом уровне в базовом токене
+    pub size: f64,
+    // Через сколько слотов неисполненный ордер уровня снимается и выставляется заново:
+    // ордер, бандл которого так и не попал в блок, не должен занимать уровень вечно
+    #[serde(default = "default_order_timeout_slots")]
+    pub order_timeout_slots: u64,
+}
+
+fn default_spacing() -> GridSpacing {
+    GridSpacing::Arithmetic
+}
+
+// Около часа при слоте в 400 мс
+fn default_order_timeout_slots() -> u64 {
+    9_000
+}
+
+impl GridConfig {
+    fn validate(&self) -> Result<(), Box<dyn Error>> {
+        if !(self.lower > 0.0 && self.upper > self.lower) {
+            return Err("grid range must satisfy 0 < lower < upper".into());
+        }
+        if self.levels < 2 {
+            return Err("grid needs at least 2 levels".into());
+        }
+        if !(self.size.is_finite() && self.size > 0.0) {
+            return Err("grid level size must be positive".into());
+        }
+        if self.order_timeout_slots == 0 {
+            return Err("grid order timeout must be positive".into());
+        }
+        Ok(())
+    }
+
+    pub fn prices(&self) -> Vec<f64> {
+        let steps = (self.levels - 1) as f64;
+        (0..self.levels)
+            .map(|i| match self.spacing {
+                GridSpacing::Arithmetic => self.lower + (self.upper - self.lower) * i as f64 / steps,
+                GridSpacing::Geometric => self.lower * (self.upper / self.lower).powf(i as f64 / steps),
+            })
+            .collect()
+    }
+}
+
+// Post-only ордер ячейки в стакане и его исполненная часть
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Order {
+    client_order_id: u64,
+    placed_slot: u64,
+    size: f64,
+    filled: f64,
+    // Сумма цена * объем по исполнениям
+    notional: f64,
+    fee: f64,
+    // Слот отправки отмены: до ее подтверждения ордер может исполниться
+    canceled_slot: Option<u64>,
+}
+
+impl Order {
+    fn new(client_order_id: u64, placed_slot: u64, size: f64) -> Self {
+        Self { client_order_id, placed_slot, size, filled: 0.0, notional: 0.0, fee: 0.0, canceled_slot: None }
+    }
+
+    fn remaining(&self) -> f64 {
+        self.size - self.filled
+    }
+}
+
+// Объем, купленный на нижнем уровне ячейки
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Lot {
+    size: f64,
+    buy_price: f64,
+    buy_fee: f64,
+}
+
+impl Lot {
+    // Купленная часть ордера на покупку; None, если ничего не исполнилось
+    fn bought(order: &Order) -> Option<Self> {
+        if order.filled <= 1e-9 {
+            return None;
+        }
+        Some(Self { size: order.filled, buy_price: order.notional / order.filled, buy_fee: order.fee })
+    }
+}
+
+// Ячейка сетки между двумя соседними уровнями: либо покупаем на нижнем
+// уровне, либо держим купленный объем и продаем его на верхнем
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Cell {
+    WaitingBuy,
+    BuyResting(Order),
+    WaitingSell(Lot),
+    SellResting(Order, Lot),
+}
+
+impl Cell {
+    fn order(&self) -> Option<&Order> {
+        match self {
+            Cell::BuyResting(order) | Cell::SellResting(order, _) => Some(order),
+            Cell::WaitingBuy | Cell::WaitingSell(_) => None,
+        }
+    }
+}
+
+// Сеточная стратегия для пары в боковом диапазоне. На каждом уровне ниже цены
+// стоит post-only покупка; после ее исполнения на соседнем уровне выше
+// выставляется post-only продажа купленного объема. Ордер, отклоненный биржей,
+// освобождает уровень сразу, а не исполненный за `order_timeout_slots` - после
+// подтверждения отмены.
+pub struct GridStrategy {
+    config: GridConfig,
+    prices: Vec<f64>,
+    cells: Vec<Cell>,
+    round_trips: usize,
+    realized_profit: f64,
+}
+
+impl GridStrategy {
+    pub fn new(config: GridConfig) -> Result<Self, Box<dyn Error>> {
+        config.validate()?;
+        let prices = config.prices();
+        Ok(Self {
+            cells: vec![Cell::WaitingBuy; prices.len() - 1],
+            prices,
+            config,
+            round_trips: 0,
+            realized_profit: 0.0,
+        })
+    }
+
+    pub fn from_params(params: &Value) -> Result<Self, Box<dyn Error>> {
+        Self::new(parse_params::<Option<GridConfig>>(params)?.ok_or("grid params are required")?)
+    }
+
+    fn pair<'a>(&self, ctx: &'a StrategyContext) -> &'a TokenPair {
+        &ctx.pairs[0]
+    }
+
+    fn cell_of_order(&self, client_order_id: u64) -> Option<usize> {
+        self.cells.iter().position(|cell| cell.order().map(|order| order.client_order_id) == Some(client_order_id))
+    }
+
+    // Ячейка без ордера: исполненная часть покупки ждет продажи,
+    // непроданный остаток лота - новой продажи
+    fn release(&mut self, i: usize, ctx: &StrategyContext) {
+        self.cells[i] = match self.cells[i] {
+            Cell::BuyResting(order) => Lot::bought(&order).map_or(Cell::WaitingBuy, Cell::WaitingSell),
+            Cell::SellResting(order, lot) => {
+                if order.filled > 1e-9 {
+                    self.record_profit(ctx, &order, &lot);
+                }
+                let size = lot.size - order.filled;
+                // Проданная часть лота уносит свою долю комиссии покупки
+                Cell::WaitingSell(Lot { size, buy_fee: lot.buy_fee * size / lot.size, ..lot })
+            }
+            cell => cell,
+        };
+    }
+
+    fn record_profit(&mut self, ctx: &StrategyContext, sell: &Order, lot: &Lot) {
+        let buy_fee = lot.buy_fee * sell.filled / lot.size;
+        let profit = sell.notional - lot.buy_price * sell.filled - buy_fee - sell.fee;
+        self.realized_profit += profit;
+        info!(
+            "Grid {}: sold {} bought at {} for {}, profit {:.4}, total {:.4} over {} round trips",
+            ctx.id, sell.filled, lot.buy_price, sell.notional / sell.filled, profit, self.realized_profit, self.round_trips
+        );
+    }
+}
+
+impl Strategy for GridStrategy {
+    fn name(&self) -> &str {
+        "grid"
+    }
+
+    fn manages_resting_orders(&self) -> bool {
+        true
+    }
+
+    fn init(&mut self, ctx: &StrategyContext) -> Result<(), Box<dyn Error>> {
+        if ctx.pairs.len() != 1 {
+            return Err("grid strategy trades exactly one pair".into());
+        }
+        Ok(())
+    }
+
+    fn on_tick(&mut self, ctx: &StrategyContext, price_data: &PriceData) -> Result<Vec<TradingSignal>, Box<dyn Error>> {
+        let pair = self.pair(ctx);
+        if !price_data.is_tradable(pair) {
+            return Ok(Vec::new());
+        }
+        let price = price_data.get_price(pair)?;
+        let slot = price_data.slot();
+        let mut signals = Vec::new();
+
+        // Просроченные ордера снимаются. Уровень выставляется заново после
+        // подтверждения отмены, а неподтвержденная отмена повторяется через тот же срок.
+        for i in 0..self.cells.len() {
+            let (action, order) = match &mut self.cells[i] {
+                Cell::BuyResting(order) => (TradeAction::Buy, order),
+                Cell::SellResting(order, _) => (TradeAction::Sell, order),
+                _ => continue,
+            };
+            if slot >= order.canceled_slot.unwrap_or(order.placed_slot) + self.config.order_timeout_slots {
+                signals.push(ctx.cancel_signal(pair, action, order.client_order_id)?);
+                order.canceled_slot = Some(slot);
+            }
+        }
+
+        // Стоящие ордера уже держат свою часть средств
+        let mut cash = ctx.cash();
+        let mut position = ctx.position(pair);
+        for (i, cell) in self.cells.iter().enumerate() {
+            match cell {
+                Cell::BuyResting(order) => cash -= self.prices[i] * order.remaining(),
+                Cell::SellResting(order, _) => position -= order.remaining(),
+                _ => {}
+            }
+        }
+
+        // Сначала уровни, ближайшие к цене сверху вниз; post-only ордер не должен пересекать цену
+        for i in (0..self.cells.len()).rev() {
+            let (buy_level, sell_level) = (self.prices[i], self.prices[i + 1]);
+            match self.cells[i] {
+                Cell::WaitingBuy if price > buy_level => {
+                    let notional = buy_level * self.config.size;
+                    if notional > cash {
+                        continue;
+                    }
+                    if let Some(signal) = ctx.quote_signal(pair, TradeAction::Buy, self.config.size, buy_level)? {
+                        cash -= notional;
+                        self.cells[i] = Cell::BuyResting(Order::new(signal.client_order_id, slot, self.config.size));
+                        signals.push(signal);
+                    }
+                }
+                Cell::WaitingSell(lot) if price < sell_level => {
+                    if lot.size > position + 1e-9 {
+                        continue;
+                    }
+                    if let Some(signal) = ctx.quote_signal(pair, TradeAction::Sell, lot.size, sell_level)? {
+                        position -= lot.size;
+                        self.cells[i] = Cell::SellResting(Order::new(signal.client_order_id, slot, lot.size), lot);
+                        signals.push(signal);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(signals)
+    }
+
+    fn on_fill(&mut self, ctx: &StrategyContext, fill: &Fill) -> Result<Vec<TradingSignal>, Box<dyn Error>> {
+        let i = match self.cell_of_order(fill.client_order_id) {
+            Some(i) => i,
+            None => return Ok(Vec::new()),
+        };
+
+        match &mut self.cells[i] {
+            Cell::BuyResting(order) | Cell::SellResting(order, _) => {
+                order.filled += fill.size;
+                order.notional += fill.price * fill.size;
+                order.fee += fill.fee;
+            }
+            _ => {}
+        }
+
+        self.cells[i] = match self.cells[i] {
+            // Купили на нижнем уровне - на следующем тике выставляется продажа на верхнем
+            Cell::BuyResting(order) if order.remaining() <= 1e-9 => Lot::bought(&order).map_or(Cell::WaitingBuy, Cell::WaitingSell),
+            // Продали на верхнем уровне - круг завершен, снова ждем покупки
+            Cell::SellResting(order, lot) if order.remaining() <= 1e-9 => {
+                self.round_trips += 1;
+                self.record_profit(ctx, &order, &lot);
+                Cell::WaitingBuy
+            }
+            cell => cell,
+        };
+        Ok(Vec::new())
+    }
+
+    fn on_reject(&mut self, ctx: &StrategyContext, client_order_id: u64) -> Result<(), Box<dyn Error>> {
+        if let Some(i) = self.cell_of_order(client_order_id) {
+            self.release(i, ctx);
+        }
+        Ok(())
+    }
+
+    fn on_canceled(&mut self, ctx: &StrategyContext, client_order_id: u64) -> Result<(), Box<dyn Error>> {
+        if let Some(i) = self.cell_of_order(client_order_id) {
+            self.release(i, ctx);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[path = "grid_test.rs"]
+mod tests;
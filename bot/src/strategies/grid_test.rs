@@ -0,0 +1,197 @@
+use super::*;
+use crate::jito_integration::{OrderAction, TimeInForce};
+use crate::market_registry::test_registry;
+use crate::strategies::Allocation;
+use solana_sdk::pubkey::Pubkey;
+
+fn grid(spacing: &str) -> GridStrategy {
+    GridStrategy::from_params(&serde_json::json!({
+        "lower": 10.0,
+        "upper": 14.0,
+        "levels": 5,
+        "spacing": spacing,
+        "size": 1.0,
+        "order_timeout_slots": 100
+    })).unwrap()
+}
+
+fn tick(strategy: &mut GridStrategy, ctx: &StrategyContext, price: f64) -> Vec<TradingSignal> {
+    tick_at(strategy, ctx, price, 0)
+}
+
+fn tick_at(strategy: &mut GridStrategy, ctx: &StrategyContext, price: f64, slot: u64) -> Vec<TradingSignal> {
+    let mut price_data = PriceData::with_prices(&[("SOL/USDC", price)]);
+    price_data.set_slot(slot);
+    strategy.on_tick(ctx, &price_data).unwrap()
+}
+
+fn fill(signal: &TradingSignal, price: f64, size: f64) -> Fill {
+    Fill {
+        pair: TokenPair::new("SOL", "USDC"),
+        client_order_id: signal.client_order_id,
+        action: signal.action,
+        price,
+        size,
+        fee: 0.01 * size,
+        timestamp: 0,
+    }
+}
+
+fn quotes(signals: &[TradingSignal]) -> Vec<(TradeAction, u64)> {
+    signals.iter().map(|signal| (signal.action, signal.price)).collect()
+}
+
+#[test]
+fn test_level_prices() {
+    assert_eq!(grid("arithmetic").prices, vec![10.0, 11.0, 12.0, 13.0, 14.0]);
+
+    // Постоянное отношение соседних уровней: 40 / 10 = 2^2
+    let config = GridConfig { lower: 10.0, upper: 40.0, levels: 5, spacing: GridSpacing::Geometric, size: 1.0, order_timeout_slots: 100 };
+    let expected = [10.0, 14.142136, 20.0, 28.284271, 40.0];
+    for (price, expected) in config.prices().iter().zip(expected.iter()) {
+        assert!((price - expected).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn test_round_trip_places_opposite_order() {
+    let markets = test_registry();
+    let pairs = [TokenPair::new("SOL", "USDC")];
+    let mut allocation = Allocation::new(100.0);
+    let mut strategy = grid("arithmetic");
+
+    let buy = {
+        let ctx = StrategyContext { id: "grid-sol", pairs: &pairs, markets: &markets, allocation: &allocation, payer: Pubkey::new_unique() };
+        strategy.init(&ctx).unwrap();
+        // Покупки стоят на всех уровнях ниже цены, ближайший первым
+        let signals = tick(&mut strategy, &ctx, 12.5);
+        assert_eq!(quotes(&signals), vec![(TradeAction::Buy, 12_000_000), (TradeAction::Buy, 11_000_000), (TradeAction::Buy, 10_000_000)]);
+        assert!(signals.iter().all(|signal| signal.time_in_force == TimeInForce::PostOnly));
+        // Ордера уже стоят, повторно не выставляются
+        assert!(tick(&mut strategy, &ctx, 12.4).is_empty());
+        signals[0].clone()
+    };
+
+    // Покупка на 12 исполнилась в два приема
+    let sell = {
+        for part in [0.4, 0.6] {
+            let buy_fill = fill(&buy, 12.0, part);
+            allocation.apply_fill(&buy_fill);
+            let ctx = StrategyContext { id: "grid-sol", pairs: &pairs, markets: &markets, allocation: &allocation, payer: Pubkey::new_unique() };
+            strategy.on_fill(&ctx, &buy_fill).unwrap();
+        }
+        let ctx = StrategyContext { id: "grid-sol", pairs: &pairs, markets: &markets, allocation: &allocation, payer: Pubkey::new_unique() };
+        // После покупки продажа купленного объема выставляется на соседнем уровне 13
+        let signals = tick(&mut strategy, &ctx, 11.8);
+        assert_eq!(quotes(&signals), vec![(TradeAction::Sell, 13_000_000)]);
+        assert_eq!(signals[0].amount, 1_000_000_000);
+        signals[0].clone()
+    };
+
+    let sell_fill = fill(&sell, 13.0, 1.0);
+    allocation.apply_fill(&sell_fill);
+    let ctx = StrategyContext { id: "grid-sol", pairs: &pairs, markets: &markets, allocation: &allocation, payer: Pubkey::new_unique() };
+    strategy.on_fill(&ctx, &sell_fill).unwrap();
+    assert_eq!(strategy.round_trips, 1);
+    assert!((strategy.realized_profit - 0.98).abs() < 1e-9);
+
+    // Ячейка снова покупает на уровне 12, когда цена выше него
+    assert!(tick(&mut strategy, &ctx, 11.9).is_empty());
+    assert_eq!(quotes(&tick(&mut strategy, &ctx, 12.3)), vec![(TradeAction::Buy, 12_000_000)]);
+}
+
+#[test]
+fn test_buys_limited_by_cash() {
+    let markets = test_registry();
+    let pairs = [TokenPair::new("SOL", "USDC")];
+    let allocation = Allocation::new(25.0);
+    let ctx = StrategyContext { id: "grid-sol", pairs: &pairs, markets: &markets, allocation: &allocation, payer: Pubkey::new_unique() };
+    let mut strategy = grid("arithmetic");
+
+    // Цена выше уровней 10-13, но денег хватает только на два ближайших ордера
+    let signals = tick(&mut strategy, &ctx, 13.5);
+    assert_eq!(quotes(&signals), vec![(TradeAction::Buy, 13_000_000), (TradeAction::Buy, 12_000_000)]);
+    assert!(tick(&mut strategy, &ctx, 13.5).is_empty());
+}
+
+#[test]
+fn test_rejected_buy_frees_level() {
+    let markets = test_registry();
+    let pairs = [TokenPair::new("SOL", "USDC")];
+    let allocation = Allocation::new(15.0);
+    let ctx = StrategyContext { id: "grid-sol", pairs: &pairs, markets: &markets, allocation: &allocation, payer: Pubkey::new_unique() };
+    let mut strategy = grid("arithmetic");
+
+    let signals = tick(&mut strategy, &ctx, 12.5);
+    assert_eq!(quotes(&signals), vec![(TradeAction::Buy, 12_000_000)]);
+    assert!(tick(&mut strategy, &ctx, 12.5).is_empty());
+
+    // Биржа отклонила покупку: уровень и капитал свободны, ордер выставляется заново
+    strategy.on_reject(&ctx, signals[0].client_order_id).unwrap();
+    let retry = tick(&mut strategy, &ctx, 12.5);
+    assert_eq!(quotes(&retry), vec![(TradeAction::Buy, 12_000_000)]);
+    assert_ne!(retry[0].client_order_id, signals[0].client_order_id);
+    // Отказ по чужому ордеру ничего не меняет
+    strategy.on_reject(&ctx, signals[0].client_order_id).unwrap();
+    assert!(tick(&mut strategy, &ctx, 12.5).is_empty());
+}
+
+#[test]
+fn test_stale_order_is_canceled_and_requoted() {
+    let markets = test_registry();
+    let pairs = [TokenPair::new("SOL", "USDC")];
+    let allocation = Allocation::new(15.0);
+    let ctx = StrategyContext { id: "grid-sol", pairs: &pairs, markets: &markets, allocation: &allocation, payer: Pubkey::new_unique() };
+    let mut strategy = grid("arithmetic");
+
+    let placed = tick_at(&mut strategy, &ctx, 12.5, 10);
+    assert!(tick_at(&mut strategy, &ctx, 12.5, 109).is_empty());
+
+    // Ордер без исполнений за 100 слотов снимается, но уровень занят до подтверждения
+    let signals = tick_at(&mut strategy, &ctx, 12.5, 110);
+    assert_eq!(signals.len(), 1);
+    assert_eq!(signals[0].order_action, OrderAction::Cancel);
+    assert_eq!(signals[0].client_order_id, placed[0].client_order_id);
+    assert!(tick_at(&mut strategy, &ctx, 12.5, 150).is_empty());
+
+    // Отмена подтверждена: уровень выставляется заново
+    strategy.on_canceled(&ctx, placed[0].client_order_id).unwrap();
+    let requoted = tick_at(&mut strategy, &ctx, 12.5, 160);
+    assert_eq!(quotes(&requoted), vec![(TradeAction::Buy, 12_000_000)]);
+
+    // Неподтвержденная отмена повторяется через тот же срок
+    let cancel = tick_at(&mut strategy, &ctx, 12.5, 260);
+    assert_eq!(cancel.len(), 1);
+    assert!(tick_at(&mut strategy, &ctx, 12.5, 359).is_empty());
+    let retry = tick_at(&mut strategy, &ctx, 12.5, 360);
+    assert_eq!(retry.len(), 1);
+    assert_eq!(retry[0].client_order_id, requoted[0].client_order_id);
+}
+
+#[test]
+fn test_fill_before_cancel_confirmation_is_kept() {
+    let markets = test_registry();
+    let pairs = [TokenPair::new("SOL", "USDC")];
+    let allocation = Allocation::new(15.0);
+    let ctx = StrategyContext { id: "grid-sol", pairs: &pairs, markets: &markets, allocation: &allocation, payer: Pubkey::new_unique() };
+    let mut strategy = grid("arithmetic");
+
+    let placed = tick_at(&mut strategy, &ctx, 12.5, 10);
+    assert_eq!(tick_at(&mut strategy, &ctx, 12.5, 110)[0].order_action, OrderAction::Cancel);
+
+    // Исполнение пришло раньше подтверждения отмены: купленная часть ждет продажи
+    strategy.on_fill(&ctx, &fill(&placed[0], 12.0, 0.4)).unwrap();
+    strategy.on_canceled(&ctx, placed[0].client_order_id).unwrap();
+    match strategy.cells[2] {
+        Cell::WaitingSell(lot) => assert!((lot.size - 0.4).abs() < 1e-9 && (lot.buy_price - 12.0).abs() < 1e-9),
+        cell => panic!("unexpected cell {:?}", cell),
+    }
+}
+
+#[test]
+fn test_invalid_params() {
+    assert!(GridStrategy::from_params(&Value::Null).is_err());
+    assert!(GridStrategy::from_params(&serde_json::json!({"lower": 10.0, "upper": 14.0, "levels": 1, "size": 1.0})).is_err());
+    assert!(GridStrategy::from_params(&serde_json::json!({"lower": 14.0, "upper": 10.0, "levels": 3, "size": 1.0})).is_err());
+    assert!(GridStrategy::from_params(&serde_json::json!({"lower": 10.0, "upper": 14.0, "levels": 3, "size": 0.0})).is_err());
+}
@@ -0,0 +1,304 @@
+use std::error::Error;
+
+use log::info;
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::registry::parse_params;
+use super::{CandleCursor, Fill, Strategy, StrategyContext};
+use crate::candles::Timeframe;
+use crate::indicators::Atr;
+use crate::jito_integration::{TradeAction, TradingSignal};
+use crate::price_feed::{PriceData, TokenPair};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FairPrice {
+    // Агрегированная цена оракулов
+    Oracle,
+    // Средняя лучших цен стакана, взвешенная объемом противоположной стороны
+    MicroPrice,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct MarketMakerConfig {
+    pub fair_price: FairPrice,
+    // Полный спред между котировками
+    pub spread_bps: f64,
+    // Объем котировки в базовом токене
+    pub size: f64,
+    // Целевая позиция в базовом токене
+    pub inventory_target: f64,
+    // Отклонение от цели, при котором сдвиг максимален и котировка,
+    // увеличивающая отклонение, снимается
+    pub max_inventory: f64,
+    // Сдвиг котировок при максимальном отклонении позиции
+    pub skew_bps: f64,
+    // Котировка переставляется, если желаемая цена ушла дальше допуска
+    pub requote_tolerance_bps: f64,
+    // Котировки снимаются, если интервал оракула шире этой доли цены...
+    pub max_confidence_ratio: f64,
+    // ...или ATR превышает эту долю цены
+    pub max_volatility_ratio: f64,
+    pub volatility_timeframe: Timeframe,
+    pub atr_period: usize,
+}
+
+impl Default for MarketMakerConfig {
+    fn default() -> Self {
+        Self {
+            fair_price: FairPrice::Oracle,
+            spread_bps: 20.0,
+            size: 1.0,
+            inventory_target: 0.0,
+            max_inventory: 10.0,
+            skew_bps: 10.0,
+            requote_tolerance_bps: 5.0,
+            max_confidence_ratio: 0.005,
+            max_volatility_ratio: 0.01,
+            volatility_timeframe: Timeframe::Sec1,
+            atr_period: 14,
+        }
+    }
+}
+
+// Котировка, отправленная на биржу
+#[derive(Debug, Clone, Copy)]
+struct Quote {
+    client_order_id: u64,
+    price: f64,
+    size: f64,
+    // Котировка, которую заменяет эта: при отказе замены она остается в стакане
+    replaces: Option<u64>,
+}
+
+// Маркет-мейкер: держит котировки на покупку и продажу вокруг справедливой цены.
+// Котировки сдвигаются против накопленной позиции, переставляются при уходе цены
+// и снимаются при расширении интервала оракула или всплеске волатильности.
+pub struct MarketMakerStrategy {
+    config: MarketMakerConfig,
+    bid: Option<Quote>,
+    ask: Option<Quote>,
+    // Котировки, оставшиеся в стакане после отклоненной замены: снимаются на следующем тике
+    orphans: Vec<(TradeAction, u64)>,
+    volatility: (CandleCursor, Atr),
+}
+
+impl MarketMakerStrategy {
+    pub fn new(config: MarketMakerConfig) -> Result<Self, Box<dyn Error>> {
+        if !(config.spread_bps > 0.0 && config.size > 0.0 && config.max_inventory > 0.0) {
+            return Err("spread_bps, size and max_inventory must be positive".into());
+        }
+        if config.atr_period == 0 {
+            return Err("atr_period must be positive".into());
+        }
+        let atr = Atr::new(config.atr_period);
+        Ok(Self {
+            config,
+            bid: None,
+            ask: None,
+            orphans: Vec::new(),
+            volatility: (CandleCursor::default(), atr),
+        })
+    }
+
+    pub fn from_params(params: &Value) -> Result<Self, Box<dyn Error>> {
+        Self::new(parse_params(params)?)
+    }
+
+    fn fair_price(&self, pair: &TokenPair, price_data: &PriceData) -> Result<f64, Box<dyn Error>> {
+        if self.config.fair_price == FairPrice::MicroPrice {
+            let book = price_data.get_order_book(pair);
+            if let Some(((bid, bid_size), (ask, ask_size))) = book.and_then(|book| book.best_bid().zip(book.best_ask())) {
+                if bid_size + ask_size > 0.0 {
+                    return Ok((bid * ask_size + ask * bid_size) / (bid_size + ask_size));
+                }
+            }
+        }
+        // Стакан пуст или справедливая цена берется у оракула
+        price_data.get_price(pair)
+    }
+
+    // Причина снять котировки или None, если котировать можно
+    fn pull_reason(&mut self, pair: &TokenPair, price_data: &PriceData) -> Result<Option<String>, Box<dyn Error>> {
+        if !price_data.is_tradable(pair) {
+            return Ok(Some("price is not tradable".to_string()));
+        }
+        let confidence_ratio = price_data.get_confidence_ratio(pair)?;
+        if confidence_ratio > self.config.max_confidence_ratio {
+            return Ok(Some(format!("oracle confidence {:.4} of price", confidence_ratio)));
+        }
+
+        // Бары могут еще не появиться - тогда волатильность не проверяем
+        let (cursor, atr) = &mut self.volatility;
+        if let Ok(candles) = cursor.new_candles(price_data, pair, self.config.volatility_timeframe) {
+            for candle in &candles {
+                atr.update(candle);
+            }
+        }
+        if let Some(atr) = atr.value() {
+            let volatility_ratio = atr / price_data.get_price(pair)?;
+            if volatility_ratio > self.config.max_volatility_ratio {
+                return Ok(Some(format!("volatility {:.4} of price", volatility_ratio)));
+            }
+        }
+        Ok(None)
+    }
+
+    fn cancel_quotes(&mut self, ctx: &StrategyContext, pair: &TokenPair) -> Result<Vec<TradingSignal>, Box<dyn Error>> {
+        let mut signals = Vec::new();
+        if let Some(quote) = self.bid.take() {
+            signals.push(ctx.cancel_signal(pair, TradeAction::Buy, quote.client_order_id)?);
+        }
+        if let Some(quote) = self.ask.take() {
+            signals.push(ctx.cancel_signal(pair, TradeAction::Sell, quote.client_order_id)?);
+        }
+        Ok(signals)
+    }
+
+    // Приводит котировку стороны к желаемой: выставляет, переставляет или снимает
+    fn update_quote(
+        &self,
+        ctx: &StrategyContext,
+        pair: &TokenPair,
+        action: TradeAction,
+        current: Option<Quote>,
+        desired: Option<(f64, f64)>,
+    ) -> Result<(Option<Quote>, Option<TradingSignal>), Box<dyn Error>> {
+        let (price, size) = match desired {
+            Some(desired) => desired,
+            None => {
+                return Ok(match current {
+                    Some(quote) => (None, Some(ctx.cancel_signal(pair, action, quote.client_order_id)?)),
+                    None => (None, None),
+                });
+            }
+        };
+
+        let signal = match current {
+            None => ctx.quote_signal(pair, action, size, price)?,
+            Some(quote) => {
+                let moved_bps = (price - quote.price).abs() / quote.price * 10_000.0;
+                if moved_bps <= self.config.requote_tolerance_bps && (size - quote.size).abs() < 1e-9 {
+                    return Ok((current, None));
+                }
+                match ctx.replace_signal(pair, action, size, price, quote.client_order_id)? {
+                    Some(signal) => Some(signal),
+                    // Новый объем меньше минимального - просто снимаем котировку
+                    None => return Ok((None, Some(ctx.cancel_signal(pair, action, quote.client_order_id)?))),
+                }
+            }
+        };
+
+        Ok(match signal {
+            Some(signal) => (
+                Some(Quote {
+                    client_order_id: signal.client_order_id,
+                    price,
+                    size,
+                    replaces: current.map(|quote| quote.client_order_id),
+                }),
+                Some(signal),
+            ),
+            None => (current, None),
+        })
+    }
+}
+
+impl Strategy for MarketMakerStrategy {
+    fn name(&self) -> &str {
+        "market_maker"
+    }
+
+    fn manages_resting_orders(&self) -> bool {
+        true
+    }
+
+    fn init(&mut self, ctx: &StrategyContext) -> Result<(), Box<dyn Error>> {
+        if ctx.pairs.len() != 1 {
+            return Err("market maker quotes exactly one pair".into());
+        }
+        Ok(())
+    }
+
+    fn on_tick(&mut self, ctx: &StrategyContext, price_data: &PriceData) -> Result<Vec<TradingSignal>, Box<dyn Error>> {
+        let pair = &ctx.pairs[0];
+        let mut signals = Vec::new();
+        for (action, client_order_id) in std::mem::take(&mut self.orphans) {
+            signals.push(ctx.cancel_signal(pair, action, client_order_id)?);
+        }
+        if let Some(reason) = self.pull_reason(pair, price_data)? {
+            if self.bid.is_some() || self.ask.is_some() {
+                info!("Market maker {}: pulling quotes on {}: {}", ctx.id, pair, reason);
+            }
+            signals.extend(self.cancel_quotes(ctx, pair)?);
+            return Ok(signals);
+        }
+
+        let fair = self.fair_price(pair, price_data)?;
+        let position = ctx.position(pair);
+
+        // Сдвигаем цену резервирования против отклонения позиции от цели:
+        // при избытке котировки дешевеют, чтобы чаще продавать
+        let deviation = ((position - self.config.inventory_target) / self.config.max_inventory).clamp(-1.0, 1.0);
+        let reservation = fair * (1.0 - deviation * self.config.skew_bps / 10_000.0);
+        let half_spread = self.config.spread_bps / 2.0 / 10_000.0;
+
+        let bid_price = reservation * (1.0 - half_spread);
+        let bid = if deviation < 1.0 && bid_price * self.config.size <= ctx.cash() {
+            Some((bid_price, self.config.size))
+        } else {
+            None
+        };
+        // Продаем только то, что есть в позиции
+        let ask_size = self.config.size.min(position);
+        let ask = if deviation > -1.0 && ask_size > 0.0 {
+            Some((reservation * (1.0 + half_spread), ask_size))
+        } else {
+            None
+        };
+
+        let (quote, signal) = self.update_quote(ctx, pair, TradeAction::Buy, self.bid, bid)?;
+        self.bid = quote;
+        signals.extend(signal);
+        let (quote, signal) = self.update_quote(ctx, pair, TradeAction::Sell, self.ask, ask)?;
+        self.ask = quote;
+        signals.extend(signal);
+        Ok(signals)
+    }
+
+    fn on_fill(&mut self, _ctx: &StrategyContext, fill: &Fill) -> Result<Vec<TradingSignal>, Box<dyn Error>> {
+        for quote in [&mut self.bid, &mut self.ask] {
+            if let Some(current) = quote {
+                if current.client_order_id == fill.client_order_id {
+                    current.size -= fill.size;
+                    // Исполненная котировка будет выставлена заново на следующем тике
+                    if current.size <= 1e-9 {
+                        *quote = None;
+                    }
+                }
+            }
+        }
+        Ok(Vec::new())
+    }
+
+    // Отклоненная котировка освобождает сторону: на следующем тике выставляется новая
+    fn on_reject(&mut self, _ctx: &StrategyContext, client_order_id: u64) -> Result<(), Box<dyn Error>> {
+        for (action, quote) in [(TradeAction::Buy, &mut self.bid), (TradeAction::Sell, &mut self.ask)] {
+            if let Some(current) = quote {
+                if current.client_order_id == client_order_id {
+                    if let Some(replaced) = current.replaces {
+                        self.orphans.push((action, replaced));
+                    }
+                    *quote = None;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[path = "market_maker_test.rs"]
+mod tests;
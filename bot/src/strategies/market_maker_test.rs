@@ -0,0 +1,144 @@
+use super::*;
+use crate::jito_integration::{OrderAction, TimeInForce};
+use crate::market_registry::test_registry;
+use crate::strategies::Allocation;
+use solana_sdk::pubkey::Pubkey;
+
+fn strategy() -> MarketMakerStrategy {
+    MarketMakerStrategy::from_params(&serde_json::json!({
+        "spread_bps": 20.0,
+        "size": 1.0,
+        "inventory_target": 5.0,
+        "max_inventory": 5.0,
+        "skew_bps": 10.0,
+        "requote_tolerance_bps": 5.0
+    })).unwrap()
+}
+
+fn allocation(position: f64) -> Allocation {
+    let mut allocation = Allocation::new(1000.0);
+    allocation.positions.insert("SOL/USDC".to_string(), position);
+    allocation
+}
+
+fn tick(strategy: &mut MarketMakerStrategy, allocation: &Allocation, price: f64) -> Vec<TradingSignal> {
+    let markets = test_registry();
+    let pairs = [TokenPair::new("SOL", "USDC")];
+    let ctx = StrategyContext { id: "mm-sol", pairs: &pairs, markets: &markets, allocation, payer: Pubkey::new_unique() };
+    strategy.on_tick(&ctx, &PriceData::with_prices(&[("SOL/USDC", price)])).unwrap()
+}
+
+#[test]
+fn test_quotes_both_sides_at_target_inventory() {
+    let mut strategy = strategy();
+    let signals = tick(&mut strategy, &allocation(5.0), 100.0);
+
+    assert_eq!(signals.len(), 2);
+    assert!(signals.iter().all(|signal| signal.time_in_force == TimeInForce::PostOnly && signal.order_action == OrderAction::Place));
+    assert_eq!((signals[0].action, signals[0].price), (TradeAction::Buy, 99_900_000));
+    assert_eq!((signals[1].action, signals[1].price), (TradeAction::Sell, 100_100_000));
+}
+
+#[test]
+fn test_inventory_skews_quotes() {
+    let mut strategy = strategy();
+    // Позиция на половине допустимого избытка: котировки ниже на 5 bps
+    let signals = tick(&mut strategy, &allocation(7.5), 100.0);
+    assert_eq!(signals[0].price, 99_850_000);
+    assert_eq!(signals[1].price, 100_050_000);
+
+    // При максимальном избытке покупка не котируется
+    let mut strategy = self::strategy();
+    let signals = tick(&mut strategy, &allocation(10.0), 100.0);
+    assert_eq!(signals.len(), 1);
+    assert_eq!(signals[0].action, TradeAction::Sell);
+}
+
+#[test]
+fn test_requote_beyond_tolerance() {
+    let mut strategy = strategy();
+    let allocation = allocation(5.0);
+    let placed = tick(&mut strategy, &allocation, 100.0);
+
+    // 3 bps - в пределах допуска
+    assert!(tick(&mut strategy, &allocation, 100.03).is_empty());
+
+    let replaced = tick(&mut strategy, &allocation, 100.2);
+    assert_eq!(replaced.len(), 2);
+    for (old, new) in placed.iter().zip(replaced.iter()) {
+        assert_eq!(new.order_action, OrderAction::Replace { cancel_client_order_id: old.client_order_id });
+        assert_ne!(new.client_order_id, old.client_order_id);
+    }
+}
+
+#[test]
+fn test_pull_quotes_on_wide_confidence() {
+    let markets = test_registry();
+    let pairs = [TokenPair::new("SOL", "USDC")];
+    let allocation = allocation(5.0);
+    let ctx = StrategyContext { id: "mm-sol", pairs: &pairs, markets: &markets, allocation: &allocation, payer: Pubkey::new_unique() };
+    let mut strategy = strategy();
+
+    let placed = strategy.on_tick(&ctx, &PriceData::with_prices(&[("SOL/USDC", 100.0)])).unwrap();
+    let mut price_data = PriceData::with_prices(&[("SOL/USDC", 100.0)]);
+    price_data.set_confidence("SOL/USDC", 1.0);
+    let pulled = strategy.on_tick(&ctx, &price_data).unwrap();
+
+    assert_eq!(pulled.len(), 2);
+    for (quote, cancel) in placed.iter().zip(pulled.iter()) {
+        assert_eq!(cancel.order_action, OrderAction::Cancel);
+        assert_eq!(cancel.client_order_id, quote.client_order_id);
+    }
+    // Котировок больше нет - повторной отмены не будет
+    assert!(strategy.on_tick(&ctx, &price_data).unwrap().is_empty());
+}
+
+#[test]
+fn test_filled_quote_is_replaced_on_next_tick() {
+    let mut strategy = strategy();
+    let allocation = allocation(5.0);
+    let placed = tick(&mut strategy, &allocation, 100.0);
+
+    let markets = test_registry();
+    let pairs = [TokenPair::new("SOL", "USDC")];
+    let ctx = StrategyContext { id: "mm-sol", pairs: &pairs, markets: &markets, allocation: &allocation, payer: Pubkey::new_unique() };
+    strategy.on_fill(&ctx, &Fill {
+        pair: TokenPair::new("SOL", "USDC"),
+        client_order_id: placed[0].client_order_id,
+        action: TradeAction::Buy,
+        price: 99.9,
+        size: 1.0,
+        fee: -0.01,
+        timestamp: 0,
+    }).unwrap();
+
+    let signals = tick(&mut strategy, &allocation, 100.0);
+    assert_eq!(signals.len(), 1);
+    assert_eq!((signals[0].action, signals[0].order_action), (TradeAction::Buy, OrderAction::Place));
+}
+
+#[test]
+fn test_rejected_quote_is_cleared() {
+    let markets = test_registry();
+    let pairs = [TokenPair::new("SOL", "USDC")];
+    let allocation = allocation(5.0);
+    let ctx = StrategyContext { id: "mm-sol", pairs: &pairs, markets: &markets, allocation: &allocation, payer: Pubkey::new_unique() };
+    let mut strategy = strategy();
+    let placed = tick(&mut strategy, &allocation, 100.0);
+
+    // Отклоненная покупка выставляется заново, продажа остается
+    strategy.on_reject(&ctx, placed[0].client_order_id).unwrap();
+    let signals = tick(&mut strategy, &allocation, 100.0);
+    assert_eq!(signals.len(), 1);
+    assert_eq!((signals[0].action, signals[0].order_action), (TradeAction::Buy, OrderAction::Place));
+    assert_ne!(signals[0].client_order_id, placed[0].client_order_id);
+
+    // Отклоненная замена оставила прежнюю продажу в стакане: она снимается
+    let replaced = tick(&mut strategy, &allocation, 100.2);
+    let sell = replaced.iter().find(|signal| signal.action == TradeAction::Sell).unwrap();
+    strategy.on_reject(&ctx, sell.client_order_id).unwrap();
+    let signals = tick(&mut strategy, &allocation, 100.2);
+    assert_eq!(signals.len(), 2);
+    assert_eq!((signals[0].order_action, signals[0].client_order_id), (OrderAction::Cancel, placed[1].client_order_id));
+    assert_eq!((signals[1].action, signals[1].order_action), (TradeAction::Sell, OrderAction::Place));
+}
@@ -0,0 +1,315 @@
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::candles::{Candle, Timeframe};
+use crate::jito_integration::{self, OrderAction, OrderKind, TimeInForce, TradeAction, TradingSignal, Venue};
+use crate::market_registry::MarketRegistry;
+use crate::price_feed::{PriceData, TokenPair};
+use crate::quantization::Rounding;
+
+pub mod arbitrage;
+pub mod dca;
+pub mod ensemble;
+pub mod grid;
+pub mod market_maker;
+pub mod momentum;
+pub mod pairs;
+pub mod rebalance;
+pub mod registry;
+pub mod runner;
+pub mod schedule;
+
+pub use registry::{RunnerConfig, StrategyRegistry};
+pub use runner::StrategyRunner;
+
+// Исполненная сделка стратегии в UI-единицах
+#[derive(Debug, Clone)]
+pub struct Fill {
+    pub pair: TokenPair,
+    // Ордер, по которому прошла сделка
+    pub client_order_id: u64,
+    pub action: TradeAction,
+    pub price: f64,
+    pub size: f64,
+    // Комиссия в котируемом токене
+    pub fee: f64,
+    // Unix-время в секундах
+    pub timestamp: i64,
+}
+
+impl Fill {
+    pub fn notional(&self) -> f64 {
+        self.price * self.size
+    }
+}
+
+// Доля стоимости короткой позиции, которая блокируется из свободных средств
+// сверх выручки от продажи
+pub const SHORT_MARGIN: f64 = 0.5;
+
+// Капитал, выделенный экземпляру стратегии, и его текущее состояние
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Allocation {
+    // Выделенный капитал в котируемом токене
+    pub capital: f64,
+    // Свободные средства в котируемом токене
+    pub cash: f64,
+    // Позиции в базовом токене по ключу пары; отрицательные - короткие
+    pub positions: HashMap<String, f64>,
+    // Залог коротких позиций по ключу пары: выручка от продажи и маржа
+    pub margin: HashMap<String, f64>,
+}
+
+impl Allocation {
+    pub fn new(capital: f64) -> Self {
+        Self {
+            capital,
+            cash: capital,
+            positions: HashMap::new(),
+            margin: HashMap::new(),
+        }
+    }
+
+    pub fn position(&self, pair: &TokenPair) -> f64 {
+        self.positions.get(&pair.to_string()).copied().unwrap_or(0.0)
+    }
+
+    pub fn margin(&self, pair: &TokenPair) -> f64 {
+        self.margin.get(&pair.to_string()).copied().unwrap_or(0.0)
+    }
+
+    // Стоимость капитала по ценам пар: свободные средства, залог и позиции
+    pub fn equity(&self, prices: &HashMap<String, f64>) -> f64 {
+        let positions: f64 = self.positions.iter()
+            .map(|(pair, size)| size * prices.get(pair).copied().unwrap_or(0.0))
+            .sum();
+        self.cash + self.margin.values().sum::<f64>() + positions
+    }
+
+    pub fn apply_fill(&mut self, fill: &Fill) {
+        self.apply_trade(&fill.pair, fill.action, fill.size, fill.price, fill.fee);
+    }
+
+    // Продажа сверх позиции открывает короткую: ее выручка и маржа уходят в залог.
+    // Покупка сначала откупает короткую позицию и освобождает залог пропорционально.
+    pub fn apply_trade(&mut self, pair: &TokenPair, action: TradeAction, size: f64, price: f64, fee: f64) {
+        let key = pair.to_string();
+        let position = self.position(pair);
+        match action {
+            TradeAction::Buy => {
+                let cover = size.min(-position);
+                if cover > 0.0 {
+                    let margin = self.margin.entry(key.clone()).or_insert(0.0);
+                    let released = *margin * cover / -position;
+                    *margin -= released;
+                    self.cash += released;
+                }
+                self.cash -= price * size + fee;
+                self.positions.insert(key.clone(), position + size);
+            }
+            TradeAction::Sell => {
+                let short = size - position.max(0.0);
+                let locked = short.max(0.0) * price * (1.0 + SHORT_MARGIN);
+                if locked > 0.0 {
+                    *self.margin.entry(key.clone()).or_insert(0.0) += locked;
+                }
+                self.cash += price * size - fee - locked;
+                self.positions.insert(key.clone(), position - size);
+            }
+        }
+        if self.position(pair) >= 0.0 {
+            if let Some(margin) = self.margin.remove(&key) {
+                self.cash += margin;
+            }
+        }
+    }
+}
+
+// Все, что стратегия знает о своем экземпляре: назначенные пары, рынки,
+// выделенный капитал и плательщика для сигналов
+pub struct StrategyContext<'a> {
+    pub id: &'a str,
+    pub pairs: &'a [TokenPair],
+    pub markets: &'a MarketRegistry,
+    pub allocation: &'a Allocation,
+    pub payer: Pubkey,
+}
+
+impl<'a> StrategyContext<'a> {
+    pub fn cash(&self) -> f64 {
+        self.allocation.cash
+    }
+
+    pub fn position(&self, pair: &TokenPair) -> f64 {
+        self.allocation.position(pair)
+    }
+
+    // Приводит цену и объем (в UI-единицах) к шагам рынка из реестра и проверяет ордер.
+    // None, если после округления объем меньше минимального.
+    pub fn build_signal(&self, pair: &TokenPair, action: TradeAction, size: f64, price: f64) -> Result<Option<TradingSignal>, Box<dyn Error>> {
+        let market = self.markets.get(pair)?;
+        let quantizer = market.quantizer();
+
+        let size = quantizer.size_from_ui(size, Rounding::Down)?;
+        if quantizer.size_to_ui(size) < market.min_order_size {
+            return Ok(None);
+        }
+
+        // Лимитная цена округляется в сторону, не ухудшающую исполнение для нас
+        let price = match action {
+            TradeAction::Buy => quantizer.price_from_ui(price, Rounding::Down)?,
+            TradeAction::Sell => quantizer.price_from_ui(price, Rounding::Up)?,
+        };
+        market.validate_order(quantizer.price_to_ui(price), quantizer.size_to_ui(size))?;
+
+        Ok(Some(TradingSignal {
+            market: market.dex_market,
+            action,
+            amount: quantizer.size_to_native(size)?,
+            price: quantizer.price_to_native(price)?,
+            payer: self.payer,
+            client_order_id: jito_integration::next_client_order_id(),
+            order_action: OrderAction::Place,
+            time_in_force: TimeInForce::Ioc,
+            venue: Venue::Serum,
+            bundle_id: None,
+            strategy_id: self.id.to_string(),
+            confidence: 1.0,
+            expiry_slot: None,
+            order_kind: OrderKind::Limit,
+            slippage_bps: 0.0,
+            reason: BTreeMap::new(),
+        }))
+    }
+
+    // Обмен в AMM-пуле пары. Объем и предельная цена приводятся к шагам рынка
+    // Serum, чтобы ноги арбитража на разных площадках совпадали по объему.
+    pub fn swap_signal(&self, pair: &TokenPair, action: TradeAction, size: f64, price: f64, venue: Venue) -> Result<Option<TradingSignal>, Box<dyn Error>> {
+        Ok(self.build_signal(pair, action, size, price)?.map(|signal| TradingSignal {
+            venue,
+            ..signal
+        }))
+    }
+
+    // Котировка: post-only ордер, который остается в стакане до исполнения или отмены
+    pub fn quote_signal(&self, pair: &TokenPair, action: TradeAction, size: f64, price: f64) -> Result<Option<TradingSignal>, Box<dyn Error>> {
+        Ok(self.build_signal(pair, action, size, price)?.map(|signal| TradingSignal {
+            time_in_force: TimeInForce::PostOnly,
+            ..signal
+        }))
+    }
+
+    // Заменяет стоящий ордер новой котировкой одной транзакцией
+    pub fn replace_signal(&self, pair: &TokenPair, action: TradeAction, size: f64, price: f64, cancel_client_order_id: u64) -> Result<Option<TradingSignal>, Box<dyn Error>> {
+        Ok(self.quote_signal(pair, action, size, price)?.map(|signal| TradingSignal {
+            order_action: OrderAction::Replace { cancel_client_order_id },
+            ..signal
+        }))
+    }
+
+    pub fn cancel_signal(&self, pair: &TokenPair, action: TradeAction, client_order_id: u64) -> Result<TradingSignal, Box<dyn Error>> {
+        let market = self.markets.get(pair)?;
+        Ok(TradingSignal {
+            market: market.dex_market,
+            action,
+            amount: 0,
+            price: 0,
+            payer: self.payer,
+            client_order_id,
+            order_action: OrderAction::Cancel,
+            time_in_force: TimeInForce::Ioc,
+            venue: Venue::Serum,
+            bundle_id: None,
+            strategy_id: self.id.to_string(),
+            confidence: 1.0,
+            expiry_slot: None,
+            order_kind: OrderKind::Limit,
+            slippage_bps: 0.0,
+            reason: BTreeMap::new(),
+        })
+    }
+}
+
+// Объединяет ноги в бандл: исполнитель отправит их атомарно, а раннер
+// пропустит либо все ноги, либо ни одной
+pub fn bundle(legs: Vec<TradingSignal>) -> Vec<TradingSignal> {
+    let bundle_id = jito_integration::next_client_order_id();
+    legs.into_iter()
+        .map(|leg| TradingSignal {
+            bundle_id: Some(bundle_id),
+            ..leg
+        })
+        .collect()
+}
+
+// Позиция последнего обработанного закрытого бара: индикаторы получают
+// каждый бар ровно один раз, даже если тики пропускались
+#[derive(Debug, Clone, Default)]
+pub struct CandleCursor {
+    last_open_time: Option<i64>,
+}
+
+impl CandleCursor {
+    pub fn new_candles(&mut self, price_data: &PriceData, pair: &TokenPair, timeframe: Timeframe) -> Result<Vec<Candle>, Box<dyn Error>> {
+        let candles = price_data.get_candles_after(pair, timeframe, self.last_open_time)?;
+        if let Some(last) = candles.last() {
+            self.last_open_time = Some(last.open_time);
+        }
+        Ok(candles)
+    }
+}
+
+// Торговая стратегия с хуками жизненного цикла. Хуки, кроме `on_tick`,
+// необязательны. Сигналы из `on_fill` исполняются на следующем тике.
+pub trait Strategy: Send {
+    fn name(&self) -> &str;
+
+    fn init(&mut self, _ctx: &StrategyContext) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    fn on_tick(&mut self, ctx: &StrategyContext, price_data: &PriceData) -> Result<Vec<TradingSignal>, Box<dyn Error>>;
+
+    fn on_fill(&mut self, _ctx: &StrategyContext, _fill: &Fill) -> Result<Vec<TradingSignal>, Box<dyn Error>> {
+        Ok(Vec::new())
+    }
+
+    // Ордер стратегии не дошел до стакана: бандл не отправлен, отклонен
+    // биржей или не прошел проверку средств
+    fn on_reject(&mut self, _ctx: &StrategyContext, _client_order_id: u64) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    // Стоящий ордер стратегии снят биржей с неисполненным остатком. До этого
+    // отправленная отмена еще не действует: ордер может исполниться.
+    fn on_canceled(&mut self, _ctx: &StrategyContext, _client_order_id: u64) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    // Период вызова `on_timer` в секундах; None - таймер не нужен
+    fn timer_interval(&self) -> Option<i64> {
+        None
+    }
+
+    fn on_timer(&mut self, _ctx: &StrategyContext, _timestamp: i64) -> Result<Vec<TradingSignal>, Box<dyn Error>> {
+        Ok(Vec::new())
+    }
+
+    // Держит ли стратегия стоящие ордера, которые сама отменяет и заменяет
+    fn manages_resting_orders(&self) -> bool {
+        false
+    }
+
+    // Может ли стратегия продавать сверх позиции, открывая короткие позиции под залог
+    fn allows_short(&self) -> bool {
+        false
+    }
+
+    // Выставляет ли стратегия ордера из on_fill, которые нельзя потерять
+    fn trades_on_fill(&self) -> bool {
+        false
+    }
+}
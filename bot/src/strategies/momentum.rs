@@ -1,61 +1,91 @@
-use super::Strategy;
+use super::registry::parse_params;
+use super::{CandleCursor, Strategy, StrategyContext};
+use crate::candles::Timeframe;
+use crate::indicators::Rsi;
 use crate::jito_integration::{TradeAction, TradingSignal};
 use crate::price_feed::{PriceData, TokenPair};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
 use std::error::Error;
 
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
 pub struct MomentumStrategy {
+    timeframe: Timeframe,
     rsi_period: usize,
     rsi_overbought: f64,
     rsi_oversold: f64,
     volume_threshold: f64,
+    max_confidence_ratio: f64,
+    // Доля свободного капитала на одну покупку
+    risk_percentage: f64,
+    // Максимальный объем сделки в базовом токене
+    max_position_size: f64,
+    // RSI по ключу пары
+    #[serde(skip)]
+    rsi: HashMap<String, (CandleCursor, Rsi)>,
+}
+
+impl Default for MomentumStrategy {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl MomentumStrategy {
     pub fn new() -> Self {
         Self {
+            timeframe: Timeframe::Min1,
             rsi_period: 14,
             rsi_overbought: 70.0,
             rsi_oversold: 30.0,
             volume_threshold: 1000000.0, // в USDC
+            max_confidence_ratio: 0.01,
+            risk_percentage: 0.01,
+            max_position_size: 100.0,
+            rsi: HashMap::new(),
         }
     }
 
-    fn calculate_rsi(&self, prices: &[f64]) -> f64 {
-        if prices.len() < self.rsi_period + 1 {
-            return 50.0;
-        }
-
-        let mut gains = 0.0;
-        let mut losses = 0.0;
-
-        for i in 1..=self.rsi_period {
-            let diff = prices[i] - prices[i - 1];
-            if diff > 0.0 {
-                gains += diff;
-            } else {
-                losses -= diff;
-            }
+    pub fn from_params(params: &Value) -> Result<Self, Box<dyn Error>> {
+        let strategy: Self = parse_params(params)?;
+        if strategy.rsi_period == 0 {
+            return Err("rsi_period must be positive".into());
         }
+        Ok(strategy)
+    }
 
-        let avg_gain = gains / self.rsi_period as f64;
-        let avg_loss = losses / self.rsi_period as f64;
-
-        if avg_loss == 0.0 {
-            return 100.0;
+    // RSI Уайлдера по закрытым барам; бары, пришедшие с прошлого тика, учитываются инкрементально
+    fn calculate_rsi(&mut self, pair: &TokenPair, price_data: &PriceData) -> Result<Option<f64>, Box<dyn Error>> {
+        let period = self.rsi_period;
+        let (cursor, rsi) = self.rsi.entry(pair.to_string())
+            .or_insert_with(|| (CandleCursor::default(), Rsi::new(period)));
+        for candle in cursor.new_candles(price_data, pair, self.timeframe)? {
+            rsi.update(candle.close);
         }
-
-        let rs = avg_gain / avg_loss;
-        100.0 - (100.0 / (1.0 + rs))
+        Ok(rsi.value())
     }
-}
 
-impl Strategy for MomentumStrategy {
     fn analyze(
-        &self,
+        &mut self,
+        ctx: &StrategyContext,
         pair: &TokenPair,
         price_data: &PriceData,
     ) -> Result<Option<TradingSignal>, Box<dyn Error>> {
-        let prices = price_data.get_price_history(pair, self.rsi_period + 1)?;
+        // Источники цены разошлись или интервал оракула слишком широк - сигнал не генерируем
+        if !price_data.is_tradable(pair) {
+            return Ok(None);
+        }
+        if price_data.get_confidence_ratio(pair)? > self.max_confidence_ratio {
+            return Ok(None);
+        }
+
+        // RSI считается по закрытым барам, чтобы период соответствовал реальному времени
+        let rsi = match self.calculate_rsi(pair, price_data)? {
+            Some(rsi) => rsi,
+            None => return Ok(None),
+        };
         let volume = price_data.get_volume(pair)?;
 
         // Проверяем достаточный ли объем
@@ -63,27 +93,33 @@ impl Strategy for MomentumStrategy {
             return Ok(None);
         }
 
-        let rsi = self.calculate_rsi(&prices);
-        let current_price = prices.last().unwrap();
+        let current_price = price_data.get_price(pair)?;
 
-        if rsi < self.rsi_oversold {
-            Ok(Some(TradingSignal {
-                market: get_market_address(pair)?,
-                action: TradeAction::Buy,
-                amount: calculate_position_size(*current_price)?,
-                price: (*current_price * 1e6) as u64,
-                payer: get_payer()?,
-            }))
-        } else if rsi > self.rsi_overbought {
-            Ok(Some(TradingSignal {
-                market: get_market_address(pair)?,
-                action: TradeAction::Sell,
-                amount: calculate_position_size(*current_price)?,
-                price: (*current_price * 1e6) as u64,
-                payer: get_payer()?,
-            }))
+        let signal = if rsi < self.rsi_oversold {
+            let size = (ctx.cash().max(0.0) * self.risk_percentage / current_price).min(self.max_position_size);
+            ctx.build_signal(pair, TradeAction::Buy, size, current_price)?
+        } else if rsi > self.rsi_overbought && ctx.position(pair) > 0.0 {
+            let size = ctx.position(pair).min(self.max_position_size);
+            ctx.build_signal(pair, TradeAction::Sell, size, current_price)?
         } else {
-            Ok(None)
+            None
+        };
+        Ok(signal.map(|signal| signal.with_reason("rsi", format!("{:.2}", rsi)).with_reason("volume", volume)))
+    }
+}
+
+impl Strategy for MomentumStrategy {
+    fn name(&self) -> &str {
+        "momentum"
+    }
+
+    fn on_tick(&mut self, ctx: &StrategyContext, price_data: &PriceData) -> Result<Vec<TradingSignal>, Box<dyn Error>> {
+        let mut signals = Vec::new();
+        for pair in ctx.pairs {
+            if let Some(signal) = self.analyze(ctx, pair, price_data)? {
+                signals.push(signal);
+            }
         }
+        Ok(signals)
     }
 }
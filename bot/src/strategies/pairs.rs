@@ -0,0 +1,443 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::error::Error;
+
+use log::{info, warn};
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::registry::parse_params;
+use super::{bundle, CandleCursor, Fill, Strategy, StrategyContext};
+use crate::candles::Timeframe;
+use crate::jito_integration::{TradeAction, TradingSignal};
+use crate::price_feed::PriceData;
+
+// Позиции меньше этого объема считаются закрытыми
+const POSITION_EPSILON: f64 = 1e-9;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct PairsConfig {
+    pub timeframe: Timeframe,
+    // Окно в барах для коэффициента хеджирования и статистик спреда
+    pub lookback: usize,
+    // Вход, когда |z| спреда не меньше этого порога
+    pub entry_z: f64,
+    // Выход, когда спред вернулся к среднему до этого |z|
+    pub exit_z: f64,
+    // Позиция закрывается принудительно по истечении этого времени
+    pub max_holding_secs: i64,
+    // Вход только при периоде полураспада спреда не больше этого числа баров
+    pub max_half_life: f64,
+    // Объем первой ноги в котируемом токене; вторая - по ее исполнению и коэффициенту хеджирования
+    pub notional: f64,
+    pub max_slippage_bps: f64,
+}
+
+impl Default for PairsConfig {
+    fn default() -> Self {
+        Self {
+            timeframe: Timeframe::Min1,
+            lookback: 120,
+            entry_z: 2.0,
+            exit_z: 0.5,
+            max_holding_secs: 24 * 60 * 60,
+            max_half_life: 60.0,
+            notional: 100.0,
+            max_slippage_bps: 30.0,
+        }
+    }
+}
+
+// Модель спреда ln(A) - beta * ln(B) - alpha по скользящему окну
+#[derive(Debug, Clone, Copy)]
+pub struct SpreadModel {
+    pub alpha: f64,
+    // Коэффициент хеджирования: стоимость B на единицу стоимости A
+    pub beta: f64,
+    pub z: f64,
+    // Период полураспада отклонения в барах; None, если спред не возвращается к среднему
+    pub half_life: Option<f64>,
+}
+
+impl SpreadModel {
+    // МНК по логарифмам цен; для полураспада - AR(1) по приращениям спреда
+    pub fn fit(closes: &VecDeque<(f64, f64)>) -> Option<Self> {
+        let n = closes.len() as f64;
+        if closes.len() < 3 {
+            return None;
+        }
+        let points: Vec<(f64, f64)> = closes.iter().map(|&(a, b)| (a.ln(), b.ln())).collect();
+        let mean_y = points.iter().map(|p| p.0).sum::<f64>() / n;
+        let mean_x = points.iter().map(|p| p.1).sum::<f64>() / n;
+        let cov = points.iter().map(|p| (p.0 - mean_y) * (p.1 - mean_x)).sum::<f64>();
+        let var = points.iter().map(|p| (p.1 - mean_x).powi(2)).sum::<f64>();
+        if var <= 0.0 {
+            return None;
+        }
+        let beta = cov / var;
+        let alpha = mean_y - beta * mean_x;
+
+        let spread: Vec<f64> = points.iter().map(|p| p.0 - beta * p.1 - alpha).collect();
+        let std = (spread.iter().map(|s| s * s).sum::<f64>() / n).sqrt();
+        if !(std > 0.0 && std.is_finite()) {
+            return None;
+        }
+        let z = spread[spread.len() - 1] / std;
+
+        // Δs(t) = lambda * s(t-1) + c: возврат к среднему при lambda < 0
+        let lagged = &spread[..spread.len() - 1];
+        let deltas: Vec<f64> = spread.windows(2).map(|w| w[1] - w[0]).collect();
+        let m = lagged.len() as f64;
+        let mean_lag = lagged.iter().sum::<f64>() / m;
+        let mean_delta = deltas.iter().sum::<f64>() / m;
+        let var_lag = lagged.iter().map(|s| (s - mean_lag).powi(2)).sum::<f64>();
+        let cov_lag = lagged.iter().zip(&deltas).map(|(s, d)| (s - mean_lag) * (d - mean_delta)).sum::<f64>();
+        let lambda = if var_lag > 0.0 { cov_lag / var_lag } else { 0.0 };
+        let half_life = if lambda < 0.0 && lambda > -1.0 {
+            Some(-std::f64::consts::LN_2 / (1.0 + lambda).ln())
+        } else {
+            None
+        };
+
+        Some(Self { alpha, beta, z, half_life })
+    }
+}
+
+// Сторона спреда: Long - длинная позиция в A и короткая в B
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpreadSide {
+    Long,
+    Short,
+}
+
+impl SpreadSide {
+    // Направления сделок по ногам A и B при открытии
+    pub fn entry_actions(&self) -> (TradeAction, TradeAction) {
+        match self {
+            SpreadSide::Long => (TradeAction::Buy, TradeAction::Sell),
+            SpreadSide::Short => (TradeAction::Sell, TradeAction::Buy),
+        }
+    }
+}
+
+// Хеджированная позиция по паре: две ноги противоположного направления.
+// Первая нога - IOC-ордер по A, вторая выставляется по B на фактически
+// исполненный объем первой. Объемы ног берутся из позиций экземпляра сверх
+// выделенного запаса.
+#[derive(Debug, Clone)]
+pub struct HedgedPosition {
+    pub side: SpreadSide,
+    pub hedge_ratio: f64,
+    pub entry_z: f64,
+    pub opened_at: i64,
+    // Ордер первой ноги и цена B при входе, по которой считается объем хеджа
+    pub entry_order: u64,
+    pub hedge_price: f64,
+    // Отправленные ордера второй ноги
+    pub hedge_orders: Vec<u64>,
+}
+
+impl HedgedPosition {
+    // Валовая и чистая стоимость ног (объем, цена): чистая близка к нулю, пока хедж держится
+    pub fn exposure(legs: &[(f64, f64)]) -> (f64, f64) {
+        legs.iter().fold((0.0, 0.0), |(gross, net), &(size, price)| {
+            let value = size * price;
+            (gross + value.abs(), net + value)
+        })
+    }
+}
+
+// Парный арбитраж двух связанных токенов с общим котируемым, например
+// mSOL/USDC и SOL/USDC. По скользящему окну закрытых баров оцениваются
+// коэффициент хеджирования и z-оценка спреда; при сильном отклонении
+// открываются противоположные позиции по обеим парам, при возврате к
+// среднему или по истечении времени удержания они закрываются. На споте
+// Serum занять токены нельзя, поэтому "короткая" нога продает запас базового
+// токена, выделенный экземпляру, а закрытие возвращает его.
+pub struct PairsStrategy {
+    config: PairsConfig,
+    // Выделенный экземпляру запас базовых токенов A и B
+    inventory: [f64; 2],
+    cursors: [CandleCursor; 2],
+    // Закрытия баров, еще не сопоставленные с баром другой пары
+    unmatched: [BTreeMap<i64, f64>; 2],
+    // Сопоставленные закрытия (A, B) за последние `lookback` баров
+    closes: VecDeque<(f64, f64)>,
+    last_open_time: Option<i64>,
+    position: Option<HedgedPosition>,
+}
+
+impl PairsStrategy {
+    pub fn new(config: PairsConfig) -> Result<Self, Box<dyn Error>> {
+        if config.lookback < 10 {
+            return Err("lookback must be at least 10 bars".into());
+        }
+        if !(config.exit_z >= 0.0 && config.entry_z > config.exit_z) {
+            return Err("entry_z must exceed exit_z, both non-negative".into());
+        }
+        if !(config.notional > 0.0 && config.max_holding_secs > 0) {
+            return Err("notional and max_holding_secs must be positive".into());
+        }
+        Ok(Self {
+            config,
+            cursors: Default::default(),
+            unmatched: Default::default(),
+            closes: VecDeque::new(),
+            last_open_time: None,
+            position: None,
+            inventory: [0.0; 2],
+        })
+    }
+
+    pub fn from_params(params: &Value) -> Result<Self, Box<dyn Error>> {
+        Self::new(parse_params(params)?)
+    }
+
+    pub fn position(&self) -> Option<&HedgedPosition> {
+        self.position.as_ref()
+    }
+
+    // Добавляет в окно бары, закрывшиеся по обеим парам. Возвращает true, если окно сдвинулось.
+    fn update_closes(&mut self, ctx: &StrategyContext, price_data: &PriceData) -> Result<bool, Box<dyn Error>> {
+        for leg in 0..2 {
+            for candle in self.cursors[leg].new_candles(price_data, &ctx.pairs[leg], self.config.timeframe)? {
+                self.unmatched[leg].insert(candle.open_time, candle.close);
+            }
+        }
+
+        let mut updated = false;
+        let open_times: Vec<i64> = self.unmatched[0].keys()
+            .filter(|open_time| self.unmatched[1].contains_key(open_time))
+            .copied()
+            .collect();
+        for open_time in open_times {
+            let a = self.unmatched[0].remove(&open_time).unwrap_or_default();
+            let b = self.unmatched[1].remove(&open_time).unwrap_or_default();
+            if a > 0.0 && b > 0.0 {
+                self.closes.push_back((a, b));
+                if self.closes.len() > self.config.lookback {
+                    self.closes.pop_front();
+                }
+                self.last_open_time = Some(open_time);
+                updated = true;
+            }
+        }
+        // Бары без пары старше последнего сопоставленного уже не понадобятся
+        if let Some(last) = self.last_open_time {
+            for unmatched in &mut self.unmatched {
+                unmatched.retain(|&open_time, _| open_time > last);
+            }
+        }
+        Ok(updated)
+    }
+
+    // Позиция ноги сверх выделенного запаса
+    fn leg_position(&self, ctx: &StrategyContext, leg: usize) -> f64 {
+        ctx.position(&ctx.pairs[leg]) - self.inventory[leg]
+    }
+
+    // Первая нога входа. Обе ноги уменьшаются, если на продажу не хватает
+    // позиции экземпляра, а на покупку - свободных средств: иначе хедж не пройдет.
+    fn open(&self, ctx: &StrategyContext, side: SpreadSide, model: &SpreadModel, prices: [f64; 2]) -> Result<Option<TradingSignal>, Box<dyn Error>> {
+        let (action_a, _) = side.entry_actions();
+        let sizes = [
+            self.config.notional / prices[0],
+            self.config.notional * model.beta / prices[1],
+        ];
+        let (sell_leg, buy_leg) = if action_a == TradeAction::Sell { (0, 1) } else { (1, 0) };
+        let scale = (ctx.position(&ctx.pairs[sell_leg]) / sizes[sell_leg])
+            .min(ctx.cash() / (sizes[buy_leg] * self.limit_price(TradeAction::Buy, prices[buy_leg])))
+            .min(1.0);
+        if scale < 1.0 {
+            info!("Pairs {}: entry scaled to {:.2} of notional by inventory and cash", ctx.id, scale);
+        }
+
+        let signal = ctx.build_signal(&ctx.pairs[0], action_a, sizes[0] * scale, self.limit_price(action_a, prices[0]))?;
+        Ok(signal.map(|signal| {
+            signal
+                .with_reason("z", format!("{:.2}", model.z))
+                .with_reason("hedge_ratio", format!("{:.4}", model.beta))
+        }))
+    }
+
+    // Закрывает обе ноги по текущим позициям экземпляра сверх запаса
+    fn close(&self, ctx: &StrategyContext, prices: [f64; 2]) -> Result<Vec<TradingSignal>, Box<dyn Error>> {
+        let mut legs = Vec::new();
+        for (leg, pair) in ctx.pairs.iter().enumerate() {
+            let position = self.leg_position(ctx, leg);
+            let action = if position > 0.0 { TradeAction::Sell } else { TradeAction::Buy };
+            if position.abs() > POSITION_EPSILON {
+                if let Some(signal) = ctx.build_signal(pair, action, position.abs(), self.limit_price(action, prices[leg]))? {
+                    legs.push(signal);
+                }
+            }
+        }
+        Ok(bundle(legs))
+    }
+
+    fn limit_price(&self, action: TradeAction, price: f64) -> f64 {
+        let slippage = self.config.max_slippage_bps / 10_000.0;
+        match action {
+            TradeAction::Buy => price * (1.0 + slippage),
+            TradeAction::Sell => price * (1.0 - slippage),
+        }
+    }
+}
+
+impl Strategy for PairsStrategy {
+    fn name(&self) -> &str {
+        "pairs"
+    }
+
+    fn init(&mut self, ctx: &StrategyContext) -> Result<(), Box<dyn Error>> {
+        match ctx.pairs {
+            [a, b] if a.quote() == b.quote() && a.base() != b.base() => {}
+            _ => return Err("pairs needs exactly two pairs with a common quote token".into()),
+        }
+        // Продать можно любую из ног, поэтому запас нужен по обеим
+        for (leg, pair) in ctx.pairs.iter().enumerate() {
+            let inventory = ctx.position(pair);
+            if inventory <= POSITION_EPSILON {
+                return Err(format!("pairs needs {} inventory allocated to the instance to sell", pair.base()).into());
+            }
+            self.inventory[leg] = inventory;
+        }
+        Ok(())
+    }
+
+    fn on_tick(&mut self, ctx: &StrategyContext, price_data: &PriceData) -> Result<Vec<TradingSignal>, Box<dyn Error>> {
+        if !self.update_closes(ctx, price_data)? {
+            return Ok(Vec::new());
+        }
+        let now = match self.last_open_time {
+            Some(open_time) => open_time + self.config.timeframe.seconds(),
+            None => return Ok(Vec::new()),
+        };
+        if !ctx.pairs.iter().all(|pair| price_data.is_tradable(pair)) {
+            return Ok(Vec::new());
+        }
+        let prices = [price_data.get_price(&ctx.pairs[0])?, price_data.get_price(&ctx.pairs[1])?];
+
+        // Позиция сверяется с исполнением: если ноги не открылись или уже
+        // закрыты, стратегия снова свободна
+        let flat = (0..2).all(|leg| self.leg_position(ctx, leg).abs() <= POSITION_EPSILON);
+        if flat {
+            self.position = None;
+        }
+
+        let model = if self.closes.len() >= self.config.lookback {
+            SpreadModel::fit(&self.closes)
+        } else {
+            None
+        };
+
+        let position = match &self.position {
+            Some(position) => position,
+            None if !flat => {
+                // Остаток ног без открытой позиции, например после частичного закрытия
+                return self.close(ctx, prices);
+            }
+            None => {
+                let model = match model {
+                    Some(model) if model.beta > 0.0 => model,
+                    _ => return Ok(Vec::new()),
+                };
+                let reverting = match model.half_life {
+                    Some(half_life) => half_life <= self.config.max_half_life,
+                    None => false,
+                };
+                if !reverting || model.z.abs() < self.config.entry_z {
+                    return Ok(Vec::new());
+                }
+                // Спред выше среднего - A дорог относительно B
+                let side = if model.z > 0.0 { SpreadSide::Short } else { SpreadSide::Long };
+                let signal = match self.open(ctx, side, &model, prices)? {
+                    Some(signal) => signal,
+                    None => return Ok(Vec::new()),
+                };
+                info!(
+                    "Pairs {}: open {:?} spread {}/{} at z {:.2}, hedge ratio {:.4}, half-life {:.1} bars",
+                    ctx.id, side, ctx.pairs[0], ctx.pairs[1], model.z, model.beta, model.half_life.unwrap_or_default()
+                );
+                self.position = Some(HedgedPosition {
+                    side,
+                    hedge_ratio: model.beta,
+                    entry_z: model.z,
+                    opened_at: now,
+                    entry_order: signal.client_order_id,
+                    hedge_price: prices[1],
+                    hedge_orders: Vec::new(),
+                });
+                return Ok(vec![signal]);
+            }
+        };
+
+        let expired = now - position.opened_at >= self.config.max_holding_secs;
+        let reverted = match (model, position.side) {
+            (Some(model), SpreadSide::Long) => model.z >= -self.config.exit_z,
+            (Some(model), SpreadSide::Short) => model.z <= self.config.exit_z,
+            (None, _) => false,
+        };
+        if !(expired || reverted) {
+            return Ok(Vec::new());
+        }
+        let (gross, net) = HedgedPosition::exposure(&[
+            (self.leg_position(ctx, 0), prices[0]),
+            (self.leg_position(ctx, 1), prices[1]),
+        ]);
+        info!(
+            "Pairs {}: close {:?} spread opened at z {:.2} ({}), gross {:.2}, net {:.2}",
+            ctx.id, position.side, position.entry_z,
+            if expired { "max holding time" } else { "reverted" }, gross, net
+        );
+        self.close(ctx, prices)
+    }
+
+    // Исполнение первой ноги: хедж по B на исполненную стоимость, а не на заявленную
+    fn on_fill(&mut self, ctx: &StrategyContext, fill: &Fill) -> Result<Vec<TradingSignal>, Box<dyn Error>> {
+        let (side, hedge_ratio, hedge_price) = match &self.position {
+            Some(position) if position.entry_order == fill.client_order_id => (position.side, position.hedge_ratio, position.hedge_price),
+            _ => return Ok(Vec::new()),
+        };
+        let (_, action) = side.entry_actions();
+        let pair = &ctx.pairs[1];
+        let mut size = fill.notional() * hedge_ratio / hedge_price;
+        if action == TradeAction::Sell {
+            size = size.min(ctx.position(pair));
+        }
+        let signal = match ctx.build_signal(pair, action, size, self.limit_price(action, hedge_price))? {
+            Some(signal) => signal,
+            None => {
+                warn!("Pairs {}: hedge of {} {} is below minimum order size", ctx.id, size, pair.base());
+                return Ok(Vec::new());
+            }
+        };
+        if let Some(position) = &mut self.position {
+            position.hedge_orders.push(signal.client_order_id);
+        }
+        Ok(vec![signal])
+    }
+
+    // Первая нога не дошла до биржи - позиции нет. Не прошел хедж - исполненная
+    // первая нога закрывается на следующем баре как остаток без позиции.
+    fn on_reject(&mut self, ctx: &StrategyContext, client_order_id: u64) -> Result<(), Box<dyn Error>> {
+        let hedge = match &self.position {
+            Some(position) if position.entry_order == client_order_id => false,
+            Some(position) if position.hedge_orders.contains(&client_order_id) => true,
+            _ => return Ok(()),
+        };
+        if hedge {
+            warn!("Pairs {}: hedge order {} rejected, unwinding the first leg", ctx.id, client_order_id);
+        }
+        self.position = None;
+        Ok(())
+    }
+
+    fn trades_on_fill(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+#[path = "pairs_test.rs"]
+mod tests;
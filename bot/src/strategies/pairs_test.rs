@@ -0,0 +1,193 @@
+use super::*;
+use crate::candles::CandleBuilder;
+use crate::market_registry::test_registry;
+use crate::price_feed::TokenPair;
+use crate::strategies::Allocation;
+use solana_sdk::pubkey::Pubkey;
+
+const START: i64 = 1_704_067_200;
+
+// Запас mSOL и SOL, выделенный экземпляру
+const INVENTORY: f64 = 5.0;
+
+fn strategy(max_holding_secs: i64) -> PairsStrategy {
+    let mut strategy = PairsStrategy::from_params(&serde_json::json!({
+        "timeframe": "1m",
+        "lookback": 40,
+        "entry_z": 2.0,
+        "exit_z": 0.5,
+        "max_holding_secs": max_holding_secs,
+        "max_half_life": 10.0,
+        "notional": 110.0,
+        "max_slippage_bps": 0.0
+    })).unwrap();
+    with_ctx(&held(0.0, 0.0), |ctx| strategy.init(ctx)).unwrap();
+    strategy
+}
+
+// Цены (mSOL, SOL) по барам: SOL растет, спред ln(mSOL) - ln(SOL) колеблется
+// около ln(1.1), последние бары сдвигаются на `shifts`
+fn history(shifts: &[f64]) -> Vec<(f64, f64)> {
+    let bars = 40;
+    (0..bars + shifts.len())
+        .map(|i| {
+            let sol = 100.0 + i as f64 * 0.5;
+            let mut spread = 0.002 * (i as f64 * std::f64::consts::FRAC_PI_4).sin();
+            if i >= bars {
+                spread = shifts[i - bars];
+            }
+            (1.1 * sol * spread.exp(), sol)
+        })
+        .collect()
+}
+
+// Данные после закрытия первых `count` баров истории
+fn price_data(history: &[(f64, f64)], count: usize) -> PriceData {
+    let mut candles = CandleBuilder::new();
+    for (i, &(msol, sol)) in history[..count].iter().enumerate() {
+        let timestamp = START + i as i64 * 60;
+        candles.on_tick("mSOL/USDC", timestamp, msol);
+        candles.on_tick("SOL/USDC", timestamp, sol);
+    }
+    candles.advance(START + count as i64 * 60);
+    let (msol, sol) = history[count - 1];
+    let mut price_data = PriceData::with_prices(&[("mSOL/USDC", msol), ("SOL/USDC", sol)]);
+    price_data.set_candles(&candles);
+    price_data
+}
+
+fn with_ctx<T>(allocation: &Allocation, f: impl FnOnce(&StrategyContext) -> T) -> T {
+    let markets = test_registry();
+    let pairs = [TokenPair::new("mSOL", "USDC"), TokenPair::new("SOL", "USDC")];
+    f(&StrategyContext { id: "pairs-msol", pairs: &pairs, markets: &markets, allocation, payer: Pubkey::new_unique() })
+}
+
+fn tick(strategy: &mut PairsStrategy, allocation: &Allocation, price_data: &PriceData) -> Vec<TradingSignal> {
+    with_ctx(allocation, |ctx| strategy.on_tick(ctx, price_data)).unwrap()
+}
+
+// Позиции экземпляра: запас плюс открытые ноги
+fn held(msol: f64, sol: f64) -> Allocation {
+    let mut allocation = Allocation::new(1000.0);
+    allocation.positions.insert("mSOL/USDC".to_string(), INVENTORY + msol);
+    allocation.positions.insert("SOL/USDC".to_string(), INVENTORY + sol);
+    allocation
+}
+
+fn fill(signal: &TradingSignal, price: f64, size: f64) -> Fill {
+    Fill {
+        pair: TokenPair::new("mSOL", "USDC"),
+        client_order_id: signal.client_order_id,
+        action: signal.action,
+        price,
+        size,
+        fee: 0.0,
+        timestamp: START,
+    }
+}
+
+#[test]
+fn test_fit_recovers_hedge_ratio() {
+    let closes: VecDeque<(f64, f64)> = history(&[]).into_iter().collect();
+    let model = SpreadModel::fit(&closes).unwrap();
+    assert!((model.beta - 1.0).abs() < 0.05, "{}", model.beta);
+    assert!(model.half_life.unwrap() < 10.0);
+
+    // mSOL растет в 1.5 раза быстрее SOL в логарифмах
+    let closes: VecDeque<(f64, f64)> = closes.iter().map(|&(msol, sol)| (msol.powf(1.5), sol)).collect();
+    assert!((SpreadModel::fit(&closes).unwrap().beta - 1.5).abs() < 0.05);
+}
+
+#[test]
+fn test_opens_hedged_legs_and_closes_on_reversion() {
+    let history = history(&[0.02, 0.0]);
+    let mut strategy = strategy(3600);
+    assert!(tick(&mut strategy, &held(0.0, 0.0), &price_data(&history, 40)).is_empty());
+
+    // mSOL подорожал относительно SOL: сначала продается mSOL из запаса
+    let signals = tick(&mut strategy, &held(0.0, 0.0), &price_data(&history, 41));
+    assert_eq!(signals.len(), 1);
+    assert_eq!(signals[0].action, TradeAction::Sell);
+    assert!(signals[0].bundle_id.is_none());
+    assert_eq!(strategy.position().unwrap().side, SpreadSide::Short);
+
+    // Хедж по SOL - на исполненную часть первой ноги, а не на заявленную
+    let hedge_price = strategy.position().unwrap().hedge_price;
+    let hedge_ratio = strategy.position().unwrap().hedge_ratio;
+    let hedge = with_ctx(&held(-0.5, 0.0), |ctx| strategy.on_fill(ctx, &fill(&signals[0], 120.0, 0.5))).unwrap();
+    assert_eq!(hedge.len(), 1);
+    assert_eq!(hedge[0].action, TradeAction::Buy);
+    let expected = 60.0 * hedge_ratio / hedge_price;
+    assert!((hedge[0].amount as f64 / 1e9 - expected).abs() < 0.1, "{} {}", hedge[0].amount, expected);
+
+    // Спред вернулся: обе ноги закрываются одним бандлом по позициям сверх запаса
+    let signals = tick(&mut strategy, &held(-0.9, 1.0), &price_data(&history, 42));
+    assert_eq!(signals.len(), 2);
+    assert_eq!((signals[0].action, signals[0].amount), (TradeAction::Buy, 900_000_000));
+    assert_eq!((signals[1].action, signals[1].amount), (TradeAction::Sell, 1_000_000_000));
+    assert_eq!(signals[0].bundle_id, signals[1].bundle_id);
+
+    // После закрытия стратегия снова свободна
+    let history = self::history(&[0.02, 0.0, 0.0]);
+    assert!(tick(&mut strategy, &held(0.0, 0.0), &price_data(&history, 43)).is_empty());
+    assert!(strategy.position().is_none());
+}
+
+#[test]
+fn test_closes_after_max_holding_time() {
+    let history = history(&[0.02, 0.02, 0.02]);
+    let mut strategy = strategy(120);
+    tick(&mut strategy, &held(0.0, 0.0), &price_data(&history, 40));
+    assert_eq!(tick(&mut strategy, &held(0.0, 0.0), &price_data(&history, 41)).len(), 1);
+
+    // Спред не вернулся, но через два бара позиция закрывается принудительно
+    assert!(tick(&mut strategy, &held(-0.9, 1.0), &price_data(&history, 42)).is_empty());
+    let signals = tick(&mut strategy, &held(-0.9, 1.0), &price_data(&history, 43));
+    assert_eq!(signals.iter().map(|signal| signal.action).collect::<Vec<_>>(), vec![TradeAction::Buy, TradeAction::Sell]);
+}
+
+#[test]
+fn test_rejected_entry_frees_strategy() {
+    let history = history(&[0.02, 0.02]);
+    let mut strategy = strategy(3600);
+    tick(&mut strategy, &held(0.0, 0.0), &price_data(&history, 40));
+    let signals = tick(&mut strategy, &held(0.0, 0.0), &price_data(&history, 41));
+    assert_eq!(signals.len(), 1);
+
+    // Первая нога не прошла: позиций нет, и при сохранившемся отклонении вход повторяется
+    with_ctx(&held(0.0, 0.0), |ctx| strategy.on_reject(ctx, signals[0].client_order_id)).unwrap();
+    assert!(strategy.position().is_none());
+    assert_eq!(tick(&mut strategy, &held(0.0, 0.0), &price_data(&history, 42)).len(), 1);
+}
+
+#[test]
+fn test_rejected_hedge_unwinds_first_leg() {
+    let history = history(&[0.02, 0.02]);
+    let mut strategy = strategy(3600);
+    tick(&mut strategy, &held(0.0, 0.0), &price_data(&history, 40));
+    let signals = tick(&mut strategy, &held(0.0, 0.0), &price_data(&history, 41));
+    let hedge = with_ctx(&held(-0.8, 0.0), |ctx| strategy.on_fill(ctx, &fill(&signals[0], 120.0, 0.8))).unwrap();
+
+    // Хедж отклонен: проданный mSOL откупается на следующем баре
+    with_ctx(&held(-0.8, 0.0), |ctx| strategy.on_reject(ctx, hedge[0].client_order_id)).unwrap();
+    assert!(strategy.position().is_none());
+    let signals = tick(&mut strategy, &held(-0.8, 0.0), &price_data(&history, 42));
+    assert_eq!(signals.len(), 1);
+    assert_eq!((signals[0].action, signals[0].amount), (TradeAction::Buy, 800_000_000));
+}
+
+#[test]
+fn test_entry_limited_by_inventory() {
+    let mut strategy = strategy(3600);
+    let mut allocation = held(0.0, 0.0);
+    allocation.positions.insert("mSOL/USDC".to_string(), 0.0);
+    assert!(with_ctx(&allocation, |ctx| strategy.init(ctx)).is_err());
+
+    // Запаса mSOL меньше заявленного объема: продается только он
+    allocation.positions.insert("mSOL/USDC".to_string(), 0.3);
+    with_ctx(&allocation, |ctx| strategy.init(ctx)).unwrap();
+    let history = history(&[0.02]);
+    tick(&mut strategy, &allocation, &price_data(&history, 40));
+    let signals = tick(&mut strategy, &allocation, &price_data(&history, 41));
+    assert_eq!((signals[0].action, signals[0].amount), (TradeAction::Sell, 300_000_000));
+}
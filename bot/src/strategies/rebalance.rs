@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+use std::error::Error;
+
+use log::info;
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::registry::parse_params;
+use super::schedule::{CronSchedule, ScheduleTracker};
+use super::{Strategy, StrategyContext};
+use crate::jito_integration::{TradeAction, TradingSignal};
+use crate::price_feed::PriceData;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RebalanceConfig {
+    // Целевые доли токенов в стоимости портфеля, включая котируемый токен.
+    // Базовые токены пар без доли держатся на нуле.
+    pub targets: HashMap<String, f64>,
+    // Допустимое отклонение доли любого токена от целевой
+    #[serde(default = "default_band")]
+    pub band: f64,
+    // Когда проверять отклонение, в формате cron (UTC)
+    #[serde(default = "default_schedule")]
+    pub schedule: String,
+    #[serde(default = "default_max_slippage_bps")]
+    pub max_slippage_bps: f64,
+    // Сделки меньше этой суммы в котируемом токене не отправляются
+    #[serde(default = "default_min_trade_notional")]
+    pub min_trade_notional: f64,
+}
+
+fn default_band() -> f64 {
+    0.05
+}
+
+fn default_schedule() -> String {
+    "*/15 * * * *".to_string()
+}
+
+fn default_max_slippage_bps() -> f64 {
+    50.0
+}
+
+fn default_min_trade_notional() -> f64 {
+    1.0
+}
+
+// Ребалансировка: по расписанию сравнивает доли токенов портфеля с целевыми и,
+// если отклонение вышло за допуск, возвращает все доли к целевым.
+// Все пары должны котироваться в одном токене - он же свободные средства.
+pub struct RebalanceStrategy {
+    config: RebalanceConfig,
+    tracker: ScheduleTracker,
+    // Последние пригодные для торговли цены по ключу пары
+    prices: HashMap<String, f64>,
+}
+
+impl RebalanceStrategy {
+    pub fn new(config: RebalanceConfig) -> Result<Self, Box<dyn Error>> {
+        if config.targets.values().any(|&weight| !(0.0..=1.0).contains(&weight)) {
+            return Err("target weights must be between 0 and 1".into());
+        }
+        let total: f64 = config.targets.values().sum();
+        if (total - 1.0).abs() > 1e-6 {
+            return Err(format!("target weights must sum to 1, got {}", total).into());
+        }
+        if !(config.band > 0.0 && config.band < 1.0) {
+            return Err("band must be between 0 and 1".into());
+        }
+        let tracker = ScheduleTracker::new(CronSchedule::parse(&config.schedule)?);
+        Ok(Self {
+            config,
+            tracker,
+            prices: HashMap::new(),
+        })
+    }
+
+    pub fn from_params(params: &Value) -> Result<Self, Box<dyn Error>> {
+        Self::new(parse_params::<Option<RebalanceConfig>>(params)?.ok_or("rebalance params are required")?)
+    }
+
+    fn target(&self, token: &str) -> f64 {
+        self.config.targets.get(token).copied().unwrap_or(0.0)
+    }
+}
+
+impl Strategy for RebalanceStrategy {
+    fn name(&self) -> &str {
+        "rebalance"
+    }
+
+    fn init(&mut self, ctx: &StrategyContext) -> Result<(), Box<dyn Error>> {
+        let quote = match ctx.pairs.first() {
+            Some(pair) => pair.quote(),
+            None => return Err("rebalance needs at least one pair".into()),
+        };
+        if ctx.pairs.iter().any(|pair| pair.quote() != quote) {
+            return Err("all rebalanced pairs must share the quote token".into());
+        }
+        for token in self.config.targets.keys() {
+            if token != quote && !ctx.pairs.iter().any(|pair| pair.base() == token) {
+                return Err(format!("target token {} is not traded by this instance", token).into());
+            }
+        }
+        Ok(())
+    }
+
+    fn on_tick(&mut self, ctx: &StrategyContext, price_data: &PriceData) -> Result<Vec<TradingSignal>, Box<dyn Error>> {
+        for pair in ctx.pairs {
+            if price_data.is_tradable(pair) {
+                self.prices.insert(pair.to_string(), price_data.get_price(pair)?);
+            } else {
+                self.prices.remove(&pair.to_string());
+            }
+        }
+        Ok(Vec::new())
+    }
+
+    fn timer_interval(&self) -> Option<i64> {
+        Some(60)
+    }
+
+    fn on_timer(&mut self, ctx: &StrategyContext, timestamp: i64) -> Result<Vec<TradingSignal>, Box<dyn Error>> {
+        if !self.tracker.due(timestamp) {
+            return Ok(Vec::new());
+        }
+
+        // Без цены хотя бы одной пары стоимость портфеля неизвестна
+        let mut holdings = Vec::new();
+        for pair in ctx.pairs {
+            match self.prices.get(&pair.to_string()) {
+                Some(&price) => holdings.push((pair, price, ctx.position(pair))),
+                None => return Ok(Vec::new()),
+            }
+        }
+        let total = ctx.cash() + holdings.iter().map(|&(_, price, position)| price * position).sum::<f64>();
+        if total <= 0.0 {
+            return Ok(Vec::new());
+        }
+
+        let quote = ctx.pairs[0].quote();
+        let mut drift = (ctx.cash() / total - self.target(quote)).abs();
+        for &(pair, price, position) in &holdings {
+            drift = drift.max((price * position / total - self.target(pair.base())).abs());
+        }
+        if drift <= self.config.band {
+            return Ok(Vec::new());
+        }
+        info!("Rebalance {}: max weight drift {:.4} exceeds band {}, portfolio value {:.2}", ctx.id, drift, self.config.band, total);
+
+        // Сначала продажи: их выручка оплачивает покупки
+        let slippage = self.config.max_slippage_bps / 10_000.0;
+        let mut signals = Vec::new();
+        let mut cash = ctx.cash();
+        for &(pair, price, position) in &holdings {
+            let excess = price * position - self.target(pair.base()) * total;
+            if excess < self.config.min_trade_notional {
+                continue;
+            }
+            let limit_price = price * (1.0 - slippage);
+            let size = (excess / price).min(position);
+            if let Some(signal) = ctx.build_signal(pair, TradeAction::Sell, size, limit_price)? {
+                cash += size * limit_price;
+                signals.push(signal.with_reason("drift", format!("{:.4}", drift)));
+            }
+        }
+        for &(pair, price, position) in &holdings {
+            let shortfall = (self.target(pair.base()) * total - price * position).min(cash);
+            if shortfall < self.config.min_trade_notional {
+                continue;
+            }
+            let limit_price = price * (1.0 + slippage);
+            if let Some(signal) = ctx.build_signal(pair, TradeAction::Buy, shortfall / limit_price, limit_price)? {
+                cash -= shortfall;
+                signals.push(signal.with_reason("drift", format!("{:.4}", drift)));
+            }
+        }
+        Ok(signals)
+    }
+}
+
+#[cfg(test)]
+#[path = "rebalance_test.rs"]
+mod tests;
@@ -0,0 +1,76 @@
+use super::*;
+use crate::market_registry::test_registry;
+use crate::price_feed::TokenPair;
+use crate::strategies::Allocation;
+use solana_sdk::pubkey::Pubkey;
+
+// 2024-01-01 00:00 UTC
+const NEW_YEAR: i64 = 1_704_067_200;
+
+fn strategy() -> RebalanceStrategy {
+    RebalanceStrategy::from_params(&serde_json::json!({
+        "targets": { "SOL": 0.5, "RAY": 0.3, "USDC": 0.2 },
+        "band": 0.05,
+        "schedule": "0 * * * *"
+    })).unwrap()
+}
+
+fn allocation(cash: f64, sol: f64, ray: f64) -> Allocation {
+    let mut allocation = Allocation::new(cash);
+    allocation.positions.insert("SOL/USDC".to_string(), sol);
+    allocation.positions.insert("RAY/USDC".to_string(), ray);
+    allocation
+}
+
+fn run(strategy: &mut RebalanceStrategy, allocation: &Allocation, timestamp: i64) -> Result<Vec<TradingSignal>, Box<dyn Error>> {
+    let markets = test_registry();
+    let pairs = [TokenPair::new("SOL", "USDC"), TokenPair::new("RAY", "USDC")];
+    let ctx = StrategyContext { id: "rebalance", pairs: &pairs, markets: &markets, allocation, payer: Pubkey::new_unique() };
+    strategy.init(&ctx)?;
+    strategy.on_tick(&ctx, &PriceData::with_prices(&[("SOL/USDC", 100.0), ("RAY/USDC", 2.0)]))?;
+    strategy.on_timer(&ctx, timestamp)
+}
+
+#[test]
+fn test_initial_allocation_buys_to_targets() {
+    let mut strategy = strategy();
+    let signals = run(&mut strategy, &allocation(1000.0, 0.0, 0.0), NEW_YEAR).unwrap();
+
+    assert_eq!(signals.len(), 2);
+    assert!(signals.iter().all(|signal| signal.action == TradeAction::Buy));
+    // 500 USDC в SOL по 100.5 и 300 USDC в RAY по 2.01, объемы округлены вниз до лота
+    assert_eq!((signals[0].price, signals[0].amount), (100_500_000, 4_900_000_000));
+    assert_eq!((signals[1].price, signals[1].amount), (2_010_000, 149_200_000));
+}
+
+#[test]
+fn test_drift_within_band_is_ignored() {
+    let mut strategy = strategy();
+    // SOL 52%, RAY 28%, USDC 20%
+    assert!(run(&mut strategy, &allocation(200.0, 5.2, 140.0), NEW_YEAR).unwrap().is_empty());
+}
+
+#[test]
+fn test_sells_before_buys() {
+    let mut strategy = strategy();
+    // SOL 80% портфеля: продаем излишек и на выручку докупаем RAY
+    let signals = run(&mut strategy, &allocation(200.0, 8.0, 0.0), NEW_YEAR).unwrap();
+
+    assert_eq!(signals.len(), 2);
+    assert_eq!((signals[0].action, signals[0].price, signals[0].amount), (TradeAction::Sell, 99_500_000, 3_000_000_000));
+    assert_eq!((signals[1].action, signals[1].amount), (TradeAction::Buy, 149_200_000));
+
+    // До следующего часа по расписанию проверок нет
+    let mut strategy = self::strategy();
+    assert!(run(&mut strategy, &allocation(200.0, 8.0, 0.0), NEW_YEAR + 60).unwrap().is_empty());
+}
+
+#[test]
+fn test_invalid_targets() {
+    assert!(RebalanceStrategy::from_params(&Value::Null).is_err());
+    assert!(RebalanceStrategy::from_params(&serde_json::json!({ "targets": { "SOL": 0.5, "USDC": 0.4 } })).is_err());
+
+    // Токен без пары в экземпляре
+    let mut strategy = RebalanceStrategy::from_params(&serde_json::json!({ "targets": { "BTC": 0.5, "USDC": 0.5 } })).unwrap();
+    assert!(run(&mut strategy, &allocation(1000.0, 0.0, 0.0), NEW_YEAR).is_err());
+}
@@ -0,0 +1,111 @@
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fs;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::arbitrage::ArbitrageStrategy;
+use super::dca::DcaStrategy;
+use super::ensemble::EnsembleStrategy;
+use super::grid::GridStrategy;
+use super::market_maker::MarketMakerStrategy;
+use super::momentum::MomentumStrategy;
+use super::pairs::PairsStrategy;
+use super::rebalance::RebalanceStrategy;
+use super::Strategy;
+use crate::trading_strategy::TradingStrategy;
+
+// Создает стратегию по параметрам из конфигурации. Реестр передается фабрике,
+// чтобы составные стратегии создавали дочерние из того же набора.
+pub type StrategyFactory = fn(&Value, &StrategyRegistry) -> Result<Box<dyn Strategy>, Box<dyn Error>>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunnerConfig {
+    pub strategies: Vec<StrategyInstanceConfig>,
+}
+
+// Экземпляр стратегии в конфигурационном файле
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyInstanceConfig {
+    // Уникальный идентификатор экземпляра, например "momentum-ray"
+    pub id: String,
+    // Имя стратегии в реестре
+    pub strategy: String,
+    // Пары в формате "BASE/QUOTE"
+    pub pairs: Vec<String>,
+    // Выделенный капитал в котируемом токене
+    pub capital: f64,
+    // Базовые токены, выделенные экземпляру вместе с капиталом, по паре:
+    // стратегия может продавать их, не открывая короткую позицию
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub inventory: BTreeMap<String, f64>,
+    #[serde(default)]
+    pub params: Value,
+    // Через сколько слотов после тика неисполненный сигнал устаревает
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signal_ttl_slots: Option<u64>,
+}
+
+impl RunnerConfig {
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read strategies config {}: {}", path, e))?;
+        Self::from_json(&contents)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, Box<dyn Error>> {
+        Ok(serde_json::from_str(json)?)
+    }
+}
+
+pub struct StrategyRegistry {
+    factories: HashMap<String, StrategyFactory>,
+}
+
+impl StrategyRegistry {
+    pub fn new() -> Self {
+        Self {
+            factories: HashMap::new(),
+        }
+    }
+
+    // Реестр со всеми стратегиями из этого крейта
+    pub fn with_builtin() -> Self {
+        let mut registry = Self::new();
+        registry.register("ma_crossover", |params, _| Ok(Box::new(TradingStrategy::from_params(params)?)));
+        registry.register("momentum", |params, _| Ok(Box::new(MomentumStrategy::from_params(params)?)));
+        registry.register("grid", |params, _| Ok(Box::new(GridStrategy::from_params(params)?)));
+        registry.register("market_maker", |params, _| Ok(Box::new(MarketMakerStrategy::from_params(params)?)));
+        registry.register("dca", |params, _| Ok(Box::new(DcaStrategy::from_params(params)?)));
+        registry.register("rebalance", |params, _| Ok(Box::new(RebalanceStrategy::from_params(params)?)));
+        registry.register("arbitrage", |params, _| Ok(Box::new(ArbitrageStrategy::from_params(params)?)));
+        registry.register("pairs", |params, _| Ok(Box::new(PairsStrategy::from_params(params)?)));
+        registry.register("ensemble", |params, registry| Ok(Box::new(EnsembleStrategy::from_params(params, registry)?)));
+        registry
+    }
+
+    pub fn register(&mut self, name: &str, factory: StrategyFactory) {
+        self.factories.insert(name.to_string(), factory);
+    }
+
+    pub fn create(&self, name: &str, params: &Value) -> Result<Box<dyn Strategy>, Box<dyn Error>> {
+        let factory = self.factories.get(name)
+            .ok_or_else(|| format!("Unknown strategy '{}'", name))?;
+        factory(params, self).map_err(|e| format!("Invalid params for strategy '{}': {}", name, e).into())
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.factories.keys().map(|name| name.as_str()).collect();
+        names.sort_unstable();
+        names
+    }
+}
+
+// Разбирает параметры стратегии; отсутствующие поля берутся из Default
+pub fn parse_params<T: Default + for<'de> Deserialize<'de>>(params: &Value) -> Result<T, Box<dyn Error>> {
+    if params.is_null() {
+        return Ok(T::default());
+    }
+    Ok(serde_json::from_value(params.clone())?)
+}
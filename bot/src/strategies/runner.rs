@@ -0,0 +1,670 @@
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+
+use log::{error, warn};
+use solana_sdk::pubkey::Pubkey;
+
+use super::registry::{RunnerConfig, StrategyRegistry};
+use super::{Allocation, Fill, Strategy, StrategyContext};
+use crate::jito_integration::{OrderAction, TradeAction, TradingSignal, Venue};
+use crate::market_registry::MarketRegistry;
+use crate::orderbook::{MarketFill, OrderOut};
+use crate::price_feed::{PriceData, TokenPair};
+
+// Сигнал с указанием экземпляра стратегии, который его сгенерировал
+#[derive(Debug, Clone)]
+pub struct StrategySignal {
+    pub strategy_id: String,
+    pub pair: TokenPair,
+    pub signal: TradingSignal,
+}
+
+struct StrategyInstance {
+    id: String,
+    strategy: Box<dyn Strategy>,
+    pairs: Vec<TokenPair>,
+    allocation: Allocation,
+    next_timer: Option<i64>,
+    // Сигналы из on_fill, ожидающие следующего тика
+    pending: Vec<TradingSignal>,
+    // Срок действия сигналов без собственного срока в слотах
+    signal_ttl_slots: Option<u64>,
+}
+
+// Post-only ордер стратегии, стоящий в стакане
+#[derive(Debug, Clone)]
+struct RestingOrder {
+    strategy_id: String,
+    pair: TokenPair,
+    action: TradeAction,
+    price: f64,
+    // Неисполненный остаток в базовом токене
+    size: f64,
+}
+
+// Срок действия blockhash: позже транзакция IOC-ордера в блок не попадет
+const TAKER_TIMEOUT_SLOTS: u64 = 150;
+
+// IOC или рыночный ордер, отправленный в сеть и ожидающий событий тейкера
+#[derive(Debug, Clone)]
+struct TakerOrder {
+    // Цена сигнала и неисполненный объем
+    order: RestingOrder,
+    // Худшая допустимая цена: по ней резервируется капитал до исполнения
+    limit_price: f64,
+    // Обмен в AMM-пуле: событий в event queue не будет
+    amm: bool,
+    bundle_id: Option<u64>,
+    // Слот, после которого транзакция уже не попадет в блок
+    expires_slot: u64,
+}
+
+// Запускает несколько экземпляров стратегий, каждый со своими парами и капиталом
+pub struct StrategyRunner {
+    markets: Arc<MarketRegistry>,
+    payer: Pubkey,
+    instances: Vec<StrategyInstance>,
+    // Стоящие ордера по client_order_id
+    resting: HashMap<u64, RestingOrder>,
+    // Отправленные IOC и рыночные ордера по client_order_id
+    takers: HashMap<u64, TakerOrder>,
+    // Слот последнего тика: от него отсчитывается срок действия сигналов
+    slot: u64,
+}
+
+impl StrategyRunner {
+    pub fn new(
+        config: &RunnerConfig,
+        strategies: &StrategyRegistry,
+        markets: Arc<MarketRegistry>,
+        payer: Pubkey,
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut ids = HashSet::new();
+        let mut instances = Vec::with_capacity(config.strategies.len());
+
+        for instance in &config.strategies {
+            if !ids.insert(instance.id.as_str()) {
+                return Err(format!("Duplicate strategy id '{}'", instance.id).into());
+            }
+            if !(instance.capital.is_finite() && instance.capital > 0.0) {
+                return Err(format!("{}: capital must be positive", instance.id).into());
+            }
+
+            let pairs = instance.pairs.iter()
+                .map(|pair| parse_pair(pair))
+                .collect::<Result<Vec<_>, _>>()?;
+            for pair in &pairs {
+                markets.get(pair).map_err(|e| format!("{}: {}", instance.id, e))?;
+            }
+            // Выделенные токены - начальные позиции экземпляра
+            let mut allocation = Allocation::new(instance.capital);
+            for (pair, &size) in &instance.inventory {
+                let pair = parse_pair(pair)?;
+                if !pairs.iter().any(|traded| traded.to_string() == pair.to_string()) {
+                    return Err(format!("{}: inventory of {} which the instance does not trade", instance.id, pair).into());
+                }
+                if !(size.is_finite() && size > 0.0) {
+                    return Err(format!("{}: inventory of {} must be positive", instance.id, pair).into());
+                }
+                allocation.positions.insert(pair.to_string(), size);
+            }
+
+            let mut strategy_instance = StrategyInstance {
+                id: instance.id.clone(),
+                strategy: strategies.create(&instance.strategy, &instance.params)?,
+                pairs,
+                allocation,
+                next_timer: None,
+                pending: Vec::new(),
+                signal_ttl_slots: instance.signal_ttl_slots,
+            };
+            let ctx = StrategyContext {
+                id: &strategy_instance.id,
+                pairs: &strategy_instance.pairs,
+                markets: &markets,
+                allocation: &strategy_instance.allocation,
+                payer,
+            };
+            strategy_instance.strategy.init(&ctx)
+                .map_err(|e| format!("{}: init failed: {}", instance.id, e))?;
+            instances.push(strategy_instance);
+        }
+
+        Ok(Self {
+            markets,
+            payer,
+            instances,
+            resting: HashMap::new(),
+            takers: HashMap::new(),
+            slot: 0,
+        })
+    }
+
+    pub fn allocation(&self, strategy_id: &str) -> Option<&Allocation> {
+        self.instances.iter()
+            .find(|instance| instance.id == strategy_id)
+            .map(|instance| &instance.allocation)
+    }
+
+    pub fn allocations(&self) -> impl Iterator<Item = (&str, &Allocation)> {
+        self.instances.iter().map(|instance| (instance.id.as_str(), &instance.allocation))
+    }
+
+    // Восстанавливает капитал и позиции экземпляра после перезапуска. Изменение
+    // капитала в конфигурации добавляется к свободным средствам. false - такого
+    // экземпляра больше нет.
+    pub fn restore_allocation(&mut self, strategy_id: &str, allocation: Allocation) -> bool {
+        let instance = match self.instances.iter_mut().find(|instance| instance.id == strategy_id) {
+            Some(instance) => instance,
+            None => return false,
+        };
+        let capital = instance.allocation.capital;
+        instance.allocation = Allocation {
+            capital,
+            cash: allocation.cash + (capital - allocation.capital),
+            ..allocation
+        };
+        true
+    }
+
+    // Стоящий ордер прошлого запуска: резервирует капитал экземпляра, а его
+    // исполнения доходят до стратегии как обычные. false - такого экземпляра больше нет.
+    pub fn restore_resting(&mut self, strategy_id: &str, client_order_id: u64, pair: &TokenPair, action: TradeAction, price: f64, size: f64) -> bool {
+        if !self.instances.iter().any(|instance| instance.id == strategy_id) {
+            return false;
+        }
+        self.resting.insert(client_order_id, RestingOrder {
+            strategy_id: strategy_id.to_string(),
+            pair: pair.clone(),
+            action,
+            price,
+            size,
+        });
+        true
+    }
+
+    // Вызывает on_tick всех стратегий по очереди. Ошибка или паника одной
+    // стратегии не останавливает остальные, а сигналы, отложенные из on_fill,
+    // отправляются и тогда.
+    pub fn on_tick(&mut self, price_data: &PriceData) -> Vec<StrategySignal> {
+        self.slot = price_data.slot();
+        self.expire_takers();
+        let mut approved = Vec::new();
+        for index in 0..self.instances.len() {
+            let instance = &mut self.instances[index];
+            let ctx = StrategyContext {
+                id: &instance.id,
+                pairs: &instance.pairs,
+                markets: &self.markets,
+                allocation: &instance.allocation,
+                payer: self.payer,
+            };
+            let mut signals = std::mem::take(&mut instance.pending);
+            let strategy = &mut instance.strategy;
+            let result = panic::catch_unwind(AssertUnwindSafe(|| strategy.on_tick(&ctx, price_data)))
+                .unwrap_or_else(|_| Err("strategy panicked".into()));
+            match result {
+                Ok(tick_signals) => signals.extend(tick_signals),
+                Err(e) => error!("Strategy {} failed on tick: {}", self.instances[index].id, e),
+            }
+            approved.extend(self.check_budget(&self.instances[index], signals));
+        }
+        approved
+    }
+
+    // Вызывает on_timer стратегий, у которых подошло время таймера
+    pub fn on_timer(&mut self, timestamp: i64) -> Vec<StrategySignal> {
+        let mut approved = Vec::new();
+        for index in 0..self.instances.len() {
+            let instance = &mut self.instances[index];
+            let interval = match instance.strategy.timer_interval() {
+                Some(interval) if interval > 0 => interval,
+                _ => continue,
+            };
+            if let Some(next) = instance.next_timer {
+                if timestamp < next {
+                    continue;
+                }
+            }
+            instance.next_timer = Some(timestamp + interval);
+
+            let instance = &mut self.instances[index];
+            let ctx = StrategyContext {
+                id: &instance.id,
+                pairs: &instance.pairs,
+                markets: &self.markets,
+                allocation: &instance.allocation,
+                payer: self.payer,
+            };
+            match instance.strategy.on_timer(&ctx, timestamp) {
+                Ok(signals) => approved.extend(self.check_budget(&self.instances[index], signals)),
+                Err(e) => error!("Strategy {} failed on timer: {}", self.instances[index].id, e),
+            }
+        }
+        approved
+    }
+
+    // Учитывает отправленный сигнал. GTC и post-only ордер встает в стакан и
+    // исполняется по событиям мейкера, IOC и рыночный ордер - по событиям тейкера
+    // из event queue. Обмен в AMM-пуле событий не оставляет: нога бандла
+    // подтверждается исполнением ноги Serum того же бандла (Jito включает бандл
+    // целиком), а одиночный обмен учитывается сразу по цене сигнала. Отменяемый
+    // ордер (в том числе при замене) остается на учете до подтверждения отмены
+    // в `on_canceled`: до нее он еще может исполниться.
+    pub fn on_executed(&mut self, executed: &StrategySignal, timestamp: i64) -> Result<(), Box<dyn Error>> {
+        let signal = &executed.signal;
+        if signal.order_action == OrderAction::Cancel {
+            return Ok(());
+        }
+
+        let taker = self.taker_order(executed)?;
+        if signal.is_resting() {
+            self.resting.insert(signal.client_order_id, taker.order);
+            return Ok(());
+        }
+
+        if taker.amm && signal.bundle_id.is_none() {
+            // Комиссия AMM-пула уже учтена в цене обмена
+            let order = taker.order;
+            return self.on_fill(&order.strategy_id, &Fill {
+                pair: order.pair.clone(),
+                client_order_id: signal.client_order_id,
+                action: order.action,
+                price: order.price,
+                size: order.size,
+                fee: 0.0,
+                timestamp,
+            });
+        }
+        self.takers.insert(signal.client_order_id, taker);
+        Ok(())
+    }
+
+    // Ордер отправлен на симулированную биржу. Как и отправленный в сеть, IOC
+    // или рыночный ордер резервирует капитал, пока биржа не вернет исполнение
+    // (`on_taker_fill`) или отказ; стоящий ордер учитывается по событию `Resting`.
+    pub fn on_submitted(&mut self, submitted: &StrategySignal) -> Result<(), Box<dyn Error>> {
+        let signal = &submitted.signal;
+        if signal.order_action == OrderAction::Cancel || signal.is_resting() {
+            return Ok(());
+        }
+        let taker = self.taker_order(submitted)?;
+        self.takers.insert(signal.client_order_id, taker);
+        Ok(())
+    }
+
+    // Исполнение тейкера на симулированной бирже: остаток IOC-ордера биржа уже
+    // отменила, резерв снимается
+    pub fn on_taker_fill(&mut self, strategy_id: &str, fill: &Fill) -> Result<(), Box<dyn Error>> {
+        self.takers.remove(&fill.client_order_id);
+        self.on_fill(strategy_id, fill)
+    }
+
+    fn taker_order(&self, submitted: &StrategySignal) -> Result<TakerOrder, Box<dyn Error>> {
+        let signal = &submitted.signal;
+        let quantizer = self.markets.get(&submitted.pair)?.quantizer();
+        Ok(TakerOrder {
+            order: RestingOrder {
+                strategy_id: submitted.strategy_id.clone(),
+                pair: submitted.pair.clone(),
+                action: signal.action,
+                price: quantizer.price_native_to_ui(signal.price),
+                size: quantizer.size_native_to_ui(signal.amount),
+            },
+            limit_price: quantizer.price_native_to_ui(signal.limit_price()),
+            amm: signal.venue != Venue::Serum,
+            bundle_id: signal.bundle_id,
+            expires_slot: self.slot + TAKER_TIMEOUT_SLOTS,
+        })
+    }
+
+    // Сигнал не исполнен: резерв ордера освобождается, стратегия узнает об отказе.
+    // Отклоненная отмена ничего не меняет - ордер остается в стакане.
+    pub fn on_reject(&mut self, rejected: &StrategySignal) {
+        let signal = &rejected.signal;
+        if signal.order_action == OrderAction::Cancel {
+            return;
+        }
+        self.resting.remove(&signal.client_order_id);
+        self.takers.remove(&signal.client_order_id);
+
+        let instance = match self.instances.iter_mut().find(|instance| instance.id == rejected.strategy_id) {
+            Some(instance) => instance,
+            None => return,
+        };
+        let ctx = StrategyContext {
+            id: &instance.id,
+            pairs: &instance.pairs,
+            markets: &self.markets,
+            allocation: &instance.allocation,
+            payer: self.payer,
+        };
+        if let Err(e) = instance.strategy.on_reject(&ctx, signal.client_order_id) {
+            error!("Strategy {} failed on reject: {}", instance.id, e);
+        }
+    }
+
+    // Стоящий ордер снят биржей: отмена или замена дошла до стакана. Резерв
+    // остатка освобождается, стратегия узнает о снятии.
+    pub fn on_canceled(&mut self, client_order_id: u64) {
+        let order = match self.resting.remove(&client_order_id) {
+            Some(order) => order,
+            None => return,
+        };
+        let instance = match self.instances.iter_mut().find(|instance| instance.id == order.strategy_id) {
+            Some(instance) => instance,
+            None => return,
+        };
+        let ctx = StrategyContext {
+            id: &instance.id,
+            pairs: &instance.pairs,
+            markets: &self.markets,
+            allocation: &instance.allocation,
+            payer: self.payer,
+        };
+        if let Err(e) = instance.strategy.on_canceled(&ctx, client_order_id) {
+            error!("Strategy {} failed on cancel: {}", instance.id, e);
+        }
+    }
+
+    // События Out из event queue по ордерам OpenOrders аккаунта бота. Полностью
+    // исполненный ордер к этому моменту уже снят с учета.
+    pub fn on_market_outs(&mut self, outs: &[OrderOut]) {
+        for out in outs {
+            if self.markets.get(&out.pair).ok().and_then(|market| market.open_orders) == Some(out.owner) {
+                self.on_canceled(out.client_order_id);
+            }
+        }
+    }
+
+    // Сделки из event queue: события мейкера по стоящим ордерам и события
+    // тейкера по отправленным IOC-ордерам стратегий передаются владельцам ордеров.
+    // Учитываются только события OpenOrders аккаунта бота из реестра рынков:
+    // client_order_id чужого ордера может совпасть с нашим.
+    pub fn on_market_fills(&mut self, fills: &[MarketFill], timestamp: i64) -> Result<(), Box<dyn Error>> {
+        let own: Vec<MarketFill> = fills.iter()
+            .filter(|fill| self.markets.get(&fill.pair).ok().and_then(|market| market.open_orders) == Some(fill.owner))
+            .cloned()
+            .collect();
+        self.on_own_fills(&own, timestamp)
+    }
+
+    // Сделки, заведомо относящиеся к ордерам бота, например от симулированной биржи
+    pub fn on_own_fills(&mut self, fills: &[MarketFill], timestamp: i64) -> Result<(), Box<dyn Error>> {
+        let mut landed = Vec::new();
+        for market_fill in fills {
+            let order = if market_fill.maker {
+                self.resting.get_mut(&market_fill.client_order_id)
+            } else {
+                self.takers.get_mut(&market_fill.client_order_id).map(|taker| &mut taker.order)
+            };
+            let order = match order {
+                Some(order) if order.pair.to_string() == market_fill.pair.to_string() => order,
+                _ => continue,
+            };
+
+            let size = market_fill.size.min(order.size);
+            order.size -= size;
+            let fill = Fill {
+                pair: order.pair.clone(),
+                client_order_id: market_fill.client_order_id,
+                action: order.action,
+                price: market_fill.price,
+                size,
+                fee: market_fill.fee,
+                timestamp,
+            };
+            let strategy_id = order.strategy_id.clone();
+            if !market_fill.maker {
+                landed.push(market_fill.client_order_id);
+            } else if order.size <= 1e-9 {
+                self.resting.remove(&market_fill.client_order_id);
+            }
+            self.on_fill(&strategy_id, &fill)?;
+        }
+
+        // Все события одной транзакции приходят одним обновлением event queue:
+        // неисполненный остаток IOC-ордера уже отменен биржей
+        for client_order_id in landed {
+            if let Some(bundle_id) = self.takers.remove(&client_order_id).and_then(|taker| taker.bundle_id) {
+                self.confirm_bundle(bundle_id, timestamp)?;
+            }
+        }
+        Ok(())
+    }
+
+    // Бандл попал в блок: обмены в AMM-пулах из него исполнены не хуже цены сигнала
+    fn confirm_bundle(&mut self, bundle_id: u64, timestamp: i64) -> Result<(), Box<dyn Error>> {
+        let legs: Vec<u64> = self.takers.iter()
+            .filter(|(_, taker)| taker.amm && taker.bundle_id == Some(bundle_id))
+            .map(|(&client_order_id, _)| client_order_id)
+            .collect();
+        for client_order_id in legs {
+            let order = match self.takers.remove(&client_order_id) {
+                Some(taker) => taker.order,
+                None => continue,
+            };
+            self.on_fill(&order.strategy_id, &Fill {
+                pair: order.pair.clone(),
+                client_order_id,
+                action: order.action,
+                price: order.price,
+                size: order.size,
+                fee: 0.0,
+                timestamp,
+            })?;
+        }
+        Ok(())
+    }
+
+    // Снимает с учета IOC-ордера, транзакция которых так и не попала в блок
+    fn expire_takers(&mut self) {
+        let slot = self.slot;
+        self.takers.retain(|client_order_id, taker| {
+            if slot < taker.expires_slot {
+                return true;
+            }
+            warn!("Strategy {}: order {} got no fills by slot {}", taker.order.strategy_id, client_order_id, taker.expires_slot);
+            false
+        });
+    }
+
+    pub fn on_fill(&mut self, strategy_id: &str, fill: &Fill) -> Result<(), Box<dyn Error>> {
+        let instance = self.instances.iter_mut()
+            .find(|instance| instance.id == strategy_id)
+            .ok_or_else(|| format!("Unknown strategy id '{}'", strategy_id))?;
+        instance.allocation.apply_fill(fill);
+
+        let ctx = StrategyContext {
+            id: &instance.id,
+            pairs: &instance.pairs,
+            markets: &self.markets,
+            allocation: &instance.allocation,
+            payer: self.payer,
+        };
+        match instance.strategy.on_fill(&ctx, fill) {
+            Ok(signals) => instance.pending.extend(signals),
+            Err(e) => error!("Strategy {} failed on fill: {}", instance.id, e),
+        }
+        Ok(())
+    }
+
+    // Пропускает только сигналы, которые укладываются в капитал стратегии:
+    // покупки - в свободные средства, продажи - в текущую позицию (или в маржу,
+    // если стратегия открывает короткие позиции). Стоящие ордера стратегии
+    // резервируют свою часть капитала до исполнения или отмены.
+    fn check_budget(&self, instance: &StrategyInstance, signals: Vec<TradingSignal>) -> Vec<StrategySignal> {
+        let mut budget = Budget {
+            allocation: instance.allocation.clone(),
+            resting: self.resting.iter()
+                .filter(|(_, order)| order.strategy_id == instance.id)
+                .map(|(&client_order_id, order)| (client_order_id, order))
+                .collect(),
+        };
+        for order in budget.resting.values() {
+            reserve(&mut budget.allocation, order.action, &order.pair, order.price * order.size, order.size);
+        }
+        // Отправленные IOC-ордера держат капитал до событий тейкера
+        for taker in self.takers.values().filter(|taker| taker.order.strategy_id == instance.id) {
+            let order = &taker.order;
+            reserve(&mut budget.allocation, order.action, &order.pair, taker.limit_price * order.size, order.size);
+        }
+        let mut approved = Vec::new();
+
+        // Срок по умолчанию не ставится отменам: запоздавшая отмена все еще нужна
+        let slot = self.slot;
+        let mut signals = signals.into_iter()
+            .map(|signal| match (signal.expiry_slot, instance.signal_ttl_slots) {
+                (None, Some(ttl)) if signal.order_action != OrderAction::Cancel => TradingSignal {
+                    expiry_slot: Some(slot + ttl),
+                    ..signal
+                },
+                _ => signal,
+            })
+            .peekable();
+        while let Some(signal) = signals.next() {
+            let bundle_id = signal.bundle_id;
+            let mut legs = vec![signal];
+            if let Some(bundle_id) = bundle_id {
+                while let Some(leg) = signals.next_if(|leg| leg.bundle_id == Some(bundle_id)) {
+                    legs.push(leg);
+                }
+            }
+
+            // Ноги бандла проверяются вместе: если не проходит одна, отбрасываются все
+            let mut after = budget.clone();
+            let legs: Option<Vec<StrategySignal>> = legs.into_iter()
+                .map(|leg| self.approve(instance, &mut after, leg))
+                .collect();
+            match legs {
+                Some(legs) => {
+                    budget = after;
+                    approved.extend(legs);
+                }
+                None => {
+                    if let Some(bundle_id) = bundle_id {
+                        warn!("Strategy {}: dropping bundle {}", instance.id, bundle_id);
+                    }
+                }
+            }
+        }
+        approved
+    }
+
+    fn approve(&self, instance: &StrategyInstance, budget: &mut Budget, signal: TradingSignal) -> Option<StrategySignal> {
+        let market = match self.markets.by_dex_market(&signal.market) {
+            Some(market) if instance.pairs.iter().any(|pair| pair.to_string() == market.pair.to_string()) => market,
+            _ => {
+                warn!("Strategy {} emitted a signal for a market it does not trade: {}", instance.id, signal.market);
+                return None;
+            }
+        };
+
+        // Отмена освобождает резерв отменяемого ордера
+        let cancel_id = match signal.order_action {
+            OrderAction::Place => None,
+            OrderAction::Cancel => Some(signal.client_order_id),
+            OrderAction::Replace { cancel_client_order_id } => Some(cancel_client_order_id),
+        };
+        if let Some(cancel_id) = cancel_id {
+            match budget.resting.remove(&cancel_id) {
+                Some(order) => reserve(&mut budget.allocation, order.action, &order.pair, -order.price * order.size, -order.size),
+                None if self.resting.contains_key(&cancel_id) => {
+                    warn!("Strategy {} tried to cancel an order it does not own: {}", instance.id, cancel_id);
+                    return None;
+                }
+                None => {}
+            }
+        }
+        if signal.order_action == OrderAction::Cancel {
+            return Some(StrategySignal {
+                strategy_id: instance.id.clone(),
+                pair: market.pair.clone(),
+                signal,
+            });
+        }
+
+        // Рыночный ордер проверяется по худшей допустимой цене
+        let quantizer = market.quantizer();
+        let size = quantizer.size_native_to_ui(signal.amount);
+        let price = quantizer.price_native_to_ui(signal.limit_price());
+        let notional = price * size;
+        let position = budget.allocation.position(&market.pair);
+
+        if signal.is_resting() {
+            match signal.action {
+                TradeAction::Buy if notional > budget.allocation.cash => {
+                    warn!("Strategy {}: buy of {:.2} exceeds free capital {:.2}", instance.id, notional, budget.allocation.cash);
+                    return None;
+                }
+                TradeAction::Sell if size > position + 1e-9 => {
+                    warn!("Strategy {}: sell of {} {} exceeds position {}", instance.id, size, market.pair.base(), position);
+                    return None;
+                }
+                _ => {}
+            }
+            // Стоящий ордер только резервирует средства
+            reserve(&mut budget.allocation, signal.action, &market.pair, notional, size);
+        } else {
+            let mut after = budget.allocation.clone();
+            after.apply_trade(&market.pair, signal.action, size, price, 0.0);
+            if after.position(&market.pair) < -1e-9 && !instance.strategy.allows_short() {
+                warn!("Strategy {}: sell of {} {} exceeds position {}", instance.id, size, market.pair.base(), position);
+                return None;
+            }
+            // Покупке не хватает средств, короткой продаже - маржи
+            if after.cash < 0.0 {
+                warn!("Strategy {}: {:?} of {:.2} exceeds free capital {:.2}", instance.id, signal.action, notional, budget.allocation.cash);
+                return None;
+            }
+            budget.allocation = after;
+        }
+
+        Some(StrategySignal {
+            strategy_id: instance.id.clone(),
+            pair: market.pair.clone(),
+            signal,
+        })
+    }
+}
+
+// Свободные средства и позиции экземпляра по мере одобрения его сигналов
+#[derive(Clone)]
+struct Budget<'a> {
+    allocation: Allocation,
+    resting: HashMap<u64, &'a RestingOrder>,
+}
+
+// Резервирует (или при отрицательных значениях освобождает) средства под стоящий ордер
+fn reserve(allocation: &mut Allocation, action: TradeAction, pair: &TokenPair, notional: f64, size: f64) {
+    match action {
+        TradeAction::Buy => allocation.cash -= notional,
+        TradeAction::Sell => *allocation.positions.entry(pair.to_string()).or_insert(0.0) -= size,
+    }
+}
+
+// Группирует одобренные сигналы для отправки: ноги одного бандла идут
+// одной группой, остальные сигналы - по одному
+pub fn bundles(signals: Vec<StrategySignal>) -> Vec<Vec<StrategySignal>> {
+    let mut groups: Vec<Vec<StrategySignal>> = Vec::new();
+    for signal in signals {
+        match groups.last_mut() {
+            Some(group) if signal.signal.bundle_id.is_some() && group[0].signal.bundle_id == signal.signal.bundle_id => group.push(signal),
+            _ => groups.push(vec![signal]),
+        }
+    }
+    groups
+}
+
+fn parse_pair(pair: &str) -> Result<TokenPair, Box<dyn Error>> {
+    match pair.split_once('/') {
+        Some((base, quote)) if !base.is_empty() && !quote.is_empty() => Ok(TokenPair::new(base, quote)),
+        _ => Err(format!("Invalid pair '{}', expected BASE/QUOTE", pair).into()),
+    }
+}
+
+#[cfg(test)]
+#[path = "runner_test.rs"]
+mod tests;
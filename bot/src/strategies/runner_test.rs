@@ -0,0 +1,482 @@
+use super::*;
+use crate::jito_integration::OrderAction;
+use crate::market_registry::test_registry;
+use crate::strategies::bundle;
+use crate::strategies::registry::parse_params;
+use serde::Deserialize;
+use serde_json::Value;
+
+// Каждый тик покупает заданный объем по фиксированной цене
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct FixedBuy {
+    size: f64,
+    price: f64,
+    timer: Option<i64>,
+}
+
+impl Strategy for FixedBuy {
+    fn name(&self) -> &str {
+        "fixed_buy"
+    }
+
+    fn on_tick(&mut self, ctx: &StrategyContext, _price_data: &PriceData) -> Result<Vec<TradingSignal>, Box<dyn Error>> {
+        let mut signals = Vec::new();
+        for pair in ctx.pairs {
+            signals.extend(ctx.build_signal(pair, TradeAction::Buy, self.size, self.price)?);
+        }
+        Ok(signals)
+    }
+
+    fn on_fill(&mut self, ctx: &StrategyContext, fill: &Fill) -> Result<Vec<TradingSignal>, Box<dyn Error>> {
+        // Сразу выставляем продажу купленного
+        Ok(ctx.build_signal(&fill.pair, TradeAction::Sell, fill.size, fill.price * 1.1)?.into_iter().collect())
+    }
+
+    fn timer_interval(&self) -> Option<i64> {
+        self.timer
+    }
+
+    fn on_timer(&mut self, ctx: &StrategyContext, timestamp: i64) -> Result<Vec<TradingSignal>, Box<dyn Error>> {
+        self.on_tick(ctx, &PriceData::default())
+            .map_err(|e| format!("timer at {}: {}", timestamp, e).into())
+    }
+}
+
+struct Failing;
+
+impl Strategy for Failing {
+    fn name(&self) -> &str {
+        "failing"
+    }
+
+    fn on_tick(&mut self, _ctx: &StrategyContext, _price_data: &PriceData) -> Result<Vec<TradingSignal>, Box<dyn Error>> {
+        Err("no data".into())
+    }
+
+    fn on_fill(&mut self, ctx: &StrategyContext, fill: &Fill) -> Result<Vec<TradingSignal>, Box<dyn Error>> {
+        Ok(ctx.build_signal(&fill.pair, TradeAction::Sell, fill.size, fill.price)?.into_iter().collect())
+    }
+}
+
+// Покупка и продажа одним бандлом, как у арбитража
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct RoundTrip {
+    buy_size: f64,
+    sell_size: f64,
+    price: f64,
+    // Продажа сверх позиции открывает короткую
+    short: bool,
+}
+
+impl Strategy for RoundTrip {
+    fn name(&self) -> &str {
+        "round_trip"
+    }
+
+    fn on_tick(&mut self, ctx: &StrategyContext, _price_data: &PriceData) -> Result<Vec<TradingSignal>, Box<dyn Error>> {
+        let pair = &ctx.pairs[0];
+        let mut legs = Vec::new();
+        legs.extend(ctx.build_signal(pair, TradeAction::Buy, self.buy_size, self.price)?);
+        legs.extend(ctx.build_signal(pair, TradeAction::Sell, self.sell_size, self.price)?);
+        // Одиночная покупка после бандла
+        let mut signals = bundle(legs);
+        signals.extend(ctx.build_signal(pair, TradeAction::Buy, 0.1, self.price)?);
+        Ok(signals)
+    }
+
+    fn allows_short(&self) -> bool {
+        self.short
+    }
+}
+
+fn registry() -> StrategyRegistry {
+    let mut registry = StrategyRegistry::with_builtin();
+    registry.register("fixed_buy", |params: &Value, _| Ok(Box::new(parse_params::<FixedBuy>(params)?)));
+    registry.register("round_trip", |params: &Value, _| Ok(Box::new(parse_params::<RoundTrip>(params)?)));
+    registry.register("failing", |_, _| Ok(Box::new(Failing)));
+    registry
+}
+
+fn runner(config: &str) -> Result<StrategyRunner, Box<dyn Error>> {
+    let markets = Arc::new(test_registry());
+    let config = RunnerConfig::from_json(config).unwrap();
+    StrategyRunner::new(&config, &registry(), markets, Pubkey::new_unique())
+}
+
+#[test]
+fn test_signals_limited_by_allocated_capital() {
+    let mut runner = runner(r#"{"strategies": [
+        {"id": "small", "strategy": "fixed_buy", "pairs": ["SOL/USDC"], "capital": 50.0,
+         "params": {"size": 1.0, "price": 20.0}},
+        {"id": "large", "strategy": "fixed_buy", "pairs": ["SOL/USDC", "RAY/USDC"], "capital": 30.0,
+         "params": {"size": 1.0, "price": 20.0}},
+        {"id": "broken", "strategy": "failing", "pairs": ["RAY/USDC"], "capital": 100.0}
+    ]}"#).unwrap();
+
+    let signals = runner.on_tick(&PriceData::default());
+    let ids: Vec<(&str, String)> = signals.iter()
+        .map(|signal| (signal.strategy_id.as_str(), signal.pair.to_string()))
+        .collect();
+    // У "large" хватает капитала только на первую покупку, ошибка "broken" не мешает остальным
+    assert_eq!(ids, vec![("small", "SOL/USDC".to_string()), ("large", "SOL/USDC".to_string())]);
+}
+
+#[test]
+fn test_fill_updates_allocation_and_queues_signals() {
+    let mut runner = runner(r#"{"strategies": [
+        {"id": "buyer", "strategy": "fixed_buy", "pairs": ["SOL/USDC"], "capital": 50.0,
+         "params": {"size": 1.0, "price": 20.0}}
+    ]}"#).unwrap();
+
+    let signals = runner.on_tick(&PriceData::default());
+    assert_eq!(signals.len(), 1);
+    runner.on_executed(&signals[0], 100).unwrap();
+    // Отправка IOC-ордера еще не исполнение
+    assert_eq!(runner.allocation("buyer").unwrap().cash, 50.0);
+    let fill = taker_fill(signals[0].signal.client_order_id, 20.0, 1.0, 0.02);
+    runner.on_market_fills(&[fill], 100).unwrap();
+
+    let allocation = runner.allocation("buyer").unwrap();
+    assert!((allocation.position(&TokenPair::new("SOL", "USDC")) - 1.0).abs() < 1e-9);
+    // 20 USDC за 1 SOL и 0.1% комиссии
+    assert!((allocation.cash - 29.98).abs() < 1e-9);
+
+    // Продажа из on_fill выходит на следующем тике вместе с новой покупкой
+    let actions: Vec<TradeAction> = runner.on_tick(&PriceData::default()).iter()
+        .map(|signal| signal.signal.action)
+        .collect();
+    assert!(matches!(actions.as_slice(), [TradeAction::Sell, TradeAction::Buy]));
+}
+
+#[test]
+fn test_fill_signals_survive_failing_tick() {
+    let mut runner = runner(r#"{"strategies": [
+        {"id": "broken", "strategy": "failing", "pairs": ["RAY/USDC"], "capital": 100.0}
+    ]}"#).unwrap();
+    runner.on_fill("broken", &Fill {
+        pair: TokenPair::new("RAY", "USDC"),
+        client_order_id: 1,
+        action: TradeAction::Buy,
+        price: 2.0,
+        size: 10.0,
+        fee: 0.0,
+        timestamp: 0,
+    }).unwrap();
+
+    // on_tick падает, но продажа из on_fill все равно уходит
+    let signals = runner.on_tick(&PriceData::default());
+    assert_eq!(signals.len(), 1);
+    assert_eq!(signals[0].signal.action, TradeAction::Sell);
+}
+
+#[test]
+fn test_timer_fires_on_interval() {
+    let mut runner = runner(r#"{"strategies": [
+        {"id": "timed", "strategy": "fixed_buy", "pairs": ["SOL/USDC"], "capital": 50.0,
+         "params": {"size": 1.0, "price": 20.0, "timer": 60}}
+    ]}"#).unwrap();
+
+    assert_eq!(runner.on_timer(0).len(), 1);
+    assert!(runner.on_timer(30).is_empty());
+    assert_eq!(runner.on_timer(60).len(), 1);
+    assert!(runner.on_timer(90).is_empty());
+}
+
+#[test]
+fn test_invalid_config_rejected() {
+    let unknown = runner(r#"{"strategies": [
+        {"id": "a", "strategy": "nope", "pairs": ["SOL/USDC"], "capital": 10.0}
+    ]}"#);
+    assert!(unknown.is_err());
+
+    let duplicate = runner(r#"{"strategies": [
+        {"id": "a", "strategy": "momentum", "pairs": ["SOL/USDC"], "capital": 10.0},
+        {"id": "a", "strategy": "momentum", "pairs": ["RAY/USDC"], "capital": 10.0}
+    ]}"#);
+    assert!(duplicate.is_err());
+
+    let no_market = runner(r#"{"strategies": [
+        {"id": "a", "strategy": "ma_crossover", "pairs": ["BTC/USDC"], "capital": 10.0}
+    ]}"#);
+    assert!(no_market.is_err());
+
+    let bad_params = runner(r#"{"strategies": [
+        {"id": "a", "strategy": "ma_crossover", "pairs": ["SOL/USDC"], "capital": 10.0,
+         "params": {"ma_timeframe": "2m"}}
+    ]}"#);
+    assert!(bad_params.is_err());
+
+    let foreign_inventory = runner(r#"{"strategies": [
+        {"id": "a", "strategy": "momentum", "pairs": ["SOL/USDC"], "capital": 10.0,
+         "inventory": {"RAY/USDC": 5.0}}
+    ]}"#);
+    assert!(foreign_inventory.is_err());
+
+    let negative_inventory = runner(r#"{"strategies": [
+        {"id": "a", "strategy": "momentum", "pairs": ["SOL/USDC"], "capital": 10.0,
+         "inventory": {"SOL/USDC": -1.0}}
+    ]}"#);
+    assert!(negative_inventory.is_err());
+}
+
+// OpenOrders аккаунт бота на SOL/USDC из тестового реестра
+fn own_open_orders() -> Pubkey {
+    test_registry().get(&TokenPair::new("SOL", "USDC")).unwrap().open_orders.unwrap()
+}
+
+fn maker_fill(client_order_id: u64, size: f64, maker: bool) -> MarketFill {
+    MarketFill {
+        pair: TokenPair::new("SOL", "USDC"),
+        market: Pubkey::new_unique(),
+        seq_num: 0,
+        slot: 0,
+        price: 99.9,
+        size,
+        taker_action: TradeAction::Sell,
+        maker,
+        owner: own_open_orders(),
+        client_order_id,
+        fee: -0.01,
+    }
+}
+
+fn taker_fill(client_order_id: u64, price: f64, size: f64, fee: f64) -> MarketFill {
+    MarketFill {
+        price,
+        size,
+        taker_action: TradeAction::Buy,
+        fee,
+        ..maker_fill(client_order_id, size, false)
+    }
+}
+
+#[test]
+fn test_taker_orders_fill_from_taker_events() {
+    let mut runner = runner(r#"{"strategies": [
+        {"id": "buyer", "strategy": "fixed_buy", "pairs": ["SOL/USDC"], "capital": 50.0,
+         "params": {"size": 2.0, "price": 20.0}}
+    ]}"#).unwrap();
+    let sol = TokenPair::new("SOL", "USDC");
+
+    let signals = runner.on_tick(&PriceData::default());
+    let client_order_id = signals[0].signal.client_order_id;
+    runner.on_executed(&signals[0], 0).unwrap();
+    // Пока ордер в пути, его капитал зарезервирован и новая покупка не проходит
+    assert!(runner.on_tick(&PriceData::default()).is_empty());
+
+    // IOC исполнился частично: остаток отменен биржей, резерв освобождается
+    runner.on_market_fills(&[taker_fill(client_order_id, 19.9, 0.5, 0.01)], 1).unwrap();
+    let allocation = runner.allocation("buyer").unwrap();
+    assert!((allocation.position(&sol) - 0.5).abs() < 1e-9);
+    assert!((allocation.cash - (50.0 - 0.5 * 19.9 - 0.01)).abs() < 1e-9);
+    runner.on_market_fills(&[taker_fill(client_order_id, 19.9, 0.5, 0.01)], 2).unwrap();
+    assert!((runner.allocation("buyer").unwrap().position(&sol) - 0.5).abs() < 1e-9);
+
+    // Ордер без событий тейкера снимается после срока действия транзакции
+    let signals = runner.on_tick(&PriceData::default());
+    let buy = signals.iter().find(|signal| signal.signal.action == TradeAction::Buy).unwrap();
+    runner.on_executed(buy, 3).unwrap();
+    let mut price_data = PriceData::default();
+    price_data.set_slot(TAKER_TIMEOUT_SLOTS - 1);
+    assert!(runner.on_tick(&price_data).is_empty());
+    price_data.set_slot(TAKER_TIMEOUT_SLOTS);
+    assert_eq!(runner.on_tick(&price_data).len(), 1);
+    assert!((runner.allocation("buyer").unwrap().position(&sol) - 0.5).abs() < 1e-9);
+}
+
+#[test]
+fn test_rejected_order_releases_capital() {
+    let mut runner = runner(r#"{"strategies": [
+        {"id": "buyer", "strategy": "fixed_buy", "pairs": ["SOL/USDC"], "capital": 30.0,
+         "params": {"size": 1.0, "price": 20.0}}
+    ]}"#).unwrap();
+
+    let signals = runner.on_tick(&PriceData::default());
+    runner.on_executed(&signals[0], 0).unwrap();
+    assert!(runner.on_tick(&PriceData::default()).is_empty());
+
+    // Бандл не попал в блок: резерв снимается, следующая покупка проходит
+    runner.on_reject(&signals[0]);
+    assert_eq!(runner.on_tick(&PriceData::default()).len(), 1);
+    assert_eq!(runner.allocation("buyer").unwrap().cash, 30.0);
+}
+
+#[test]
+fn test_resting_orders_fill_from_maker_events() {
+    let mut runner = runner(r#"{"strategies": [
+        {"id": "mm", "strategy": "market_maker", "pairs": ["SOL/USDC"], "capital": 150.0,
+         "params": {"size": 1.0, "spread_bps": 20.0}}
+    ]}"#).unwrap();
+    let price_data = PriceData::with_prices(&[("SOL/USDC", 100.0)]);
+
+    let signals = runner.on_tick(&price_data);
+    assert_eq!(signals.len(), 1);
+    let client_order_id = signals[0].signal.client_order_id;
+    runner.on_executed(&signals[0], 0).unwrap();
+
+    // Выставленная котировка не меняет капитал до исполнения
+    let sol = TokenPair::new("SOL", "USDC");
+    assert_eq!(runner.allocation("mm").unwrap().cash, 150.0);
+
+    // Событие тейкера и чужие ордера игнорируются, в том числе с тем же client_order_id
+    let foreign = MarketFill {
+        owner: Pubkey::new_unique(),
+        ..maker_fill(client_order_id, 0.4, true)
+    };
+    runner.on_market_fills(&[maker_fill(client_order_id, 0.4, false), maker_fill(client_order_id + 1000, 0.4, true), foreign], 1).unwrap();
+    assert_eq!(runner.allocation("mm").unwrap().position(&sol), 0.0);
+
+    runner.on_market_fills(&[maker_fill(client_order_id, 0.4, true)], 1).unwrap();
+    let allocation = runner.allocation("mm").unwrap();
+    assert!((allocation.position(&sol) - 0.4).abs() < 1e-9);
+    assert!((allocation.cash - (150.0 - 0.4 * 99.9 + 0.01)).abs() < 1e-9);
+
+    // Остаток 0.6: исполнение сверх остатка обрезается, ордер снимается с учета
+    runner.on_market_fills(&[maker_fill(client_order_id, 1.0, true)], 2).unwrap();
+    assert!((runner.allocation("mm").unwrap().position(&sol) - 1.0).abs() < 1e-9);
+    runner.on_market_fills(&[maker_fill(client_order_id, 1.0, true)], 3).unwrap();
+    assert!((runner.allocation("mm").unwrap().position(&sol) - 1.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_resting_orders_reserve_capital() {
+    let mut runner = runner(r#"{"strategies": [
+        {"id": "mm", "strategy": "market_maker", "pairs": ["SOL/USDC"], "capital": 150.0,
+         "params": {"size": 1.0, "spread_bps": 20.0, "requote_tolerance_bps": 1.0}}
+    ]}"#).unwrap();
+
+    let placed = runner.on_tick(&PriceData::with_prices(&[("SOL/USDC", 100.0)]));
+    runner.on_executed(&placed[0], 0).unwrap();
+
+    // Замена освобождает резерв старой котировки, поэтому проходит по капиталу
+    let replaced = runner.on_tick(&PriceData::with_prices(&[("SOL/USDC", 110.0)]));
+    assert_eq!(replaced.len(), 1);
+    assert_eq!(replaced[0].signal.order_action, OrderAction::Replace { cancel_client_order_id: placed[0].signal.client_order_id });
+}
+
+#[test]
+fn test_canceled_order_fills_until_confirmed() {
+    let mut runner = runner(r#"{"strategies": [
+        {"id": "grid", "strategy": "grid", "pairs": ["SOL/USDC"], "capital": 15.0,
+         "params": {"lower": 10.0, "upper": 14.0, "levels": 5, "size": 1.0, "order_timeout_slots": 100}}
+    ]}"#).unwrap();
+    let sol = TokenPair::new("SOL", "USDC");
+    let mut price_data = PriceData::with_prices(&[("SOL/USDC", 12.5)]);
+
+    price_data.set_slot(10);
+    let placed = runner.on_tick(&price_data);
+    assert_eq!(placed.len(), 1);
+    let client_order_id = placed[0].signal.client_order_id;
+    runner.on_executed(&placed[0], 0).unwrap();
+
+    // Отмена отправлена, но ордер еще в стакане и держит резерв
+    price_data.set_slot(110);
+    let cancel = runner.on_tick(&price_data);
+    assert_eq!(cancel[0].signal.order_action, OrderAction::Cancel);
+    runner.on_executed(&cancel[0], 1).unwrap();
+    assert!(runner.resting.contains_key(&client_order_id));
+
+    // Исполнение до подтверждения отмены учитывается
+    let late = MarketFill { price: 12.0, ..maker_fill(client_order_id, 0.4, true) };
+    runner.on_market_fills(&[late], 2).unwrap();
+    assert!((runner.allocation("grid").unwrap().position(&sol) - 0.4).abs() < 1e-9);
+
+    // Out чужого ордера с тем же client_order_id не подтверждает отмену
+    let out = OrderOut { pair: sol.clone(), owner: Pubkey::new_unique(), client_order_id };
+    runner.on_market_outs(std::slice::from_ref(&out));
+    assert!(runner.resting.contains_key(&client_order_id));
+
+    // Подтверждение снимает ордер, сетка продает купленную часть на уровне 13
+    runner.on_market_outs(&[OrderOut { owner: own_open_orders(), ..out }]);
+    assert!(!runner.resting.contains_key(&client_order_id));
+    price_data.set_slot(120);
+    let signals = runner.on_tick(&price_data);
+    let sell = signals.iter().find(|signal| signal.signal.action == TradeAction::Sell).unwrap();
+    assert_eq!((sell.signal.price, sell.signal.amount), (13_000_000, 400_000_000));
+}
+
+#[test]
+fn test_bundle_legs_approved_together() {
+    let mut runner = runner(r#"{"strategies": [
+        {"id": "arb", "strategy": "round_trip", "pairs": ["SOL/USDC"], "capital": 30.0,
+         "params": {"buy_size": 1.0, "sell_size": 1.0, "price": 20.0}},
+        {"id": "oversell", "strategy": "round_trip", "pairs": ["SOL/USDC"], "capital": 30.0,
+         "params": {"buy_size": 1.0, "sell_size": 2.0, "price": 20.0}},
+        {"id": "poor", "strategy": "round_trip", "pairs": ["SOL/USDC"], "capital": 10.0,
+         "params": {"buy_size": 1.0, "sell_size": 1.0, "price": 20.0}}
+    ]}"#).unwrap();
+
+    let signals = runner.on_tick(&PriceData::default());
+    let ids: Vec<&str> = signals.iter().map(|signal| signal.strategy_id.as_str()).collect();
+    // Продажа сверх купленного отменяет и покупку бандла; одиночные покупки проходят отдельно
+    assert_eq!(ids, vec!["arb", "arb", "arb", "oversell", "poor"]);
+
+    let groups: Vec<usize> = bundles(signals).iter().map(|group| group.len()).collect();
+    assert_eq!(groups, vec![2, 1, 1, 1]);
+}
+
+#[test]
+fn test_short_sale_needs_margin() {
+    let mut runner = runner(r#"{"strategies": [
+        {"id": "short", "strategy": "round_trip", "pairs": ["SOL/USDC"], "capital": 30.0,
+         "params": {"sell_size": 1.0, "price": 20.0, "short": true}},
+        {"id": "thin", "strategy": "round_trip", "pairs": ["SOL/USDC"], "capital": 5.0,
+         "params": {"sell_size": 1.0, "price": 20.0, "short": true}},
+        {"id": "long_only", "strategy": "round_trip", "pairs": ["SOL/USDC"], "capital": 30.0,
+         "params": {"sell_size": 1.0, "price": 20.0}}
+    ]}"#).unwrap();
+
+    // Короткая продажа 1 SOL блокирует выручку 20 и маржу 10 из свободных средств;
+    // без разрешения на короткие позиции продажа отклоняется независимо от капитала
+    let signals = runner.on_tick(&PriceData::default());
+    let ids: Vec<&str> = signals.iter().map(|signal| signal.strategy_id.as_str()).collect();
+    assert_eq!(ids, vec!["short", "short", "thin", "long_only"]);
+}
+
+#[test]
+fn test_inventory_covers_sales_without_short() {
+    let mut runner = runner(r#"{"strategies": [
+        {"id": "stocked", "strategy": "round_trip", "pairs": ["SOL/USDC"], "capital": 30.0,
+         "inventory": {"SOL/USDC": 1.0}, "params": {"sell_size": 1.0, "price": 20.0}}
+    ]}"#).unwrap();
+    assert_eq!(runner.allocation("stocked").unwrap().position(&TokenPair::new("SOL", "USDC")), 1.0);
+
+    // Выделенный запас продается как обычная позиция, без маржи
+    let signals = runner.on_tick(&PriceData::default());
+    let actions: Vec<TradeAction> = signals.iter().map(|signal| signal.signal.action).collect();
+    assert_eq!(actions, vec![TradeAction::Sell, TradeAction::Buy]);
+}
+
+#[test]
+fn test_short_position_margin_released_on_cover() {
+    let pair = TokenPair::new("SOL", "USDC");
+    let mut allocation = Allocation::new(1000.0);
+    allocation.apply_trade(&pair, TradeAction::Sell, 2.0, 100.0, 0.0);
+    assert_eq!((allocation.cash, allocation.margin(&pair), allocation.position(&pair)), (900.0, 300.0, -2.0));
+
+    // Откуп половины освобождает половину залога
+    allocation.apply_trade(&pair, TradeAction::Buy, 1.0, 90.0, 0.0);
+    assert_eq!((allocation.cash, allocation.margin(&pair)), (960.0, 150.0));
+
+    // Переворот в длинную позицию закрывает залог целиком: прибыль 2 * 10 минус покупка 1 SOL
+    allocation.apply_trade(&pair, TradeAction::Buy, 2.0, 90.0, 0.0);
+    assert_eq!((allocation.cash, allocation.margin(&pair), allocation.position(&pair)), (930.0, 0.0, 1.0));
+}
+
+#[test]
+fn test_signal_ttl_sets_expiry_slot() {
+    let mut runner = runner(r#"{"strategies": [
+        {"id": "buyer", "strategy": "fixed_buy", "pairs": ["SOL/USDC"], "capital": 50.0,
+         "params": {"size": 1.0, "price": 20.0}, "signal_ttl_slots": 5}
+    ]}"#).unwrap();
+
+    let mut price_data = PriceData::default();
+    price_data.set_slot(1_000);
+    let signals = runner.on_tick(&price_data);
+    let signal = &signals[0].signal;
+    assert_eq!((signal.strategy_id.as_str(), signal.expiry_slot), ("buyer", Some(1_005)));
+    assert!(!signal.is_expired(1_005));
+    assert!(signal.is_expired(1_006));
+}
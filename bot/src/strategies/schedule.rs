@@ -0,0 +1,154 @@
+use std::error::Error;
+
+// Расписание в формате cron из пяти полей (UTC): минута, час, день месяца,
+// месяц, день недели (0 - воскресенье). Поле - список через запятую из
+// `*`, `N`, `N-M` с необязательным шагом `/S`, например "0 */4 * * 1-5".
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    // Если ограничены и день месяца, и день недели, достаточно совпадения одного из них
+    days_restricted: (bool, bool),
+}
+
+impl CronSchedule {
+    pub fn parse(expression: &str) -> Result<Self, Box<dyn Error>> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(format!("Cron expression '{}' must have 5 fields", expression).into());
+        }
+
+        let mut days_of_week = parse_field(fields[4], 0, 7)?;
+        // 7 - тоже воскресенье
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week = (days_of_week | 1) & !(1 << 7);
+        }
+
+        Ok(Self {
+            minutes: parse_field(fields[0], 0, 59)?,
+            hours: parse_field(fields[1], 0, 23)?,
+            days_of_month: parse_field(fields[2], 1, 31)?,
+            months: parse_field(fields[3], 1, 12)?,
+            days_of_week,
+            days_restricted: (fields[2] != "*", fields[4] != "*"),
+        })
+    }
+
+    // Совпадает ли минута, в которую попадает Unix-время `timestamp`
+    pub fn matches(&self, timestamp: i64) -> bool {
+        let minutes = timestamp.div_euclid(60);
+        let days = minutes.div_euclid(24 * 60);
+        let minute_of_day = minutes.rem_euclid(24 * 60);
+        let (_, month, day) = civil_from_days(days);
+        // 1 января 1970 года - четверг
+        let weekday = (days + 4).rem_euclid(7);
+
+        let day_of_month = bit(self.days_of_month, day);
+        let day_of_week = bit(self.days_of_week, weekday);
+        let day_matches = match self.days_restricted {
+            (true, true) => day_of_month || day_of_week,
+            _ => day_of_month && day_of_week,
+        };
+
+        bit(self.minutes, minute_of_day % 60)
+            && bit(self.hours, minute_of_day / 60)
+            && bit(self.months, month)
+            && day_matches
+    }
+}
+
+// Отслеживает срабатывания расписания между вызовами: минута засчитывается
+// один раз, а пропущенные из-за задержек минуты не теряются
+#[derive(Debug, Clone)]
+pub struct ScheduleTracker {
+    schedule: CronSchedule,
+    last_minute: Option<i64>,
+}
+
+// Сколько пропущенных минут проверяется при долгом перерыве
+const MAX_CATCH_UP_MINUTES: i64 = 24 * 60;
+
+impl ScheduleTracker {
+    pub fn new(schedule: CronSchedule) -> Self {
+        Self {
+            schedule,
+            last_minute: None,
+        }
+    }
+
+    // true, если с прошлого вызова наступила хотя бы одна минута расписания
+    pub fn due(&mut self, timestamp: i64) -> bool {
+        let minute = timestamp.div_euclid(60);
+        let first = match self.last_minute {
+            Some(last) if last >= minute => return false,
+            Some(last) => (last + 1).max(minute - MAX_CATCH_UP_MINUTES),
+            None => minute,
+        };
+        self.last_minute = Some(minute);
+        (first..=minute).any(|m| self.schedule.matches(m * 60))
+    }
+}
+
+fn bit(mask: u64, value: i64) -> bool {
+    (0..64).contains(&value) && mask & (1 << value) != 0
+}
+
+fn parse_field(field: &str, min: i64, max: i64) -> Result<u64, Box<dyn Error>> {
+    let mut mask = 0u64;
+    for item in field.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => (range, parse_number(step)?),
+            None => (item, 1),
+        };
+        if step == 0 {
+            return Err(format!("Invalid cron step in '{}'", field).into());
+        }
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else {
+            match range.split_once('-') {
+                Some((start, end)) => (parse_number(start)?, parse_number(end)?),
+                // "N/S" - от N до конца диапазона
+                None if step > 1 => (parse_number(range)?, max),
+                None => {
+                    let value = parse_number(range)?;
+                    (value, value)
+                }
+            }
+        };
+        if start < min || end > max || start > end {
+            return Err(format!("Cron field '{}' is out of range {}-{}", field, min, max).into());
+        }
+        let mut value = start;
+        while value <= end {
+            mask |= 1 << value;
+            value += step;
+        }
+    }
+    Ok(mask)
+}
+
+fn parse_number(value: &str) -> Result<i64, Box<dyn Error>> {
+    value.parse().map_err(|_| format!("Invalid cron value '{}'", value).into())
+}
+
+// Дата (год, месяц, день) по числу дней от 1970-01-01 в пролептическом григорианском календаре
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+#[path = "schedule_test.rs"]
+mod tests;
@@ -0,0 +1,70 @@
+use super::*;
+
+// 2024-01-01 00:00 UTC, понедельник
+const NEW_YEAR: i64 = 1_704_067_200;
+const HOUR: i64 = 3600;
+const DAY: i64 = 24 * HOUR;
+
+#[test]
+fn test_civil_from_days() {
+    assert_eq!(civil_from_days(0), (1970, 1, 1));
+    assert_eq!(civil_from_days(NEW_YEAR / DAY), (2024, 1, 1));
+    // Високосный день
+    assert_eq!(civil_from_days(NEW_YEAR / DAY + 59), (2024, 2, 29));
+    assert_eq!(civil_from_days(-1), (1969, 12, 31));
+}
+
+#[test]
+fn test_fields_and_steps() {
+    let schedule = CronSchedule::parse("30 */4 * * *").unwrap();
+    assert!(schedule.matches(NEW_YEAR + 30 * 60));
+    assert!(schedule.matches(NEW_YEAR + 8 * HOUR + 30 * 60 + 59));
+    assert!(!schedule.matches(NEW_YEAR + 9 * HOUR + 30 * 60));
+    assert!(!schedule.matches(NEW_YEAR + 31 * 60));
+
+    let schedule = CronSchedule::parse("0 9-17/4,22 * 2 *").unwrap();
+    let february = NEW_YEAR + 31 * DAY;
+    for hour in [9, 13, 17, 22] {
+        assert!(schedule.matches(february + hour * HOUR), "hour {}", hour);
+    }
+    assert!(!schedule.matches(february + 11 * HOUR));
+    assert!(!schedule.matches(NEW_YEAR + 9 * HOUR));
+}
+
+#[test]
+fn test_day_of_week() {
+    // Будни в 00:00; 7 - воскресенье
+    let weekdays = CronSchedule::parse("0 0 * * 1-5").unwrap();
+    let sundays = CronSchedule::parse("0 0 * * 7").unwrap();
+    assert!(weekdays.matches(NEW_YEAR));
+    assert!(weekdays.matches(NEW_YEAR + 4 * DAY));
+    assert!(!weekdays.matches(NEW_YEAR + 5 * DAY));
+    assert!(sundays.matches(NEW_YEAR + 6 * DAY));
+    assert!(!sundays.matches(NEW_YEAR));
+
+    // Ограничены оба дня - достаточно совпадения любого
+    let either = CronSchedule::parse("0 0 15 * 0").unwrap();
+    assert!(either.matches(NEW_YEAR + 6 * DAY));
+    assert!(either.matches(NEW_YEAR + 14 * DAY));
+    assert!(!either.matches(NEW_YEAR + 2 * DAY));
+}
+
+#[test]
+fn test_invalid_expressions() {
+    for expression in ["", "* * * *", "60 * * * *", "* 24 * * *", "* * 0 * *", "*/0 * * * *", "5-1 * * * *", "x * * * *"] {
+        assert!(CronSchedule::parse(expression).is_err(), "{}", expression);
+    }
+}
+
+#[test]
+fn test_tracker_fires_once_per_minute_and_catches_up() {
+    let mut tracker = ScheduleTracker::new(CronSchedule::parse("0 * * * *").unwrap());
+    assert!(tracker.due(NEW_YEAR + 5));
+    assert!(!tracker.due(NEW_YEAR + 50));
+    assert!(!tracker.due(NEW_YEAR + 60));
+
+    // Проверка опоздала на две минуты - пропущенная минута все равно засчитывается
+    assert!(!tracker.due(NEW_YEAR + HOUR - 60));
+    assert!(tracker.due(NEW_YEAR + HOUR + 90));
+    assert!(!tracker.due(NEW_YEAR + HOUR + 150));
+}
@@ -0,0 +1,60 @@
+use std::error::Error;
+
+use crate::dex::{read_i128, read_i64, read_u32, read_u64};
+
+// Дискриминатор Anchor для AggregatorAccountData (Switchboard V2)
+const AGGREGATOR_DISCRIMINATOR: [u8; 8] = [217, 230, 65, 101, 201, 162, 27, 125];
+// Смещение latest_confirmed_round в AggregatorAccountData
+const LATEST_ROUND_OFFSET: usize = 341;
+// Смещения полей внутри AggregatorRound
+const ROUND_NUM_SUCCESS: usize = 0;
+const ROUND_OPEN_SLOT: usize = 9;
+const ROUND_OPEN_TIMESTAMP: usize = 17;
+const ROUND_RESULT: usize = 25;
+const ROUND_STD_DEVIATION: usize = 45;
+// SwitchboardDecimal: мантисса i128 и масштаб u32
+const DECIMAL_LEN: usize = 20;
+
+// Последний подтвержденный раунд агрегатора
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SwitchboardRound {
+    pub price: f64,
+    pub std_deviation: f64,
+    pub num_success: u32,
+    pub round_open_slot: u64,
+    pub round_open_timestamp: i64,
+}
+
+pub fn decode_aggregator(data: &[u8]) -> Result<SwitchboardRound, Box<dyn Error>> {
+    if data.len() < LATEST_ROUND_OFFSET + ROUND_STD_DEVIATION + DECIMAL_LEN {
+        return Err(format!("Switchboard aggregator account is too short: {} bytes", data.len()).into());
+    }
+    if data[..8] != AGGREGATOR_DISCRIMINATOR {
+        return Err("Account is not a Switchboard aggregator".into());
+    }
+
+    let round = &data[LATEST_ROUND_OFFSET..];
+    let truncated = || "Truncated Switchboard aggregator round";
+    let num_success = read_u32(round, ROUND_NUM_SUCCESS).ok_or_else(truncated)?;
+    if num_success == 0 {
+        return Err("Switchboard aggregator has no confirmed round".into());
+    }
+
+    Ok(SwitchboardRound {
+        price: read_decimal(round, ROUND_RESULT).ok_or_else(truncated)?,
+        std_deviation: read_decimal(round, ROUND_STD_DEVIATION).ok_or_else(truncated)?,
+        num_success,
+        round_open_slot: read_u64(round, ROUND_OPEN_SLOT).ok_or_else(truncated)?,
+        round_open_timestamp: read_i64(round, ROUND_OPEN_TIMESTAMP).ok_or_else(truncated)?,
+    })
+}
+
+fn read_decimal(data: &[u8], offset: usize) -> Option<f64> {
+    let mantissa = read_i128(data, offset)?;
+    let scale = read_u32(data, offset + 16)?;
+    Some(mantissa as f64 / 10f64.powi(scale as i32))
+}
+
+#[cfg(test)]
+#[path = "switchboard_test.rs"]
+mod tests;
@@ -0,0 +1,53 @@
+use super::*;
+
+// Аккаунт агрегатора SOL/USD в раскладке AggregatorAccountData Switchboard V2:
+// имя, очередь и настройки перед последним подтвержденным раундом заполнены так же,
+// как в сети, чтобы смещения проверялись на полном аккаунте
+fn aggregator(num_success: u32, result: (i128, u32), std_deviation: (i128, u32)) -> Vec<u8> {
+    let mut data = vec![0u8; 3851];
+    data[..8].copy_from_slice(&AGGREGATOR_DISCRIMINATOR);
+    data[8..15].copy_from_slice(b"SOL_USD");
+    data[232..236].copy_from_slice(&16u32.to_le_bytes()); // oracle_request_batch_size
+    data[236..240].copy_from_slice(&12u32.to_le_bytes()); // min_oracle_results
+    data[244..248].copy_from_slice(&15u32.to_le_bytes()); // min_update_delay_seconds
+
+    let round = &mut data[LATEST_ROUND_OFFSET..];
+    round[0..4].copy_from_slice(&num_success.to_le_bytes());
+    round[4..8].copy_from_slice(&1u32.to_le_bytes()); // num_error
+    round[9..17].copy_from_slice(&215_000_000u64.to_le_bytes());
+    round[17..25].copy_from_slice(&1_690_000_000i64.to_le_bytes());
+    round[25..41].copy_from_slice(&result.0.to_le_bytes());
+    round[41..45].copy_from_slice(&result.1.to_le_bytes());
+    round[45..61].copy_from_slice(&std_deviation.0.to_le_bytes());
+    round[61..65].copy_from_slice(&std_deviation.1.to_le_bytes());
+    // min_response и max_response сразу за std_deviation не должны попасть в результат
+    round[65..81].copy_from_slice(&1i128.to_le_bytes());
+    round[85..101].copy_from_slice(&i128::MAX.to_le_bytes());
+    data
+}
+
+#[test]
+fn test_decode_aggregator_latest_round() {
+    // Switchboard хранит результат с масштабом до 28 знаков, мантисса не влезает в i64
+    let data = aggregator(14, (20_512_345_678_901_234_567_890, 21), (123, 4));
+    let round = decode_aggregator(&data).unwrap();
+
+    assert!((round.price - 20.512_345_678_901).abs() < 1e-9);
+    assert!((round.std_deviation - 0.0123).abs() < 1e-12);
+    assert_eq!(round.num_success, 14);
+    assert_eq!(round.round_open_slot, 215_000_000);
+    assert_eq!(round.round_open_timestamp, 1_690_000_000);
+}
+
+#[test]
+fn test_decode_aggregator_rejects_invalid_accounts() {
+    let data = aggregator(14, (2051, 2), (1, 2));
+    assert!(decode_aggregator(&data[..LATEST_ROUND_OFFSET + 40]).is_err());
+
+    let mut foreign = data.clone();
+    foreign[0] ^= 0xff;
+    assert!(decode_aggregator(&foreign).is_err());
+
+    // Раунд еще не подтвержден ни одним оракулом
+    assert!(decode_aggregator(&aggregator(0, (2051, 2), (1, 2))).is_err());
+}
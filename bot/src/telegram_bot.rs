@@ -1,55 +1,44 @@
-use teloxide::{prelude::*, utils::command::BotCommands};
-use std::sync::Arc;
+use log::{error, warn};
+use teloxide::prelude::*;
 
-#[derive(BotCommands, Clone)]
-#[command(rename_rule = "lowercase")]
-enum Command {
-    #[command(description = "Показать текущие позиции")]
-    Positions,
-    #[command(description = "Установить параметры торговой стратегии")]
-    SetStrategy { name: String, params: String },
-    #[command(description = "Остановить торговлю")]
-    Stop,
-    #[command(description = "Возобновить торговлю")]
-    Start,
-}
+use crate::telegram_commands::{Command, CommandContext};
 
+// Бот отвечает только в одном чате: команды останавливают торговлю и
+// показывают состояние счета
 pub struct TelegramBot {
     bot: Bot,
-    trading_strategy: Arc<trading_strategy::TradingStrategy>,
+    chat_id: ChatId,
+    ctx: CommandContext,
 }
 
 impl TelegramBot {
-    pub async fn new(trading_strategy: Arc<trading_strategy::TradingStrategy>) -> Result<Self, Box<dyn std::error::Error>> {
-        let bot = Bot::from_env();
-        Ok(Self { bot, trading_strategy })
+    pub fn new(token: &str, chat_id: i64, ctx: CommandContext) -> Self {
+        Self {
+            bot: Bot::new(token),
+            chat_id: ChatId(chat_id),
+            ctx,
+        }
     }
 
     pub async fn run(self) {
         let handler = Update::filter_message()
             .filter_command::<Command>()
-            .endpoint(|msg: Message, bot: Bot, cmd: Command| async move {
-                match cmd {
-                    Command::Positions => {
-                        // Получение и отображение текущих позиций
-                    }
-                    Command::SetStrategy { name, params } => {
-                        // Обновление параметров стратегии
-                    }
-                    Command::Stop => {
-                        // Остановка торговли
-                    }
-                    Command::Start => {
-                        // Запуск торговли
-                    }
-                };
-                Ok(())
+            .endpoint(|msg: Message, bot: Bot, cmd: Command, chat_id: ChatId, ctx: CommandContext| async move {
+                if msg.chat.id != chat_id {
+                    warn!("Telegram command from unknown chat {}", msg.chat.id);
+                    return respond(());
+                }
+                let reply = cmd.handle(&ctx);
+                if let Err(e) = bot.send_message(msg.chat.id, reply).await {
+                    error!("Failed to send Telegram reply: {}", e);
+                }
+                respond(())
             });
 
         Dispatcher::builder(self.bot, handler)
-            .enable_ctrlc_handler()
+            .dependencies(dptree::deps![self.chat_id, self.ctx])
             .build()
             .dispatch()
             .await;
     }
-} 
\ No newline at end of file
+}
@@ -1,111 +1,135 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
 use teloxide::utils::command::BotCommands;
-use serde::{Deserialize, Serialize};
+
+use crate::jito_integration::{OrderKind, TradingSignal};
+use crate::market_registry::MarketRegistry;
+use crate::monitoring::Monitor;
+use crate::paper::PaperSummary;
 
 #[derive(BotCommands, Clone)]
-#[command(rename_rule = "lowercase")]
+#[command(rename_rule = "lowercase", description = "Команды торгового бота:")]
 pub enum Command {
-    #[command(description = "Показать текущие позиции")]
-    Positions,
-    #[command(description = "Установить параметры торговой стратегии")]
-    SetStrategy { name: String, params: String },
     #[command(description = "Остановить торговлю")]
     Stop,
     #[command(description = "Возобновить торговлю")]
     Start,
     #[command(description = "Показать статистику")]
     Stats,
-    #[command(description = "Установить оповещения")]
-    SetAlert { pair: String, price: f64 },
-    #[command(description = "Показать балансы")]
-    Balances,
+    #[command(description = "Показать последние сигналы стратегий")]
+    Signals,
+    #[command(description = "Показать виртуальный счет бумажной торговли")]
+    Paper,
     #[command(description = "Помощь")]
     Help,
 }
 
-#[derive(Serialize, Deserialize)]
-pub struct AlertConfig {
-    pub pair: String,
-    pub price: f64,
-    pub direction: AlertDirection,
-    pub user_id: i64,
-}
-
-#[derive(Serialize, Deserialize)]
-pub enum AlertDirection {
-    Above,
-    Below,
+// Общее с основным циклом состояние, которое читают и меняют команды
+#[derive(Clone)]
+pub struct CommandContext {
+    pub monitor: Arc<Mutex<Monitor>>,
+    pub markets: Arc<MarketRegistry>,
+    // Снятый флаг останавливает отправку сигналов в основном цикле
+    pub trading_enabled: Arc<AtomicBool>,
 }
 
 impl Command {
-    pub async fn handle(&self, ctx: CommandContext) -> Result<(), Box<dyn std::error::Error>> {
+    // Ответ на команду; блокировка монитора держится только на время форматирования
+    pub fn handle(&self, ctx: &CommandContext) -> String {
         match self {
-            Command::Positions => {
-                let positions = ctx.trading_strategy.get_positions().await?;
-                let message = format_positions(positions);
-                ctx.reply(message).await?;
-            }
-            Command::SetStrategy { name, params } => {
-                ctx.trading_strategy.update_config(name, params).await?;
-                ctx.reply("Стратегия обновлена").await?;
-            }
             Command::Stop => {
-                ctx.trading_strategy.stop().await?;
-                ctx.reply("Торговля остановлена").await?;
+                ctx.trading_enabled.store(false, Ordering::Relaxed);
+                String::from("Торговля остановлена")
             }
             Command::Start => {
-                ctx.trading_strategy.start().await?;
-                ctx.reply("Торговля запущена").await?;
-            }
-            Command::Stats => {
-                let stats = ctx.monitor.get_stats().await?;
-                let message = format_stats(stats);
-                ctx.reply(message).await?;
-            }
-            Command::SetAlert { pair, price } => {
-                ctx.alerts.add_alert(AlertConfig {
-                    pair: pair.clone(),
-                    price: *price,
-                    direction: AlertDirection::Above,
-                    user_id: ctx.user_id,
-                }).await?;
-                ctx.reply("Оповещение установлено").await?;
-            }
-            Command::Balances => {
-                let balances = ctx.trading_strategy.get_balances().await?;
-                let message = format_balances(balances);
-                ctx.reply(message).await?;
-            }
-            Command::Help => {
-                ctx.reply(Command::descriptions()).await?;
+                ctx.trading_enabled.store(true, Ordering::Relaxed);
+                String::from("Торговля запущена")
             }
+            Command::Stats => format_stats(&ctx.monitor.lock().unwrap()),
+            Command::Signals => format_signals(ctx.monitor.lock().unwrap().recent_signals(), &ctx.markets),
+            Command::Paper => format_paper_portfolio(ctx.monitor.lock().unwrap().paper_portfolio()),
+            Command::Help => Command::descriptions().to_string(),
         }
-        Ok(())
     }
 }
 
-fn format_positions(positions: Vec<Position>) -> String {
-    let mut message = String::from("📊 Текущие позиции:\n\n");
-    for pos in positions {
+// Объем и цена сигнала в нативных единицах переводятся в UI по параметрам рынка
+fn format_signals<'a>(signals: impl Iterator<Item = &'a TradingSignal>, markets: &MarketRegistry) -> String {
+    let mut message = String::from("📡 Последние сигналы:\n\n");
+    for signal in signals {
+        let (pair, amount, price) = match markets.by_dex_market(&signal.market) {
+            Some(market) => {
+                let quantizer = market.quantizer();
+                (
+                    market.pair.to_string(),
+                    quantizer.size_native_to_ui(signal.amount),
+                    quantizer.price_native_to_ui(signal.price),
+                )
+            }
+            None => {
+                message.push_str(&format!("🔸 {} {:?}: неизвестный рынок {}\n", signal.strategy_id, signal.action, signal.market));
+                continue;
+            }
+        };
+        let order = match signal.order_kind {
+            OrderKind::Limit => format!("{:?}", signal.time_in_force),
+            OrderKind::Market => format!("Market ±{}bps", signal.slippage_bps),
+        };
         message.push_str(&format!(
-            "🔸 {}: {} @ ${:.2} (PnL: ${:.2})\n",
-            pos.pair, pos.size, pos.entry_price, pos.unrealized_pnl
+            "🔸 {} {:?} {} {} @ {} ({}), уверенность {:.0}%\n",
+            signal.strategy_id, signal.action, amount, pair, price, order, signal.confidence * 100.0
         ));
+        if let Some(expiry_slot) = signal.expiry_slot {
+            message.push_str(&format!("    действует до слота {}\n", expiry_slot));
+        }
+        for (key, value) in &signal.reason {
+            message.push_str(&format!("    {}: {}\n", key, value));
+        }
     }
     message
 }
 
-fn format_stats(stats: Statistics) -> String {
+fn format_paper_portfolio(portfolio: Option<&PaperSummary>) -> String {
+    let portfolio = match portfolio {
+        Some(portfolio) => portfolio,
+        None => return String::from("Бумажная торговля не включена"),
+    };
+    let mut message = String::from("🧪 Виртуальный счет:\n\n");
+    for (token, balance) in &portfolio.balances {
+        message.push_str(&format!("🔸 {}: {:.6}\n", token, balance));
+    }
+    message.push_str(&format!(
+        "\nСтоимость: {:.2} {}\n\
+        P&L: {:.2} {} (к удержанию начальных балансов)\n\
+        Комиссии: {:.2}\n\
+        Сделок: {}",
+        portfolio.equity, portfolio.quote, portfolio.pnl, portfolio.quote, portfolio.fees, portfolio.fills
+    ));
+    message
+}
+
+fn format_stats(monitor: &Monitor) -> String {
+    let metrics = monitor.metrics();
+    let (win_rate, avg_trade_pnl) = if metrics.trades_executed > 0 {
+        (
+            metrics.successful_trades as f64 / metrics.trades_executed as f64 * 100.0,
+            metrics.total_profit_loss / metrics.trades_executed as f64,
+        )
+    } else {
+        (0.0, 0.0)
+    };
     format!(
         "📈 Статистика торговли:\n\n\
         Всего сделок: {}\n\
-        Успешных: {}%\n\
+        Успешных: {:.1}%\n\
         Общий P&L: ${:.2}\n\
         Средний P&L на сделку: ${:.2}\n\
-        Время работы: {}h",
-        stats.total_trades,
-        stats.win_rate,
-        stats.total_pnl,
-        stats.avg_trade_pnl,
-        stats.uptime_hours
+        Время работы: {:.1}h",
+        metrics.trades_executed,
+        win_rate,
+        metrics.total_profit_loss,
+        avg_trade_pnl,
+        monitor.uptime().as_secs_f64() / 3600.0
     )
-} 
\ No newline at end of file
+}
@@ -1,98 +1,137 @@
-use anchor_client::Program;
-use solana_sdk::pubkey::Pubkey;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
 use std::error::Error;
+use crate::candles::Timeframe;
+use crate::indicators::Sma;
 use crate::price_feed::{PriceData, TokenPair};
 use crate::jito_integration::{TradingSignal, TradeAction};
+use crate::strategies::registry::parse_params;
+use crate::strategies::{CandleCursor, Strategy, StrategyContext};
 
+// Пересечение цены и скользящей средней, в реестре стратегий - "ma_crossover"
 pub struct TradingStrategy {
-    program: Program,
     config: StrategyConfig,
+    // Скользящие средние по ключу пары
+    averages: HashMap<String, (CandleCursor, Sma)>,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
 pub struct StrategyConfig {
     min_price_change: f64,
-    max_position_size: u64,
+    ma_timeframe: Timeframe,
+    ma_periods: usize,
+    max_confidence_ratio: f64,
+    max_position_size: f64,
     risk_percentage: f64,
-    trading_pairs: Vec<TokenPair>,
 }
 
-impl TradingStrategy {
-    pub fn new(program: Program) -> Self {
+impl Default for StrategyConfig {
+    fn default() -> Self {
         Self {
-            program,
-            config: StrategyConfig {
-                min_price_change: 0.02, // 2% минимальное изменение цены
-                max_position_size: 1000_000_000, // 1 SOL в ламопртах
-                risk_percentage: 0.01, // 1% риска на сделку
-                trading_pairs: vec![
-                    TokenPair::new("SOL", "USDC"),
-                    TokenPair::new("RAY", "USDC"),
-                ],
-            },
+            min_price_change: 0.02, // 2% минимальное изменение цены
+            ma_timeframe: Timeframe::Min1,
+            ma_periods: 20, // MA по 20 минутным барам
+            max_confidence_ratio: 0.01, // не торгуем, если интервал Pyth шире 1% цены
+            max_position_size: 1.0, // в базовом токене
+            risk_percentage: 0.01, // 1% выделенного капитала на сделку
         }
     }
+}
 
-    pub async fn analyze_market(&self, price_data: &PriceData) -> Result<Vec<TradingSignal>, Box<dyn Error>> {
-        let mut signals = Vec::new();
-
-        for pair in &self.config.trading_pairs {
-            if let Some(signal) = self.analyze_pair(pair, price_data).await? {
-                signals.push(signal);
-            }
+impl TradingStrategy {
+    pub fn new(config: StrategyConfig) -> Self {
+        Self {
+            config,
+            averages: HashMap::new(),
         }
+    }
 
-        Ok(signals)
+    pub fn from_params(params: &Value) -> Result<Self, Box<dyn Error>> {
+        let config: StrategyConfig = parse_params(params)?;
+        if config.ma_periods == 0 {
+            return Err("ma_periods must be positive".into());
+        }
+        Ok(Self::new(config))
     }
 
-    async fn analyze_pair(&self, pair: &TokenPair, price_data: &PriceData) -> Result<Option<TradingSignal>, Box<dyn Error>> {
+    fn analyze_pair(&mut self, ctx: &StrategyContext, pair: &TokenPair, price_data: &PriceData) -> Result<Option<TradingSignal>, Box<dyn Error>> {
+        // Цена отброшена фидом (статус, устаревание или расхождение источников) - пару пропускаем
+        if !price_data.is_tradable(pair) {
+            return Ok(None);
+        }
+        if price_data.get_confidence_ratio(pair)? > self.config.max_confidence_ratio {
+            return Ok(None);
+        }
+
         let current_price = price_data.get_price(pair)?;
-        let moving_average = self.calculate_moving_average(pair, price_data)?;
+        let moving_average = match self.calculate_moving_average(pair, price_data)? {
+            Some(moving_average) => moving_average,
+            // Закрытых баров пока недостаточно
+            None => return Ok(None),
+        };
         
         // Простая стратегия пересечения скользящей средней
-        if current_price > moving_average * (1.0 + self.config.min_price_change) {
-            // Сигнал на покупку
-            let amount = self.calculate_position_size(current_price)?;
-            return Ok(Some(TradingSignal {
-                market: self.get_market_address(pair)?,
-                action: TradeAction::Buy,
-                amount,
-                price: (current_price * 1e6) as u64, // Конвертируем в микро-USDC
-                payer: self.program.payer(),
-            }));
+        let action = if current_price > moving_average * (1.0 + self.config.min_price_change) {
+            TradeAction::Buy
         } else if current_price < moving_average * (1.0 - self.config.min_price_change) {
-            // Сигнал на продажу
-            let amount = self.calculate_position_size(current_price)?;
-            return Ok(Some(TradingSignal {
-                market: self.get_market_address(pair)?,
-                action: TradeAction::Sell,
-                amount,
-                price: (current_price * 1e6) as u64,
-                payer: self.program.payer(),
-            }));
-        }
+            TradeAction::Sell
+        } else {
+            return Ok(None);
+        };
 
-        Ok(None)
+        let size = match action {
+            TradeAction::Buy => self.calculate_position_size(ctx, current_price),
+            // Продаем не больше, чем есть в позиции стратегии
+            TradeAction::Sell => ctx.position(pair).min(self.config.max_position_size),
+        };
+        if size <= 0.0 {
+            return Ok(None);
+        }
+        Ok(ctx.build_signal(pair, action, size, current_price)?.map(|signal| {
+            signal
+                .with_reason("price", current_price)
+                .with_reason("ma", format!("{:.6}", moving_average))
+        }))
     }
 
-    fn calculate_moving_average(&self, pair: &TokenPair, price_data: &PriceData) -> Result<f64, Box<dyn Error>> {
-        // Реализация расчета скользящей средней
-        Ok(price_data.get_average_price(pair, 20)?) // 20-периодная MA
+    fn calculate_moving_average(&mut self, pair: &TokenPair, price_data: &PriceData) -> Result<Option<f64>, Box<dyn Error>> {
+        // Скользящая средняя по ценам закрытия баров, а не по итерациям основного цикла
+        let periods = self.config.ma_periods;
+        let (cursor, sma) = self.averages.entry(pair.to_string())
+            .or_insert_with(|| (CandleCursor::default(), Sma::new(periods)));
+        for candle in cursor.new_candles(price_data, pair, self.config.ma_timeframe)? {
+            sma.update(candle.close);
+        }
+        Ok(sma.value())
     }
 
-    fn calculate_position_size(&self, current_price: f64) -> Result<u64, Box<dyn Error>> {
-        let account_balance = self.get_account_balance().await?;
-        let position_size = (account_balance as f64 * self.config.risk_percentage) / current_price;
-        Ok((position_size.min(self.config.max_position_size as f64)) as u64)
+    // Объем в базовом токене: доля свободного капитала стратегии
+    fn calculate_position_size(&self, ctx: &StrategyContext, current_price: f64) -> f64 {
+        let position_size = ctx.cash().max(0.0) * self.config.risk_percentage / current_price;
+        position_size.min(self.config.max_position_size)
     }
+}
 
-    async fn get_account_balance(&self) -> Result<u64, Box<dyn Error>> {
-        // Получение баланса аккаунта
-        Ok(self.program.rpc().get_balance(&self.program.payer()).await?)
+impl Strategy for TradingStrategy {
+    fn name(&self) -> &str {
+        "ma_crossover"
     }
 
-    fn get_market_address(&self, pair: &TokenPair) -> Result<Pubkey, Box<dyn Error>> {
-        // Получение адреса рынка для торговой пары
-        // В реальном приложении здесь будет логика получения адреса из Serum DEX
-        Ok(Pubkey::new_unique())
+    fn on_tick(&mut self, ctx: &StrategyContext, price_data: &PriceData) -> Result<Vec<TradingSignal>, Box<dyn Error>> {
+        let mut signals = Vec::new();
+
+        for pair in ctx.pairs {
+            if let Some(signal) = self.analyze_pair(ctx, pair, price_data)? {
+                signals.push(signal);
+            }
+        }
+
+        Ok(signals)
     }
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+#[path = "trading_strategy_test.rs"]
+mod tests;
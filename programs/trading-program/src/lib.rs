@@ -1,9 +1,12 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
 use serum_dex::matching::{OrderType, Side};
 
 declare_id!("YOUR_PROGRAM_ID");
 
+// Программа Serum DEX v3: только ее аккаунтам рынков можно доверять минты
+pub const SERUM_DEX_PROGRAM_ID: Pubkey = anchor_lang::solana_program::pubkey!("9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin");
+
 #[program]
 pub mod trading_program {
     use super::*;
@@ -31,10 +34,21 @@ pub mod trading_program {
         order.order_type = order_type;
         order.status = OrderStatus::Pending;
 
+        // Счет пользователя должен быть в котируемом токене рынка для покупки
+        // и в базовом - для продажи, иначе проверка средств ничего не значит
+        let (base_mint, quote_mint) = {
+            let data = ctx.accounts.market.try_borrow_data()?;
+            market_mints(&data).ok_or(TradingError::InvalidMarket)?
+        };
+        let expected_mint = if side == Side::Bid { quote_mint } else { base_mint };
+        require_keys_eq!(ctx.accounts.user_token_account.mint, expected_mint, TradingError::MintMismatch);
+
         // Проверяем достаточность средств
         if side == Side::Bid {
+            let required = quote_required(amount, price, ctx.accounts.base_mint.decimals)
+                .ok_or(TradingError::MathOverflow)?;
             require!(
-                ctx.accounts.user_token_account.amount >= amount * price,
+                ctx.accounts.user_token_account.amount >= required,
                 TradingError::InsufficientFunds
             );
         } else {
@@ -58,6 +72,28 @@ pub mod trading_program {
     }
 }
 
+// Минты базового и котируемого токена из аккаунта MarketState Serum: после
+// 5 байт заголовка "serum" идут флаги (u64), адрес рынка (32), nonce (u64),
+// coin_mint (32) и pc_mint (32)
+pub fn market_mints(data: &[u8]) -> Option<(Pubkey, Pubkey)> {
+    const COIN_MINT_OFFSET: usize = 5 + 8 + 32 + 8;
+    if data.get(..5)? != b"serum" {
+        return None;
+    }
+    let coin_mint = data.get(COIN_MINT_OFFSET..COIN_MINT_OFFSET + 32)?;
+    let pc_mint = data.get(COIN_MINT_OFFSET + 32..COIN_MINT_OFFSET + 64)?;
+    Some((Pubkey::try_from(coin_mint).ok()?, Pubkey::try_from(pc_mint).ok()?))
+}
+
+// Сумма заявки в нативных единицах котируемого токена: `amount` - в нативных
+// единицах базового токена, `price` - нативные единицы котируемого за целый
+// базовый токен, поэтому произведение делится на 10^base_decimals
+pub fn quote_required(amount: u64, price: u64, base_decimals: u8) -> Option<u64> {
+    let scale = 10u128.checked_pow(base_decimals as u32)?;
+    let required = (amount as u128).checked_mul(price as u128)? / scale;
+    u64::try_from(required).ok()
+}
+
 #[derive(Accounts)]
 pub struct InitializeTradingAccount<'info> {
     #[account(init, payer = owner, space = 8 + 32 + 1)]
@@ -79,7 +115,12 @@ pub struct CreateTradeOrder<'info> {
     pub order: Account<'info, Order>,
     #[account(mut)]
     pub owner: Signer<'info>,
+    /// CHECK: рынок Serum, владелец проверяется; минты читаются из его данных
+    #[account(owner = SERUM_DEX_PROGRAM_ID)]
     pub market: AccountInfo<'info>,
+    // Минт базового токена рынка: из него берутся decimals для суммы заявки
+    #[account(constraint = market_mints(&market.try_borrow_data()?).map(|(base, _)| base) == Some(base_mint.key()) @ TradingError::MintMismatch)]
+    pub base_mint: Account<'info, Mint>,
     #[account(mut)]
     pub user_token_account: Account<'info, TokenAccount>,
     pub token_program: Program<'info, Token>,
@@ -119,4 +160,14 @@ pub enum TradingError {
     InsufficientFunds,
     #[msg("Неавторизованный доступ")]
     Unauthorized,
+    #[msg("Переполнение при вычислении суммы ордера")]
+    MathOverflow,
+    #[msg("Аккаунт рынка не является рынком Serum")]
+    InvalidMarket,
+    #[msg("Минт не совпадает с токенами рынка")]
+    MintMismatch,
 }
+
+#[cfg(test)]
+#[path = "lib_test.rs"]
+mod tests;
@@ -0,0 +1,36 @@
+use super::*;
+
+#[test]
+fn test_quote_required_uses_base_decimals() {
+    // 1.5 SOL (9 знаков) по 20.5 USDC (6 знаков) - 30.75 USDC
+    assert_eq!(quote_required(1_500_000_000, 20_500_000, 9), Some(30_750_000));
+    // 0.1 SOL по 0.001 USDC округляется вниз до нативной единицы
+    assert_eq!(quote_required(100_000_000, 1_000, 9), Some(100));
+    assert_eq!(quote_required(0, 20_500_000, 9), Some(0));
+}
+
+#[test]
+fn test_quote_required_overflow() {
+    // Произведение помещается в u128, но результат не помещается в u64
+    assert_eq!(quote_required(u64::MAX, u64::MAX, 9), None);
+    assert_eq!(quote_required(u64::MAX, 1_000_000_000, 9), Some(u64::MAX));
+    assert_eq!(quote_required(1, 1, 40), None);
+}
+
+#[test]
+fn test_market_mints_read_from_market_state() {
+    let coin_mint = Pubkey::new_unique();
+    let pc_mint = Pubkey::new_unique();
+    let mut data = b"serum".to_vec();
+    data.extend_from_slice(&[0u8; 8 + 32 + 8]);
+    data.extend_from_slice(coin_mint.as_ref());
+    data.extend_from_slice(pc_mint.as_ref());
+    data.extend_from_slice(&[0u8; 64]);
+    assert_eq!(market_mints(&data), Some((coin_mint, pc_mint)));
+
+    // Чужой аккаунт и обрезанные данные не дают минтов
+    let mut foreign = data.clone();
+    foreign[..5].copy_from_slice(b"other");
+    assert_eq!(market_mints(&foreign), None);
+    assert_eq!(market_mints(&data[..80]), None);
+}